@@ -1,4 +1,10 @@
+use std::path::PathBuf;
+use std::process::Command;
+
 fn main() {
+    compile_schemas();
+    compile_resources();
+
     // Only compile resources on Windows
     #[cfg(target_os = "windows")]
     {
@@ -14,3 +20,36 @@ fn main() {
         res.compile().unwrap();
     }
 }
+
+/// Compiles the GSettings schema into OUT_DIR. The binary embeds
+/// gschemas.compiled and installs it into its data directory at startup,
+/// so settings work without a system-wide install.
+fn compile_schemas() {
+    println!("cargo:rerun-if-changed=resources/com.linkwithmentor.gschema.xml");
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").expect("OUT_DIR is set by cargo"));
+    let status = Command::new("glib-compile-schemas")
+        .arg("--strict")
+        .arg("--targetdir")
+        .arg(&out_dir)
+        .arg("resources")
+        .status()
+        .expect("glib-compile-schemas not found; it comes with GLib's development tools");
+    assert!(status.success(), "glib-compile-schemas failed");
+}
+
+/// Bundles the stylesheets, icons and UI files listed in
+/// resources/com.linkwithmentor.gresource.xml into OUT_DIR, to be embedded
+/// with `gio::resources_register_include!`.
+fn compile_resources() {
+    println!("cargo:rerun-if-changed=resources");
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").expect("OUT_DIR is set by cargo"));
+    let status = Command::new("glib-compile-resources")
+        .arg("--sourcedir")
+        .arg("resources")
+        .arg("--target")
+        .arg(out_dir.join("linkwithmentor.gresource"))
+        .arg("resources/com.linkwithmentor.gresource.xml")
+        .status()
+        .expect("glib-compile-resources not found; it comes with GLib's development tools");
+    assert!(status.success(), "glib-compile-resources failed");
+}
@@ -0,0 +1,506 @@
+// Accounts: the sign-in page with registration, the two-factor step and
+// password reset, and the Password and Devices & sessions settings
+use crate::auth::SignIn;
+use crate::i18n::{tr, tr_noop, tr_with};
+use crate::session;
+use crate::sizing;
+use crate::state::AppState;
+use crate::two_factor_view::{self, create_text};
+use crate::util;
+use gtk4::prelude::*;
+use gtk4::{Box as GtkBox, Button, Entry, Label, Orientation, PasswordEntry, Stack};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const PASSWORD_CHANGED: &str = tr_noop("Your password was changed. Sign in with the new one.");
+
+fn create_email_entry(email: &str) -> Entry {
+    let entry = Entry::new();
+    entry.set_text(email);
+    entry.set_placeholder_text(Some(&tr("Email")));
+    entry.set_input_purpose(gtk4::InputPurpose::Email);
+    entry
+}
+
+fn create_password_entry(placeholder: &str) -> PasswordEntry {
+    let entry = PasswordEntry::new();
+    entry.set_placeholder_text(Some(placeholder));
+    entry.set_show_peek_icon(true);
+    entry
+}
+
+fn create_link(label: &str) -> Button {
+    let button = Button::with_label(label);
+    button.add_css_class("back-button");
+    button
+}
+
+fn create_primary_button(label: &str) -> Button {
+    let button = Button::with_label(label);
+    button.add_css_class("create-post-button");
+    button.set_hexpand(true);
+    button
+}
+
+/// Enter in any of `entries` presses `button`.
+fn submit_on_enter(button: &Button, entries: &[&gtk4::Widget]) {
+    for entry in entries {
+        let button = button.clone();
+        if let Some(entry) = entry.downcast_ref::<Entry>() {
+            entry.connect_activate(move |_| button.emit_clicked());
+        } else if let Some(entry) = entry.downcast_ref::<PasswordEntry>() {
+            entry.connect_activate(move |_| button.emit_clicked());
+        }
+    }
+}
+
+/// The page shown instead of the app while signed out. Signing in calls
+/// `session::sign_in`, which swaps in the app.
+pub fn create_sign_in_page(state: &Rc<AppState>) -> GtkBox {
+    let page = GtkBox::new(Orientation::Vertical, 16);
+    page.set_halign(gtk4::Align::Center);
+    page.set_valign(gtk4::Align::Center);
+
+    let logo = Label::new(Some("🎓 LinkWithMentor"));
+    logo.add_css_class("sign-in-logo");
+
+    let card = GtkBox::new(Orientation::Vertical, 0);
+    card.add_css_class("sign-in-page");
+    sizing::set_size_request(&card, 380, -1);
+
+    let stack = Stack::builder()
+        .transition_type(gtk4::StackTransitionType::Crossfade)
+        .vhomogeneous(false)
+        .interpolate_size(true)
+        .build();
+    let notice = create_text("", "sign-in-notice");
+    if let Some(text) = session::take_notice() {
+        notice.set_text(&text);
+    } else {
+        notice.set_visible(false);
+    }
+    // The pending two-factor challenge, between the two steps
+    let challenge = Rc::new(RefCell::new(String::new()));
+
+    stack.add_named(
+        &create_sign_in_form(state, &stack, &notice, &challenge),
+        Some("sign-in"),
+    );
+    stack.add_named(
+        &create_two_factor_form(state, &stack, &challenge),
+        Some("two-factor"),
+    );
+    stack.add_named(&create_register_form(state, &stack), Some("register"));
+    let stack_clone = stack.clone();
+    let notice_clone = notice.clone();
+    stack.add_named(
+        &create_reset_form(state, "", &tr("Back to sign in"), move |changed| {
+            if changed {
+                notice_clone.set_text(&tr(PASSWORD_CHANGED));
+                notice_clone.set_visible(true);
+            }
+            stack_clone.set_visible_child_name("sign-in");
+        }),
+        Some("reset"),
+    );
+    stack.set_visible_child_name("sign-in");
+
+    card.append(&stack);
+    page.append(&logo);
+    page.append(&card);
+    page
+}
+
+fn create_sign_in_form(
+    state: &Rc<AppState>,
+    stack: &Stack,
+    notice: &Label,
+    challenge: &Rc<RefCell<String>>,
+) -> GtkBox {
+    let form = GtkBox::new(Orientation::Vertical, 12);
+    let title = create_text(&tr("Sign in"), "dialog-title");
+    let email_entry = create_email_entry("");
+    let password_entry = create_password_entry(&tr("Password"));
+    let error_label = create_text("", "form-error");
+    let sign_in_btn = create_primary_button(&tr("Sign In"));
+
+    let links = GtkBox::new(Orientation::Horizontal, 8);
+    let forgot_btn = create_link(&tr("Forgot password?"));
+    forgot_btn.set_hexpand(true);
+    forgot_btn.set_halign(gtk4::Align::Start);
+    let register_btn = create_link(&tr("Create an account"));
+    links.append(&forgot_btn);
+    links.append(&register_btn);
+
+    form.append(&title);
+    form.append(notice);
+    form.append(&email_entry);
+    form.append(&password_entry);
+    form.append(&error_label);
+    form.append(&sign_in_btn);
+    form.append(&links);
+
+    let stack_clone = stack.clone();
+    forgot_btn.connect_clicked(move |_| stack_clone.set_visible_child_name("reset"));
+    let stack_clone = stack.clone();
+    register_btn.connect_clicked(move |_| stack_clone.set_visible_child_name("register"));
+
+    submit_on_enter(
+        &sign_in_btn,
+        &[email_entry.upcast_ref(), password_entry.upcast_ref()],
+    );
+    let state = state.clone();
+    let stack = stack.clone();
+    let challenge = challenge.clone();
+    sign_in_btn.connect_clicked(move |_| {
+        let result = state.auth.borrow_mut().sign_in(
+            &email_entry.text(),
+            &password_entry.text(),
+            &session::device_name(),
+            util::now(),
+        );
+        password_entry.set_text("");
+        match result {
+            Ok(SignIn::Session(token)) => session::sign_in(&state, token),
+            Ok(SignIn::TwoFactorRequired(token)) => {
+                error_label.set_text("");
+                *challenge.borrow_mut() = token;
+                stack.set_visible_child_name("two-factor");
+            }
+            Err(error) => error_label.set_text(&error.to_string()),
+        }
+    });
+    form
+}
+
+/// Asks for a two-factor or recovery code after a correct password.
+fn create_two_factor_form(
+    state: &Rc<AppState>,
+    stack: &Stack,
+    challenge: &Rc<RefCell<String>>,
+) -> GtkBox {
+    let form = GtkBox::new(Orientation::Vertical, 12);
+    let title = create_text(&tr("🔐 Two-factor authentication"), "dialog-title");
+    let intro = create_text(
+        &tr("Enter the code from your authenticator app, or one of your recovery codes."),
+        "settings-description",
+    );
+    let code_entry = two_factor_view::create_code_entry(&tr("Code"));
+    let error_label = create_text("", "form-error");
+    let verify_btn = create_primary_button(&tr("Verify"));
+    let back_btn = create_link(&tr("Back to sign in"));
+    back_btn.set_halign(gtk4::Align::Start);
+
+    form.append(&title);
+    form.append(&intro);
+    form.append(&code_entry);
+    form.append(&error_label);
+    form.append(&verify_btn);
+    form.append(&back_btn);
+
+    let stack_clone = stack.clone();
+    let challenge_clone = challenge.clone();
+    back_btn.connect_clicked(move |_| {
+        challenge_clone.borrow_mut().clear();
+        stack_clone.set_visible_child_name("sign-in");
+    });
+
+    let state = state.clone();
+    let challenge = challenge.clone();
+    let entry = code_entry.clone();
+    two_factor_view::connect_submit(&verify_btn, &code_entry, move || {
+        let result = state.auth.borrow_mut().complete_sign_in(
+            &challenge.borrow(),
+            &entry.text(),
+            util::now(),
+        );
+        entry.set_text("");
+        match result {
+            Ok(token) => {
+                error_label.set_text("");
+                session::sign_in(&state, token);
+            }
+            Err(error) => error_label.set_text(&error.to_string()),
+        }
+    });
+    form
+}
+
+fn create_register_form(state: &Rc<AppState>, stack: &Stack) -> GtkBox {
+    let form = GtkBox::new(Orientation::Vertical, 12);
+    let title = create_text(&tr("Create your account"), "dialog-title");
+    let name_entry = Entry::new();
+    name_entry.set_placeholder_text(Some(&tr("Full name")));
+    name_entry.set_input_purpose(gtk4::InputPurpose::Name);
+    let email_entry = create_email_entry("");
+    let password_entry = create_password_entry(&tr("Password (at least 8 characters)"));
+    let confirm_entry = create_password_entry(&tr("Repeat password"));
+    let error_label = create_text("", "form-error");
+    let create_btn = create_primary_button(&tr("Create Account"));
+    let back_btn = create_link(&tr("I already have an account"));
+    back_btn.set_halign(gtk4::Align::Start);
+
+    form.append(&title);
+    form.append(&name_entry);
+    form.append(&email_entry);
+    form.append(&password_entry);
+    form.append(&confirm_entry);
+    form.append(&error_label);
+    form.append(&create_btn);
+    form.append(&back_btn);
+
+    let stack = stack.clone();
+    back_btn.connect_clicked(move |_| stack.set_visible_child_name("sign-in"));
+
+    submit_on_enter(
+        &create_btn,
+        &[
+            name_entry.upcast_ref(),
+            email_entry.upcast_ref(),
+            password_entry.upcast_ref(),
+            confirm_entry.upcast_ref(),
+        ],
+    );
+    let state = state.clone();
+    create_btn.connect_clicked(move |_| {
+        if password_entry.text() != confirm_entry.text() {
+            error_label.set_text(&tr("The passwords don't match."));
+            return;
+        }
+        let result = state.auth.borrow_mut().register(
+            &email_entry.text(),
+            &name_entry.text(),
+            &password_entry.text(),
+            &session::device_name(),
+            util::now(),
+        );
+        match result {
+            Ok(token) => session::sign_in(&state, token),
+            Err(error) => error_label.set_text(&error.to_string()),
+        }
+    });
+    form
+}
+
+/// Mails a reset code, then sets a new password with it. `on_done` gets
+/// whether the password was changed; the reset signs out every session,
+/// this one included.
+fn create_reset_form(
+    state: &Rc<AppState>,
+    email: &str,
+    back_label: &str,
+    on_done: impl Fn(bool) + 'static,
+) -> GtkBox {
+    let on_done = Rc::new(on_done);
+    let form = GtkBox::new(Orientation::Vertical, 12);
+    let title = create_text(&tr("Reset your password"), "dialog-title");
+    let intro = create_text(
+        &tr("Enter your account's email and we'll send you a code for choosing a new password."),
+        "settings-description",
+    );
+    let email_entry = create_email_entry(email);
+    let send_btn = create_primary_button(&tr("Send Code"));
+
+    // Shown once a code is on its way
+    let code_box = GtkBox::new(Orientation::Vertical, 12);
+    code_box.set_visible(false);
+    let sent_label = create_text("", "settings-description");
+    let code_entry = two_factor_view::create_code_entry(&tr("Reset code"));
+    code_entry.set_input_purpose(gtk4::InputPurpose::Digits);
+    let password_entry = create_password_entry(&tr("New password (at least 8 characters)"));
+    let confirm_entry = create_password_entry(&tr("Repeat new password"));
+    let set_btn = create_primary_button(&tr("Set Password"));
+    code_box.append(&sent_label);
+    code_box.append(&code_entry);
+    code_box.append(&password_entry);
+    code_box.append(&confirm_entry);
+    code_box.append(&set_btn);
+
+    let error_label = create_text("", "form-error");
+    let back_btn = create_link(back_label);
+    back_btn.set_halign(gtk4::Align::Start);
+
+    form.append(&title);
+    form.append(&intro);
+    form.append(&email_entry);
+    form.append(&send_btn);
+    form.append(&code_box);
+    form.append(&error_label);
+    form.append(&back_btn);
+
+    let on_done_clone = on_done.clone();
+    back_btn.connect_clicked(move |_| on_done_clone(false));
+
+    submit_on_enter(&send_btn, &[email_entry.upcast_ref()]);
+    {
+        let state = state.clone();
+        let email_entry = email_entry.clone();
+        let code_box = code_box.clone();
+        let error_label = error_label.clone();
+        let code_entry = code_entry.clone();
+        send_btn.connect_clicked(move |button| {
+            let email = email_entry.text();
+            if !email.contains('@') {
+                error_label.set_text(&tr("Enter a valid email address."));
+                return;
+            }
+            state
+                .auth
+                .borrow_mut()
+                .request_password_reset(&email, util::now());
+            error_label.set_text("");
+            sent_label.set_text(&tr_with(
+                "If {email} has an account, a code is on its way. It expires in 15 minutes.",
+                &[("email", email.as_str())],
+            ));
+            button.set_label(&tr("Send Another Code"));
+            button.remove_css_class("create-post-button");
+            button.add_css_class("secondary-button");
+            code_box.set_visible(true);
+            code_entry.grab_focus();
+        });
+    }
+
+    submit_on_enter(
+        &set_btn,
+        &[
+            code_entry.upcast_ref(),
+            password_entry.upcast_ref(),
+            confirm_entry.upcast_ref(),
+        ],
+    );
+    let state = state.clone();
+    set_btn.connect_clicked(move |_| {
+        if password_entry.text() != confirm_entry.text() {
+            error_label.set_text(&tr("The passwords don't match."));
+            return;
+        }
+        let result = state.auth.borrow_mut().reset_password(
+            &email_entry.text(),
+            &code_entry.text(),
+            &password_entry.text(),
+            util::now(),
+        );
+        match result {
+            Ok(()) => {
+                if state.session.borrow().is_some() {
+                    session::check(&state);
+                    session::set_notice(PASSWORD_CHANGED);
+                }
+                on_done(true);
+            }
+            Err(error) => error_label.set_text(&error.to_string()),
+        }
+    });
+    form
+}
+
+/// The settings button for changing the password, by mailed reset code.
+pub fn create_password_button(state: &Rc<AppState>) -> Button {
+    let button = Button::with_label(&tr("Change"));
+    button.add_css_class("edit-button");
+    button.set_valign(gtk4::Align::Center);
+    let state = state.clone();
+    button.connect_clicked(move |button| {
+        let (window, content) = two_factor_view::create_dialog(button, &tr("Change Password"));
+        let window_clone = window.clone();
+        content.append(&create_reset_form(
+            &state,
+            &session::email(&state),
+            &tr("Cancel"),
+            move |_| window_clone.close(),
+        ));
+        window.present();
+    });
+    button
+}
+
+/// The account's sessions, with a Revoke button for each one on another
+/// device, kept current.
+pub fn create_sessions_section(state: &Rc<AppState>) -> GtkBox {
+    let section = GtkBox::new(Orientation::Vertical, 12);
+    section.add_css_class("settings-section");
+    let title = Label::new(Some(&tr("💻 Devices & sessions")));
+    title.add_css_class("settings-section-title");
+    title.set_halign(gtk4::Align::Start);
+    let description = create_text(
+        &tr("These devices are signed in to your account. Revoke any you don't recognize."),
+        "settings-description",
+    );
+    let list = GtkBox::new(Orientation::Vertical, 0);
+    section.append(&title);
+    section.append(&description);
+    section.append(&list);
+
+    let update = {
+        let state = state.clone();
+        move || {
+            while let Some(child) = list.first_child() {
+                list.remove(&child);
+            }
+            let Some((email, current)) = state
+                .session
+                .borrow()
+                .as_ref()
+                .map(|session| (session.email.clone(), session.id.clone()))
+            else {
+                return;
+            };
+            let now = util::now();
+            // Other devices may have signed in or out since
+            state.auth.borrow_mut().reload();
+            for device in state.auth.borrow().sessions(&email) {
+                let row = GtkBox::new(Orientation::Horizontal, 12);
+                row.add_css_class("settings-item");
+
+                let text_box = GtkBox::new(Orientation::Vertical, 2);
+                text_box.set_hexpand(true);
+                let name = create_text(&device.device, "settings-label");
+                let last_active = if device.id == current {
+                    tr("Active now")
+                } else {
+                    tr_with(
+                        "Last active {time}",
+                        &[("time", &util::format_relative_time(device.last_seen, now))],
+                    )
+                };
+                let details = create_text(
+                    &format!(
+                        "{last_active} · {}",
+                        tr_with(
+                            "Signed in {date}",
+                            &[("date", &util::format_date_time(device.created_at))],
+                        )
+                    ),
+                    "settings-description",
+                );
+                text_box.append(&name);
+                text_box.append(&details);
+                row.append(&text_box);
+
+                if device.id == current {
+                    let label = Label::new(Some(&tr("This device")));
+                    label.add_css_class("settings-value");
+                    row.append(&label);
+                } else {
+                    let revoke_btn = Button::with_label(&tr("Revoke"));
+                    revoke_btn.add_css_class("danger-button");
+                    revoke_btn.set_valign(gtk4::Align::Center);
+                    let state = state.clone();
+                    let email = email.clone();
+                    revoke_btn.connect_clicked(move |_| {
+                        state.auth.borrow_mut().revoke_session(&email, &device.id);
+                        // Not while this button's row is being removed
+                        let state = state.clone();
+                        gtk4::glib::idle_add_local_once(move || state.auth_changed.emit());
+                    });
+                    row.append(&revoke_btn);
+                }
+                list.append(&row);
+            }
+        }
+    };
+    update();
+    state.auth_changed.connect(update);
+    section
+}
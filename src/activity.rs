@@ -0,0 +1,205 @@
+// Activity timelines, and who may see each of the signed-in user's items
+use crate::community::ME;
+use crate::i18n::{tr_noop, tr_with};
+use crate::share;
+use crate::state::AppState;
+use crate::storage;
+use gtk4::glib;
+use std::collections::HashMap;
+
+const ACTIVITY_FILE: &str = "activity.ini";
+const VISIBILITY_GROUP: &str = "visibility";
+
+/// Timeline items shown per page.
+pub const PAGE_SIZE: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Visibility {
+    Public,
+    Followers,
+    OnlyMe,
+}
+
+impl Visibility {
+    pub const ALL: [Visibility; 3] = [
+        Visibility::Public,
+        Visibility::Followers,
+        Visibility::OnlyMe,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Visibility::Public => tr_noop("🌍 Public"),
+            Visibility::Followers => tr_noop("👥 Followers"),
+            Visibility::OnlyMe => tr_noop("🔒 Only me"),
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            Visibility::Public => "public",
+            Visibility::Followers => "followers",
+            Visibility::OnlyMe => "only-me",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|visibility| visibility.key() == key)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActivityKind {
+    Post,
+    Comment,
+    Join,
+    Share,
+}
+
+impl ActivityKind {
+    pub fn icon(self) -> &'static str {
+        match self {
+            ActivityKind::Post => "📝",
+            ActivityKind::Comment => "💬",
+            ActivityKind::Join => "👥",
+            ActivityKind::Share => "📤",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Activity {
+    /// Stable id such as "post:<id>", used for the visibility setting.
+    pub key: String,
+    pub kind: ActivityKind,
+    pub text: String,
+    pub timestamp: i64,
+}
+
+/// Per-item visibility; items without a setting are public.
+pub struct ActivityPrivacy {
+    overrides: HashMap<String, Visibility>,
+}
+
+impl ActivityPrivacy {
+    pub fn load() -> Self {
+        let overrides = storage::load_key_file(ACTIVITY_FILE)
+            .and_then(|key_file| {
+                let keys = key_file.keys(VISIBILITY_GROUP).ok()?;
+                Some(
+                    keys.iter()
+                        .filter_map(|key| {
+                            let value = key_file.string(VISIBILITY_GROUP, key).ok()?;
+                            Some((key.to_string(), Visibility::from_key(&value)?))
+                        })
+                        .collect(),
+                )
+            })
+            .unwrap_or_default();
+        Self { overrides }
+    }
+
+    fn save(&self) {
+        let key_file = glib::KeyFile::new();
+        for (key, visibility) in &self.overrides {
+            key_file.set_string(VISIBILITY_GROUP, key, visibility.key());
+        }
+        storage::save_key_file(ACTIVITY_FILE, &key_file);
+    }
+
+    pub fn visibility(&self, key: &str) -> Visibility {
+        self.overrides
+            .get(key)
+            .copied()
+            .unwrap_or(Visibility::Public)
+    }
+
+    pub fn set_visibility(&mut self, key: &str, visibility: Visibility) {
+        if visibility == Visibility::Public {
+            self.overrides.remove(key);
+        } else {
+            self.overrides.insert(key.to_string(), visibility);
+        }
+        self.save();
+    }
+}
+
+/// Posts and comments by `user_id`, newest first. The signed-in user's
+/// timeline also has their group joins and shares.
+pub fn timeline(state: &AppState, user_id: &str) -> Vec<Activity> {
+    let mut items = Vec::new();
+    let community = state.community.borrow();
+
+    for post in state.feed.borrow().posts() {
+        if post.author == user_id {
+            items.push(Activity {
+                key: format!("post:{}", post.id),
+                kind: ActivityKind::Post,
+                text: tr_with("Posted “{title}”", &[("title", &post.title())]),
+                timestamp: post.created_at,
+            });
+        }
+        for (index, comment) in post.comments.iter().enumerate() {
+            if comment.author != user_id {
+                continue;
+            }
+            let template = if post.author != user_id {
+                tr_noop("Commented on {author}’s post “{title}”")
+            } else if user_id == ME {
+                tr_noop("Commented on your own post “{title}”")
+            } else {
+                tr_noop("Commented on their own post “{title}”")
+            };
+            items.push(Activity {
+                key: format!("comment:{}:{index}", post.id),
+                kind: ActivityKind::Comment,
+                text: tr_with(
+                    template,
+                    &[
+                        ("author", &community.user_name(&post.author)),
+                        ("title", &post.title()),
+                    ],
+                ),
+                timestamp: comment.created_at,
+            });
+        }
+    }
+
+    if user_id != ME {
+        items.sort_by_key(|item| std::cmp::Reverse(item.timestamp));
+        return items;
+    }
+
+    for group in community.groups() {
+        if let (true, Some(joined_at)) = (group.joined, group.joined_at) {
+            items.push(Activity {
+                key: format!("join:{}", group.id),
+                kind: ActivityKind::Join,
+                text: tr_with(
+                    "Joined {group}",
+                    &[("group", &format!("{} {}", group.emoji, group.name))],
+                ),
+                timestamp: joined_at,
+            });
+        }
+    }
+    drop(community);
+
+    for record in state.shares.borrow().records() {
+        items.push(Activity {
+            key: format!("share:{}", record.id),
+            kind: ActivityKind::Share,
+            text: format!(
+                "{} · {}",
+                record.content.title(),
+                share::describe_recipients(state, &record.recipients)
+            ),
+            timestamp: record.created_at,
+        });
+    }
+
+    items.sort_by_key(|item| std::cmp::Reverse(item.timestamp));
+    items
+}
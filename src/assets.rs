@@ -0,0 +1,115 @@
+// Stylesheets and other assets, embedded in the binary as a GResource bundle
+//
+// build.rs compiles resources/ (see com.linkwithmentor.gresource.xml), so
+// the app looks the same whatever directory it is launched from. For theme
+// work, point LINKWITHMENTOR_STYLE_DIR at a folder laid out like resources/,
+// e.g. the checkout's own resources/: stylesheets found there are used
+// instead of the embedded ones and reloaded whenever they are saved, and
+// translation catalogs there are picked up on the next language change.
+use gtk4::prelude::*;
+use gtk4::{gio, glib, CssProvider};
+use std::cell::RefCell;
+use std::path::PathBuf;
+
+/// Resource paths are "/com/linkwithmentor/<file>", e.g. ".../style.css".
+const RESOURCE_PREFIX: &str = "/com/linkwithmentor";
+const OVERRIDE_ENV: &str = "LINKWITHMENTOR_STYLE_DIR";
+
+thread_local! {
+    /// Monitors for overridden stylesheets; dropping one stops its reloads.
+    static MONITORS: RefCell<Vec<gio::FileMonitor>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Makes the embedded bundle available under resource://. Call before
+/// loading any asset.
+pub fn register() {
+    gio::resources_register_include!("linkwithmentor.gresource")
+        .expect("the resource bundle is compiled into the app");
+}
+
+fn override_dir() -> Option<PathBuf> {
+    std::env::var_os(OVERRIDE_ENV)
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_dir())
+}
+
+/// The contents of `name` (relative to resources/, e.g. "po/ar.po"), from
+/// the override directory when one is set and has the file, else from the
+/// bundle. None if neither has it.
+pub fn load_bytes(name: &str) -> Option<glib::Bytes> {
+    if let Some(path) = override_dir()
+        .map(|dir| dir.join(name))
+        .filter(|path| path.is_file())
+    {
+        match std::fs::read(&path) {
+            Ok(data) => return Some(glib::Bytes::from_owned(data)),
+            Err(err) => eprintln!("Could not read {}: {err}", path.display()),
+        }
+    }
+    gio::resources_lookup_data(
+        &format!("{RESOURCE_PREFIX}/{name}"),
+        gio::ResourceLookupFlags::NONE,
+    )
+    .ok()
+}
+
+/// Loads `name` (relative to resources/, e.g. "themes/dark.css") into
+/// `provider`, from the override directory when one is set and has the
+/// file, else from the bundle.
+pub fn load_css(provider: &CssProvider, name: &str) {
+    let Some(dir) = override_dir() else {
+        provider.load_from_resource(&format!("{RESOURCE_PREFIX}/{name}"));
+        return;
+    };
+
+    let path = dir.join(name);
+    let load = {
+        let provider = provider.clone();
+        let name = name.to_string();
+        move || {
+            if path.is_file() {
+                provider.load_from_path(&path);
+            } else {
+                provider.load_from_resource(&format!("{RESOURCE_PREFIX}/{name}"));
+            }
+        }
+    };
+    provider.connect_parsing_error(|_, section, err| {
+        eprintln!("{section}: {err}");
+    });
+    load();
+    watch(name, load);
+}
+
+/// Calls `on_change` whenever `name` (relative to resources/) changes in the
+/// override directory. Without one, assets never change and this does
+/// nothing.
+pub fn watch(name: &str, on_change: impl Fn() + 'static) {
+    let Some(dir) = override_dir() else {
+        return;
+    };
+    let file = gio::File::for_path(dir.join(name));
+
+    // Editors often save by replacing the file, which still shows up here
+    match file.monitor_file(
+        gio::FileMonitorFlags::WATCH_MOVES,
+        None::<&gio::Cancellable>,
+    ) {
+        Ok(monitor) => {
+            monitor.connect_changed(move |_, _, _, event| {
+                if matches!(
+                    event,
+                    gio::FileMonitorEvent::ChangesDoneHint
+                        | gio::FileMonitorEvent::Created
+                        | gio::FileMonitorEvent::Deleted
+                        | gio::FileMonitorEvent::MovedIn
+                        | gio::FileMonitorEvent::Renamed
+                ) {
+                    on_change();
+                }
+            });
+            MONITORS.with(|monitors| monitors.borrow_mut().push(monitor));
+        }
+        Err(err) => eprintln!("Could not watch {name} for changes: {err}"),
+    }
+}
@@ -0,0 +1,1168 @@
+// Accounts, sessions, password resets and two-factor authentication
+//
+// There is no account server yet, so `AuthServer` stands in for one: it
+// keeps what the server would (accounts with hashed passwords, session
+// tokens, the two-factor secret, the last code used and hashed recovery
+// codes) in auth.ini and checks everything the same way. Mail it would
+// send, such as password reset codes, is appended to outbox.txt in the data
+// directory. Any authenticator app works against it; see the README for
+// testing with oathtool.
+use crate::i18n::tr;
+use crate::storage;
+use crate::totp;
+use gtk4::glib;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Write;
+
+const AUTH_FILE: &str = "auth.ini";
+const OUTBOX_FILE: &str = "outbox.txt";
+const ACCOUNT_PREFIX: &str = "account:";
+const SESSION_PREFIX: &str = "session:";
+const RESET_PREFIX: &str = "reset:";
+
+/// Shown as the issuer in authenticator apps.
+pub const ISSUER: &str = "LinkWithMentor";
+
+const MIN_PASSWORD_LENGTH: usize = 8;
+/// Sessions unused for this long have to sign in again.
+const SESSION_LIFETIME: i64 = 30 * 24 * 60 * 60;
+/// How long the code step of a sign-in and a password reset code last.
+const CHALLENGE_LIFETIME: i64 = 5 * 60;
+const RESET_LIFETIME: i64 = 15 * 60;
+
+const RECOVERY_CODE_COUNT: usize = 10;
+/// Letters and digits that can't be mistaken for each other.
+const RECOVERY_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+/// Wrong passwords or codes allowed before the account is locked for
+/// `LOCKOUT_SECONDS`.
+const MAX_ATTEMPTS: i32 = 5;
+const LOCKOUT_SECONDS: i64 = 5 * 60;
+
+/// PBKDF2 rounds for passwords, as OWASP recommends for HMAC-SHA256, so a
+/// leaked file can't be brute-forced quickly. Fewer in tests.
+const PASSWORD_ROUNDS: u32 = if cfg!(test) { 1_000 } else { 600_000 };
+/// Random tokens and codes can't be guessed from a dictionary; one round
+/// keeps checking them cheap.
+const TOKEN_ROUNDS: u32 = 1;
+const SALT_LEN: usize = 16;
+const HASH_LEN: usize = 32;
+
+/// A salted hash of a secret such as a password, a session token or a
+/// recovery code; the secret itself is never stored.
+#[derive(Clone, Debug)]
+pub struct PasswordHash {
+    salt: String,
+    hash: String,
+    /// PBKDF2-HMAC-SHA256 rounds, or 0 for hashes saved before PBKDF2: a
+    /// single SHA-256 of "salt:secret".
+    rounds: u32,
+}
+
+impl PasswordHash {
+    /// Hashes a password, slowly.
+    pub fn new(password: &str) -> Self {
+        Self::with_rounds(password, PASSWORD_ROUNDS)
+    }
+
+    /// Hashes a random token or code, quickly.
+    fn for_token(token: &str) -> Self {
+        Self::with_rounds(token, TOKEN_ROUNDS)
+    }
+
+    fn with_rounds(secret: &str, rounds: u32) -> Self {
+        let salt = to_hex(&totp::random_bytes(SALT_LEN));
+        let hash = Self::derive(secret, &salt, rounds);
+        Self { salt, hash, rounds }
+    }
+
+    fn derive(secret: &str, salt: &str, rounds: u32) -> String {
+        if rounds == 0 {
+            return glib::compute_checksum_for_string(
+                glib::ChecksumType::Sha256,
+                format!("{salt}:{secret}"),
+            )
+            .map(|digest| digest.to_string())
+            .unwrap_or_default();
+        }
+        let mut hash = [0; HASH_LEN];
+        pbkdf2::pbkdf2_hmac::<sha2::Sha256>(secret.as_bytes(), salt.as_bytes(), rounds, &mut hash);
+        to_hex(&hash)
+    }
+
+    pub fn verify(&self, secret: &str) -> bool {
+        constant_time_eq(
+            Self::derive(secret, &self.salt, self.rounds).as_bytes(),
+            self.hash.as_bytes(),
+        )
+    }
+
+    /// Takes as long as verifying a password, so unknown accounts are
+    /// refused no faster than wrong passwords.
+    fn verify_nothing(password: &str) {
+        Self::derive(password, "", PASSWORD_ROUNDS);
+    }
+
+    /// Reads `<key>_salt`, `<key>_hash` and `<key>_rounds` from `group`.
+    pub fn load(key_file: &glib::KeyFile, group: &str, key: &str) -> Option<Self> {
+        Some(Self {
+            salt: key_file
+                .string(group, &format!("{key}_salt"))
+                .ok()?
+                .to_string(),
+            hash: key_file
+                .string(group, &format!("{key}_hash"))
+                .ok()?
+                .to_string(),
+            rounds: key_file
+                .uint64(group, &format!("{key}_rounds"))
+                .map_or(0, |rounds| rounds as u32),
+        })
+    }
+
+    pub fn save(&self, key_file: &glib::KeyFile, group: &str, key: &str) {
+        key_file.set_string(group, &format!("{key}_salt"), &self.salt);
+        key_file.set_string(group, &format!("{key}_hash"), &self.hash);
+        key_file.set_uint64(group, &format!("{key}_rounds"), u64::from(self.rounds));
+    }
+
+    /// "salt:hash:rounds", for lists.
+    fn to_entry(&self) -> String {
+        format!("{}:{}:{}", self.salt, self.hash, self.rounds)
+    }
+
+    fn from_entry(entry: &str) -> Option<Self> {
+        let mut parts = entry.split(':');
+        let salt = parts.next()?.to_string();
+        let hash = parts.next()?.to_string();
+        let rounds = parts.next().map_or(Some(0), |rounds| rounds.parse().ok())?;
+        Some(Self { salt, hash, rounds })
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Compares secrets without stopping at the first difference, so timing
+/// doesn't tell how much of a guess was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Why a request was refused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuthError {
+    InvalidEmail,
+    EmptyName,
+    WeakPassword,
+    EmailTaken,
+    /// Unknown email or wrong password; which one isn't revealed.
+    WrongPassword,
+    InvalidCode,
+    /// The code's time step has been used already, so it could be a replay.
+    CodeAlreadyUsed,
+    TooManyAttempts,
+    /// Confirming an enrollment that was never started.
+    NoEnrollment,
+    /// The code step of a sign-in was not finished in time.
+    SignInExpired,
+    InvalidResetCode,
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            AuthError::InvalidEmail => tr("Enter a valid email address."),
+            AuthError::EmptyName => tr("Enter your name."),
+            AuthError::WeakPassword => tr("Use at least 8 characters for your password."),
+            AuthError::EmailTaken => tr("An account with this email already exists."),
+            AuthError::WrongPassword => tr("Incorrect email or password."),
+            AuthError::InvalidCode => tr("That code isn't right. Check your authenticator app."),
+            AuthError::CodeAlreadyUsed => {
+                tr("That code has already been used. Wait for the next one.")
+            }
+            AuthError::TooManyAttempts => {
+                tr("Too many failed attempts. Try again in a few minutes.")
+            }
+            AuthError::NoEnrollment => tr("Start setting up two-factor authentication again."),
+            AuthError::SignInExpired => tr("This sign-in took too long. Please start again."),
+            AuthError::InvalidResetCode => tr("That reset code is wrong or has expired."),
+        };
+        f.write_str(&text)
+    }
+}
+
+/// The signed-in account behind a session token.
+#[derive(Clone, Debug)]
+pub struct SignedIn {
+    pub session_id: String,
+    pub email: String,
+    pub name: String,
+}
+
+/// A session as listed under Devices & sessions.
+#[derive(Clone, Debug)]
+pub struct DeviceSession {
+    pub id: String,
+    pub device: String,
+    pub created_at: i64,
+    pub last_seen: i64,
+}
+
+/// What a correct password leads to.
+pub enum SignIn {
+    /// Signed in, with the new session's token.
+    Session(String),
+    /// A two-factor code is needed too; pass this challenge with it to
+    /// `complete_sign_in`.
+    TwoFactorRequired(String),
+}
+
+struct TwoFactor {
+    secret: Vec<u8>,
+    /// Time step of the last accepted code; it and earlier ones are refused.
+    last_step: i64,
+    recovery_codes: Vec<PasswordHash>,
+}
+
+struct Account {
+    name: String,
+    password: PasswordHash,
+    created_at: i64,
+    two_factor: Option<TwoFactor>,
+    failed_attempts: i32,
+    locked_until: i64,
+}
+
+impl Account {
+    fn check_lock(&self, now: i64) -> Result<(), AuthError> {
+        if now < self.locked_until {
+            Err(AuthError::TooManyAttempts)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Passes `result` through, counting a failure and locking the account
+    /// after too many in a row.
+    fn record<T>(&mut self, result: Result<T, AuthError>, now: i64) -> Result<T, AuthError> {
+        if result.is_ok() {
+            self.failed_attempts = 0;
+        } else {
+            self.failed_attempts += 1;
+            if self.failed_attempts >= MAX_ATTEMPTS {
+                self.failed_attempts = 0;
+                self.locked_until = now + LOCKOUT_SECONDS;
+            }
+        }
+        result
+    }
+
+    fn check_password(&mut self, password: &str, now: i64) -> Result<(), AuthError> {
+        self.check_lock(now)?;
+        let result = if self.password.verify(password) {
+            Ok(())
+        } else {
+            Err(AuthError::WrongPassword)
+        };
+        self.record(result, now)
+    }
+
+    /// Checks a code from the authenticator app or an unused recovery code,
+    /// which is then used up. Always succeeds when 2FA is off.
+    fn check_code(&mut self, code: &str, now: i64) -> Result<(), AuthError> {
+        self.check_lock(now)?;
+        let Some(two_factor) = &mut self.two_factor else {
+            return Ok(());
+        };
+        let result = match totp::verify(&two_factor.secret, code, now) {
+            Some(step) if step <= two_factor.last_step => Err(AuthError::CodeAlreadyUsed),
+            Some(step) => {
+                two_factor.last_step = step;
+                Ok(())
+            }
+            None => {
+                let code = normalize_recovery_code(code);
+                match two_factor
+                    .recovery_codes
+                    .iter()
+                    .position(|hash| hash.verify(&code))
+                {
+                    Some(index) => {
+                        two_factor.recovery_codes.remove(index);
+                        Ok(())
+                    }
+                    None => Err(AuthError::InvalidCode),
+                }
+            }
+        };
+        self.record(result, now)
+    }
+}
+
+struct Session {
+    id: String,
+    account: String,
+    token: PasswordHash,
+    device: String,
+    created_at: i64,
+    last_seen: i64,
+}
+
+struct PasswordReset {
+    code: PasswordHash,
+    expires_at: i64,
+}
+
+/// A sign-in waiting for its two-factor code. Only kept in memory.
+struct Challenge {
+    token: String,
+    account: String,
+    device: String,
+    expires_at: i64,
+}
+
+pub struct AuthServer {
+    /// By normalized email.
+    accounts: BTreeMap<String, Account>,
+    sessions: Vec<Session>,
+    resets: BTreeMap<String, PasswordReset>,
+    challenges: Vec<Challenge>,
+    /// Account and secret shown during enrollment, until a code from it is
+    /// confirmed.
+    pending_enrollment: Option<(String, Vec<u8>)>,
+}
+
+impl AuthServer {
+    pub fn load() -> Self {
+        let mut server = Self {
+            accounts: BTreeMap::new(),
+            sessions: Vec::new(),
+            resets: BTreeMap::new(),
+            challenges: Vec::new(),
+            pending_enrollment: None,
+        };
+        server.reload();
+        server
+    }
+
+    /// Rereads the accounts, sessions and resets, which other devices may
+    /// have changed, e.g. by revoking this device's session. Every request
+    /// that may save starts with it, so none saves back stale state.
+    /// Challenges and enrollments in progress only live here.
+    pub fn reload(&mut self) {
+        self.accounts.clear();
+        self.sessions.clear();
+        self.resets.clear();
+        let Some(key_file) = storage::load_device_key_file(AUTH_FILE) else {
+            return;
+        };
+
+        for (email, group) in storage::groups_with_prefix(&key_file, ACCOUNT_PREFIX) {
+            let Some(password) = PasswordHash::load(&key_file, &group, "password") else {
+                continue;
+            };
+            let two_factor = key_file
+                .string(&group, "two_factor_secret")
+                .ok()
+                .and_then(|secret| totp::base32_decode(&secret))
+                .filter(|secret| !secret.is_empty())
+                .map(|secret| TwoFactor {
+                    secret,
+                    last_step: key_file
+                        .int64(&group, "two_factor_last_step")
+                        .unwrap_or_default(),
+                    recovery_codes: storage::list(&key_file, &group, "recovery_codes")
+                        .iter()
+                        .filter_map(|entry| PasswordHash::from_entry(entry))
+                        .collect(),
+                });
+            let account = Account {
+                name: storage::string_or(&key_file, &group, "name", ""),
+                password,
+                created_at: key_file.int64(&group, "created_at").unwrap_or_default(),
+                two_factor,
+                failed_attempts: key_file
+                    .integer(&group, "failed_attempts")
+                    .unwrap_or_default(),
+                locked_until: key_file.int64(&group, "locked_until").unwrap_or_default(),
+            };
+            self.accounts.insert(email, account);
+        }
+
+        self.sessions = storage::groups_with_prefix(&key_file, SESSION_PREFIX)
+            .into_iter()
+            .filter_map(|(id, group)| {
+                Some(Session {
+                    token: PasswordHash::load(&key_file, &group, "token")?,
+                    account: storage::string_or(&key_file, &group, "account", ""),
+                    device: storage::string_or(&key_file, &group, "device", ""),
+                    created_at: key_file.int64(&group, "created_at").unwrap_or_default(),
+                    last_seen: key_file.int64(&group, "last_seen").unwrap_or_default(),
+                    id,
+                })
+            })
+            .collect();
+
+        for (email, group) in storage::groups_with_prefix(&key_file, RESET_PREFIX) {
+            if let Some(code) = PasswordHash::load(&key_file, &group, "code") {
+                let expires_at = key_file.int64(&group, "expires_at").unwrap_or_default();
+                self.resets
+                    .insert(email, PasswordReset { code, expires_at });
+            }
+        }
+    }
+
+    fn save(&self) {
+        let key_file = glib::KeyFile::new();
+        for (email, account) in &self.accounts {
+            let group = format!("{ACCOUNT_PREFIX}{email}");
+            key_file.set_string(&group, "name", &account.name);
+            account.password.save(&key_file, &group, "password");
+            key_file.set_int64(&group, "created_at", account.created_at);
+            key_file.set_integer(&group, "failed_attempts", account.failed_attempts);
+            key_file.set_int64(&group, "locked_until", account.locked_until);
+            if let Some(two_factor) = &account.two_factor {
+                key_file.set_string(
+                    &group,
+                    "two_factor_secret",
+                    &totp::base32_encode(&two_factor.secret),
+                );
+                key_file.set_int64(&group, "two_factor_last_step", two_factor.last_step);
+                let codes: Vec<String> = two_factor
+                    .recovery_codes
+                    .iter()
+                    .map(PasswordHash::to_entry)
+                    .collect();
+                storage::set_list(&key_file, &group, "recovery_codes", &codes);
+            }
+        }
+        for session in &self.sessions {
+            let group = format!("{SESSION_PREFIX}{}", session.id);
+            key_file.set_string(&group, "account", &session.account);
+            session.token.save(&key_file, &group, "token");
+            key_file.set_string(&group, "device", &session.device);
+            key_file.set_int64(&group, "created_at", session.created_at);
+            key_file.set_int64(&group, "last_seen", session.last_seen);
+        }
+        for (email, reset) in &self.resets {
+            let group = format!("{RESET_PREFIX}{email}");
+            reset.code.save(&key_file, &group, "code");
+            key_file.set_int64(&group, "expires_at", reset.expires_at);
+        }
+        storage::save_device_key_file(AUTH_FILE, &key_file);
+    }
+
+    /// Creates an account and signs it in on `device`, returning the
+    /// session token.
+    pub fn register(
+        &mut self,
+        email: &str,
+        name: &str,
+        password: &str,
+        device: &str,
+        now: i64,
+    ) -> Result<String, AuthError> {
+        self.reload();
+        let email = normalize_email(email).ok_or(AuthError::InvalidEmail)?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AuthError::EmptyName);
+        }
+        if password.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(AuthError::WeakPassword);
+        }
+        if self.accounts.contains_key(&email) {
+            return Err(AuthError::EmailTaken);
+        }
+        self.accounts.insert(
+            email.clone(),
+            Account {
+                name: name.to_string(),
+                password: PasswordHash::new(password),
+                created_at: now,
+                two_factor: None,
+                failed_attempts: 0,
+                locked_until: 0,
+            },
+        );
+        Ok(self.create_session(&email, device, now))
+    }
+
+    /// Checks an email and password. With two-factor authentication on,
+    /// the session is only created once `complete_sign_in` gets a code.
+    pub fn sign_in(
+        &mut self,
+        email: &str,
+        password: &str,
+        device: &str,
+        now: i64,
+    ) -> Result<SignIn, AuthError> {
+        self.reload();
+        let Some(email) = normalize_email(email).filter(|email| self.accounts.contains_key(email))
+        else {
+            PasswordHash::verify_nothing(password);
+            return Err(AuthError::WrongPassword);
+        };
+        let account = self
+            .accounts
+            .get_mut(&email)
+            .ok_or(AuthError::WrongPassword)?;
+        let result = account.check_password(password, now);
+        let two_factor = account.two_factor.is_some();
+        self.save();
+        result?;
+
+        if !two_factor {
+            return Ok(SignIn::Session(self.create_session(&email, device, now)));
+        }
+        self.challenges
+            .retain(|challenge| challenge.expires_at > now);
+        let token = random_token();
+        self.challenges.push(Challenge {
+            token: token.clone(),
+            account: email,
+            device: device.to_string(),
+            expires_at: now + CHALLENGE_LIFETIME,
+        });
+        Ok(SignIn::TwoFactorRequired(token))
+    }
+
+    /// Finishes a sign-in with a two-factor or recovery code, returning the
+    /// session token.
+    pub fn complete_sign_in(
+        &mut self,
+        challenge: &str,
+        code: &str,
+        now: i64,
+    ) -> Result<String, AuthError> {
+        self.reload();
+        let index = self
+            .challenges
+            .iter()
+            .position(|pending| {
+                constant_time_eq(pending.token.as_bytes(), challenge.as_bytes())
+                    && pending.expires_at > now
+            })
+            .ok_or(AuthError::SignInExpired)?;
+        let email = self.challenges[index].account.clone();
+        let account = self
+            .accounts
+            .get_mut(&email)
+            .ok_or(AuthError::SignInExpired)?;
+        let result = account.check_code(code, now);
+        self.save();
+        result?;
+
+        let challenge = self.challenges.remove(index);
+        Ok(self.create_session(&email, &challenge.device, now))
+    }
+
+    fn create_session(&mut self, email: &str, device: &str, now: i64) -> String {
+        let id = glib::uuid_string_random().replace('-', "");
+        let secret = random_token();
+        self.sessions.push(Session {
+            id: id.clone(),
+            account: email.to_string(),
+            token: PasswordHash::for_token(&secret),
+            device: device.to_string(),
+            created_at: now,
+            last_seen: now,
+        });
+        self.save();
+        format!("{id}.{secret}")
+    }
+
+    /// The account a session token belongs to, if the session is still
+    /// valid. Marks the session as used.
+    pub fn authenticate(&mut self, token: &str, now: i64) -> Option<SignedIn> {
+        self.reload();
+        let (id, secret) = token.split_once('.')?;
+        let index = self
+            .sessions
+            .iter()
+            .position(|session| session.id == id && session.token.verify(secret))?;
+        if self.sessions[index].last_seen + SESSION_LIFETIME < now {
+            self.sessions.remove(index);
+            self.save();
+            return None;
+        }
+        let session = &mut self.sessions[index];
+        let account = self.accounts.get(&session.account)?;
+        let signed_in = SignedIn {
+            session_id: session.id.clone(),
+            email: session.account.clone(),
+            name: account.name.clone(),
+        };
+        // Once a minute is plenty for "last active"
+        if now - session.last_seen >= 60 {
+            session.last_seen = now;
+            self.save();
+        }
+        Some(signed_in)
+    }
+
+    /// Ends the session behind `token`.
+    pub fn sign_out(&mut self, token: &str) {
+        self.reload();
+        let Some((id, secret)) = token.split_once('.') else {
+            return;
+        };
+        self.sessions
+            .retain(|session| !(session.id == id && session.token.verify(secret)));
+        self.save();
+    }
+
+    /// `email`'s sessions, most recently used first.
+    pub fn sessions(&self, email: &str) -> Vec<DeviceSession> {
+        let mut sessions: Vec<DeviceSession> = self
+            .sessions
+            .iter()
+            .filter(|session| session.account == email)
+            .map(|session| DeviceSession {
+                id: session.id.clone(),
+                device: session.device.clone(),
+                created_at: session.created_at,
+                last_seen: session.last_seen,
+            })
+            .collect();
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.last_seen));
+        sessions
+    }
+
+    /// Signs one of `email`'s sessions out wherever it is used.
+    pub fn revoke_session(&mut self, email: &str, id: &str) {
+        self.reload();
+        self.sessions
+            .retain(|session| !(session.account == email && session.id == id));
+        self.save();
+    }
+
+    /// Mails a reset code if `email` has an account. Says nothing either
+    /// way, so addresses can't be probed.
+    pub fn request_password_reset(&mut self, email: &str, now: i64) {
+        self.reload();
+        let Some(email) = normalize_email(email) else {
+            return;
+        };
+        if !self.accounts.contains_key(&email) {
+            return;
+        }
+        let bytes = totp::random_bytes(4);
+        let code = format!(
+            "{:06}",
+            u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) % 1_000_000
+        );
+        self.resets.insert(
+            email.clone(),
+            PasswordReset {
+                code: PasswordHash::for_token(&code),
+                expires_at: now + RESET_LIFETIME,
+            },
+        );
+        self.save();
+        send_mail(
+            &email,
+            "Your LinkWithMentor password reset code",
+            &format!(
+                "Enter {code} in LinkWithMentor to choose a new password.\n\
+                 The code expires in 15 minutes. If you didn't ask for it, ignore this mail."
+            ),
+        );
+    }
+
+    /// Sets a new password with a mailed reset code. Every session of the
+    /// account is signed out.
+    pub fn reset_password(
+        &mut self,
+        email: &str,
+        code: &str,
+        password: &str,
+        now: i64,
+    ) -> Result<(), AuthError> {
+        self.reload();
+        let email = normalize_email(email).ok_or(AuthError::InvalidResetCode)?;
+        if password.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(AuthError::WeakPassword);
+        }
+        let account = self
+            .accounts
+            .get_mut(&email)
+            .ok_or(AuthError::InvalidResetCode)?;
+        account.check_lock(now)?;
+        let valid = self
+            .resets
+            .get(&email)
+            .is_some_and(|reset| reset.expires_at > now && reset.code.verify(code.trim()));
+        let result = account.record(
+            if valid {
+                Ok(())
+            } else {
+                Err(AuthError::InvalidResetCode)
+            },
+            now,
+        );
+        if result.is_ok() {
+            account.password = PasswordHash::new(password);
+            self.resets.remove(&email);
+            self.sessions.retain(|session| session.account != email);
+        }
+        self.save();
+        result
+    }
+
+    pub fn two_factor_enabled(&self, email: &str) -> bool {
+        self.accounts
+            .get(email)
+            .is_some_and(|account| account.two_factor.is_some())
+    }
+
+    pub fn recovery_codes_left(&self, email: &str) -> usize {
+        self.accounts
+            .get(email)
+            .and_then(|account| account.two_factor.as_ref())
+            .map_or(0, |two_factor| two_factor.recovery_codes.len())
+    }
+
+    /// Starts enrollment with a fresh secret, for the user to add to their
+    /// authenticator app. Nothing changes until `confirm_enrollment`.
+    pub fn begin_enrollment(&mut self, email: &str) -> Vec<u8> {
+        let secret = totp::generate_secret();
+        self.pending_enrollment = Some((email.to_string(), secret.clone()));
+        secret
+    }
+
+    /// Turns two-factor authentication on if `code` comes from the secret
+    /// being enrolled, proving the app has it. Returns the recovery codes,
+    /// which are shown this once.
+    pub fn confirm_enrollment(
+        &mut self,
+        email: &str,
+        code: &str,
+        now: i64,
+    ) -> Result<Vec<String>, AuthError> {
+        self.reload();
+        let secret = match &self.pending_enrollment {
+            Some((pending, secret)) if pending == email => secret.clone(),
+            _ => return Err(AuthError::NoEnrollment),
+        };
+        let account = self
+            .accounts
+            .get_mut(email)
+            .ok_or(AuthError::NoEnrollment)?;
+        let step = totp::verify(&secret, code, now).ok_or(AuthError::InvalidCode)?;
+        let (codes, recovery_codes) = new_recovery_codes();
+        account.two_factor = Some(TwoFactor {
+            secret,
+            last_step: step,
+            recovery_codes,
+        });
+        self.pending_enrollment = None;
+        self.save();
+        Ok(codes)
+    }
+
+    /// Checks the password and a current code, as changes to two-factor
+    /// authentication need.
+    fn reauthenticate(
+        &mut self,
+        email: &str,
+        password: &str,
+        code: &str,
+        now: i64,
+    ) -> Result<&mut Account, AuthError> {
+        let account = self
+            .accounts
+            .get_mut(email)
+            .ok_or(AuthError::WrongPassword)?;
+        account.check_password(password, now)?;
+        account.check_code(code, now)?;
+        Ok(account)
+    }
+
+    /// Turns two-factor authentication off, after checking the password
+    /// and a current code.
+    pub fn disable_two_factor(
+        &mut self,
+        email: &str,
+        password: &str,
+        code: &str,
+        now: i64,
+    ) -> Result<(), AuthError> {
+        self.reload();
+        let result = self
+            .reauthenticate(email, password, code, now)
+            .map(|account| account.two_factor = None);
+        self.save();
+        result
+    }
+
+    /// Replaces all recovery codes, after checking the password and a
+    /// current code, and returns the new ones.
+    pub fn regenerate_recovery_codes(
+        &mut self,
+        email: &str,
+        password: &str,
+        code: &str,
+        now: i64,
+    ) -> Result<Vec<String>, AuthError> {
+        self.reload();
+        let result = self
+            .reauthenticate(email, password, code, now)
+            .map(|account| {
+                let (codes, hashes) = new_recovery_codes();
+                if let Some(two_factor) = &mut account.two_factor {
+                    two_factor.recovery_codes = hashes;
+                }
+                codes
+            });
+        self.save();
+        result
+    }
+}
+
+/// Lowercased and trimmed, or None unless it looks like an address.
+fn normalize_email(email: &str) -> Option<String> {
+    let email = email.trim().to_lowercase();
+    let (local, domain) = email.split_once('@')?;
+    let valid = !local.is_empty()
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !domain.contains('@')
+        && !email
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || matches!(c, '[' | ']'));
+    valid.then_some(email)
+}
+
+/// 160 random bits from the OS, as text.
+pub fn random_token() -> String {
+    totp::base32_encode(&totp::random_bytes(totp::SECRET_LEN))
+}
+
+/// "Sends" mail the way a server would, by appending it to the outbox file
+/// and noting it on the console.
+fn send_mail(to: &str, subject: &str, body: &str) {
+    eprintln!("Mail to {to}: {subject} (see {OUTBOX_FILE} in the data directory)");
+    let dir = storage::data_dir();
+    let path = dir.join(OUTBOX_FILE);
+    let result = std::fs::create_dir_all(&dir).and_then(|()| {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        writeln!(file, "To: {to}\nSubject: {subject}\n\n{body}\n")
+    });
+    if let Err(err) = result {
+        eprintln!("Could not write {}: {err}", path.display());
+    }
+}
+
+/// Recovery codes as shown to the user ("k7mq-2xpd"), with their hashes.
+fn new_recovery_codes() -> (Vec<String>, Vec<PasswordHash>) {
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let letters = random_letters(8);
+            format!("{}-{}", &letters[..4], &letters[4..])
+        })
+        .collect();
+    let hashes = codes
+        .iter()
+        .map(|code| PasswordHash::for_token(&normalize_recovery_code(code)))
+        .collect();
+    (codes, hashes)
+}
+
+/// `len` characters from the recovery alphabet. Bytes past the last whole
+/// multiple of its length are skipped, so every character is equally likely.
+fn random_letters(len: usize) -> String {
+    let limit = 256 - 256 % RECOVERY_ALPHABET.len();
+    let mut letters = String::new();
+    while letters.len() < len {
+        for byte in totp::random_bytes(len) {
+            if usize::from(byte) < limit && letters.len() < len {
+                letters
+                    .push(RECOVERY_ALPHABET[usize::from(byte) % RECOVERY_ALPHABET.len()] as char);
+            }
+        }
+    }
+    letters
+}
+
+/// Recovery codes are accepted in any case, with or without the dash.
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMAIL: &str = "ada@example.com";
+    const PASSWORD: &str = "correct horse";
+    /// A time in the middle of a code step.
+    const NOW: i64 = 1_700_000_010;
+
+    fn signed_up() -> (AuthServer, String) {
+        let mut server = AuthServer::load();
+        let token = server
+            .register(" Ada@Example.com ", "Ada", PASSWORD, "laptop", NOW)
+            .unwrap();
+        (server, token)
+    }
+
+    /// Turns two-factor authentication on, returning the secret and the
+    /// recovery codes.
+    fn enrolled() -> (AuthServer, Vec<u8>, Vec<String>) {
+        let (mut server, _) = signed_up();
+        let secret = server.begin_enrollment(EMAIL);
+        let code = totp::code_for_step(&secret, totp::step(NOW));
+        let codes = server.confirm_enrollment(EMAIL, &code, NOW).unwrap();
+        (server, secret, codes)
+    }
+
+    fn challenge(server: &mut AuthServer, now: i64) -> String {
+        match server.sign_in(EMAIL, PASSWORD, "phone", now) {
+            Ok(SignIn::TwoFactorRequired(challenge)) => challenge,
+            _ => panic!("expected a two-factor challenge"),
+        }
+    }
+
+    /// The reset code in the last mail sent.
+    fn mailed_reset_code() -> String {
+        let outbox = std::fs::read_to_string(storage::data_dir().join(OUTBOX_FILE)).unwrap();
+        let (_, rest) = outbox.rsplit_once("Enter ").unwrap();
+        rest[..6].to_string()
+    }
+
+    #[test]
+    fn sign_up_signs_in_and_is_saved() {
+        let (mut server, token) = signed_up();
+        let signed_in = server.authenticate(&token, NOW).unwrap();
+        assert_eq!(signed_in.email, EMAIL);
+        assert_eq!(signed_in.name, "Ada");
+
+        // Another device reads the same records
+        let mut other = AuthServer::load();
+        assert!(other.authenticate(&token, NOW).is_some());
+        assert!(matches!(
+            other.sign_in(EMAIL, PASSWORD, "phone", NOW),
+            Ok(SignIn::Session(_))
+        ));
+        assert_eq!(
+            other.register(EMAIL, "Ada", PASSWORD, "phone", NOW).err(),
+            Some(AuthError::EmailTaken)
+        );
+    }
+
+    #[test]
+    fn sign_up_checks_its_fields() {
+        let mut server = AuthServer::load();
+        let mut register =
+            |email, name, password| server.register(email, name, password, "laptop", NOW).err();
+        assert_eq!(
+            register("ada", "Ada", PASSWORD),
+            Some(AuthError::InvalidEmail)
+        );
+        assert_eq!(register(EMAIL, " ", PASSWORD), Some(AuthError::EmptyName));
+        assert_eq!(
+            register(EMAIL, "Ada", "short"),
+            Some(AuthError::WeakPassword)
+        );
+    }
+
+    #[test]
+    fn wrong_passwords_and_unknown_emails_look_the_same() {
+        let (mut server, _) = signed_up();
+        assert_eq!(
+            server.sign_in(EMAIL, "wrong password", "phone", NOW).err(),
+            Some(AuthError::WrongPassword)
+        );
+        assert_eq!(
+            server
+                .sign_in("bob@example.com", PASSWORD, "phone", NOW)
+                .err(),
+            Some(AuthError::WrongPassword)
+        );
+    }
+
+    #[test]
+    fn repeated_failures_lock_the_account() {
+        let (mut server, _) = signed_up();
+        for _ in 0..MAX_ATTEMPTS {
+            let _ = server.sign_in(EMAIL, "wrong password", "phone", NOW);
+        }
+        assert_eq!(
+            server.sign_in(EMAIL, PASSWORD, "phone", NOW).err(),
+            Some(AuthError::TooManyAttempts)
+        );
+        assert!(server
+            .sign_in(EMAIL, PASSWORD, "phone", NOW + LOCKOUT_SECONDS)
+            .is_ok());
+    }
+
+    #[test]
+    fn two_factor_sign_in_needs_a_code() {
+        let (mut server, secret, _) = enrolled();
+        assert!(server.two_factor_enabled(EMAIL));
+
+        let now = NOW + totp::PERIOD;
+        let pending = challenge(&mut server, now);
+        assert_eq!(
+            server.complete_sign_in(&pending, "000000", now).err(),
+            Some(AuthError::InvalidCode)
+        );
+        let code = totp::code_for_step(&secret, totp::step(now));
+        let token = server.complete_sign_in(&pending, &code, now).unwrap();
+        assert_eq!(server.authenticate(&token, now).unwrap().email, EMAIL);
+
+        // A challenge only counts once, and not after it expires
+        assert_eq!(
+            server.complete_sign_in(&pending, &code, now).err(),
+            Some(AuthError::SignInExpired)
+        );
+        let late = challenge(&mut server, now);
+        let later = now + CHALLENGE_LIFETIME;
+        let code = totp::code_for_step(&secret, totp::step(later));
+        assert_eq!(
+            server.complete_sign_in(&late, &code, later).err(),
+            Some(AuthError::SignInExpired)
+        );
+    }
+
+    #[test]
+    fn codes_cannot_be_replayed() {
+        let (mut server, secret, _) = enrolled();
+        // The code confirming enrollment is used up already
+        let enrollment_code = totp::code_for_step(&secret, totp::step(NOW));
+        let pending = challenge(&mut server, NOW);
+        assert_eq!(
+            server
+                .complete_sign_in(&pending, &enrollment_code, NOW)
+                .err(),
+            Some(AuthError::CodeAlreadyUsed)
+        );
+
+        let now = NOW + totp::PERIOD;
+        let code = totp::code_for_step(&secret, totp::step(now));
+        server.complete_sign_in(&pending, &code, now).unwrap();
+        let pending = challenge(&mut server, now);
+        assert_eq!(
+            server.complete_sign_in(&pending, &code, now).err(),
+            Some(AuthError::CodeAlreadyUsed)
+        );
+    }
+
+    #[test]
+    fn recovery_codes_work_once() {
+        let (mut server, _, codes) = enrolled();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        assert_eq!(server.recovery_codes_left(EMAIL), RECOVERY_CODE_COUNT);
+
+        let pending = challenge(&mut server, NOW);
+        // Typed loosely, as from paper
+        let typed = codes[3].to_uppercase().replace('-', " ");
+        assert!(server.complete_sign_in(&pending, &typed, NOW).is_ok());
+        assert_eq!(server.recovery_codes_left(EMAIL), RECOVERY_CODE_COUNT - 1);
+
+        let pending = challenge(&mut server, NOW);
+        assert_eq!(
+            server.complete_sign_in(&pending, &codes[3], NOW).err(),
+            Some(AuthError::InvalidCode)
+        );
+        assert!(server.complete_sign_in(&pending, &codes[4], NOW).is_ok());
+    }
+
+    #[test]
+    fn enrollment_needs_a_code_from_the_new_secret() {
+        let (mut server, _) = signed_up();
+        assert_eq!(
+            server.confirm_enrollment(EMAIL, "123456", NOW).err(),
+            Some(AuthError::NoEnrollment)
+        );
+        let secret = server.begin_enrollment(EMAIL);
+        let other = totp::code_for_step(&totp::generate_secret(), totp::step(NOW));
+        assert_eq!(
+            server.confirm_enrollment(EMAIL, &other, NOW).err(),
+            Some(AuthError::InvalidCode)
+        );
+        assert!(!server.two_factor_enabled(EMAIL));
+        let code = totp::code_for_step(&secret, totp::step(NOW));
+        assert!(server.confirm_enrollment(EMAIL, &code, NOW).is_ok());
+        assert!(server.two_factor_enabled(EMAIL));
+    }
+
+    #[test]
+    fn password_reset_replaces_the_password_and_ends_sessions() {
+        let (mut server, token) = signed_up();
+        server.request_password_reset(EMAIL, NOW);
+        let code = mailed_reset_code();
+        let wrong = if code == "000000" { "000001" } else { "000000" };
+        assert_eq!(
+            server
+                .reset_password(EMAIL, wrong, "new password", NOW)
+                .err(),
+            Some(AuthError::InvalidResetCode)
+        );
+        server
+            .reset_password(EMAIL, &code, "new password", NOW)
+            .unwrap();
+
+        assert!(server.authenticate(&token, NOW).is_none());
+        assert_eq!(
+            server.sign_in(EMAIL, PASSWORD, "laptop", NOW).err(),
+            Some(AuthError::WrongPassword)
+        );
+        assert!(server.sign_in(EMAIL, "new password", "laptop", NOW).is_ok());
+        // Codes work once
+        assert_eq!(
+            server
+                .reset_password(EMAIL, &code, "newer password", NOW)
+                .err(),
+            Some(AuthError::InvalidResetCode)
+        );
+    }
+
+    #[test]
+    fn reset_codes_expire() {
+        let (mut server, _) = signed_up();
+        server.request_password_reset(EMAIL, NOW);
+        let code = mailed_reset_code();
+        assert_eq!(
+            server
+                .reset_password(EMAIL, &code, "new password", NOW + RESET_LIFETIME)
+                .err(),
+            Some(AuthError::InvalidResetCode)
+        );
+    }
+
+    #[test]
+    fn revoking_elsewhere_ends_the_session_here() {
+        let (mut server, token) = signed_up();
+        let id = server.authenticate(&token, NOW).unwrap().session_id;
+        AuthServer::load().revoke_session(EMAIL, &id);
+        assert!(server.authenticate(&token, NOW).is_none());
+    }
+
+    #[test]
+    fn passwords_are_salted_and_verified() {
+        let first = PasswordHash::new(PASSWORD);
+        let second = PasswordHash::new(PASSWORD);
+        assert_ne!(first.hash, second.hash);
+        assert!(first.verify(PASSWORD));
+        assert!(!first.verify("correct horsE"));
+
+        let key_file = glib::KeyFile::new();
+        first.save(&key_file, "account", "password");
+        let loaded = PasswordHash::load(&key_file, "account", "password").unwrap();
+        assert!(loaded.verify(PASSWORD));
+        let entry = PasswordHash::for_token("abc").to_entry();
+        assert!(PasswordHash::from_entry(&entry).unwrap().verify("abc"));
+    }
+}
@@ -0,0 +1,913 @@
+// People, groups and conversations
+use crate::location::Location;
+use crate::profile;
+use crate::storage;
+use crate::util::{self, DAY, HOUR, MINUTE};
+use gtk4::glib;
+use std::path::PathBuf;
+
+const COMMUNITY_FILE: &str = "community.ini";
+const USER_PREFIX: &str = "user:";
+const GROUP_PREFIX: &str = "group:";
+const CONVERSATION_PREFIX: &str = "conversation:";
+const MESSAGE_PREFIX: &str = "message:";
+const REQUEST_PREFIX: &str = "request:";
+/// Blocked and muted users of the signed-in user.
+const SOCIAL_GROUP: &str = "social";
+
+/// Id of the signed-in user.
+pub const ME: &str = "me";
+
+#[derive(Clone, Debug)]
+pub struct User {
+    pub id: String,
+    pub name: String,
+    pub handle: String,
+    pub avatar: String,
+    pub headline: String,
+    /// Picture shown instead of the emoji avatar.
+    pub avatar_image: Option<PathBuf>,
+    /// Ids of the users this user follows.
+    pub following: Vec<String>,
+    /// Private profiles approve followers through follow requests.
+    pub private: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct FollowRequest {
+    pub from: String,
+    pub to: String,
+    pub requested_at: i64,
+}
+
+/// How the signed-in user relates to someone else.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Relationship {
+    Myself,
+    Blocked,
+    Following,
+    Requested,
+    NotFollowing,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FollowOutcome {
+    Following,
+    Requested,
+}
+
+#[derive(Clone, Debug)]
+pub struct Group {
+    pub id: String,
+    pub name: String,
+    pub emoji: String,
+    pub description: String,
+    pub member_count: u32,
+    pub joined: bool,
+    /// When the signed-in user joined, if known.
+    pub joined_at: Option<i64>,
+    /// Ids of members the app knows about, oldest first.
+    pub members: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Message {
+    pub sender: String,
+    pub text: String,
+    pub sent_at: i64,
+    /// Shown as a map preview under the text.
+    pub location: Option<Location>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Conversation {
+    pub id: String,
+    /// Other participants; the signed-in user is implied.
+    pub participants: Vec<String>,
+    /// Set for group chats; direct messages use the participant's name.
+    pub title: Option<String>,
+    pub messages: Vec<Message>,
+    pub unread: bool,
+}
+
+impl Conversation {
+    pub fn last_activity(&self) -> i64 {
+        self.messages.last().map_or(0, |m| m.sent_at)
+    }
+}
+
+pub struct Community {
+    users: Vec<User>,
+    groups: Vec<Group>,
+    conversations: Vec<Conversation>,
+    requests: Vec<FollowRequest>,
+    blocked: Vec<String>,
+    muted: Vec<String>,
+}
+
+impl Community {
+    pub fn load() -> Self {
+        let Some(key_file) = storage::load_key_file(COMMUNITY_FILE) else {
+            return Self::with_samples();
+        };
+
+        let users = storage::groups_with_prefix(&key_file, USER_PREFIX)
+            .into_iter()
+            .map(|(id, group)| User {
+                name: storage::string_or(&key_file, &group, "name", &id),
+                handle: storage::string_or(&key_file, &group, "handle", &id),
+                avatar: storage::string_or(&key_file, &group, "avatar", "👤"),
+                headline: storage::string_or(&key_file, &group, "headline", ""),
+                avatar_image: key_file
+                    .string(&group, "avatar_image")
+                    .ok()
+                    .map(|path| PathBuf::from(path.as_str())),
+                following: storage::list(&key_file, &group, "following"),
+                private: key_file.boolean(&group, "private").unwrap_or_default(),
+                id,
+            })
+            .collect();
+
+        let groups = storage::groups_with_prefix(&key_file, GROUP_PREFIX)
+            .into_iter()
+            .map(|(id, group)| Group {
+                name: storage::string_or(&key_file, &group, "name", &id),
+                emoji: storage::string_or(&key_file, &group, "emoji", "👥"),
+                description: storage::string_or(&key_file, &group, "description", ""),
+                member_count: key_file.integer(&group, "members").unwrap_or_default() as u32,
+                joined: key_file.boolean(&group, "joined").unwrap_or_default(),
+                joined_at: key_file.int64(&group, "joined_at").ok(),
+                members: storage::list(&key_file, &group, "members"),
+                id,
+            })
+            .collect();
+
+        let mut conversations: Vec<Conversation> =
+            storage::groups_with_prefix(&key_file, CONVERSATION_PREFIX)
+                .into_iter()
+                .map(|(id, group)| Conversation {
+                    participants: storage::list(&key_file, &group, "participants"),
+                    title: key_file.string(&group, "title").ok().map(String::from),
+                    messages: Vec::new(),
+                    unread: key_file.boolean(&group, "unread").unwrap_or_default(),
+                    id,
+                })
+                .collect();
+
+        // Messages are stored as "message:<conversation>:<index>"
+        let mut messages = storage::groups_with_prefix(&key_file, MESSAGE_PREFIX);
+        messages.sort_by_key(|(id, _)| {
+            id.rsplit_once(':')
+                .and_then(|(_, index)| index.parse::<usize>().ok())
+                .unwrap_or_default()
+        });
+        for (id, group) in messages {
+            let Some((conversation_id, _)) = id.rsplit_once(':') else {
+                continue;
+            };
+            if let Some(conversation) = conversations
+                .iter_mut()
+                .find(|conversation| conversation.id == conversation_id)
+            {
+                let location = match (
+                    key_file.double(&group, "latitude"),
+                    key_file.double(&group, "longitude"),
+                ) {
+                    (Ok(latitude), Ok(longitude)) => Some(Location {
+                        latitude,
+                        longitude,
+                        label: storage::string_or(&key_file, &group, "location_label", ""),
+                    }),
+                    _ => None,
+                };
+                conversation.messages.push(Message {
+                    sender: storage::string_or(&key_file, &group, "sender", ME),
+                    text: storage::string_or(&key_file, &group, "text", ""),
+                    sent_at: key_file.int64(&group, "sent_at").unwrap_or_default(),
+                    location,
+                });
+            }
+        }
+
+        // Follow requests are stored as "request:<from>:<to>"
+        let requests = storage::groups_with_prefix(&key_file, REQUEST_PREFIX)
+            .into_iter()
+            .filter_map(|(id, group)| {
+                let (from, to) = id.split_once(':')?;
+                Some(FollowRequest {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    requested_at: key_file.int64(&group, "requested_at").unwrap_or_default(),
+                })
+            })
+            .collect();
+
+        let mut community = Self {
+            users,
+            groups,
+            conversations,
+            requests,
+            blocked: storage::list(&key_file, SOCIAL_GROUP, "blocked"),
+            muted: storage::list(&key_file, SOCIAL_GROUP, "muted"),
+        };
+        community.sort_conversations();
+        community
+    }
+
+    fn with_samples() -> Self {
+        let now = util::now();
+        let user = |id: &str, name: &str, avatar: &str, headline: &str, following: &[&str]| User {
+            id: id.to_string(),
+            name: name.to_string(),
+            handle: if id == ME { "you" } else { id }.to_string(),
+            avatar: avatar.to_string(),
+            headline: headline.to_string(),
+            avatar_image: None,
+            following: following.iter().map(|id| id.to_string()).collect(),
+            private: id == "frank",
+        };
+        let users = vec![
+            user(
+                ME,
+                profile::PLACEHOLDER_NAME,
+                "👤",
+                "",
+                &["alice", "bob", "carol", "emma"],
+            ),
+            user(
+                "alice",
+                "Alice Johnson",
+                "🎯",
+                "Rust mentor · Systems programming",
+                &[ME, "bob", "emma"],
+            ),
+            user(
+                "bob",
+                "Bob Smith",
+                "🤖",
+                "Learning machine learning",
+                &[ME, "alice"],
+            ),
+            user(
+                "carol",
+                "Carol Williams",
+                "💻",
+                "Desktop apps with GTK",
+                &["alice"],
+            ),
+            user(
+                "david",
+                "David Brown",
+                "⚡",
+                "Async Rust enthusiast",
+                &[ME, "carol"],
+            ),
+            user("emma", "Emma Davis", "🌸", "UI/UX designer", &[ME, "alice"]),
+            user("frank", "Frank Miller", "🎸", "Career coach", &[ME]),
+            user(
+                "grace",
+                "Grace Lee",
+                "🌟",
+                "ML researcher and mentor",
+                &["frank"],
+            ),
+        ];
+        let requests = vec![FollowRequest {
+            from: "grace".to_string(),
+            to: ME.to_string(),
+            requested_at: now - 3 * HOUR,
+        }];
+
+        let joined_age = |id: &str| match id {
+            "rust-developers" => Some(30 * DAY),
+            "ui-ux-design" => Some(DAY),
+            _ => None,
+        };
+        let known_members = |id: &str| -> &[&str] {
+            match id {
+                "rust-developers" => &["alice", "carol"],
+                "ui-ux-design" => &["emma"],
+                _ => &[],
+            }
+        };
+        let group = |id: &str, name: &str, emoji: &str, description: &str, members: u32| Group {
+            id: id.to_string(),
+            name: name.to_string(),
+            emoji: emoji.to_string(),
+            description: description.to_string(),
+            member_count: members,
+            joined: joined_age(id).is_some(),
+            joined_at: joined_age(id).map(|age| now - age),
+            members: known_members(id).iter().map(|id| id.to_string()).collect(),
+        };
+        let groups = vec![
+            group(
+                "rust-developers",
+                "Rust Developers",
+                "🦀",
+                "Discuss Rust programming",
+                2500,
+            ),
+            group(
+                "ui-ux-design",
+                "UI/UX Design",
+                "🎨",
+                "Share design resources",
+                1800,
+            ),
+            group(
+                "career-mentorship",
+                "Career Mentorship",
+                "🎯",
+                "Get career guidance",
+                3200,
+            ),
+            group(
+                "open-source",
+                "Open Source",
+                "💻",
+                "Collaborate on projects",
+                4100,
+            ),
+        ];
+
+        let message = |sender: &str, text: &str, age: i64| Message {
+            sender: sender.to_string(),
+            text: text.to_string(),
+            sent_at: now - age,
+            location: None,
+        };
+        let direct = |id: &str, messages: Vec<Message>, unread: bool| Conversation {
+            id: format!("dm-{id}"),
+            participants: vec![id.to_string()],
+            title: None,
+            messages,
+            unread,
+        };
+        let conversations = vec![
+            direct(
+                "alice",
+                vec![
+                    message("alice", "Hey! How's your project going?", 20 * MINUTE),
+                    message(ME, "It's going great! Just implemented the UI", 15 * MINUTE),
+                    message("alice", "Awesome! Can I see a demo?", 10 * MINUTE),
+                    message(ME, "Sure! Let me share my screen", 5 * MINUTE),
+                    message("alice", "Great! Let's schedule for tomorrow", 2 * MINUTE),
+                ],
+                true,
+            ),
+            direct(
+                "bob",
+                vec![message("bob", "Thanks for the resources!", HOUR)],
+                false,
+            ),
+            direct(
+                "carol",
+                vec![message("carol", "Can you review my code?", 3 * HOUR)],
+                true,
+            ),
+            direct(
+                "david",
+                vec![message("david", "See you in the meeting!", DAY)],
+                false,
+            ),
+            Conversation {
+                id: "study-group".to_string(),
+                participants: vec!["alice".to_string(), "bob".to_string(), "emma".to_string()],
+                title: Some("Rust Study Group".to_string()),
+                messages: vec![message("emma", "Chapter 8 for next week?", 2 * DAY)],
+                unread: false,
+            },
+        ];
+
+        Self {
+            users,
+            groups,
+            conversations,
+            requests,
+            blocked: Vec::new(),
+            muted: Vec::new(),
+        }
+    }
+
+    pub fn save(&self) {
+        let key_file = glib::KeyFile::new();
+
+        for user in &self.users {
+            let group = format!("{USER_PREFIX}{}", user.id);
+            key_file.set_string(&group, "name", &user.name);
+            key_file.set_string(&group, "handle", &user.handle);
+            key_file.set_string(&group, "avatar", &user.avatar);
+            key_file.set_string(&group, "headline", &user.headline);
+            storage::set_list(&key_file, &group, "following", &user.following);
+            key_file.set_boolean(&group, "private", user.private);
+            if let Some(image) = &user.avatar_image {
+                key_file.set_string(&group, "avatar_image", &image.to_string_lossy());
+            }
+        }
+
+        for g in &self.groups {
+            let group = format!("{GROUP_PREFIX}{}", g.id);
+            key_file.set_string(&group, "name", &g.name);
+            key_file.set_string(&group, "emoji", &g.emoji);
+            key_file.set_string(&group, "description", &g.description);
+            key_file.set_integer(&group, "members", g.member_count as i32);
+            key_file.set_boolean(&group, "joined", g.joined);
+            if let Some(joined_at) = g.joined_at {
+                key_file.set_int64(&group, "joined_at", joined_at);
+            }
+            storage::set_list(&key_file, &group, "members", &g.members);
+        }
+
+        for conversation in &self.conversations {
+            let group = format!("{CONVERSATION_PREFIX}{}", conversation.id);
+            storage::set_list(
+                &key_file,
+                &group,
+                "participants",
+                &conversation.participants,
+            );
+            if let Some(title) = &conversation.title {
+                key_file.set_string(&group, "title", title);
+            }
+            key_file.set_boolean(&group, "unread", conversation.unread);
+
+            for (index, message) in conversation.messages.iter().enumerate() {
+                let group = format!("{MESSAGE_PREFIX}{}:{index}", conversation.id);
+                key_file.set_string(&group, "sender", &message.sender);
+                key_file.set_string(&group, "text", &message.text);
+                key_file.set_int64(&group, "sent_at", message.sent_at);
+                if let Some(location) = &message.location {
+                    key_file.set_double(&group, "latitude", location.latitude);
+                    key_file.set_double(&group, "longitude", location.longitude);
+                    key_file.set_string(&group, "location_label", &location.label);
+                }
+            }
+        }
+
+        for request in &self.requests {
+            let group = format!("{REQUEST_PREFIX}{}:{}", request.from, request.to);
+            key_file.set_int64(&group, "requested_at", request.requested_at);
+        }
+        storage::set_list(&key_file, SOCIAL_GROUP, "blocked", &self.blocked);
+        storage::set_list(&key_file, SOCIAL_GROUP, "muted", &self.muted);
+
+        storage::save_key_file(COMMUNITY_FILE, &key_file);
+    }
+
+    fn sort_conversations(&mut self) {
+        self.conversations
+            .sort_by_key(|conversation| std::cmp::Reverse(conversation.last_activity()));
+    }
+
+    pub fn users(&self) -> &[User] {
+        &self.users
+    }
+
+    pub fn user(&self, id: &str) -> Option<&User> {
+        self.users.iter().find(|user| user.id == id)
+    }
+
+    /// Display name for a user id, falling back to the id itself.
+    pub fn user_name(&self, id: &str) -> String {
+        self.user(id)
+            .map(|user| user.name.clone())
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    /// Users following `id`.
+    pub fn followers(&self, id: &str) -> Vec<&User> {
+        self.users
+            .iter()
+            .filter(|user| user.following.iter().any(|followed| followed == id))
+            .collect()
+    }
+
+    /// Users `id` follows.
+    pub fn following(&self, id: &str) -> Vec<&User> {
+        self.user(id)
+            .map(|user| {
+                user.following
+                    .iter()
+                    .filter_map(|followed| self.user(followed))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn groups(&self) -> &[Group] {
+        &self.groups
+    }
+
+    pub fn group(&self, id: &str) -> Option<&Group> {
+        self.groups.iter().find(|group| group.id == id)
+    }
+
+    /// Conversations ordered by most recent activity.
+    pub fn conversations(&self) -> &[Conversation] {
+        &self.conversations
+    }
+
+    pub fn conversation(&self, id: &str) -> Option<&Conversation> {
+        self.conversations
+            .iter()
+            .find(|conversation| conversation.id == id)
+    }
+
+    pub fn conversation_title(&self, conversation: &Conversation) -> String {
+        conversation.title.clone().unwrap_or_else(|| {
+            conversation
+                .participants
+                .iter()
+                .map(|id| self.user_name(id))
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+    }
+
+    /// The direct conversation with `user_id`, created if it doesn't exist.
+    pub fn direct_conversation(&mut self, user_id: &str) -> String {
+        if let Some(existing) = self.conversations.iter().find(|conversation| {
+            conversation.title.is_none() && conversation.participants == [user_id]
+        }) {
+            return existing.id.clone();
+        }
+
+        let id = format!("dm-{user_id}");
+        self.conversations.push(Conversation {
+            id: id.clone(),
+            participants: vec![user_id.to_string()],
+            title: None,
+            messages: Vec::new(),
+            unread: false,
+        });
+        id
+    }
+
+    /// Mirrors the signed-in user's profile name, picture and privacy.
+    pub fn update_me(&mut self, name: &str, avatar_image: Option<PathBuf>, private: bool) {
+        let Some(me) = self.users.iter_mut().find(|user| user.id == ME) else {
+            return;
+        };
+        me.name = name.to_string();
+        me.avatar_image = avatar_image;
+        self.set_private(private);
+    }
+
+    /// Whether the signed-in user approves new followers.
+    pub fn is_private(&self) -> bool {
+        self.user(ME).is_some_and(|me| me.private)
+    }
+
+    pub fn set_private(&mut self, private: bool) {
+        let Some(me) = self.users.iter_mut().find(|user| user.id == ME) else {
+            return;
+        };
+        let now_public = me.private && !private;
+        me.private = private;
+        if now_public {
+            // Going public lets everyone who asked follow right away
+            let pending: Vec<String> = self
+                .incoming_requests()
+                .iter()
+                .map(|request| request.from.clone())
+                .collect();
+            for from in pending {
+                self.accept_request(&from);
+            }
+        }
+        self.save();
+    }
+
+    // ------------------------------------------------------------------------
+    // Follow graph
+    // ------------------------------------------------------------------------
+
+    pub fn relationship(&self, id: &str) -> Relationship {
+        if id == ME {
+            Relationship::Myself
+        } else if self.is_blocked(id) {
+            Relationship::Blocked
+        } else if self
+            .user(ME)
+            .is_some_and(|me| me.following.iter().any(|f| f == id))
+        {
+            Relationship::Following
+        } else if self
+            .requests
+            .iter()
+            .any(|request| request.from == ME && request.to == id)
+        {
+            Relationship::Requested
+        } else {
+            Relationship::NotFollowing
+        }
+    }
+
+    /// Follows `id`, or asks to if their profile is private.
+    pub fn follow(&mut self, id: &str) -> Option<FollowOutcome> {
+        let target = self.user(id)?;
+        if id == ME || self.is_blocked(id) {
+            return None;
+        }
+        let outcome = if target.private {
+            if self.relationship(id) != Relationship::Requested {
+                self.requests.push(FollowRequest {
+                    from: ME.to_string(),
+                    to: id.to_string(),
+                    requested_at: util::now(),
+                });
+            }
+            FollowOutcome::Requested
+        } else {
+            self.add_follow(ME, id);
+            FollowOutcome::Following
+        };
+        self.save();
+        Some(outcome)
+    }
+
+    /// Stops following `id` or withdraws a pending request.
+    pub fn unfollow(&mut self, id: &str) {
+        self.remove_follow(ME, id);
+        self.requests
+            .retain(|request| !(request.from == ME && request.to == id));
+        self.save();
+    }
+
+    /// Follow requests waiting for the signed-in user, oldest first.
+    pub fn incoming_requests(&self) -> Vec<&FollowRequest> {
+        let mut requests: Vec<&FollowRequest> = self
+            .requests
+            .iter()
+            .filter(|request| request.to == ME && !self.is_blocked(&request.from))
+            .collect();
+        requests.sort_by_key(|request| request.requested_at);
+        requests
+    }
+
+    pub fn accept_request(&mut self, from: &str) {
+        if self.take_request(from) {
+            self.add_follow(from, ME);
+            self.save();
+        }
+    }
+
+    pub fn decline_request(&mut self, from: &str) {
+        if self.take_request(from) {
+            self.save();
+        }
+    }
+
+    fn take_request(&mut self, from: &str) -> bool {
+        let before = self.requests.len();
+        self.requests
+            .retain(|request| !(request.from == from && request.to == ME));
+        self.requests.len() != before
+    }
+
+    fn add_follow(&mut self, follower: &str, followed: &str) {
+        if let Some(user) = self.users.iter_mut().find(|user| user.id == follower) {
+            if !user.following.iter().any(|id| id == followed) {
+                user.following.push(followed.to_string());
+            }
+        }
+    }
+
+    fn remove_follow(&mut self, follower: &str, followed: &str) {
+        if let Some(user) = self.users.iter_mut().find(|user| user.id == follower) {
+            user.following.retain(|id| id != followed);
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Blocks and mutes
+    // ------------------------------------------------------------------------
+
+    pub fn is_blocked(&self, id: &str) -> bool {
+        self.blocked.iter().any(|blocked| blocked == id)
+    }
+
+    pub fn is_muted(&self, id: &str) -> bool {
+        self.muted.iter().any(|muted| muted == id)
+    }
+
+    /// Blocked users are hidden everywhere, muted ones only from the feed.
+    pub fn hidden_from_feed(&self, id: &str) -> bool {
+        self.is_blocked(id) || self.is_muted(id)
+    }
+
+    /// Blocking cuts follows and requests both ways and stops messages.
+    pub fn block(&mut self, id: &str) {
+        if id == ME || self.is_blocked(id) {
+            return;
+        }
+        self.blocked.push(id.to_string());
+        self.remove_follow(ME, id);
+        self.remove_follow(id, ME);
+        self.requests.retain(|request| {
+            !(request.from == ME && request.to == id || request.from == id && request.to == ME)
+        });
+        self.save();
+    }
+
+    pub fn unblock(&mut self, id: &str) {
+        self.blocked.retain(|blocked| blocked != id);
+        self.save();
+    }
+
+    pub fn mute(&mut self, id: &str) {
+        if id != ME && !self.is_muted(id) {
+            self.muted.push(id.to_string());
+            self.save();
+        }
+    }
+
+    pub fn unmute(&mut self, id: &str) {
+        self.muted.retain(|muted| muted != id);
+        self.save();
+    }
+
+    pub fn blocked_users(&self) -> Vec<&User> {
+        self.blocked.iter().filter_map(|id| self.user(id)).collect()
+    }
+
+    pub fn muted_users(&self) -> Vec<&User> {
+        self.muted.iter().filter_map(|id| self.user(id)).collect()
+    }
+
+    /// A direct chat with a blocked user, which is hidden and can't be used.
+    pub fn is_blocked_conversation(&self, conversation: &Conversation) -> bool {
+        conversation.title.is_none()
+            && conversation
+                .participants
+                .iter()
+                .any(|id| self.is_blocked(id))
+    }
+
+    pub fn send_message(
+        &mut self,
+        conversation_id: &str,
+        sender: &str,
+        text: &str,
+        location: Option<Location>,
+    ) -> bool {
+        if self.is_blocked(sender)
+            || self
+                .conversation(conversation_id)
+                .is_some_and(|conversation| self.is_blocked_conversation(conversation))
+        {
+            return false;
+        }
+        let Some(conversation) = self
+            .conversations
+            .iter_mut()
+            .find(|conversation| conversation.id == conversation_id)
+        else {
+            return false;
+        };
+
+        conversation.messages.push(Message {
+            sender: sender.to_string(),
+            text: text.to_string(),
+            sent_at: util::now(),
+            location,
+        });
+        if sender != ME {
+            conversation.unread = true;
+        }
+        self.sort_conversations();
+        self.save();
+        true
+    }
+
+    pub fn mark_read(&mut self, conversation_id: &str) {
+        if let Some(conversation) = self
+            .conversations
+            .iter_mut()
+            .find(|conversation| conversation.id == conversation_id && conversation.unread)
+        {
+            conversation.unread = false;
+            self.save();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn follows(community: &Community, follower: &str, followed: &str) -> bool {
+        community
+            .user(follower)
+            .is_some_and(|user| user.following.iter().any(|id| id == followed))
+    }
+
+    fn has_request(community: &Community, from: &str, to: &str) -> bool {
+        community
+            .requests
+            .iter()
+            .any(|request| request.from == from && request.to == to)
+    }
+
+    #[test]
+    fn public_profiles_are_followed_directly() {
+        let mut community = Community::with_samples();
+        assert_eq!(community.relationship("david"), Relationship::NotFollowing);
+        assert_eq!(community.follow("david"), Some(FollowOutcome::Following));
+        assert_eq!(community.relationship("david"), Relationship::Following);
+        assert!(!has_request(&community, ME, "david"));
+
+        community.unfollow("david");
+        assert_eq!(community.relationship("david"), Relationship::NotFollowing);
+    }
+
+    #[test]
+    fn private_profiles_get_a_request() {
+        let mut community = Community::with_samples();
+        assert_eq!(community.follow("frank"), Some(FollowOutcome::Requested));
+        assert_eq!(community.follow("frank"), Some(FollowOutcome::Requested));
+        assert_eq!(community.relationship("frank"), Relationship::Requested);
+        assert!(!follows(&community, ME, "frank"));
+        assert_eq!(
+            community
+                .requests
+                .iter()
+                .filter(|request| request.to == "frank")
+                .count(),
+            1
+        );
+
+        community.unfollow("frank");
+        assert_eq!(community.relationship("frank"), Relationship::NotFollowing);
+    }
+
+    #[test]
+    fn accepting_a_request_adds_a_follower() {
+        let mut community = Community::with_samples();
+        assert_eq!(community.incoming_requests().len(), 1);
+        community.accept_request("grace");
+        assert!(community.incoming_requests().is_empty());
+        assert!(follows(&community, "grace", ME));
+    }
+
+    #[test]
+    fn nobody_follows_themselves_or_strangers() {
+        let mut community = Community::with_samples();
+        assert_eq!(community.follow(ME), None);
+        assert_eq!(community.follow("nobody"), None);
+    }
+
+    #[test]
+    fn blocking_cuts_follows_both_ways() {
+        let mut community = Community::with_samples();
+        assert!(follows(&community, ME, "alice") && follows(&community, "alice", ME));
+        community.block("alice");
+        assert!(!follows(&community, ME, "alice"));
+        assert!(!follows(&community, "alice", ME));
+        assert_eq!(community.relationship("alice"), Relationship::Blocked);
+        assert_eq!(community.follow("alice"), None);
+
+        community.unblock("alice");
+        assert_eq!(community.relationship("alice"), Relationship::NotFollowing);
+    }
+
+    #[test]
+    fn blocking_drops_requests_both_ways() {
+        let mut community = Community::with_samples();
+        community.block("grace");
+        assert!(!has_request(&community, "grace", ME));
+        assert!(community.incoming_requests().is_empty());
+
+        community.follow("frank");
+        community.block("frank");
+        assert!(!has_request(&community, ME, "frank"));
+        assert!(!follows(&community, "frank", ME));
+    }
+
+    #[test]
+    fn blocked_direct_conversations_refuse_messages() {
+        let mut community = Community::with_samples();
+        community.block("alice");
+        let before = community.conversation("dm-alice").unwrap().messages.len();
+        assert!(!community.send_message("dm-alice", ME, "Hello?", None));
+        assert!(!community.send_message("dm-alice", "alice", "Hi!", None));
+        assert_eq!(
+            community.conversation("dm-alice").unwrap().messages.len(),
+            before
+        );
+
+        // Group chats stay usable, but the blocked person can't post there
+        assert!(community.send_message("study-group", ME, "Yes!", None));
+        assert!(!community.send_message("study-group", "alice", "Me too", None));
+
+        community.unblock("alice");
+        assert!(community.send_message("dm-alice", ME, "Hello again", None));
+    }
+
+    #[test]
+    fn messages_to_unknown_conversations_are_refused() {
+        let mut community = Community::with_samples();
+        assert!(!community.send_message("dm-nobody", ME, "Hello?", None));
+    }
+}
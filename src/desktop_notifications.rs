@@ -0,0 +1,478 @@
+// Desktop notifications for new messages and mentions while the window is
+// hidden or unfocused, with `app.` actions behind their buttons, plus the
+// periodic digest and saved search alerts
+use crate::community::ME;
+use crate::i18n::{tr, tr_with, trn};
+use crate::notifications::{self, NotificationFilter, NotificationKind};
+use crate::notifications_view;
+use crate::preferences::DigestMode;
+use crate::search::{SearchIndex, SearchTab};
+use crate::search_query;
+use crate::session;
+use crate::state::AppState;
+use crate::util;
+use gtk4::prelude::*;
+use gtk4::{gdk, gio, glib, Application};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// Longest message preview in a notification body.
+const PREVIEW_LENGTH: usize = 120;
+
+/// How often, in seconds, to check whether a digest is due and for new
+/// jobs and events.
+const BACKGROUND_CHECK_INTERVAL: u32 = 300;
+
+/// Seconds to wait after a change before running the saved searches, so a
+/// burst of changes indexes everything once.
+const SAVED_SEARCH_DELAY: u32 = 2;
+
+struct DesktopNotifier {
+    app: Application,
+    state: Rc<AppState>,
+    /// Messages per conversation already seen, so only new ones alert.
+    message_counts: RefCell<HashMap<String, usize>>,
+    /// Notification keys already considered for an alert.
+    alerted: RefCell<HashSet<String>>,
+    /// Whose messages and notifications the two above count.
+    account: RefCell<String>,
+    /// The saved searches waiting to run, see `schedule_saved_searches`.
+    pending_searches: RefCell<Option<glib::SourceId>>,
+}
+
+/// Registers the notification actions on `app` and starts sending desktop
+/// notifications for messages and mentions that arrive from now on.
+pub fn install(app: &Application, state: &Rc<AppState>) {
+    let notifier = Rc::new(DesktopNotifier {
+        app: app.clone(),
+        state: state.clone(),
+        message_counts: RefCell::new(message_counts(state)),
+        alerted: RefCell::new(notification_keys(state)),
+        account: RefCell::new(session::email(state)),
+        pending_searches: RefCell::new(None),
+    });
+
+    let n = notifier.clone();
+    add_action(app, "open-conversation", move |id| n.open_conversation(id));
+    let n = notifier.clone();
+    add_action(app, "reply-conversation", move |id| {
+        n.reply_conversation(id)
+    });
+    let n = notifier.clone();
+    add_action(app, "mark-conversation-read", move |id| {
+        n.mark_conversation_read(id)
+    });
+    let n = notifier.clone();
+    add_action(app, "open-notification", move |key| {
+        n.open_notification(key)
+    });
+    let n = notifier.clone();
+    add_action(app, "mark-notification-read", move |key| {
+        n.mark_notification_read(key)
+    });
+
+    let n = notifier.clone();
+    add_action(app, "show-page", move |name| n.show_page(name));
+    let n = notifier.clone();
+    add_action(app, "run-search", move |query| n.run_search(query));
+
+    let n = notifier.clone();
+    glib::timeout_add_seconds_local(BACKGROUND_CHECK_INTERVAL, move || {
+        n.check_digest();
+        if n.state.listings.borrow_mut().reload() {
+            n.state.listings_changed.emit();
+        }
+        glib::ControlFlow::Continue
+    });
+    for listeners in [
+        &state.feed_changed,
+        &state.media_changed,
+        &state.listings_changed,
+    ] {
+        let n = notifier.clone();
+        listeners.connect(move || n.schedule_saved_searches());
+    }
+
+    let n = notifier.clone();
+    state.community_changed.connect(move || {
+        n.check_messages();
+        n.schedule_saved_searches();
+    });
+    state
+        .notifications_changed
+        .connect(move || notifier.check_mentions());
+}
+
+fn add_action(app: &Application, name: &str, activate: impl Fn(&str) + 'static) {
+    let action = gio::SimpleAction::new(name, Some(glib::VariantTy::STRING));
+    action.connect_activate(move |_, parameter| {
+        if let Some(value) = parameter.and_then(|parameter| parameter.str()) {
+            activate(value);
+        }
+    });
+    app.add_action(&action);
+}
+
+fn message_counts(state: &AppState) -> HashMap<String, usize> {
+    state
+        .community
+        .borrow()
+        .conversations()
+        .iter()
+        .map(|conversation| (conversation.id.clone(), conversation.messages.len()))
+        .collect()
+}
+
+fn notification_keys(state: &AppState) -> HashSet<String> {
+    state
+        .notifications
+        .borrow()
+        .visible(&state.community.borrow(), NotificationFilter::All)
+        .into_iter()
+        .map(|notification| notification.key)
+        .collect()
+}
+
+fn preview(text: &str) -> String {
+    match text.char_indices().nth(PREVIEW_LENGTH) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+impl DesktopNotifier {
+    /// Alerts only go out when enabled, outside Do Not Disturb and quiet
+    /// hours, and while the window isn't in front.
+    fn should_alert(&self) -> bool {
+        let prefs = &self.state.preferences;
+        prefs.push_notifications()
+            && !prefs.alerts_paused(glib::DateTime::now_local().map_or(0, |now| now.hour() as u32))
+            && !self
+                .app
+                .active_window()
+                .is_some_and(|window| window.is_visible() && window.is_active())
+    }
+
+    fn send(&self, id: &str, notification: &gio::Notification) {
+        self.app.send_notification(Some(id), notification);
+        if self.state.preferences.sound() {
+            if let Some(display) = gdk::Display::default() {
+                display.beep();
+            }
+        }
+    }
+
+    /// After signing in as someone else, what their account already held
+    /// isn't news: starts counting afresh and returns true.
+    fn switched_account(&self) -> bool {
+        let email = session::email(&self.state);
+        if *self.account.borrow() == email {
+            return false;
+        }
+        self.message_counts.replace(message_counts(&self.state));
+        self.alerted.replace(notification_keys(&self.state));
+        self.account.replace(email);
+        true
+    }
+
+    fn check_messages(&self) {
+        if self.switched_account() {
+            return;
+        }
+        let previous = self.message_counts.replace(message_counts(&self.state));
+        if !self.should_alert() {
+            return;
+        }
+
+        let community = self.state.community.borrow();
+        for conversation in community.conversations() {
+            if community.is_blocked_conversation(conversation) {
+                continue;
+            }
+            let seen = previous.get(&conversation.id).copied().unwrap_or(0);
+            let incoming: Vec<_> = conversation
+                .messages
+                .iter()
+                .skip(seen)
+                .filter(|message| {
+                    message.sender != ME && !community.hidden_from_feed(&message.sender)
+                })
+                .collect();
+            let Some(last) = incoming.last() else {
+                continue;
+            };
+
+            let title = match &conversation.title {
+                Some(title) => tr_with(
+                    "{name} in {conversation}",
+                    &[
+                        ("name", &community.user_name(&last.sender)),
+                        ("conversation", title),
+                    ],
+                ),
+                None => community.user_name(&last.sender),
+            };
+            let body = match incoming.len() {
+                1 => preview(&last.text),
+                count => trn(
+                    "{n} new message · {preview}",
+                    "{n} new messages · {preview}",
+                    count as u64,
+                )
+                .replace("{preview}", &preview(&last.text)),
+            };
+            let notification = gio::Notification::new(&title);
+            notification.set_body(Some(&body));
+            notification.set_priority(gio::NotificationPriority::High);
+            let id = conversation.id.to_variant();
+            notification.set_default_action_and_target_value("app.open-conversation", Some(&id));
+            notification.add_button_with_target_value(
+                &tr("Reply"),
+                "app.reply-conversation",
+                Some(&id),
+            );
+            notification.add_button_with_target_value(
+                &tr("Mark read"),
+                "app.mark-conversation-read",
+                Some(&id),
+            );
+            self.send(&format!("conversation:{}", conversation.id), &notification);
+        }
+    }
+
+    fn check_mentions(&self) {
+        if self.switched_account() {
+            return;
+        }
+        let visible = self
+            .state
+            .notifications
+            .borrow()
+            .visible(&self.state.community.borrow(), NotificationFilter::Mentions);
+        let fresh: Vec<_> = {
+            let mut alerted = self.alerted.borrow_mut();
+            visible
+                .into_iter()
+                .filter(|notification| alerted.insert(notification.key.clone()))
+                .filter(|notification| {
+                    notification.kind == NotificationKind::Mention && !notification.read
+                })
+                .collect()
+        };
+        // With a digest on, mentions wait for the next summary
+        let digest = self.state.preferences.digest();
+        if fresh.is_empty() || digest != DigestMode::Off || !self.should_alert() {
+            return;
+        }
+
+        for mention in fresh {
+            let name = self.state.community.borrow().user_name(&mention.actor);
+            let notification =
+                gio::Notification::new(&tr_with("{name} mentioned you", &[("name", &name)]));
+            notification.set_body(Some(&mention.text));
+            let key = mention.key.to_variant();
+            notification.set_default_action_and_target_value("app.open-notification", Some(&key));
+            notification.add_button_with_target_value(
+                &tr("Open"),
+                "app.open-notification",
+                Some(&key),
+            );
+            notification.add_button_with_target_value(
+                &tr("Mark read"),
+                "app.mark-notification-read",
+                Some(&key),
+            );
+            self.send(&format!("notification:{}", mention.key), &notification);
+        }
+    }
+
+    /// Sends one notification summing up what arrived since the last
+    /// digest, once the daily or weekly period has passed.
+    fn check_digest(&self) {
+        let Some(period) = self.state.preferences.digest().period() else {
+            return;
+        };
+        let now = util::now();
+        let last = self.state.notifications.borrow().last_digest_at();
+        if last == 0 {
+            self.state
+                .notifications
+                .borrow_mut()
+                .set_last_digest_at(now);
+            return;
+        }
+        if now - last < period || !self.should_alert() {
+            return;
+        }
+
+        let pending: Vec<_> = self
+            .state
+            .notifications
+            .borrow()
+            .visible(&self.state.community.borrow(), NotificationFilter::All)
+            .into_iter()
+            .filter(|notification| !notification.read && notification.created_at > last)
+            .collect();
+        if !pending.is_empty() {
+            let title = match self.state.preferences.digest() {
+                DigestMode::Weekly => tr("Your weekly digest"),
+                _ => tr("Your daily digest"),
+            };
+            let notification = gio::Notification::new(&title);
+            notification.set_body(Some(&notifications::summarize(&pending)));
+            notification.set_default_action_and_target_value(
+                "app.show-page",
+                Some(&"notifications".to_variant()),
+            );
+            self.send("digest", &notification);
+        }
+        self.state
+            .notifications
+            .borrow_mut()
+            .set_last_digest_at(now);
+    }
+
+    /// Runs the saved searches shortly, once whatever changed has settled.
+    fn schedule_saved_searches(self: &Rc<Self>) {
+        if self.pending_searches.borrow().is_some() {
+            return;
+        }
+        let n = self.clone();
+        let source = glib::timeout_add_seconds_local_once(SAVED_SEARCH_DELAY, move || {
+            n.pending_searches.take();
+            n.check_saved_searches();
+        });
+        self.pending_searches.replace(Some(source));
+    }
+
+    /// Runs every saved search and counts results newer than its last run;
+    /// those with alerts on also get a desktop notification.
+    fn check_saved_searches(&self) {
+        let saved: Vec<_> = self.state.searches.borrow().saved().to_vec();
+        if saved.is_empty() {
+            return;
+        }
+        let index = SearchIndex::build(
+            &self.state.community.borrow(),
+            &self.state.feed.borrow(),
+            &self.state.media.borrow(),
+            &self.state.listings.borrow(),
+        );
+        let now = util::now();
+        let mut found_any = false;
+        for search in saved {
+            let (query, _) = search_query::parse(&search.query);
+            // Your own posts and messages aren't news to you
+            let found = index
+                .search(&query, SearchTab::All)
+                .iter()
+                .filter(|result| {
+                    result.timestamp > search.checked_at && result.author.as_deref() != Some(ME)
+                })
+                .count() as u32;
+            self.state
+                .searches
+                .borrow_mut()
+                .add_new_results(&search.id, found, now);
+            if found == 0 {
+                continue;
+            }
+            found_any = true;
+            if !search.alerts || !self.should_alert() {
+                continue;
+            }
+            let notification = gio::Notification::new(&tr_with(
+                "New results for “{query}”",
+                &[("query", &search.query)],
+            ));
+            notification.set_body(Some(&trn(
+                "{n} new match for your saved search",
+                "{n} new matches for your saved search",
+                (search.new_results + found).into(),
+            )));
+            notification.set_default_action_and_target_value(
+                "app.run-search",
+                Some(&search.query.to_variant()),
+            );
+            self.send(&format!("saved-search:{}", search.id), &notification);
+        }
+        if found_any {
+            self.state.searches_changed.emit();
+        }
+    }
+
+    /// The main window, built first if the app was started by a
+    /// notification.
+    fn window(&self) -> Option<gtk4::Window> {
+        if self.app.windows().is_empty() {
+            self.app.activate();
+        }
+        let window = self
+            .app
+            .active_window()
+            .or_else(|| self.app.windows().first().cloned())?;
+        window.present();
+        Some(window)
+    }
+
+    fn show_page(&self, name: &str) {
+        if let Some(window) = self.window() {
+            let _ = window.activate_action("win.show-page", Some(&name.to_variant()));
+        }
+    }
+
+    /// Shows the search page with `query` filled in.
+    fn run_search(&self, query: &str) {
+        self.state.searches.borrow_mut().request(query);
+        self.state.searches_changed.emit();
+        self.show_page("search");
+    }
+
+    fn open_conversation(&self, id: &str) {
+        self.show_conversation("win.open-conversation", id);
+    }
+
+    /// Opens the conversation with the message entry focused.
+    fn reply_conversation(&self, id: &str) {
+        self.show_conversation("win.reply-conversation", id);
+    }
+
+    fn show_conversation(&self, action: &str, id: &str) {
+        if let Some(window) = self.window() {
+            let _ = window.activate_action(action, Some(&id.to_variant()));
+        }
+        self.app
+            .withdraw_notification(&format!("conversation:{id}"));
+    }
+
+    fn mark_conversation_read(&self, id: &str) {
+        self.state.community.borrow_mut().mark_read(id);
+        self.state.community_changed.emit();
+        self.app
+            .withdraw_notification(&format!("conversation:{id}"));
+    }
+
+    fn open_notification(&self, key: &str) {
+        let target = self
+            .state
+            .notifications
+            .borrow()
+            .get(key)
+            .map(|notification| notification.target.clone());
+        if let (Some(window), Some(target)) = (self.window(), target) {
+            notifications_view::open_target(&window, &target);
+        }
+        self.mark_notification_read(key);
+    }
+
+    fn mark_notification_read(&self, key: &str) {
+        self.state
+            .notifications
+            .borrow_mut()
+            .mark_read(&[key.to_string()]);
+        self.state.notifications_changed.emit();
+        self.app
+            .withdraw_notification(&format!("notification:{key}"));
+    }
+}
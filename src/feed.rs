@@ -0,0 +1,411 @@
+// Posts shown in the home feed
+use crate::community::ME;
+use crate::i18n::tr;
+use crate::location::Location;
+use crate::poll::{Poll, Vote};
+use crate::storage;
+use crate::util::{self, DAY, HOUR, MINUTE};
+use gtk4::glib;
+
+const FEED_FILE: &str = "feed.ini";
+const POST_PREFIX: &str = "post:";
+const VOTE_PREFIX: &str = "vote:";
+const COMMENT_PREFIX: &str = "comment:";
+const LIKE_PREFIX: &str = "like:";
+
+#[derive(Clone, Debug)]
+pub struct Comment {
+    pub author: String,
+    pub text: String,
+    pub created_at: i64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Like {
+    pub user: String,
+    pub liked_at: i64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Post {
+    pub id: String,
+    /// User id of the author.
+    pub author: String,
+    pub text: String,
+    pub created_at: i64,
+    pub poll: Option<Poll>,
+    pub location: Option<Location>,
+    /// Oldest first.
+    pub comments: Vec<Comment>,
+    pub likes: Vec<Like>,
+}
+
+impl Post {
+    pub fn liked_by(&self, user: &str) -> bool {
+        self.likes.iter().any(|like| like.user == user)
+    }
+
+    /// Short title used where the whole text doesn't fit, e.g. link lists.
+    pub fn title(&self) -> String {
+        let first_line = match (self.text.lines().next(), &self.poll) {
+            (Some(line), _) if !line.trim().is_empty() => line.to_string(),
+            (_, Some(poll)) => format!("📊 {}", poll.question),
+            _ => self
+                .location
+                .as_ref()
+                .map(|location| format!("📍 {}", location.title()))
+                .unwrap_or_default(),
+        };
+        match first_line.char_indices().nth(48) {
+            Some((end, _)) => format!("{}…", &first_line[..end]),
+            None => first_line,
+        }
+    }
+}
+
+pub struct Feed {
+    posts: Vec<Post>,
+}
+
+impl Feed {
+    pub fn load() -> Self {
+        let Some(key_file) = storage::load_key_file(FEED_FILE) else {
+            return Self::with_samples();
+        };
+
+        let mut posts: Vec<Post> = storage::groups_with_prefix(&key_file, POST_PREFIX)
+            .into_iter()
+            .map(|(id, group)| {
+                let poll = key_file
+                    .string(&group, "poll_question")
+                    .ok()
+                    .map(|question| Poll {
+                        question: question.to_string(),
+                        options: storage::list(&key_file, &group, "poll_options"),
+                        multiple: key_file
+                            .boolean(&group, "poll_multiple")
+                            .unwrap_or_default(),
+                        anonymous: key_file
+                            .boolean(&group, "poll_anonymous")
+                            .unwrap_or_default(),
+                        closes_at: key_file.int64(&group, "poll_closes_at").ok(),
+                        votes: Vec::new(),
+                    });
+                let location = match (
+                    key_file.double(&group, "latitude"),
+                    key_file.double(&group, "longitude"),
+                ) {
+                    (Ok(latitude), Ok(longitude)) => Some(Location {
+                        latitude,
+                        longitude,
+                        label: storage::string_or(&key_file, &group, "location_label", ""),
+                    }),
+                    _ => None,
+                };
+                Post {
+                    author: storage::string_or(&key_file, &group, "author", ""),
+                    text: storage::string_or(&key_file, &group, "text", ""),
+                    created_at: key_file.int64(&group, "created_at").unwrap_or_default(),
+                    poll,
+                    location,
+                    comments: Vec::new(),
+                    likes: Vec::new(),
+                    id,
+                }
+            })
+            .collect();
+
+        // Votes are stored as "vote:<post>:<user>"
+        for (id, group) in storage::groups_with_prefix(&key_file, VOTE_PREFIX) {
+            let Some((post_id, user)) = id.split_once(':') else {
+                continue;
+            };
+            let Some(poll) = posts
+                .iter_mut()
+                .find(|post| post.id == post_id)
+                .and_then(|post| post.poll.as_mut())
+            else {
+                continue;
+            };
+            poll.votes.push(Vote {
+                user: user.to_string(),
+                choices: storage::list(&key_file, &group, "choices")
+                    .iter()
+                    .filter_map(|choice| choice.parse().ok())
+                    .collect(),
+                voted_at: key_file.int64(&group, "voted_at").unwrap_or_default(),
+            });
+        }
+
+        // Comments are stored as "comment:<post>:<index>"
+        let mut comments = storage::groups_with_prefix(&key_file, COMMENT_PREFIX);
+        comments.sort_by_key(|(id, _)| {
+            id.rsplit_once(':')
+                .and_then(|(_, index)| index.parse::<usize>().ok())
+                .unwrap_or_default()
+        });
+        for (id, group) in comments {
+            let Some((post_id, _)) = id.rsplit_once(':') else {
+                continue;
+            };
+            if let Some(post) = posts.iter_mut().find(|post| post.id == post_id) {
+                post.comments.push(Comment {
+                    author: storage::string_or(&key_file, &group, "author", ""),
+                    text: storage::string_or(&key_file, &group, "text", ""),
+                    created_at: key_file.int64(&group, "created_at").unwrap_or_default(),
+                });
+            }
+        }
+
+        // Likes are stored as "like:<post>:<user>"
+        for (id, group) in storage::groups_with_prefix(&key_file, LIKE_PREFIX) {
+            let Some((post_id, user)) = id.split_once(':') else {
+                continue;
+            };
+            if let Some(post) = posts.iter_mut().find(|post| post.id == post_id) {
+                post.likes.push(Like {
+                    user: user.to_string(),
+                    liked_at: key_file.int64(&group, "liked_at").unwrap_or_default(),
+                });
+            }
+        }
+
+        posts.sort_by_key(|post| std::cmp::Reverse(post.created_at));
+        Self { posts }
+    }
+
+    fn with_samples() -> Self {
+        let now = util::now();
+        let post = |id: &str, author: &str, text: &str, age: i64| Post {
+            id: id.to_string(),
+            author: author.to_string(),
+            text: text.to_string(),
+            created_at: now - age,
+            poll: None,
+            location: None,
+            comments: Vec::new(),
+            likes: Vec::new(),
+        };
+        let comment = |author: &str, text: &str, age: i64| Comment {
+            author: author.to_string(),
+            text: text.to_string(),
+            created_at: now - age,
+        };
+        let like = |user: &str, age: i64| Like {
+            user: user.to_string(),
+            liked_at: now - age,
+        };
+
+        let mut toolkit_poll = post("sample-poll", "emma", "", 3 * HOUR);
+        let vote = |user: &str, choices: Vec<usize>| Vote {
+            user: user.to_string(),
+            choices,
+            voted_at: now - 2 * HOUR,
+        };
+        toolkit_poll.poll = Some(Poll {
+            question: "Which GUI toolkit are you learning next?".to_string(),
+            options: ["GTK4", "Qt", "egui", "Slint"].map(String::from).to_vec(),
+            multiple: false,
+            anonymous: false,
+            closes_at: Some(now + 2 * DAY),
+            votes: vec![
+                vote("alice", vec![0]),
+                vote("bob", vec![2]),
+                vote("carol", vec![0]),
+                vote("frank", vec![3]),
+            ],
+        });
+
+        let mut rust_session = post(
+            "sample-1",
+            "alice",
+            "Just completed an amazing mentoring session on Rust! 🦀 #Rust #Mentorship",
+            2 * HOUR,
+        );
+        rust_session.comments = vec![comment(ME, "Congrats! Which topics did you cover?", HOUR)];
+        let mut ml_mentors = post(
+            "sample-2",
+            "bob",
+            "Looking for mentors in Machine Learning. Any recommendations? #MachineLearning #Mentorship",
+            5 * HOUR,
+        );
+        ml_mentors.comments = vec![
+            comment(
+                "carol",
+                "Grace ran a great ML workshop last month.",
+                4 * HOUR,
+            ),
+            comment(ME, "+1 for Grace! #Mentorship", 3 * HOUR),
+        ];
+        ml_mentors.likes = vec![like(ME, 4 * HOUR)];
+        let mut list_models = post(
+            "sample-5",
+            ME,
+            "Wrote up my notes on GTK4 list models — happy to walk anyone through them! #GTK4 #Rust",
+            6 * HOUR,
+        );
+        list_models.comments = vec![
+            comment("bob", "This is exactly what I needed, thanks!", 5 * HOUR),
+            comment(
+                "emma",
+                "@you could you cover custom sorters next?",
+                30 * MINUTE,
+            ),
+        ];
+        list_models.likes = vec![
+            like("alice", 5 * HOUR),
+            like("david", 2 * HOUR),
+            like("emma", 20 * MINUTE),
+        ];
+
+        Self {
+            posts: vec![
+                rust_session,
+                toolkit_poll,
+                ml_mentors,
+                list_models,
+                post(
+                    "sample-3",
+                    "carol",
+                    "Sharing my latest project: A GTK4 social media app! Check it out 🚀 #GTK4 #OpenSource",
+                    DAY,
+                ),
+                post(
+                    "sample-4",
+                    "david",
+                    "Thanks to my mentor, I finally understood async/await! 🎉 #Rust #Mentorship",
+                    2 * DAY,
+                ),
+            ],
+        }
+    }
+
+    pub fn save(&self) {
+        let key_file = glib::KeyFile::new();
+
+        for post in &self.posts {
+            let group = format!("{POST_PREFIX}{}", post.id);
+            key_file.set_string(&group, "author", &post.author);
+            key_file.set_string(&group, "text", &post.text);
+            key_file.set_int64(&group, "created_at", post.created_at);
+            if let Some(location) = &post.location {
+                key_file.set_double(&group, "latitude", location.latitude);
+                key_file.set_double(&group, "longitude", location.longitude);
+                key_file.set_string(&group, "location_label", &location.label);
+            }
+            for (index, comment) in post.comments.iter().enumerate() {
+                let group = format!("{COMMENT_PREFIX}{}:{index}", post.id);
+                key_file.set_string(&group, "author", &comment.author);
+                key_file.set_string(&group, "text", &comment.text);
+                key_file.set_int64(&group, "created_at", comment.created_at);
+            }
+            for like in &post.likes {
+                let group = format!("{LIKE_PREFIX}{}:{}", post.id, like.user);
+                key_file.set_int64(&group, "liked_at", like.liked_at);
+            }
+
+            let Some(poll) = &post.poll else {
+                continue;
+            };
+            key_file.set_string(&group, "poll_question", &poll.question);
+            storage::set_list(&key_file, &group, "poll_options", &poll.options);
+            key_file.set_boolean(&group, "poll_multiple", poll.multiple);
+            key_file.set_boolean(&group, "poll_anonymous", poll.anonymous);
+            if let Some(closes_at) = poll.closes_at {
+                key_file.set_int64(&group, "poll_closes_at", closes_at);
+            }
+
+            for vote in &poll.votes {
+                let group = format!("{VOTE_PREFIX}{}:{}", post.id, vote.user);
+                let choices: Vec<String> = vote.choices.iter().map(usize::to_string).collect();
+                storage::set_list(&key_file, &group, "choices", &choices);
+                key_file.set_int64(&group, "voted_at", vote.voted_at);
+            }
+        }
+
+        storage::save_key_file(FEED_FILE, &key_file);
+    }
+
+    /// Posts, newest first.
+    pub fn posts(&self) -> &[Post] {
+        &self.posts
+    }
+
+    pub fn post(&self, id: &str) -> Option<&Post> {
+        self.posts.iter().find(|post| post.id == id)
+    }
+
+    /// Publishes a post by the signed-in user and returns its id.
+    pub fn publish(
+        &mut self,
+        text: &str,
+        poll: Option<Poll>,
+        location: Option<Location>,
+    ) -> String {
+        let id = glib::uuid_string_random().to_string();
+        self.posts.insert(
+            0,
+            Post {
+                id: id.clone(),
+                author: ME.to_string(),
+                text: text.trim().to_string(),
+                created_at: util::now(),
+                poll,
+                location,
+                comments: Vec::new(),
+                likes: Vec::new(),
+            },
+        );
+        self.save();
+        id
+    }
+
+    /// Adds a comment by the signed-in user; false if the post is gone.
+    pub fn add_comment(&mut self, post_id: &str, text: &str) -> bool {
+        let Some(post) = self.posts.iter_mut().find(|post| post.id == post_id) else {
+            return false;
+        };
+        post.comments.push(Comment {
+            author: ME.to_string(),
+            text: text.trim().to_string(),
+            created_at: util::now(),
+        });
+        self.save();
+        true
+    }
+
+    /// Likes or unlikes a post as the signed-in user; false if the post is
+    /// gone.
+    pub fn toggle_like(&mut self, post_id: &str) -> bool {
+        let Some(post) = self.posts.iter_mut().find(|post| post.id == post_id) else {
+            return false;
+        };
+        if post.liked_by(ME) {
+            post.likes.retain(|like| like.user != ME);
+        } else {
+            post.likes.push(Like {
+                user: ME.to_string(),
+                liked_at: util::now(),
+            });
+        }
+        self.save();
+        true
+    }
+
+    /// Applies `change` to the poll on `post_id` and saves if it succeeds.
+    pub fn update_poll(
+        &mut self,
+        post_id: &str,
+        change: impl FnOnce(&mut Poll) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let poll = self
+            .posts
+            .iter_mut()
+            .find(|post| post.id == post_id)
+            .and_then(|post| post.poll.as_mut())
+            .ok_or_else(|| tr("This poll no longer exists"))?;
+        change(poll)?;
+        self.save();
+        Ok(())
+    }
+}
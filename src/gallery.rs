@@ -0,0 +1,669 @@
+// Media gallery: filters, albums, tags, date sections and bulk actions
+use crate::i18n::{tr, tr_noop, tr_with, trn};
+use crate::media::{AlbumFilter, MediaFilter, MediaItem, MediaKind};
+use crate::share::ShareContent;
+use crate::share_sheet;
+use crate::sizing;
+use crate::state::AppState;
+use crate::util;
+use crate::viewer;
+use gtk4::gio;
+use gtk4::prelude::*;
+use gtk4::{
+    Box as GtkBox, Button, CheckButton, DropDown, Entry, Frame, Label, MenuButton, Orientation,
+    Popover, ToggleButton,
+};
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::rc::Rc;
+
+const COLUMNS: usize = 3;
+
+const KIND_FILTERS: [(MediaKind, &str); 4] = [
+    (MediaKind::Image, tr_noop("🖼️ Images")),
+    (MediaKind::Video, tr_noop("📹 Videos")),
+    (MediaKind::Document, tr_noop("📄 Documents")),
+    (MediaKind::Audio, tr_noop("🎵 Audio")),
+];
+
+struct Gallery {
+    state: Rc<AppState>,
+    filter: RefCell<MediaFilter>,
+    selection: RefCell<HashSet<String>>,
+    selecting: Cell<bool>,
+    sections: GtkBox,
+    album_dropdown: DropDown,
+    album_choices: RefCell<Vec<AlbumFilter>>,
+    tag_dropdown: DropDown,
+    tag_choices: RefCell<Vec<Option<String>>>,
+    move_dropdown: DropDown,
+    delete_album_btn: Button,
+    bulk_bar: GtkBox,
+    selection_label: Label,
+    /// Set while dropdown models are rebuilt so their handlers do nothing.
+    rebuilding: Cell<bool>,
+}
+
+/// Builds the gallery with its filter bar, bulk action bar and item sections.
+pub fn create_gallery(state: &Rc<AppState>) -> GtkBox {
+    let container = GtkBox::new(Orientation::Vertical, 16);
+
+    // Filter bar
+    let filter_box = GtkBox::new(Orientation::Horizontal, 12);
+    filter_box.add_css_class("gallery-filters");
+
+    let all_btn = ToggleButton::with_label(&tr("All"));
+    all_btn.add_css_class("filter-button");
+    all_btn.add_css_class("filter-active");
+    all_btn.set_active(true);
+    filter_box.append(&all_btn);
+
+    let mut kind_buttons = Vec::new();
+    for (kind, label) in KIND_FILTERS {
+        let button = ToggleButton::with_label(&tr(label));
+        button.add_css_class("filter-button");
+        filter_box.append(&button);
+        kind_buttons.push((kind, button));
+    }
+
+    let spacer = GtkBox::new(Orientation::Horizontal, 0);
+    spacer.set_hexpand(true);
+    filter_box.append(&spacer);
+
+    let album_dropdown = DropDown::from_strings(&[tr("All albums").as_str()]);
+    album_dropdown.add_css_class("gallery-dropdown");
+    album_dropdown.set_tooltip_text(Some(&tr("Filter by album")));
+    let tag_dropdown = DropDown::from_strings(&[tr("All tags").as_str()]);
+    tag_dropdown.add_css_class("gallery-dropdown");
+    tag_dropdown.set_tooltip_text(Some(&tr("Filter by tag")));
+    filter_box.append(&album_dropdown);
+    filter_box.append(&tag_dropdown);
+
+    let new_album_btn = MenuButton::builder().label(tr("➕ New Album")).build();
+    new_album_btn.add_css_class("secondary-button");
+    filter_box.append(&new_album_btn);
+
+    let delete_album_btn = Button::with_label(&tr("🗑️ Delete Album"));
+    delete_album_btn.add_css_class("secondary-button");
+    delete_album_btn.set_visible(false);
+    filter_box.append(&delete_album_btn);
+
+    let select_btn = ToggleButton::with_label(&tr("☑️ Select"));
+    select_btn.add_css_class("secondary-button");
+    filter_box.append(&select_btn);
+
+    container.append(&filter_box);
+
+    // Bulk action bar, shown in selection mode
+    let bulk_bar = GtkBox::new(Orientation::Horizontal, 12);
+    bulk_bar.add_css_class("bulk-bar");
+    bulk_bar.set_visible(false);
+
+    let selection_label = Label::new(Some(&trn("{n} selected", "{n} selected", 0)));
+    selection_label.add_css_class("bulk-count");
+    let select_all_btn = Button::with_label(&tr("Select all"));
+    select_all_btn.add_css_class("action-button");
+
+    let bulk_spacer = GtkBox::new(Orientation::Horizontal, 0);
+    bulk_spacer.set_hexpand(true);
+
+    let move_dropdown = DropDown::from_strings(&[tr("No album").as_str()]);
+    move_dropdown.add_css_class("gallery-dropdown");
+    let move_btn = Button::with_label(&tr("📁 Move"));
+    move_btn.add_css_class("action-button");
+
+    let tag_entry = Entry::new();
+    tag_entry.set_placeholder_text(Some(&tr("Add tag…")));
+    tag_entry.set_width_chars(12);
+    let tag_btn = Button::with_label(&tr("🏷️ Tag"));
+    tag_btn.add_css_class("action-button");
+    let untag_btn = Button::with_label(&tr("Untag"));
+    untag_btn.add_css_class("action-button");
+
+    let share_btn = Button::with_label(&tr("🔗 Share"));
+    share_btn.add_css_class("action-button");
+    let delete_btn = Button::with_label(&tr("🗑️ Delete"));
+    delete_btn.add_css_class("danger-button");
+
+    bulk_bar.append(&selection_label);
+    bulk_bar.append(&select_all_btn);
+    bulk_bar.append(&bulk_spacer);
+    bulk_bar.append(&move_dropdown);
+    bulk_bar.append(&move_btn);
+    bulk_bar.append(&tag_entry);
+    bulk_bar.append(&tag_btn);
+    bulk_bar.append(&untag_btn);
+    bulk_bar.append(&share_btn);
+    bulk_bar.append(&delete_btn);
+    container.append(&bulk_bar);
+
+    let sections = GtkBox::new(Orientation::Vertical, 20);
+    container.append(&sections);
+
+    let gallery = Rc::new(Gallery {
+        state: state.clone(),
+        filter: RefCell::new(MediaFilter::default()),
+        selection: RefCell::new(HashSet::new()),
+        selecting: Cell::new(false),
+        sections,
+        album_dropdown: album_dropdown.clone(),
+        album_choices: RefCell::new(vec![AlbumFilter::Any]),
+        tag_dropdown: tag_dropdown.clone(),
+        tag_choices: RefCell::new(vec![None]),
+        move_dropdown: move_dropdown.clone(),
+        delete_album_btn: delete_album_btn.clone(),
+        bulk_bar,
+        selection_label,
+        rebuilding: Cell::new(false),
+    });
+
+    // Kind filters
+    let kind_buttons = Rc::new(kind_buttons);
+    let g = gallery.clone();
+    let buttons = kind_buttons.clone();
+    all_btn.connect_clicked(move |button| {
+        g.filter.borrow_mut().kinds.clear();
+        for (_, kind_button) in buttons.iter() {
+            kind_button.set_active(false);
+            kind_button.remove_css_class("filter-active");
+        }
+        button.set_active(true);
+        button.add_css_class("filter-active");
+        g.refresh();
+    });
+    for (kind, button) in kind_buttons.iter() {
+        let g = gallery.clone();
+        let kind = *kind;
+        let all_btn = all_btn.clone();
+        button.connect_toggled(move |button| {
+            let mut filter = g.filter.borrow_mut();
+            if button.is_active() {
+                filter.kinds.insert(kind);
+                button.add_css_class("filter-active");
+            } else {
+                filter.kinds.remove(&kind);
+                button.remove_css_class("filter-active");
+            }
+            let showing_all = filter.kinds.is_empty();
+            drop(filter);
+            all_btn.set_active(showing_all);
+            if showing_all {
+                all_btn.add_css_class("filter-active");
+            } else {
+                all_btn.remove_css_class("filter-active");
+            }
+            g.refresh();
+        });
+    }
+
+    // Album and tag filters
+    let g = gallery.clone();
+    album_dropdown.connect_selected_notify(move |dropdown| {
+        if g.rebuilding.get() {
+            return;
+        }
+        let choice = g
+            .album_choices
+            .borrow()
+            .get(dropdown.selected() as usize)
+            .cloned()
+            .unwrap_or_default();
+        g.delete_album_btn
+            .set_visible(matches!(choice, AlbumFilter::Named(_)));
+        g.filter.borrow_mut().album = choice;
+        g.refresh();
+    });
+    let g = gallery.clone();
+    tag_dropdown.connect_selected_notify(move |dropdown| {
+        if g.rebuilding.get() {
+            return;
+        }
+        let choice = g
+            .tag_choices
+            .borrow()
+            .get(dropdown.selected() as usize)
+            .cloned()
+            .flatten();
+        g.filter.borrow_mut().tag = choice;
+        g.refresh();
+    });
+
+    new_album_btn.set_popover(Some(&create_new_album_popover(&gallery)));
+
+    let g = gallery.clone();
+    delete_album_btn.connect_clicked(move |button| {
+        let AlbumFilter::Named(name) = g.filter.borrow().album.clone() else {
+            return;
+        };
+        let dialog = gtk4::AlertDialog::builder()
+            .message(tr_with("Delete the album “{name}”?", &[("name", &name)]))
+            .detail(tr("Its items stay in the gallery as unsorted."))
+            .buttons([tr("Cancel"), tr("Delete")])
+            .cancel_button(0)
+            .default_button(0)
+            .build();
+        let window = button.root().and_downcast::<gtk4::Window>();
+        let g = g.clone();
+        dialog.choose(window.as_ref(), gio::Cancellable::NONE, move |result| {
+            if result == Ok(1) {
+                g.state.media.borrow_mut().delete_album(&name);
+                g.filter.borrow_mut().album = AlbumFilter::Any;
+                g.state.media_changed.emit();
+            }
+        });
+    });
+
+    // Selection mode
+    let g = gallery.clone();
+    select_btn.connect_toggled(move |button| {
+        g.selecting.set(button.is_active());
+        g.selection.borrow_mut().clear();
+        g.bulk_bar.set_visible(button.is_active());
+        g.refresh();
+    });
+
+    let g = gallery.clone();
+    select_all_btn.connect_clicked(move |_| {
+        let visible: HashSet<String> = {
+            let filter = g.filter.borrow();
+            g.state
+                .media
+                .borrow()
+                .items()
+                .iter()
+                .filter(|item| filter.matches(item))
+                .map(|item| item.id.clone())
+                .collect()
+        };
+        g.selection.replace(visible);
+        g.refresh();
+    });
+
+    let g = gallery.clone();
+    move_btn.connect_clicked(move |_| {
+        let index = g.move_dropdown.selected() as usize;
+        let album = index
+            .checked_sub(1)
+            .and_then(|i| g.state.media.borrow().albums().get(i).cloned());
+        let ids = g.selection.borrow().clone();
+        g.state
+            .media
+            .borrow_mut()
+            .move_to_album(&ids, album.as_deref());
+        g.state.media_changed.emit();
+    });
+
+    let g = gallery.clone();
+    let entry = tag_entry.clone();
+    tag_btn.connect_clicked(move |_| {
+        let ids = g.selection.borrow().clone();
+        g.state.media.borrow_mut().add_tag(&ids, &entry.text());
+        entry.set_text("");
+        g.state.media_changed.emit();
+    });
+    let g = gallery.clone();
+    let entry = tag_entry.clone();
+    untag_btn.connect_clicked(move |_| {
+        let ids = g.selection.borrow().clone();
+        g.state.media.borrow_mut().remove_tag(&ids, &entry.text());
+        entry.set_text("");
+        g.state.media_changed.emit();
+    });
+    let tag_btn_clone = tag_btn.clone();
+    tag_entry.connect_activate(move |_| tag_btn_clone.emit_clicked());
+
+    let g = gallery.clone();
+    share_btn.connect_clicked(move |button| g.share_selection(button));
+
+    let g = gallery.clone();
+    delete_btn.connect_clicked(move |button| g.confirm_delete(button));
+
+    let g = gallery.clone();
+    state.media_changed.connect(move || g.refresh());
+
+    gallery.refresh();
+    container
+}
+
+fn create_new_album_popover(gallery: &Rc<Gallery>) -> Popover {
+    let popover = Popover::new();
+    let content = GtkBox::new(Orientation::Vertical, 8);
+    content.set_margin_start(12);
+    content.set_margin_end(12);
+    content.set_margin_top(12);
+    content.set_margin_bottom(12);
+
+    let entry = Entry::new();
+    entry.set_placeholder_text(Some(&tr("Album name")));
+    let error_label = Label::new(None);
+    error_label.add_css_class("form-error");
+    error_label.set_visible(false);
+    let create_btn = Button::with_label(&tr("Create"));
+    create_btn.add_css_class("create-post-button");
+
+    content.append(&entry);
+    content.append(&error_label);
+    content.append(&create_btn);
+    popover.set_child(Some(&content));
+
+    let g = gallery.clone();
+    let entry_clone = entry.clone();
+    let popover_clone = popover.clone();
+    create_btn.connect_clicked(move |_| {
+        let result = g.state.media.borrow_mut().create_album(&entry_clone.text());
+        match result {
+            Ok(()) => {
+                entry_clone.set_text("");
+                error_label.set_visible(false);
+                popover_clone.popdown();
+                g.state.media_changed.emit();
+            }
+            Err(message) => {
+                error_label.set_text(&message);
+                error_label.set_visible(true);
+            }
+        }
+    });
+    entry.connect_activate(move |_| create_btn.emit_clicked());
+
+    popover
+}
+
+impl Gallery {
+    fn refresh(self: &Rc<Self>) {
+        self.rebuild_dropdowns();
+
+        while let Some(child) = self.sections.first_child() {
+            self.sections.remove(&child);
+        }
+
+        let items: Vec<MediaItem> = {
+            let filter = self.filter.borrow();
+            self.state
+                .media
+                .borrow()
+                .items()
+                .iter()
+                .filter(|item| filter.matches(item))
+                .cloned()
+                .collect()
+        };
+
+        // Drop selected ids that are no longer visible
+        let visible: HashSet<&String> = items.iter().map(|item| &item.id).collect();
+        self.selection
+            .borrow_mut()
+            .retain(|id| visible.contains(id));
+        self.selection_label.set_text(&trn(
+            "{n} selected",
+            "{n} selected",
+            self.selection.borrow().len() as u64,
+        ));
+
+        if items.is_empty() {
+            let empty = Label::new(Some(&tr("No media matches these filters")));
+            empty.add_css_class("section-subtitle");
+            self.sections.append(&empty);
+            return;
+        }
+
+        // Items are newest first, so equal labels are always adjacent
+        let now = util::now();
+        let items = Rc::new(items);
+        let mut current_label = String::new();
+        let mut grid = gtk4::Grid::new();
+        let mut position = 0;
+
+        for (index, item) in items.iter().enumerate() {
+            let label = util::date_group_label(item.uploaded_at, now);
+            if label != current_label || index == 0 {
+                let header = Label::new(Some(&label));
+                header.set_halign(gtk4::Align::Start);
+                header.add_css_class("gallery-date-header");
+                self.sections.append(&header);
+
+                grid = gtk4::Grid::new();
+                grid.set_row_spacing(16);
+                grid.set_column_spacing(16);
+                grid.add_css_class("media-grid");
+                self.sections.append(&grid);
+
+                current_label = label;
+                position = 0;
+            }
+
+            let card = self.create_card(&items, index, now);
+            grid.attach(
+                &card,
+                (position % COLUMNS) as i32,
+                (position / COLUMNS) as i32,
+                1,
+                1,
+            );
+            position += 1;
+        }
+    }
+
+    fn rebuild_dropdowns(&self) {
+        self.rebuilding.set(true);
+
+        let library = self.state.media.borrow();
+        let filter = self.filter.borrow();
+
+        // Album filter
+        let mut album_choices = vec![AlbumFilter::Any, AlbumFilter::Unsorted];
+        album_choices.extend(
+            library
+                .albums()
+                .iter()
+                .map(|name| AlbumFilter::Named(name.clone())),
+        );
+        let album_labels: Vec<String> = album_choices
+            .iter()
+            .map(|choice| match choice {
+                AlbumFilter::Any => tr("All albums"),
+                AlbumFilter::Unsorted => tr("Unsorted"),
+                AlbumFilter::Named(name) => format!("📁 {name}"),
+            })
+            .collect();
+        set_dropdown_strings(&self.album_dropdown, &album_labels);
+        let selected = album_choices
+            .iter()
+            .position(|choice| *choice == filter.album)
+            .unwrap_or(0);
+        self.album_dropdown.set_selected(selected as u32);
+        self.delete_album_btn
+            .set_visible(matches!(filter.album, AlbumFilter::Named(_)));
+        self.album_choices.replace(album_choices);
+
+        // Tag filter
+        let mut tag_choices = vec![None];
+        tag_choices.extend(library.tags().into_iter().map(Some));
+        let tag_labels: Vec<String> = tag_choices
+            .iter()
+            .map(|tag| match tag {
+                Some(tag) => format!("#{tag}"),
+                None => tr("All tags"),
+            })
+            .collect();
+        set_dropdown_strings(&self.tag_dropdown, &tag_labels);
+        let selected = tag_choices
+            .iter()
+            .position(|tag| *tag == filter.tag)
+            .unwrap_or(0);
+        self.tag_dropdown.set_selected(selected as u32);
+        self.tag_choices.replace(tag_choices);
+
+        // Move target
+        let mut move_labels = vec![tr("No album")];
+        move_labels.extend(library.albums().iter().map(|name| format!("📁 {name}")));
+        set_dropdown_strings(&self.move_dropdown, &move_labels);
+
+        self.rebuilding.set(false);
+    }
+
+    fn create_card(self: &Rc<Self>, items: &Rc<Vec<MediaItem>>, index: usize, now: i64) -> Frame {
+        let item = &items[index];
+        let time = util::format_relative_time(item.uploaded_at, now);
+        let selected = self.selection.borrow().contains(&item.id);
+        let card = create_media_card(item, &time);
+
+        let check = CheckButton::new();
+        check.set_active(selected);
+        check.set_visible(self.selecting.get());
+        check.set_halign(gtk4::Align::Start);
+        check.add_css_class("media-select");
+        if let Some(card_box) = card.child().and_downcast::<GtkBox>() {
+            card_box.prepend(&check);
+        }
+        if selected {
+            card.add_css_class("media-card-selected");
+        }
+
+        let g = self.clone();
+        let id = item.id.clone();
+        let card_clone = card.clone();
+        check.connect_toggled(move |check| {
+            let mut selection = g.selection.borrow_mut();
+            if check.is_active() {
+                selection.insert(id.clone());
+                card_clone.add_css_class("media-card-selected");
+            } else {
+                selection.remove(&id);
+                card_clone.remove_css_class("media-card-selected");
+            }
+            g.selection_label.set_text(&trn(
+                "{n} selected",
+                "{n} selected",
+                selection.len() as u64,
+            ));
+        });
+
+        // Click opens the lightbox, or toggles selection in selection mode
+        let click = gtk4::GestureClick::new();
+        let g = self.clone();
+        let items = items.clone();
+        click.connect_released(move |gesture, _, _, _| {
+            if g.selecting.get() {
+                check.set_active(!check.is_active());
+            } else if let Some(card) = gesture.widget() {
+                viewer::open_viewer(&card, items.to_vec(), index);
+            }
+        });
+        card.add_controller(click);
+        card.set_cursor_from_name(Some("pointer"));
+
+        card
+    }
+
+    fn selected_items(&self) -> Vec<MediaItem> {
+        let selection = self.selection.borrow();
+        self.state
+            .media
+            .borrow()
+            .items()
+            .iter()
+            .filter(|item| selection.contains(&item.id))
+            .cloned()
+            .collect()
+    }
+
+    /// Opens the share sheet with the selected items.
+    fn share_selection(&self, button: &Button) {
+        let contents: Vec<ShareContent> = self
+            .selected_items()
+            .into_iter()
+            .map(|item| ShareContent::Media {
+                id: item.id,
+                title: item.title,
+                kind: item.kind,
+            })
+            .collect();
+        share_sheet::open_share_sheet_for(button, &self.state, contents);
+    }
+
+    fn confirm_delete(self: &Rc<Self>, button: &Button) {
+        let count = self.selection.borrow().len();
+        if count == 0 {
+            return;
+        }
+
+        let dialog = gtk4::AlertDialog::builder()
+            .message(trn("Delete {n} item?", "Delete {n} items?", count as u64))
+            .detail(tr(
+                "The files are removed from your library. This cannot be undone.",
+            ))
+            .buttons([tr("Cancel"), tr("Delete")])
+            .cancel_button(0)
+            .default_button(0)
+            .build();
+        let window = button.root().and_downcast::<gtk4::Window>();
+        let g = self.clone();
+        dialog.choose(window.as_ref(), gio::Cancellable::NONE, move |result| {
+            if result == Ok(1) {
+                let ids = g.selection.take();
+                g.state.media.borrow_mut().delete(&ids);
+                g.state.media_changed.emit();
+            }
+        });
+    }
+}
+
+fn set_dropdown_strings(dropdown: &DropDown, labels: &[String]) {
+    let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+    dropdown.set_model(Some(&gtk4::StringList::new(&labels)));
+}
+
+pub fn create_media_card(item: &MediaItem, time: &str) -> Frame {
+    let frame = Frame::new(None);
+    frame.add_css_class("media-card");
+    sizing::set_size_request(&frame, 250, 200);
+
+    let card_box = GtkBox::new(Orientation::Vertical, 12);
+    card_box.set_margin_start(16);
+    card_box.set_margin_end(16);
+    card_box.set_margin_top(16);
+    card_box.set_margin_bottom(16);
+    card_box.set_valign(gtk4::Align::Center);
+    card_box.set_halign(gtk4::Align::Center);
+
+    match (item.kind, item.path()) {
+        (MediaKind::Image, Some(path)) => {
+            let thumbnail = gtk4::Picture::for_filename(path);
+            thumbnail.set_can_shrink(true);
+            thumbnail.set_content_fit(gtk4::ContentFit::Cover);
+            sizing::set_size_request(&thumbnail, 200, 110);
+            thumbnail.add_css_class("media-thumbnail");
+            card_box.append(&thumbnail);
+        }
+        _ => {
+            let icon_label = Label::new(Some(item.kind.icon()));
+            icon_label.add_css_class("media-icon");
+            card_box.append(&icon_label);
+        }
+    }
+
+    let title_label = Label::new(Some(&item.title));
+    title_label.set_max_width_chars(24);
+    title_label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+    title_label.add_css_class("media-title");
+
+    let time_label = Label::new(Some(time));
+    time_label.add_css_class("media-time");
+
+    card_box.append(&title_label);
+    card_box.append(&time_label);
+
+    if item.album.is_some() || !item.tags.is_empty() {
+        let mut meta: Vec<String> = item.album.iter().map(|a| format!("📁 {a}")).collect();
+        meta.extend(item.tags.iter().map(|tag| format!("#{tag}")));
+        let meta_label = Label::new(Some(&meta.join("  ")));
+        meta_label.set_max_width_chars(28);
+        meta_label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+        meta_label.add_css_class("media-tags");
+        card_box.append(&meta_label);
+    }
+
+    frame.set_child(Some(&card_box));
+    frame
+}
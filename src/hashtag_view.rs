@@ -0,0 +1,266 @@
+// Hashtag pages, pushed when a #tag is clicked, and the trending topics
+use crate::feed::Post;
+use crate::hashtags;
+use crate::i18n::{tr, trn};
+use crate::link_sheet;
+use crate::navigation;
+use crate::profile_view;
+use crate::sizing;
+use crate::state::AppState;
+use crate::util;
+use gtk4::glib;
+use gtk4::prelude::*;
+use gtk4::{Box as GtkBox, Button, Frame, Label, Orientation, ScrolledWindow};
+use std::rc::Rc;
+
+/// How many trending tags the search page shows.
+const TRENDING_LIMIT: usize = 8;
+
+/// Posts using `tag`, in their text or a comment, with a follow button.
+pub fn create_hashtag_page(state: &Rc<AppState>, tag: &str) -> ScrolledWindow {
+    let tag = hashtags::normalize(tag);
+    let scroll = ScrolledWindow::new();
+    let page_box = GtkBox::new(Orientation::Vertical, 24);
+    page_box.set_margin_start(40);
+    page_box.set_margin_end(40);
+    page_box.set_margin_top(30);
+    page_box.set_margin_bottom(30);
+
+    let back_btn = Button::with_label(&tr("‹ Back"));
+    back_btn.add_css_class("back-button");
+    back_btn.set_halign(gtk4::Align::Start);
+    back_btn.connect_clicked(|button| navigation::activate(button, "navigate-back", None));
+    page_box.append(&back_btn);
+
+    let header_box = GtkBox::new(Orientation::Horizontal, 16);
+    let title_box = GtkBox::new(Orientation::Vertical, 8);
+    title_box.set_hexpand(true);
+    let title_label = Label::new(Some(&format!("#{tag}")));
+    title_label.set_halign(gtk4::Align::Start);
+    title_label.add_css_class("section-title");
+    let count_label = Label::new(None);
+    count_label.set_halign(gtk4::Align::Start);
+    count_label.add_css_class("section-subtitle");
+    title_box.append(&title_label);
+    title_box.append(&count_label);
+
+    let follow_btn = Button::new();
+    follow_btn.set_valign(gtk4::Align::Center);
+    let update_follow = {
+        let state = state.clone();
+        let tag = tag.clone();
+        let follow_btn = follow_btn.clone();
+        move || {
+            let following = state.followed_tags.borrow().is_following(&tag);
+            follow_btn.set_label(&if following {
+                tr("✓ Following")
+            } else {
+                tr("+ Follow")
+            });
+            if following {
+                follow_btn.remove_css_class("create-post-button");
+                follow_btn.add_css_class("secondary-button");
+            } else {
+                follow_btn.remove_css_class("secondary-button");
+                follow_btn.add_css_class("create-post-button");
+            }
+        }
+    };
+    update_follow();
+    let state_clone = state.clone();
+    let tag_clone = tag.clone();
+    follow_btn.connect_clicked(move |_| {
+        state_clone.followed_tags.borrow_mut().toggle(&tag_clone);
+        update_follow();
+        state_clone.tags_changed.emit();
+    });
+    header_box.append(&title_box);
+    header_box.append(&follow_btn);
+    page_box.append(&header_box);
+
+    let posts_box = GtkBox::new(Orientation::Vertical, 16);
+    page_box.append(&posts_box);
+
+    let populate: Rc<dyn Fn()> = {
+        let state = state.clone();
+        Rc::new(move || {
+            while let Some(child) = posts_box.first_child() {
+                posts_box.remove(&child);
+            }
+            let posts: Vec<Post> = {
+                let community = state.community.borrow();
+                let feed = state.feed.borrow();
+                hashtags::posts_with(&feed, &community, &tag)
+                    .into_iter()
+                    .cloned()
+                    .collect()
+            };
+            count_label.set_text(&trn("{n} post", "{n} posts", posts.len() as u64));
+            if posts.is_empty() {
+                let empty = Label::new(Some(&tr("No posts use this tag yet")));
+                empty.add_css_class("share-empty");
+                posts_box.append(&empty);
+            }
+            let now = util::now();
+            for post in &posts {
+                posts_box.append(&create_tagged_post(&state, post, &tag, now));
+            }
+        })
+    };
+    navigation::refresh_while_mapped(&page_box, state, populate);
+
+    scroll.set_child(Some(&page_box));
+    scroll
+}
+
+/// A post with its text, and any of its comments that use `tag`.
+fn create_tagged_post(state: &Rc<AppState>, post: &Post, tag: &str, now: i64) -> Frame {
+    let frame = Frame::new(None);
+    frame.add_css_class("post-card");
+
+    let card_box = GtkBox::new(Orientation::Vertical, 12);
+    card_box.set_margin_start(20);
+    card_box.set_margin_end(20);
+    card_box.set_margin_top(16);
+    card_box.set_margin_bottom(16);
+
+    let community = state.community.borrow();
+    let author = community.user(&post.author);
+
+    let user_box = GtkBox::new(Orientation::Horizontal, 12);
+    let avatar = profile_view::create_avatar(author, "avatar", 40);
+    navigation::link_to_profile(&avatar, &post.author);
+    let user_info = GtkBox::new(Orientation::Vertical, 4);
+    user_info.set_hexpand(true);
+    let user_label = Label::new(Some(&community.user_name(&post.author)));
+    user_label.set_halign(gtk4::Align::Start);
+    user_label.add_css_class("post-user");
+    navigation::link_to_profile(&user_label, &post.author);
+    let time_label = Label::new(Some(&util::format_relative_time(post.created_at, now)));
+    time_label.set_halign(gtk4::Align::Start);
+    time_label.add_css_class("post-time");
+    user_info.append(&user_label);
+    user_info.append(&time_label);
+    user_box.append(&avatar);
+    user_box.append(&user_info);
+    card_box.append(&user_box);
+
+    if !post.text.is_empty() {
+        let content_label = Label::new(None);
+        sizing::set_markup(&content_label, &util::linkify_markup(&post.text));
+        content_label.set_wrap(true);
+        content_label.set_xalign(0.0);
+        content_label.set_halign(gtk4::Align::Start);
+        content_label.add_css_class("post-content");
+        link_sheet::connect_links(&content_label, state);
+        card_box.append(&content_label);
+    }
+
+    let tagged_comments: Vec<_> = post
+        .comments
+        .iter()
+        .filter(|comment| !community.hidden_from_feed(&comment.author))
+        .filter(|comment| hashtags::tags_in(&comment.text).iter().any(|t| t == tag))
+        .collect();
+    if !tagged_comments.is_empty() {
+        let comments_box = GtkBox::new(Orientation::Vertical, 8);
+        comments_box.add_css_class("post-comments");
+        for comment in tagged_comments {
+            let text = Label::new(None);
+            sizing::set_markup(
+                &text,
+                &format!(
+                    "<b>{}</b>  {}",
+                    glib::markup_escape_text(&community.user_name(&comment.author)),
+                    util::linkify_markup(&comment.text)
+                ),
+            );
+            text.set_wrap(true);
+            text.set_xalign(0.0);
+            text.add_css_class("comment-text");
+            link_sheet::connect_links(&text, state);
+            comments_box.append(&text);
+        }
+        card_box.append(&comments_box);
+    }
+
+    frame.set_child(Some(&card_box));
+    frame
+}
+
+/// "🔥 Trending Topics" computed from recent posts and comments, and the
+/// tags you follow.
+pub fn create_trending_section(state: &Rc<AppState>) -> GtkBox {
+    let section = GtkBox::new(Orientation::Vertical, 12);
+
+    let populate: Rc<dyn Fn()> = {
+        let state = state.clone();
+        let section = section.clone();
+        Rc::new(move || {
+            while let Some(child) = section.first_child() {
+                section.remove(&child);
+            }
+            let trending =
+                hashtags::trending(&state.feed.borrow(), &state.community.borrow(), util::now());
+
+            let trending_label = Label::new(Some(&tr("🔥 Trending Topics")));
+            trending_label.add_css_class("section-subtitle");
+            trending_label.set_halign(gtk4::Align::Start);
+            trending_label.set_margin_top(20);
+            section.append(&trending_label);
+
+            let trending_box = GtkBox::new(Orientation::Horizontal, 12);
+            for topic in trending.iter().take(TRENDING_LIMIT) {
+                let button = create_tag_button(&topic.label, &topic.tag);
+                button.set_tooltip_text(Some(&trn(
+                    "{n} post or comment this week",
+                    "{n} posts and comments this week",
+                    topic.uses as u64,
+                )));
+                trending_box.append(&button);
+            }
+            if trending.is_empty() {
+                let empty = Label::new(Some(&tr("Nothing is trending yet")));
+                empty.add_css_class("share-empty");
+                trending_box.append(&empty);
+            }
+            section.append(&trending_box);
+
+            let followed = state.followed_tags.borrow();
+            if !followed.tags().is_empty() {
+                let followed_label = Label::new(Some(&tr("🏷️ Tags You Follow")));
+                followed_label.add_css_class("section-subtitle");
+                followed_label.set_halign(gtk4::Align::Start);
+                followed_label.set_margin_top(12);
+                section.append(&followed_label);
+
+                let followed_box = GtkBox::new(Orientation::Horizontal, 12);
+                for tag in followed.tags() {
+                    followed_box.append(&create_tag_button(&format!("#{tag}"), tag));
+                }
+                section.append(&followed_box);
+            }
+        })
+    };
+    populate();
+    for listeners in [
+        &state.feed_changed,
+        &state.community_changed,
+        &state.tags_changed,
+    ] {
+        let populate = populate.clone();
+        listeners.connect(move || populate());
+    }
+
+    section
+}
+
+fn create_tag_button(label: &str, tag: &str) -> Button {
+    let button = Button::with_label(label);
+    button.add_css_class("trending-tag");
+    let tag = tag.to_string();
+    button.connect_clicked(move |button| {
+        navigation::activate(button, "show-hashtag", Some(&tag.to_variant()));
+    });
+    button
+}
@@ -0,0 +1,313 @@
+// Hashtags in posts and comments, trending topics and followed tags
+use crate::community::Community;
+use crate::feed::{Feed, Post};
+use crate::links::SCHEME;
+use crate::storage;
+use crate::util::{DAY, HOUR};
+use gtk4::glib;
+use std::collections::HashMap;
+
+const HASHTAGS_FILE: &str = "hashtags.ini";
+const FOLLOWED_GROUP: &str = "followed";
+
+/// Only uses this recent count towards trending.
+const TRENDING_WINDOW: i64 = 7 * DAY;
+/// A use counts half as much after this many seconds.
+const TRENDING_HALF_LIFE: i64 = DAY;
+/// Uses newer than this count fully, so a tag trends as soon as it's used.
+const TRENDING_GRACE: i64 = HOUR;
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Byte ranges of the `#tags` in `text`, `#` included. A tag can't start
+/// in the middle of a word, and needs at least one letter, so "C#" and
+/// "#1" aren't tags.
+pub fn find(text: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut previous: Option<char> = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let at_boundary = previous.is_none_or(|p| !is_tag_char(p) && p != '&' && p != '/');
+        previous = Some(c);
+        if c != '#' || !at_boundary {
+            continue;
+        }
+        let mut end = start + 1;
+        let mut has_letter = false;
+        while let Some(&(offset, next)) = chars.peek() {
+            if !is_tag_char(next) {
+                break;
+            }
+            has_letter |= next.is_alphabetic();
+            end = offset + next.len_utf8();
+            previous = Some(next);
+            chars.next();
+        }
+        if has_letter {
+            ranges.push((start, end));
+        }
+    }
+    ranges
+}
+
+/// "#GTK4" → "gtk4".
+pub fn normalize(tag: &str) -> String {
+    tag.trim_start_matches('#').to_lowercase()
+}
+
+/// The distinct tags in `text`, normalized.
+pub fn tags_in(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for (start, end) in find(text) {
+        let tag = normalize(&text[start..end]);
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// `linkwithmentor://tag/<tag>`, used for hashtag links in text.
+pub fn url(tag: &str) -> String {
+    format!(
+        "{SCHEME}://tag/{}",
+        glib::Uri::escape_string(&normalize(tag), None, true)
+    )
+}
+
+/// The tag a hashtag link points at.
+pub fn from_url(url: &str) -> Option<String> {
+    let uri = glib::Uri::parse(url.trim(), glib::UriFlags::NONE).ok()?;
+    if uri.scheme() != SCHEME || uri.host().as_deref() != Some("tag") {
+        return None;
+    }
+    let tag = normalize(uri.path().trim_start_matches('/'));
+    (!tag.is_empty() && tag.chars().all(is_tag_char)).then_some(tag)
+}
+
+/// Posts using `tag` in their text or in a comment, newest first. Posts
+/// and comments by blocked or muted people don't count.
+pub fn posts_with<'a>(feed: &'a Feed, community: &Community, tag: &str) -> Vec<&'a Post> {
+    let tag = normalize(tag);
+    let mut posts: Vec<&Post> = feed
+        .posts()
+        .iter()
+        .filter(|post| !community.hidden_from_feed(&post.author))
+        .filter(|post| {
+            tags_in(&post.text).contains(&tag)
+                || post.comments.iter().any(|comment| {
+                    !community.hidden_from_feed(&comment.author)
+                        && tags_in(&comment.text).contains(&tag)
+                })
+        })
+        .collect();
+    posts.sort_by_key(|post| std::cmp::Reverse(post.created_at));
+    posts
+}
+
+pub struct TrendingTag {
+    /// Normalized, e.g. "gtk4".
+    pub tag: String,
+    /// As most recently written, e.g. "#GTK4".
+    pub label: String,
+    /// Posts and comments using it within the window.
+    pub uses: usize,
+    score: f64,
+}
+
+/// Tags used in the last week, ranked by uses that lose half their weight
+/// each day, so a burst of recent uses beats a steady trickle.
+pub fn trending(feed: &Feed, community: &Community, now: i64) -> Vec<TrendingTag> {
+    let mut texts: Vec<(&str, i64)> = Vec::new();
+    for post in feed.posts() {
+        if community.hidden_from_feed(&post.author) {
+            continue;
+        }
+        texts.push((&post.text, post.created_at));
+        for comment in &post.comments {
+            if !community.hidden_from_feed(&comment.author) {
+                texts.push((&comment.text, comment.created_at));
+            }
+        }
+    }
+    rank(texts, now)
+}
+
+/// Ranks the tags in `texts`, each written at the given time.
+fn rank<'a>(texts: impl IntoIterator<Item = (&'a str, i64)>, now: i64) -> Vec<TrendingTag> {
+    let mut by_tag: HashMap<String, (TrendingTag, i64)> = HashMap::new();
+    for (text, timestamp) in texts {
+        let age = now - timestamp;
+        if age > TRENDING_WINDOW {
+            continue;
+        }
+        let weight = 0.5f64.powf((age - TRENDING_GRACE).max(0) as f64 / TRENDING_HALF_LIFE as f64);
+        let mut seen: Vec<String> = Vec::new();
+        for (start, end) in find(text) {
+            let written = &text[start..end];
+            let tag = normalize(written);
+            if seen.contains(&tag) {
+                continue;
+            }
+            seen.push(tag.clone());
+            let (entry, latest) = by_tag.entry(tag.clone()).or_insert_with(|| {
+                (
+                    TrendingTag {
+                        tag,
+                        label: written.to_string(),
+                        uses: 0,
+                        score: 0.0,
+                    },
+                    timestamp,
+                )
+            });
+            entry.uses += 1;
+            entry.score += weight;
+            if timestamp >= *latest {
+                entry.label = written.to_string();
+                *latest = timestamp;
+            }
+        }
+    }
+
+    let mut tags: Vec<TrendingTag> = by_tag.into_values().map(|(tag, _)| tag).collect();
+    tags.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.tag.cmp(&b.tag)));
+    tags
+}
+
+/// Tags the signed-in user follows.
+pub struct FollowedTags {
+    tags: Vec<String>,
+}
+
+impl FollowedTags {
+    pub fn load() -> Self {
+        let tags = storage::load_key_file(HASHTAGS_FILE)
+            .map(|key_file| storage::list(&key_file, FOLLOWED_GROUP, "tags"))
+            .unwrap_or_default();
+        Self { tags }
+    }
+
+    pub fn save(&self) {
+        let key_file = glib::KeyFile::new();
+        storage::set_list(&key_file, FOLLOWED_GROUP, "tags", &self.tags);
+        storage::save_key_file(HASHTAGS_FILE, &key_file);
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn is_following(&self, tag: &str) -> bool {
+        self.tags.contains(&normalize(tag))
+    }
+
+    /// Follows `tag`, or unfollows it if already followed.
+    pub fn toggle(&mut self, tag: &str) {
+        let tag = normalize(tag);
+        if let Some(index) = self.tags.iter().position(|followed| *followed == tag) {
+            self.tags.remove(index);
+        } else {
+            self.tags.push(tag);
+        }
+        self.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(text: &str) -> Vec<&str> {
+        find(text)
+            .into_iter()
+            .map(|(start, end)| &text[start..end])
+            .collect()
+    }
+
+    #[test]
+    fn tags_start_at_word_boundaries() {
+        assert_eq!(found("#rust and #GTK4"), ["#rust", "#GTK4"]);
+        assert!(found("I write C# at work").is_empty());
+        assert!(found("a#b").is_empty());
+        assert!(found("&#39; and example.com/#top").is_empty());
+        assert_eq!(found("##rust"), ["#rust"]);
+    }
+
+    #[test]
+    fn tags_need_a_letter() {
+        assert!(found("We're #1").is_empty());
+        assert_eq!(found("#2024goals #_"), ["#2024goals"]);
+    }
+
+    #[test]
+    fn tags_stop_at_punctuation() {
+        assert_eq!(
+            found("Loving #rust! (#gtk4), #café."),
+            ["#rust", "#gtk4", "#café"]
+        );
+        assert_eq!(found("#snake_case"), ["#snake_case"]);
+    }
+
+    #[test]
+    fn tags_in_normalizes_and_dedups() {
+        assert_eq!(tags_in("#Rust #rust #RUST #Gtk4"), ["rust", "gtk4"]);
+    }
+
+    #[test]
+    fn urls_round_trip() {
+        assert_eq!(from_url(&url("#GTK4")).as_deref(), Some("gtk4"));
+        assert_eq!(from_url(&url("café")).as_deref(), Some("café"));
+        assert_eq!(
+            from_url(&format!("{SCHEME}://tag/Rust")).as_deref(),
+            Some("rust")
+        );
+    }
+
+    #[test]
+    fn from_url_rejects_other_links() {
+        assert_eq!(from_url("https://tag/rust"), None);
+        assert_eq!(from_url(&format!("{SCHEME}://post/rust")), None);
+        assert_eq!(from_url(&format!("{SCHEME}://tag/")), None);
+        assert_eq!(from_url(&format!("{SCHEME}://tag/two%20words")), None);
+        assert_eq!(from_url("not a url"), None);
+    }
+
+    #[test]
+    fn recent_bursts_beat_old_steady_use() {
+        let now = 100 * DAY;
+        let old = now - 3 * DAY;
+        let texts = [
+            ("#steady", old),
+            ("#steady", old),
+            ("#steady", old),
+            ("#steady", old),
+            ("#burst", now - 10),
+            ("#burst", now - 20),
+        ];
+        let ranked = rank(texts, now);
+        let tags: Vec<&str> = ranked.iter().map(|tag| tag.tag.as_str()).collect();
+        assert_eq!(tags, ["burst", "steady"]);
+        assert_eq!(ranked[1].uses, 4);
+        // Past the first hour, a use halves in weight each day
+        assert!((ranked[1].score - 4.0 * 0.5f64.powf(3.0 - 1.0 / 24.0)).abs() < 1e-9);
+        assert_eq!(ranked[0].score, 2.0);
+    }
+
+    #[test]
+    fn uses_older_than_a_week_are_ignored() {
+        let now = 100 * DAY;
+        let ranked = rank([("#old", now - TRENDING_WINDOW - 1), ("#new", now)], now);
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].tag, "new");
+    }
+
+    #[test]
+    fn labels_follow_the_latest_spelling_and_texts_count_once() {
+        let ranked = rank([("#gtk4 #GTK4", 10), ("#Gtk4", 20), ("#gtk4", 5)], 20);
+        assert_eq!(ranked[0].label, "#Gtk4");
+        assert_eq!(ranked[0].uses, 3);
+    }
+}
@@ -0,0 +1,391 @@
+// Translations and locale-aware number and date formatting
+//
+// User-visible strings go through `tr`, `trn` or `tr_with` with their
+// English text as the message id; labels kept in constant tables are
+// marked with `tr_noop` and translated where they are shown. Catalogs are
+// gettext .po files in resources/po/, one per language, bundled with the
+// other assets and parsed at runtime so the language can change without a
+// restart. The README describes how to extract and update them.
+use crate::assets;
+use gtk4::glib;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// A language with a catalog (or English, which needs none).
+struct Locale {
+    /// As stored in the "language" setting and named in resources/po/.
+    code: &'static str,
+    rtl: bool,
+    /// Picks the msgstr[] index for a count, like the catalog's
+    /// Plural-Forms header.
+    plural: fn(u64) -> usize,
+    /// The locale's zero, if it doesn't use ASCII digits.
+    zero: Option<char>,
+    decimal_separator: char,
+}
+
+const LOCALES: [Locale; 2] = [
+    Locale {
+        code: "en",
+        rtl: false,
+        plural: |n| usize::from(n != 1),
+        zero: None,
+        decimal_separator: '.',
+    },
+    Locale {
+        code: "ar",
+        rtl: true,
+        plural: |n| match (n, n % 100) {
+            (0, _) => 0,
+            (1, _) => 1,
+            (2, _) => 2,
+            (_, 3..=10) => 3,
+            (_, 11..=99) => 4,
+            _ => 5,
+        },
+        zero: Some('\u{0660}'),
+        decimal_separator: '\u{066B}',
+    },
+];
+
+const MONTHS: [&str; 12] = [
+    tr_noop("January"),
+    tr_noop("February"),
+    tr_noop("March"),
+    tr_noop("April"),
+    tr_noop("May"),
+    tr_noop("June"),
+    tr_noop("July"),
+    tr_noop("August"),
+    tr_noop("September"),
+    tr_noop("October"),
+    tr_noop("November"),
+    tr_noop("December"),
+];
+
+struct Catalog {
+    locale: &'static Locale,
+    /// msgid → msgstr forms; plural entries are keyed by the singular.
+    messages: HashMap<String, Vec<String>>,
+}
+
+thread_local! {
+    static CATALOG: RefCell<Catalog> = RefCell::new(Catalog {
+        locale: &LOCALES[0],
+        messages: HashMap::new(),
+    });
+}
+
+/// Switches to the language of the "language" setting, "system" meaning
+/// the first of the user's locales that has a catalog. Widgets built
+/// afterwards use it; existing ones keep their text until rebuilt.
+pub fn set_language(setting: &str) {
+    let locale = if setting == "system" {
+        glib::language_names()
+            .iter()
+            .find_map(|name| find_locale(name))
+    } else {
+        find_locale(setting)
+    }
+    .unwrap_or(&LOCALES[0]);
+
+    let messages = if locale.code == LOCALES[0].code {
+        HashMap::new()
+    } else {
+        assets::load_bytes(&format!("po/{}.po", locale.code))
+            .map(|data| parse_po(&String::from_utf8_lossy(&data)))
+            .unwrap_or_else(|| {
+                eprintln!("No translations found for {}", locale.code);
+                HashMap::new()
+            })
+    };
+    CATALOG.with(|catalog| *catalog.borrow_mut() = Catalog { locale, messages });
+
+    gtk4::Widget::set_default_direction(if locale.rtl {
+        gtk4::TextDirection::Rtl
+    } else {
+        gtk4::TextDirection::Ltr
+    });
+}
+
+/// The locale for a language name like "ar", "ar_EG" or "ar_EG.UTF-8".
+fn find_locale(name: &str) -> Option<&'static Locale> {
+    let language = name.split(['_', '.', '@', '-']).next()?;
+    LOCALES.iter().find(|locale| locale.code == language)
+}
+
+/// `msgid` in the current language, or as is if it has no translation.
+pub fn tr(msgid: &str) -> String {
+    CATALOG.with(|catalog| {
+        catalog
+            .borrow()
+            .messages
+            .get(msgid)
+            .and_then(|forms| forms.first())
+            .filter(|text| !text.is_empty())
+            .map_or_else(|| msgid.to_string(), Clone::clone)
+    })
+}
+
+/// Marks a label kept in a table for extraction; translate it with `tr`
+/// where it is shown.
+pub const fn tr_noop(msgid: &'static str) -> &'static str {
+    msgid
+}
+
+/// The form of a message for `n`, with "{n}" replaced by the localized
+/// number, e.g. `trn("{n} hour ago", "{n} hours ago", 2)`.
+pub fn trn(singular: &str, plural: &str, n: u64) -> String {
+    let text = CATALOG.with(|catalog| {
+        let catalog = catalog.borrow();
+        catalog
+            .messages
+            .get(singular)
+            .and_then(|forms| forms.get((catalog.locale.plural)(n)))
+            .filter(|text| !text.is_empty())
+            .cloned()
+            .unwrap_or_else(|| if n == 1 { singular } else { plural }.to_string())
+    });
+    text.replace("{n}", &number(n))
+}
+
+/// `tr(msgid)` with each "{name}" replaced by its value, so translations
+/// can move the parts around, e.g. `tr_with("in {time}", &[("time", …)])`.
+pub fn tr_with(msgid: &str, args: &[(&str, &str)]) -> String {
+    let mut text = tr(msgid);
+    for (name, value) in args {
+        text = text.replace(&format!("{{{name}}}"), value);
+    }
+    text
+}
+
+/// `n` in the current locale's digits.
+pub fn number(n: impl ToString) -> String {
+    localize_digits(&n.to_string())
+}
+
+/// `value` with `places` decimals, in the current locale's digits and
+/// decimal separator.
+pub fn decimal(value: f64, places: usize) -> String {
+    let separator = CATALOG.with(|catalog| catalog.borrow().locale.decimal_separator);
+    localize_digits(&format!("{value:.places$}").replace('.', &separator.to_string()))
+}
+
+fn localize_digits(text: &str) -> String {
+    let Some(zero) = CATALOG.with(|catalog| catalog.borrow().locale.zero) else {
+        return text.to_string();
+    };
+    text.chars()
+        .map(|c| match c.to_digit(10) {
+            Some(digit) => char::from_u32(zero as u32 + digit).unwrap_or(c),
+            None => c,
+        })
+        .collect()
+}
+
+/// The translated name of `month` (1–12).
+pub fn month_name(month: i32) -> String {
+    usize::try_from(month - 1)
+        .ok()
+        .and_then(|index| MONTHS.get(index))
+        .map(|name| tr(name))
+        .unwrap_or_default()
+}
+
+/// Parses the entries of a .po file. Fuzzy entries are skipped, like
+/// msgfmt does.
+fn parse_po(text: &str) -> HashMap<String, Vec<String>> {
+    #[derive(Default)]
+    struct Entry {
+        msgid: String,
+        forms: Vec<String>,
+        fuzzy: bool,
+    }
+    enum Field {
+        None,
+        Id,
+        Plural,
+        Form(usize),
+    }
+
+    let mut messages = HashMap::new();
+    let mut entry = Entry::default();
+    let mut field = Field::None;
+    let mut finish = |entry: Entry| {
+        if !entry.msgid.is_empty() && !entry.fuzzy {
+            messages.insert(entry.msgid, entry.forms);
+        }
+    };
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            // Flags come before their entry, so they start a new one
+            if matches!(field, Field::Form(_)) {
+                finish(std::mem::take(&mut entry));
+                field = Field::None;
+            }
+            if comment.starts_with(',') && comment.contains("fuzzy") {
+                entry.fuzzy = true;
+            }
+            continue;
+        }
+        let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let next = match keyword {
+            "msgid" => Some(Field::Id),
+            "msgid_plural" => Some(Field::Plural),
+            "msgstr" => Some(Field::Form(0)),
+            _ => keyword
+                .strip_prefix("msgstr[")
+                .and_then(|index| index.strip_suffix(']'))
+                .and_then(|index| index.parse().ok())
+                .map(Field::Form),
+        };
+        let value = match next {
+            Some(next) => {
+                if matches!(field, Field::Form(_)) && matches!(next, Field::Id) {
+                    finish(std::mem::take(&mut entry));
+                }
+                field = next;
+                rest
+            }
+            // A continuation line
+            None => line,
+        };
+        let value = unquote(value);
+        match field {
+            Field::None | Field::Plural => {}
+            Field::Id => entry.msgid.push_str(&value),
+            Field::Form(index) => {
+                if entry.forms.len() <= index {
+                    entry.forms.resize(index + 1, String::new());
+                }
+                entry.forms[index].push_str(&value);
+            }
+        }
+    }
+    finish(entry);
+    messages
+}
+
+/// The text of a quoted .po string, with its escapes resolved.
+fn unquote(quoted: &str) -> String {
+    let inner = quoted
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .unwrap_or("");
+    let mut text = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => text.push('\n'),
+            Some('t') => text.push('\t'),
+            Some(other) => text.push(other),
+            None => {}
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_po_joins_continuation_lines() {
+        let messages = parse_po(
+            r#"
+msgid ""
+msgstr ""
+"Content-Type: text/plain; charset=UTF-8\n"
+
+msgid "Hello, "
+"world"
+msgstr ""
+"مرحبًا "
+"بالعالم"
+"#,
+        );
+        assert_eq!(messages["Hello, world"], ["مرحبًا بالعالم"]);
+        // The header has an empty msgid
+        assert_eq!(messages.len(), 1);
+    }
+
+    #[test]
+    fn parse_po_skips_fuzzy_entries() {
+        let messages = parse_po(
+            r#"
+#: src/main.rs:10
+#, fuzzy
+msgid "Settings"
+msgstr "الإعدادات"
+
+#, c-format
+msgid "Search"
+msgstr "بحث"
+
+#, fuzzy, c-format
+msgid "Feed"
+msgstr "الموجز"
+"#,
+        );
+        assert!(!messages.contains_key("Settings"));
+        assert!(!messages.contains_key("Feed"));
+        assert_eq!(messages["Search"], ["بحث"]);
+    }
+
+    #[test]
+    fn parse_po_reads_plural_forms() {
+        let messages = parse_po(
+            r#"
+msgid "{n} vote"
+msgid_plural "{n} votes"
+msgstr[0] "zero"
+msgstr[1] "one"
+msgstr[2] "two"
+msgstr[5] "many"
+"#,
+        );
+        assert_eq!(messages["{n} vote"], ["zero", "one", "two", "", "", "many"]);
+        assert!(!messages.contains_key("{n} votes"));
+    }
+
+    #[test]
+    fn parse_po_resolves_escapes() {
+        let messages = parse_po(
+            r#"
+msgid "Say \"hi\"\nthen\tleave \\ now"
+msgstr "قل \"مرحبًا\"\nثم"
+"#,
+        );
+        assert_eq!(
+            messages["Say \"hi\"\nthen\tleave \\ now"],
+            ["قل \"مرحبًا\"\nثم"]
+        );
+    }
+
+    #[test]
+    fn arabic_has_six_plural_categories() {
+        let plural = find_locale("ar_EG.UTF-8").unwrap().plural;
+        let category =
+            |counts: &[u64]| -> Vec<usize> { counts.iter().map(|&n| plural(n)).collect() };
+        assert_eq!(category(&[0]), [0]);
+        assert_eq!(category(&[1]), [1]);
+        assert_eq!(category(&[2]), [2]);
+        assert_eq!(category(&[3, 7, 10, 103, 1010]), [3; 5]);
+        assert_eq!(category(&[11, 50, 99, 111, 1099]), [4; 5]);
+        assert_eq!(category(&[100, 101, 102, 200, 1000]), [5; 5]);
+    }
+
+    #[test]
+    fn english_has_two_plural_categories() {
+        let plural = find_locale("en_US").unwrap().plural;
+        assert_eq!([0, 1, 2, 21].map(plural), [1, 0, 1, 1]);
+    }
+}
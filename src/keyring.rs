@@ -0,0 +1,400 @@
+// Session tokens in the desktop keyring
+//
+// Tokens are stored with the Secret Service (GNOME Keyring, KWallet and
+// others) over D-Bus, one item per device name. Where there is no Secret
+// Service, or its default collection is locked and would need a prompt,
+// they go to session.enc in the data directory instead, encrypted with
+// ChaCha20-Poly1305 under a key kept in the config directory that only
+// the user can read, so a copied or synced data directory doesn't carry
+// working sessions with it. LINKWITHMENTOR_KEYRING=file forces the file.
+use crate::storage;
+use crate::totp;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use gtk4::glib::variant::ObjectPath;
+use gtk4::prelude::*;
+use gtk4::{gio, glib};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const BUS_NAME: &str = "org.freedesktop.secrets";
+const SERVICE_PATH: &str = "/org/freedesktop/secrets";
+const SERVICE_INTERFACE: &str = "org.freedesktop.Secret.Service";
+const COLLECTION_INTERFACE: &str = "org.freedesktop.Secret.Collection";
+const ITEM_INTERFACE: &str = "org.freedesktop.Secret.Item";
+/// The object path meaning "no prompt needed" (or "no such collection").
+const NONE_PATH: &str = "/";
+const TIMEOUT_MS: i32 = 5000;
+const APPLICATION: &str = "com.linkwithmentor";
+const BACKEND_ENV: &str = "LINKWITHMENTOR_KEYRING";
+
+const FALLBACK_FILE: &str = "session.enc";
+const KEY_FILE: &str = "session.key";
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// Stores the session token for `device`, replacing any earlier one.
+pub fn store(device: &str, token: &str) {
+    match SecretService::connect().and_then(|service| service.store(device, token)) {
+        Ok(()) => set_fallback(device, None),
+        Err(err) => {
+            eprintln!("Keyring unavailable ({err}); keeping the session in {FALLBACK_FILE}");
+            set_fallback(device, Some(token));
+        }
+    }
+}
+
+/// The session token stored for `device`, if any.
+pub fn load(device: &str) -> Option<String> {
+    SecretService::connect()
+        .and_then(|service| service.load(device))
+        .ok()
+        .flatten()
+        .or_else(|| fallback_tokens().remove(device))
+}
+
+/// Forgets the session token for `device`, wherever it is stored.
+pub fn clear(device: &str) {
+    if let Err(err) = SecretService::connect().and_then(|service| service.clear(device)) {
+        if !err.matches(gio::IOErrorEnum::NotSupported) {
+            eprintln!("Could not remove the session from the keyring: {err}");
+        }
+    }
+    set_fallback(device, None);
+}
+
+fn failed(message: &str) -> glib::Error {
+    glib::Error::new(gio::IOErrorEnum::Failed, message)
+}
+
+/// A connection to the Secret Service with a session for plain transfers,
+/// which is fine on the local session bus.
+struct SecretService {
+    connection: gio::DBusConnection,
+    session: ObjectPath,
+}
+
+impl SecretService {
+    fn connect() -> Result<Self, glib::Error> {
+        if std::env::var(BACKEND_ENV).is_ok_and(|backend| backend == "file") {
+            return Err(glib::Error::new(
+                gio::IOErrorEnum::NotSupported,
+                "the keyring is turned off",
+            ));
+        }
+        let connection = gio::bus_get_sync(gio::BusType::Session, None::<&gio::Cancellable>)?;
+        let reply = call(
+            &connection,
+            SERVICE_PATH,
+            SERVICE_INTERFACE,
+            "OpenSession",
+            &("plain", "".to_variant()).to_variant(),
+        )?;
+        let (_, session) = parse::<(glib::Variant, ObjectPath)>(&reply)?;
+        Ok(Self {
+            connection,
+            session,
+        })
+    }
+
+    fn call(
+        &self,
+        path: &str,
+        interface: &str,
+        method: &str,
+        parameters: &glib::Variant,
+    ) -> Result<glib::Variant, glib::Error> {
+        call(&self.connection, path, interface, method, parameters)
+    }
+
+    /// The item for `device`, unlocked. Locked items are unlocked if that
+    /// needs no prompt, as when the login keyring is open.
+    fn find(&self, device: &str) -> Result<Option<ObjectPath>, glib::Error> {
+        let reply = self.call(
+            SERVICE_PATH,
+            SERVICE_INTERFACE,
+            "SearchItems",
+            &(attributes(device),).to_variant(),
+        )?;
+        let (unlocked, locked) = parse::<(Vec<ObjectPath>, Vec<ObjectPath>)>(&reply)?;
+        if let Some(item) = unlocked.into_iter().next() {
+            return Ok(Some(item));
+        }
+        let Some(item) = locked.into_iter().next() else {
+            return Ok(None);
+        };
+        let reply = self.call(
+            SERVICE_PATH,
+            SERVICE_INTERFACE,
+            "Unlock",
+            &(vec![item.clone()],).to_variant(),
+        )?;
+        let (unlocked, _) = parse::<(Vec<ObjectPath>, ObjectPath)>(&reply)?;
+        if unlocked.contains(&item) {
+            Ok(Some(item))
+        } else {
+            Err(failed("the keyring is locked"))
+        }
+    }
+
+    fn load(&self, device: &str) -> Result<Option<String>, glib::Error> {
+        let Some(item) = self.find(device)? else {
+            return Ok(None);
+        };
+        let reply = self.call(
+            &item,
+            ITEM_INTERFACE,
+            "GetSecret",
+            &(self.session.clone(),).to_variant(),
+        )?;
+        let ((_, _, value, _),) = parse::<((ObjectPath, Vec<u8>, Vec<u8>, String),)>(&reply)?;
+        Ok(String::from_utf8(value).ok())
+    }
+
+    fn store(&self, device: &str, token: &str) -> Result<(), glib::Error> {
+        let reply = self.call(
+            SERVICE_PATH,
+            SERVICE_INTERFACE,
+            "ReadAlias",
+            &("default",).to_variant(),
+        )?;
+        let (collection,) = parse::<(ObjectPath,)>(&reply)?;
+        if collection.as_str() == NONE_PATH {
+            return Err(failed("there is no default keyring"));
+        }
+
+        let properties: HashMap<String, glib::Variant> = HashMap::from([
+            (
+                "org.freedesktop.Secret.Item.Label".to_string(),
+                format!("LinkWithMentor session ({device})").to_variant(),
+            ),
+            (
+                "org.freedesktop.Secret.Item.Attributes".to_string(),
+                attributes(device).to_variant(),
+            ),
+        ]);
+        let secret = (
+            self.session.clone(),
+            Vec::<u8>::new(),
+            token.as_bytes().to_vec(),
+            "text/plain".to_string(),
+        );
+        let reply = self.call(
+            &collection,
+            COLLECTION_INTERFACE,
+            "CreateItem",
+            &(properties, secret, true).to_variant(),
+        )?;
+        let (_, prompt) = parse::<(ObjectPath, ObjectPath)>(&reply)?;
+        if prompt.as_str() != NONE_PATH {
+            return Err(failed("the keyring is locked"));
+        }
+        Ok(())
+    }
+
+    fn clear(&self, device: &str) -> Result<(), glib::Error> {
+        if let Some(item) = self.find(device)? {
+            self.call(&item, ITEM_INTERFACE, "Delete", &().to_variant())?;
+        }
+        Ok(())
+    }
+}
+
+fn call(
+    connection: &gio::DBusConnection,
+    path: &str,
+    interface: &str,
+    method: &str,
+    parameters: &glib::Variant,
+) -> Result<glib::Variant, glib::Error> {
+    connection.call_sync(
+        Some(BUS_NAME),
+        path,
+        interface,
+        method,
+        Some(parameters),
+        None,
+        gio::DBusCallFlags::NONE,
+        TIMEOUT_MS,
+        None::<&gio::Cancellable>,
+    )
+}
+
+fn parse<T: glib::variant::FromVariant>(reply: &glib::Variant) -> Result<T, glib::Error> {
+    reply
+        .get::<T>()
+        .ok_or_else(|| failed(&format!("unexpected reply {}", reply.type_())))
+}
+
+fn attributes(device: &str) -> HashMap<String, String> {
+    HashMap::from([
+        ("application".to_string(), APPLICATION.to_string()),
+        ("device".to_string(), device.to_string()),
+    ])
+}
+
+// ============================================================================
+// Encrypted file fallback
+// ============================================================================
+
+fn fallback_path() -> PathBuf {
+    storage::data_dir().join(FALLBACK_FILE)
+}
+
+fn key_path() -> PathBuf {
+    glib::user_config_dir()
+        .join("linkwithmentor")
+        .join(KEY_FILE)
+}
+
+/// The file encryption key, created on first use.
+fn fallback_key() -> Option<Vec<u8>> {
+    let path = key_path();
+    if let Ok(text) = std::fs::read_to_string(&path) {
+        let key = glib::base64_decode(text.trim());
+        if key.len() == KEY_LEN {
+            return Some(key);
+        }
+    }
+    let key = totp::random_bytes(KEY_LEN);
+    match write_private(&path, glib::base64_encode(&key).as_bytes()) {
+        Ok(()) => Some(key),
+        Err(err) => {
+            eprintln!("Could not save {}: {err}", path.display());
+            None
+        }
+    }
+}
+
+/// Tokens in the fallback file, by device. Empty if it is missing or can't
+/// be decrypted, e.g. because the key was deleted.
+fn fallback_tokens() -> HashMap<String, String> {
+    let Ok(text) = std::fs::read_to_string(fallback_path()) else {
+        return HashMap::new();
+    };
+    let Some(plaintext) =
+        fallback_key().and_then(|key| open(&key, &glib::base64_decode(text.trim())))
+    else {
+        return HashMap::new();
+    };
+    String::from_utf8_lossy(&plaintext)
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(device, token)| (device.to_string(), token.to_string()))
+        .collect()
+}
+
+/// Sets or, with None, removes `device`'s token in the fallback file.
+fn set_fallback(device: &str, token: Option<&str>) {
+    let mut tokens = fallback_tokens();
+    let device = device.replace(['\t', '\n'], " ");
+    let changed = match token {
+        Some(token) => tokens.insert(device, token.to_string()).as_deref() != Some(token),
+        None => tokens.remove(&device).is_some(),
+    };
+    if !changed {
+        return;
+    }
+
+    let path = fallback_path();
+    if tokens.is_empty() {
+        let _ = std::fs::remove_file(&path);
+        return;
+    }
+    let Some(key) = fallback_key() else {
+        return;
+    };
+    let plaintext: String = tokens
+        .iter()
+        .map(|(device, token)| format!("{device}\t{token}\n"))
+        .collect();
+    let Some(sealed) = seal(&key, plaintext.as_bytes()) else {
+        return;
+    };
+    let sealed = glib::base64_encode(&sealed);
+    if let Err(err) = write_private(&path, sealed.as_bytes()) {
+        eprintln!("Could not save {}: {err}", path.display());
+    }
+}
+
+/// Writes a file only the user can read.
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    std::io::Write::write_all(&mut options.open(path)?, contents)
+}
+
+/// Encrypts and authenticates `plaintext` with ChaCha20-Poly1305:
+/// nonce ‖ ciphertext ‖ tag. None if `key` isn't KEY_LEN bytes.
+fn seal(key: &[u8], plaintext: &[u8]) -> Option<Vec<u8>> {
+    let cipher = ChaCha20Poly1305::new_from_slice(key).ok()?;
+    let nonce = totp::random_bytes(NONCE_LEN);
+    let ciphertext = cipher.encrypt(Nonce::from_slice(&nonce), plaintext).ok()?;
+    Some([nonce, ciphertext].concat())
+}
+
+/// The plaintext of `sealed`, or None if it was altered, cut short or
+/// made with another key.
+fn open(key: &[u8], sealed: &[u8]) -> Option<Vec<u8>> {
+    let cipher = ChaCha20Poly1305::new_from_slice(key).ok()?;
+    if sealed.len() < NONCE_LEN {
+        return None;
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    cipher.decrypt(Nonce::from_slice(nonce), ciphertext).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAINTEXT: &[u8] = b"laptop\tSESSIONTOKEN\n";
+
+    #[test]
+    fn round_trip() {
+        let key = totp::random_bytes(KEY_LEN);
+        let sealed = seal(&key, PLAINTEXT).unwrap();
+        assert_eq!(open(&key, &sealed).as_deref(), Some(PLAINTEXT));
+        // A fresh nonce each time
+        assert_ne!(seal(&key, PLAINTEXT).unwrap(), sealed);
+    }
+
+    #[test]
+    fn tampering_is_rejected() {
+        let key = totp::random_bytes(KEY_LEN);
+        let sealed = seal(&key, PLAINTEXT).unwrap();
+        // The nonce, the ciphertext and the tag
+        for index in [0, NONCE_LEN + 1, sealed.len() - 1] {
+            let mut tampered = sealed.clone();
+            tampered[index] ^= 1;
+            assert_eq!(open(&key, &tampered), None, "byte {index}");
+        }
+    }
+
+    #[test]
+    fn wrong_key_is_rejected() {
+        let sealed = seal(&totp::random_bytes(KEY_LEN), PLAINTEXT).unwrap();
+        assert_eq!(open(&totp::random_bytes(KEY_LEN), &sealed), None);
+        assert_eq!(open(&[0; 16], &sealed), None);
+    }
+
+    #[test]
+    fn truncated_blobs_are_rejected() {
+        let key = totp::random_bytes(KEY_LEN);
+        let sealed = seal(&key, PLAINTEXT).unwrap();
+        for length in [0, NONCE_LEN - 1, NONCE_LEN, sealed.len() - 1] {
+            assert_eq!(open(&key, &sealed[..length]), None, "{length} bytes");
+        }
+    }
+}
@@ -0,0 +1,580 @@
+// Share link dialogs: creating links, the management list and opening links
+use crate::hashtags;
+use crate::i18n::{tr, tr_noop, tr_with};
+use crate::links::{self, LinkError, LinkState, LinkTarget, ShareLink};
+use crate::navigation;
+use crate::share::ShareContent;
+use crate::share_sheet;
+use crate::state::AppState;
+use crate::util::{self, DAY, HOUR, WEEK};
+use crate::viewer;
+use gtk4::prelude::*;
+use gtk4::{gio, glib};
+use gtk4::{
+    Box as GtkBox, Button, Calendar, DropDown, Entry, Frame, Label, Orientation, PasswordEntry,
+    SpinButton, Stack, StringList,
+};
+use std::rc::Rc;
+
+const EXPIRY_CHOICES: [(&str, Option<i64>); 5] = [
+    (tr_noop("Never"), None),
+    (tr_noop("In 1 hour"), Some(HOUR)),
+    (tr_noop("In 1 day"), Some(DAY)),
+    (tr_noop("In 7 days"), Some(WEEK)),
+    (tr_noop("In 30 days"), Some(30 * DAY)),
+];
+const EXPIRY_CUSTOM: &str = tr_noop("On a date…");
+
+// ============================================================================
+// Create dialog
+// ============================================================================
+
+/// Opens the "Create Share Link" dialog, with `initial` preselected if given.
+pub fn open_link_dialog(
+    parent: &impl IsA<gtk4::Widget>,
+    state: &Rc<AppState>,
+    initial: Option<LinkTarget>,
+) {
+    let targets = link_targets(state);
+    if targets.is_empty() {
+        return;
+    }
+
+    let window = gtk4::Window::builder()
+        .title(tr("Create Share Link"))
+        .modal(true)
+        .default_width(480)
+        .build();
+    window.add_css_class("share-sheet");
+    if let Some(parent) = parent.root().and_downcast::<gtk4::Window>() {
+        window.set_transient_for(Some(&parent));
+    }
+
+    let stack = Stack::new();
+    let form = GtkBox::new(Orientation::Vertical, 12);
+    form.set_margin_start(24);
+    form.set_margin_end(24);
+    form.set_margin_top(24);
+    form.set_margin_bottom(24);
+
+    let title = Label::new(Some(&tr("🔗 Create Share Link")));
+    title.set_halign(gtk4::Align::Start);
+    title.add_css_class("dialog-title");
+    form.append(&title);
+
+    // What to share
+    let target_names: Vec<String> = targets
+        .iter()
+        .map(|target| format!("{} {}", target.icon(), target.title()))
+        .collect();
+    let target_names: Vec<&str> = target_names.iter().map(String::as_str).collect();
+    let target_dropdown =
+        DropDown::new(Some(StringList::new(&target_names)), gtk4::Expression::NONE);
+    if let Some(index) = initial.and_then(|initial| targets.iter().position(|t| *t == initial)) {
+        target_dropdown.set_selected(index as u32);
+    }
+    form.append(&form_label(&tr("Share")));
+    form.append(&target_dropdown);
+
+    // Expiry
+    let mut expiry_names: Vec<String> = EXPIRY_CHOICES.iter().map(|(name, _)| tr(name)).collect();
+    expiry_names.push(tr(EXPIRY_CUSTOM));
+    let expiry_names: Vec<&str> = expiry_names.iter().map(String::as_str).collect();
+    let expiry_dropdown =
+        DropDown::new(Some(StringList::new(&expiry_names)), gtk4::Expression::NONE);
+    expiry_dropdown.set_selected(3);
+    let calendar = Calendar::new();
+    calendar.set_visible(false);
+    let calendar_clone = calendar.clone();
+    expiry_dropdown.connect_selected_notify(move |dropdown| {
+        calendar_clone.set_visible(dropdown.selected() as usize == EXPIRY_CHOICES.len());
+    });
+    form.append(&form_label(&tr("Expires")));
+    form.append(&expiry_dropdown);
+    form.append(&calendar);
+
+    // Access rules
+    let password_entry = PasswordEntry::new();
+    password_entry.set_show_peek_icon(true);
+    password_entry.set_placeholder_text(Some(&tr("Optional")));
+    form.append(&form_label(&tr("Password")));
+    form.append(&password_entry);
+
+    let limit_box = GtkBox::new(Orientation::Horizontal, 12);
+    let limit_spin = SpinButton::with_range(0.0, 10_000.0, 1.0);
+    let limit_hint = Label::new(Some(&tr("0 = unlimited")));
+    limit_hint.add_css_class("upload-hint");
+    limit_box.append(&limit_spin);
+    limit_box.append(&limit_hint);
+    form.append(&form_label(&tr("Download limit")));
+    form.append(&limit_box);
+
+    let error_label = Label::new(None);
+    error_label.set_halign(gtk4::Align::Start);
+    error_label.add_css_class("form-error");
+    form.append(&error_label);
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 12);
+    buttons.set_halign(gtk4::Align::End);
+    let cancel_btn = Button::with_label(&tr("Cancel"));
+    cancel_btn.add_css_class("secondary-button");
+    let create_btn = Button::with_label(&tr("Create Link"));
+    create_btn.add_css_class("create-post-button");
+    buttons.append(&cancel_btn);
+    buttons.append(&create_btn);
+    form.append(&buttons);
+
+    stack.add_named(&form, Some("form"));
+    window.set_child(Some(&stack));
+
+    let window_clone = window.clone();
+    cancel_btn.connect_clicked(move |_| window_clone.close());
+
+    let state = state.clone();
+    let window_clone = window.clone();
+    create_btn.connect_clicked(move |_| {
+        let now = util::now();
+        let expires_at = match EXPIRY_CHOICES.get(expiry_dropdown.selected() as usize) {
+            Some((_, duration)) => duration.map(|duration| now + duration),
+            None => {
+                // Links picked by date stay valid until the end of that day
+                let (year, month, day) = calendar.date().ymd();
+                glib::DateTime::from_local(year, month, day, 23, 59, 59.0)
+                    .ok()
+                    .map(|date| date.to_unix())
+            }
+        };
+        if expires_at.is_some_and(|expires_at| expires_at <= now) {
+            error_label.set_text(&tr("The expiry date must be in the future"));
+            return;
+        }
+
+        let Some(target) = targets.get(target_dropdown.selected() as usize).cloned() else {
+            return;
+        };
+        let link = state.links.borrow_mut().create(
+            target,
+            expires_at,
+            &password_entry.text(),
+            limit_spin.value() as u32,
+        );
+        state.links_changed.emit();
+
+        let result = create_result_page(&window_clone, &state, &link);
+        stack.add_named(&result, Some("result"));
+        stack.set_visible_child_name("result");
+    });
+
+    window.present();
+}
+
+fn create_result_page(window: &gtk4::Window, state: &Rc<AppState>, link: &ShareLink) -> GtkBox {
+    let page = GtkBox::new(Orientation::Vertical, 12);
+    page.set_margin_start(24);
+    page.set_margin_end(24);
+    page.set_margin_top(24);
+    page.set_margin_bottom(24);
+
+    let title = Label::new(Some(&tr("✅ Link created")));
+    title.set_halign(gtk4::Align::Start);
+    title.add_css_class("dialog-title");
+    page.append(&title);
+
+    let summary = Label::new(Some(&link.summary(util::now())));
+    summary.set_halign(gtk4::Align::Start);
+    summary.add_css_class("share-summary");
+    page.append(&summary);
+
+    let url_entry = Entry::new();
+    url_entry.set_text(&link.url());
+    url_entry.set_editable(false);
+    page.append(&url_entry);
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 12);
+    buttons.set_halign(gtk4::Align::End);
+    let copy_btn = Button::with_label(&tr("📋 Copy"));
+    copy_btn.add_css_class("secondary-button");
+    let send_btn = Button::with_label(&tr("📤 Send to…"));
+    send_btn.add_css_class("secondary-button");
+    let done_btn = Button::with_label(&tr("Done"));
+    done_btn.add_css_class("create-post-button");
+    buttons.append(&copy_btn);
+    buttons.append(&send_btn);
+    buttons.append(&done_btn);
+    page.append(&buttons);
+
+    let url = link.url();
+    copy_btn.connect_clicked(move |button| {
+        button.clipboard().set_text(&url);
+        button.set_label(&tr("✅ Copied"));
+    });
+
+    let state = state.clone();
+    let content = ShareContent::Link {
+        url: link.url(),
+        title: link.target.title().to_string(),
+    };
+    let window_clone = window.clone();
+    send_btn.connect_clicked(move |_| {
+        let parent = window_clone.transient_for();
+        window_clone.close();
+        if let Some(parent) = parent {
+            share_sheet::open_share_sheet_for(&parent, &state, vec![content.clone()]);
+        }
+    });
+
+    let window = window.clone();
+    done_btn.connect_clicked(move |_| window.close());
+
+    page
+}
+
+/// Everything a link can point at: library items, then feed posts.
+fn link_targets(state: &AppState) -> Vec<LinkTarget> {
+    let media = state.media.borrow();
+    let feed = state.feed.borrow();
+    media
+        .items()
+        .iter()
+        .map(|item| LinkTarget::Media {
+            id: item.id.clone(),
+            title: item.title.clone(),
+            kind: item.kind,
+        })
+        .chain(feed.posts().iter().map(|post| LinkTarget::Post {
+            id: post.id.clone(),
+            title: post.title(),
+        }))
+        .collect()
+}
+
+fn form_label(text: &str) -> Label {
+    let label = Label::new(Some(text));
+    label.set_halign(gtk4::Align::Start);
+    label.add_css_class("form-label");
+    label
+}
+
+// ============================================================================
+// Management list
+// ============================================================================
+
+/// The "Share Links" section of the Share view.
+pub fn create_links_panel(state: &Rc<AppState>) -> GtkBox {
+    let panel = GtkBox::new(Orientation::Vertical, 12);
+
+    let header = GtkBox::new(Orientation::Horizontal, 12);
+    let title = Label::new(Some(&tr("Share Links")));
+    title.add_css_class("section-subtitle");
+    title.set_halign(gtk4::Align::Start);
+    title.set_hexpand(true);
+    let new_btn = Button::with_label(&tr("➕ New Link"));
+    new_btn.add_css_class("action-button");
+    header.append(&title);
+    header.append(&new_btn);
+    panel.append(&header);
+
+    // Open a link someone sent
+    let open_box = GtkBox::new(Orientation::Horizontal, 12);
+    let open_entry = Entry::new();
+    open_entry.set_placeholder_text(Some(&tr("Paste a linkwithmentor:// link to open it")));
+    open_entry.set_hexpand(true);
+    let open_btn = Button::with_label(&tr("Open"));
+    open_btn.add_css_class("action-button");
+    open_box.append(&open_entry);
+    open_box.append(&open_btn);
+    panel.append(&open_box);
+
+    let list = GtkBox::new(Orientation::Vertical, 8);
+    panel.append(&list);
+
+    let state_clone = state.clone();
+    new_btn.connect_clicked(move |button| open_link_dialog(button, &state_clone, None));
+
+    let open = {
+        let state = state.clone();
+        let open_entry = open_entry.clone();
+        move || {
+            let url = open_entry.text().trim().to_string();
+            if !url.is_empty() {
+                open_link(&open_entry, &state, &url);
+            }
+        }
+    };
+    let open = Rc::new(open);
+    let open_clone = open.clone();
+    open_btn.connect_clicked(move |_| open_clone());
+    open_entry.connect_activate(move |_| open());
+
+    let populate = {
+        let state = state.clone();
+        move || {
+            while let Some(child) = list.first_child() {
+                list.remove(&child);
+            }
+            let links = state.links.borrow().links().to_vec();
+            if links.is_empty() {
+                let empty = Label::new(Some(&tr("No share links yet")));
+                empty.add_css_class("share-empty");
+                list.append(&empty);
+            }
+            for link in links {
+                list.append(&create_link_row(&state, &link));
+            }
+        }
+    };
+    populate();
+    state.links_changed.connect(populate);
+
+    panel
+}
+
+fn create_link_row(state: &Rc<AppState>, link: &ShareLink) -> Frame {
+    let frame = Frame::new(None);
+    frame.add_css_class("share-item");
+
+    let item_box = GtkBox::new(Orientation::Horizontal, 16);
+    item_box.set_margin_start(16);
+    item_box.set_margin_end(16);
+    item_box.set_margin_top(12);
+    item_box.set_margin_bottom(12);
+
+    let icon_label = Label::new(Some(link.target.icon()));
+    icon_label.add_css_class("share-item-icon");
+
+    let info_box = GtkBox::new(Orientation::Vertical, 4);
+    info_box.set_hexpand(true);
+
+    let title_label = Label::new(Some(link.target.title()));
+    title_label.set_halign(gtk4::Align::Start);
+    title_label.add_css_class("share-item-file");
+
+    let url_label = Label::new(Some(&link.url()));
+    url_label.set_halign(gtk4::Align::Start);
+    url_label.set_selectable(true);
+    url_label.set_ellipsize(gtk4::pango::EllipsizeMode::Middle);
+    url_label.add_css_class("share-item-shared");
+
+    let now = util::now();
+    let active = link.state(now) == LinkState::Active;
+    let summary_label = Label::new(Some(&link.summary(now)));
+    summary_label.set_halign(gtk4::Align::Start);
+    summary_label.add_css_class("share-status");
+    summary_label.add_css_class(if active {
+        "share-status-delivered"
+    } else {
+        "share-status-failed"
+    });
+
+    info_box.append(&title_label);
+    info_box.append(&url_label);
+    info_box.append(&summary_label);
+
+    let copy_btn = Button::with_label(&tr("📋 Copy"));
+    copy_btn.add_css_class("action-button");
+    copy_btn.set_valign(gtk4::Align::Center);
+    copy_btn.set_sensitive(active);
+    let revoke_btn = Button::with_label(&tr("🚫 Revoke"));
+    revoke_btn.add_css_class("action-button");
+    revoke_btn.set_valign(gtk4::Align::Center);
+    revoke_btn.set_sensitive(!link.revoked);
+    let delete_btn = Button::with_label("🗑️");
+    delete_btn.set_tooltip_text(Some(&tr("Delete link")));
+    delete_btn.add_css_class("danger-button");
+    delete_btn.set_valign(gtk4::Align::Center);
+
+    item_box.append(&icon_label);
+    item_box.append(&info_box);
+    item_box.append(&copy_btn);
+    item_box.append(&revoke_btn);
+    item_box.append(&delete_btn);
+
+    let url = link.url();
+    copy_btn.connect_clicked(move |button| button.clipboard().set_text(&url));
+
+    let token = link.token.clone();
+    let state_clone = state.clone();
+    revoke_btn.connect_clicked(move |_| {
+        state_clone.links.borrow_mut().revoke(&token);
+        state_clone.links_changed.emit();
+    });
+
+    let token = link.token.clone();
+    let state_clone = state.clone();
+    delete_btn.connect_clicked(move |_| {
+        state_clone.links.borrow_mut().delete(&token);
+        state_clone.links_changed.emit();
+    });
+
+    frame.set_child(Some(&item_box));
+    frame
+}
+
+// ============================================================================
+// Opening links
+// ============================================================================
+
+/// Opens share links and #hashtags in `label`'s markup inside the app; web
+/// links go to the browser.
+pub fn connect_links(label: &Label, state: &Rc<AppState>) {
+    let state = state.clone();
+    label.connect_activate_link(move |label, uri| {
+        if uri.starts_with(&format!("{}://", links::SCHEME)) {
+            open_link(label, &state, uri);
+            glib::Propagation::Stop
+        } else {
+            glib::Propagation::Proceed
+        }
+    });
+}
+
+/// Opens a `linkwithmentor://` link: hashtag links show the tag's page,
+/// and share links ask for a password when needed.
+pub fn open_link(parent: &impl IsA<gtk4::Widget>, state: &Rc<AppState>, url: &str) {
+    if let Some(tag) = hashtags::from_url(url) {
+        navigation::activate(parent, "show-hashtag", Some(&tag.to_variant()));
+        return;
+    }
+    let result = state.links.borrow_mut().resolve(url, None, util::now());
+    state.links_changed.emit();
+    match result {
+        Ok(target) => show_target(parent, state, &target),
+        Err(LinkError::PasswordRequired) => prompt_password(parent, state, url),
+        Err(error) => show_error(parent, error),
+    }
+}
+
+fn prompt_password(parent: &impl IsA<gtk4::Widget>, state: &Rc<AppState>, url: &str) {
+    let parent_window = parent.root().and_downcast::<gtk4::Window>();
+    let window = gtk4::Window::builder()
+        .title(tr("Password Required"))
+        .modal(true)
+        .default_width(380)
+        .build();
+    window.add_css_class("share-sheet");
+    window.set_transient_for(parent_window.as_ref());
+
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_start(24);
+    content.set_margin_end(24);
+    content.set_margin_top(24);
+    content.set_margin_bottom(24);
+
+    let title = Label::new(Some(&tr("🔒 This link is password protected")));
+    title.set_halign(gtk4::Align::Start);
+    title.add_css_class("dialog-title");
+    let password_entry = PasswordEntry::new();
+    password_entry.set_show_peek_icon(true);
+    let error_label = Label::new(None);
+    error_label.set_halign(gtk4::Align::Start);
+    error_label.add_css_class("form-error");
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 12);
+    buttons.set_halign(gtk4::Align::End);
+    let cancel_btn = Button::with_label(&tr("Cancel"));
+    cancel_btn.add_css_class("secondary-button");
+    let open_btn = Button::with_label(&tr("Open"));
+    open_btn.add_css_class("create-post-button");
+    buttons.append(&cancel_btn);
+    buttons.append(&open_btn);
+
+    content.append(&title);
+    content.append(&password_entry);
+    content.append(&error_label);
+    content.append(&buttons);
+    window.set_child(Some(&content));
+
+    let window_clone = window.clone();
+    cancel_btn.connect_clicked(move |_| window_clone.close());
+
+    let submit = {
+        let state = state.clone();
+        let window = window.clone();
+        let password_entry = password_entry.clone();
+        let url = url.to_string();
+        move || {
+            let password = password_entry.text();
+            let result = state
+                .links
+                .borrow_mut()
+                .resolve(&url, Some(&password), util::now());
+            state.links_changed.emit();
+            match result {
+                Err(LinkError::WrongPassword) => {
+                    error_label.set_text(&LinkError::WrongPassword.to_string());
+                    password_entry.set_text("");
+                }
+                result => {
+                    window.close();
+                    let Some(parent) = parent_window.clone() else {
+                        return;
+                    };
+                    match result {
+                        Ok(target) => show_target(&parent, &state, &target),
+                        Err(error) => show_error(&parent, error),
+                    }
+                }
+            }
+        }
+    };
+    let submit = Rc::new(submit);
+    let submit_clone = submit.clone();
+    open_btn.connect_clicked(move |_| submit_clone());
+    password_entry.connect_activate(move |_| submit());
+
+    window.present();
+}
+
+fn show_target(parent: &impl IsA<gtk4::Widget>, state: &Rc<AppState>, target: &LinkTarget) {
+    match target {
+        LinkTarget::Media { id, title, .. } => {
+            let item = state
+                .media
+                .borrow()
+                .items()
+                .iter()
+                .find(|item| item.id == *id)
+                .cloned();
+            match item {
+                Some(item) => viewer::open_viewer(parent, vec![item], 0),
+                None => show_message(
+                    parent,
+                    &tr_with("“{title}” is no longer available", &[("title", title)]),
+                    &tr("The file was removed from the library after the link was created."),
+                ),
+            }
+        }
+        LinkTarget::Post { id, title } => {
+            let post = state.feed.borrow().post(id).cloned();
+            match post {
+                Some(post) => {
+                    let author = state.community.borrow().user_name(&post.author);
+                    show_message(
+                        parent,
+                        &tr_with("📝 Post by {author}", &[("author", &author)]),
+                        &post.text,
+                    );
+                }
+                None => show_message(
+                    parent,
+                    &tr_with("“{title}” is no longer available", &[("title", title)]),
+                    &tr("The post was deleted after the link was created."),
+                ),
+            }
+        }
+    }
+}
+
+fn show_error(parent: &impl IsA<gtk4::Widget>, error: LinkError) {
+    show_message(parent, &tr("This link can't be opened"), &error.to_string());
+}
+
+fn show_message(parent: &impl IsA<gtk4::Widget>, message: &str, detail: &str) {
+    let dialog = gtk4::AlertDialog::builder()
+        .message(message)
+        .detail(detail)
+        .buttons([tr("OK")])
+        .build();
+    let window = parent.root().and_downcast::<gtk4::Window>();
+    dialog.choose(window.as_ref(), gio::Cancellable::NONE, |_| {});
+}
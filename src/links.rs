@@ -0,0 +1,425 @@
+// Share links: expiring, password-protected and download-limited
+use crate::auth::{self, PasswordHash};
+use crate::i18n::{self, tr, tr_with, trn};
+use crate::media::MediaKind;
+use crate::storage;
+use crate::util;
+use gtk4::glib;
+use std::fmt;
+
+const LINKS_FILE: &str = "links.ini";
+const LINK_PREFIX: &str = "link:";
+
+/// URI scheme the app registers for opening share links.
+pub const SCHEME: &str = "linkwithmentor";
+
+/// What a share link points at.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LinkTarget {
+    Media {
+        id: String,
+        title: String,
+        kind: MediaKind,
+    },
+    Post {
+        id: String,
+        title: String,
+    },
+}
+
+impl LinkTarget {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            LinkTarget::Media { kind, .. } => kind.icon(),
+            LinkTarget::Post { .. } => "📝",
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        match self {
+            LinkTarget::Media { title, .. } | LinkTarget::Post { title, .. } => title,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkState {
+    Active,
+    Expired,
+    Revoked,
+    /// The download limit has been reached.
+    Exhausted,
+}
+
+#[derive(Clone, Debug)]
+pub struct ShareLink {
+    pub token: String,
+    pub target: LinkTarget,
+    pub created_at: i64,
+    pub expires_at: Option<i64>,
+    pub password: Option<PasswordHash>,
+    pub max_downloads: Option<u32>,
+    pub downloads: u32,
+    pub revoked: bool,
+}
+
+impl ShareLink {
+    pub fn url(&self) -> String {
+        format!("{SCHEME}://share/{}", self.token)
+    }
+
+    pub fn state(&self, now: i64) -> LinkState {
+        if self.revoked {
+            LinkState::Revoked
+        } else if self.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            LinkState::Expired
+        } else if self
+            .max_downloads
+            .is_some_and(|limit| self.downloads >= limit)
+        {
+            LinkState::Exhausted
+        } else {
+            LinkState::Active
+        }
+    }
+
+    /// One-line description for the management list, e.g.
+    /// "Expires in 3 days · 🔒 Password · 2/10 downloads".
+    pub fn summary(&self, now: i64) -> String {
+        let mut parts = vec![match (self.state(now), self.expires_at) {
+            (LinkState::Revoked, _) => tr("Revoked"),
+            (LinkState::Expired, _) => tr("Expired"),
+            (LinkState::Exhausted, _) => tr("Download limit reached"),
+            (LinkState::Active, Some(expires_at)) => tr_with(
+                "Expires {time}",
+                &[("time", &util::format_time_until(expires_at, now))],
+            ),
+            (LinkState::Active, None) => tr("Never expires"),
+        }];
+        if self.password.is_some() {
+            parts.push(tr("🔒 Password"));
+        }
+        parts.push(match self.max_downloads {
+            Some(limit) => trn(
+                "{count}/{n} download",
+                "{count}/{n} downloads",
+                limit.into(),
+            )
+            .replace("{count}", &i18n::number(self.downloads)),
+            None => trn("{n} download", "{n} downloads", self.downloads.into()),
+        });
+        parts.join(" · ")
+    }
+}
+
+/// Why a link could not be opened.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkError {
+    Malformed,
+    NotFound,
+    Revoked,
+    Expired,
+    LimitReached,
+    PasswordRequired,
+    WrongPassword,
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            LinkError::Malformed => tr("This isn't a LinkWithMentor share link."),
+            LinkError::NotFound => tr("This link doesn't exist or has been deleted."),
+            LinkError::Revoked => tr("The owner has revoked this link."),
+            LinkError::Expired => tr("This link has expired."),
+            LinkError::LimitReached => tr("This link has reached its download limit."),
+            LinkError::PasswordRequired => tr("This link is protected by a password."),
+            LinkError::WrongPassword => tr("Incorrect password."),
+        };
+        f.write_str(&text)
+    }
+}
+
+/// Extracts the token from `linkwithmentor://share/<token>`.
+pub fn parse_token(url: &str) -> Result<String, LinkError> {
+    let uri =
+        glib::Uri::parse(url.trim(), glib::UriFlags::NONE).map_err(|_| LinkError::Malformed)?;
+    if uri.scheme() != SCHEME || uri.host().as_deref() != Some("share") {
+        return Err(LinkError::Malformed);
+    }
+    let path = uri.path();
+    let token = path.trim_start_matches('/');
+    if token.is_empty() || !token.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(LinkError::Malformed);
+    }
+    Ok(token.to_string())
+}
+
+pub struct LinkStore {
+    links: Vec<ShareLink>,
+}
+
+impl LinkStore {
+    pub fn load() -> Self {
+        let Some(key_file) = storage::load_key_file(LINKS_FILE) else {
+            return Self { links: Vec::new() };
+        };
+
+        let mut links: Vec<ShareLink> = storage::groups_with_prefix(&key_file, LINK_PREFIX)
+            .into_iter()
+            .filter_map(|(token, group)| {
+                let get = |key: &str| storage::string_or(&key_file, &group, key, "");
+                let target = match get("type").as_str() {
+                    "media" => LinkTarget::Media {
+                        id: get("target_id"),
+                        title: get("title"),
+                        kind: MediaKind::from_id(&get("media_kind")),
+                    },
+                    "post" => LinkTarget::Post {
+                        id: get("target_id"),
+                        title: get("title"),
+                    },
+                    _ => return None,
+                };
+                let password = PasswordHash::load(&key_file, &group, "password");
+                Some(ShareLink {
+                    target,
+                    created_at: key_file.int64(&group, "created_at").unwrap_or_default(),
+                    expires_at: key_file.int64(&group, "expires_at").ok(),
+                    password,
+                    max_downloads: key_file
+                        .integer(&group, "max_downloads")
+                        .ok()
+                        .map(|limit| limit.max(0) as u32),
+                    downloads: key_file
+                        .integer(&group, "downloads")
+                        .unwrap_or_default()
+                        .max(0) as u32,
+                    revoked: key_file.boolean(&group, "revoked").unwrap_or_default(),
+                    token,
+                })
+            })
+            .collect();
+        links.sort_by_key(|link| std::cmp::Reverse(link.created_at));
+
+        Self { links }
+    }
+
+    pub fn save(&self) {
+        let key_file = glib::KeyFile::new();
+
+        for link in &self.links {
+            let group = format!("{LINK_PREFIX}{}", link.token);
+            match &link.target {
+                LinkTarget::Media { id, title, kind } => {
+                    key_file.set_string(&group, "type", "media");
+                    key_file.set_string(&group, "target_id", id);
+                    key_file.set_string(&group, "media_kind", kind.id());
+                    key_file.set_string(&group, "title", title);
+                }
+                LinkTarget::Post { id, title } => {
+                    key_file.set_string(&group, "type", "post");
+                    key_file.set_string(&group, "target_id", id);
+                    key_file.set_string(&group, "title", title);
+                }
+            }
+            key_file.set_int64(&group, "created_at", link.created_at);
+            if let Some(expires_at) = link.expires_at {
+                key_file.set_int64(&group, "expires_at", expires_at);
+            }
+            if let Some(password) = &link.password {
+                password.save(&key_file, &group, "password");
+            }
+            if let Some(limit) = link.max_downloads {
+                key_file.set_integer(&group, "max_downloads", limit as i32);
+            }
+            key_file.set_integer(&group, "downloads", link.downloads as i32);
+            key_file.set_boolean(&group, "revoked", link.revoked);
+        }
+
+        storage::save_key_file(LINKS_FILE, &key_file);
+    }
+
+    /// Links, newest first.
+    pub fn links(&self) -> &[ShareLink] {
+        &self.links
+    }
+
+    /// Creates a link; an empty `password` or a zero `max_downloads` means none.
+    pub fn create(
+        &mut self,
+        target: LinkTarget,
+        expires_at: Option<i64>,
+        password: &str,
+        max_downloads: u32,
+    ) -> ShareLink {
+        let link = ShareLink {
+            token: auth::random_token(),
+            target,
+            created_at: util::now(),
+            expires_at,
+            password: (!password.is_empty()).then(|| PasswordHash::new(password)),
+            max_downloads: (max_downloads > 0).then_some(max_downloads),
+            downloads: 0,
+            revoked: false,
+        };
+        self.links.insert(0, link.clone());
+        self.save();
+        link
+    }
+
+    pub fn revoke(&mut self, token: &str) {
+        if let Some(link) = self.links.iter_mut().find(|link| link.token == token) {
+            link.revoked = true;
+            self.save();
+        }
+    }
+
+    pub fn delete(&mut self, token: &str) {
+        self.links.retain(|link| link.token != token);
+        self.save();
+    }
+
+    /// Checks every rule on the link behind `url` and, if it may be opened,
+    /// counts the download and returns what it points at.
+    pub fn resolve(
+        &mut self,
+        url: &str,
+        password: Option<&str>,
+        now: i64,
+    ) -> Result<LinkTarget, LinkError> {
+        let token = parse_token(url)?;
+        let link = self
+            .links
+            .iter_mut()
+            .find(|link| link.token == token)
+            .ok_or(LinkError::NotFound)?;
+
+        match link.state(now) {
+            LinkState::Active => {}
+            LinkState::Revoked => return Err(LinkError::Revoked),
+            LinkState::Expired => return Err(LinkError::Expired),
+            LinkState::Exhausted => return Err(LinkError::LimitReached),
+        }
+        if let Some(hash) = &link.password {
+            match password {
+                None => return Err(LinkError::PasswordRequired),
+                Some(password) if !hash.verify(password) => return Err(LinkError::WrongPassword),
+                Some(_) => {}
+            }
+        }
+
+        link.downloads += 1;
+        let target = link.target.clone();
+        self.save();
+        Ok(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post() -> LinkTarget {
+        LinkTarget::Post {
+            id: "sample-1".to_string(),
+            title: "Just completed…".to_string(),
+        }
+    }
+
+    #[test]
+    fn tokens_are_random_and_parse() {
+        let mut store = LinkStore::load();
+        let first = store.create(post(), None, "", 0);
+        let second = store.create(post(), None, "", 0);
+        assert_ne!(first.token, second.token);
+        assert_eq!(first.token.len(), 32);
+        assert_eq!(parse_token(&first.url()), Ok(first.token));
+    }
+
+    #[test]
+    fn expired_links_are_refused() {
+        let mut store = LinkStore::load();
+        let now = util::now();
+        let link = store.create(post(), Some(now + 60), "", 0);
+        assert_eq!(store.resolve(&link.url(), None, now), Ok(post()));
+        assert_eq!(
+            store.resolve(&link.url(), None, now + 60),
+            Err(LinkError::Expired)
+        );
+    }
+
+    #[test]
+    fn revoked_links_are_refused() {
+        let mut store = LinkStore::load();
+        let link = store.create(post(), None, "", 0);
+        store.revoke(&link.token);
+        assert_eq!(
+            store.resolve(&link.url(), None, util::now()),
+            Err(LinkError::Revoked)
+        );
+        store.delete(&link.token);
+        assert_eq!(
+            store.resolve(&link.url(), None, util::now()),
+            Err(LinkError::NotFound)
+        );
+    }
+
+    #[test]
+    fn download_limit_is_enforced() {
+        let mut store = LinkStore::load();
+        let now = util::now();
+        let link = store.create(post(), None, "", 2);
+        for _ in 0..2 {
+            assert_eq!(store.resolve(&link.url(), None, now), Ok(post()));
+        }
+        assert_eq!(
+            store.resolve(&link.url(), None, now),
+            Err(LinkError::LimitReached)
+        );
+        assert_eq!(store.links()[0].downloads, 2);
+    }
+
+    #[test]
+    fn passwords_are_checked() {
+        let mut store = LinkStore::load();
+        let now = util::now();
+        let link = store.create(post(), None, "hunter2", 0);
+        assert_eq!(
+            store.resolve(&link.url(), None, now),
+            Err(LinkError::PasswordRequired)
+        );
+        assert_eq!(
+            store.resolve(&link.url(), Some("hunter3"), now),
+            Err(LinkError::WrongPassword)
+        );
+        // Refused attempts don't count as downloads
+        assert_eq!(store.links()[0].downloads, 0);
+        assert_eq!(store.resolve(&link.url(), Some("hunter2"), now), Ok(post()));
+        assert_eq!(store.links()[0].downloads, 1);
+    }
+
+    #[test]
+    fn links_survive_a_reload() {
+        // Stores are only saved while someone is signed in
+        storage::set_account(Some("ada@example.com"));
+        let mut store = LinkStore::load();
+        let link = store.create(post(), None, "hunter2", 5);
+        let mut reloaded = LinkStore::load();
+        assert_eq!(
+            reloaded.resolve(&link.url(), Some("hunter2"), util::now()),
+            Ok(post())
+        );
+    }
+
+    #[test]
+    fn other_urls_are_malformed() {
+        for url in [
+            "https://share/abc",
+            "linkwithmentor://post/abc",
+            "linkwithmentor://share/",
+            "linkwithmentor://share/ab-cd",
+        ] {
+            assert_eq!(parse_token(url), Err(LinkError::Malformed), "{url}");
+        }
+    }
+}
@@ -0,0 +1,202 @@
+// Job openings and upcoming events shown on the Jobs and Events pages
+use crate::storage;
+use crate::util::{self, DAY, HOUR};
+
+const LISTINGS_FILE: &str = "listings.ini";
+const JOB_PREFIX: &str = "job:";
+const EVENT_PREFIX: &str = "event:";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListingKind {
+    Job,
+    Event,
+}
+
+impl ListingKind {
+    fn prefix(self) -> &'static str {
+        match self {
+            ListingKind::Job => JOB_PREFIX,
+            ListingKind::Event => EVENT_PREFIX,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Listing {
+    pub id: String,
+    pub kind: ListingKind,
+    pub title: String,
+    /// The company hiring or whoever runs the event.
+    pub host: String,
+    /// A city, "Remote" or "Online".
+    pub place: String,
+    pub description: String,
+    pub posted_at: i64,
+    /// When an event begins; None for jobs.
+    pub starts_at: Option<i64>,
+}
+
+pub struct Listings {
+    listings: Vec<Listing>,
+}
+
+impl Listings {
+    pub fn load() -> Self {
+        let Some(key_file) = storage::load_key_file(LISTINGS_FILE) else {
+            return Self::with_samples();
+        };
+
+        let mut listings = Vec::new();
+        for kind in [ListingKind::Job, ListingKind::Event] {
+            for (id, group) in storage::groups_with_prefix(&key_file, kind.prefix()) {
+                listings.push(Listing {
+                    kind,
+                    title: storage::string_or(&key_file, &group, "title", ""),
+                    host: storage::string_or(&key_file, &group, "host", ""),
+                    place: storage::string_or(&key_file, &group, "place", ""),
+                    description: storage::string_or(&key_file, &group, "description", ""),
+                    posted_at: key_file.int64(&group, "posted_at").unwrap_or_default(),
+                    starts_at: key_file.int64(&group, "starts_at").ok(),
+                    id,
+                });
+            }
+        }
+        listings.sort_by_key(|listing| std::cmp::Reverse(listing.posted_at));
+        Self { listings }
+    }
+
+    fn with_samples() -> Self {
+        let now = util::now();
+        let job =
+            |id: &str, title: &str, host: &str, place: &str, description: &str, age: i64| Listing {
+                id: id.to_string(),
+                kind: ListingKind::Job,
+                title: title.to_string(),
+                host: host.to_string(),
+                place: place.to_string(),
+                description: description.to_string(),
+                posted_at: now - age,
+                starts_at: None,
+            };
+        let event = |id: &str,
+                     title: &str,
+                     host: &str,
+                     place: &str,
+                     description: &str,
+                     age: i64,
+                     starts_in: i64| Listing {
+            kind: ListingKind::Event,
+            starts_at: Some(now + starts_in),
+            ..job(id, title, host, place, description, age)
+        };
+
+        Self {
+            listings: vec![
+                job(
+                    "sample-job-1",
+                    "Junior Rust Developer",
+                    "Ferrous Labs",
+                    "Remote",
+                    "Build command-line tools and services in Rust, with a mentor for your first six months.",
+                    3 * HOUR,
+                ),
+                event(
+                    "sample-event-1",
+                    "GTK4 Hack Night",
+                    "Open Source",
+                    "Online",
+                    "Bring a GTK4 project or pick a good first issue and pair with other contributors.",
+                    6 * HOUR,
+                    3 * DAY,
+                ),
+                job(
+                    "sample-job-2",
+                    "UX Designer",
+                    "Brightside Studio",
+                    "Berlin",
+                    "Design mentoring tools used by thousands of learners, from research to prototypes.",
+                    DAY,
+                ),
+                event(
+                    "sample-event-2",
+                    "Machine Learning Career Panel",
+                    "Career Mentorship",
+                    "London",
+                    "Four engineers share how they moved into machine learning, followed by questions.",
+                    2 * DAY,
+                    10 * DAY,
+                ),
+            ],
+        }
+    }
+
+    /// Reads the listings again, e.g. after a sync added some. Returns
+    /// whether they changed. Times aren't compared, since the samples
+    /// are dated relative to now.
+    pub fn reload(&mut self) -> bool {
+        let loaded = Self::load();
+        let texts = |listings: &[Listing]| -> Vec<[String; 5]> {
+            listings
+                .iter()
+                .map(|listing| {
+                    [
+                        listing.id.clone(),
+                        listing.title.clone(),
+                        listing.host.clone(),
+                        listing.place.clone(),
+                        listing.description.clone(),
+                    ]
+                })
+                .collect()
+        };
+        let changed = texts(&loaded.listings) != texts(&self.listings);
+        if changed {
+            *self = loaded;
+        }
+        changed
+    }
+
+    /// Listings of `kind`, newest first.
+    pub fn of_kind(&self, kind: ListingKind) -> impl Iterator<Item = &Listing> {
+        self.listings
+            .iter()
+            .filter(move |listing| listing.kind == kind)
+    }
+
+    /// All listings, newest first.
+    pub fn all(&self) -> &[Listing] {
+        &self.listings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gtk4::glib;
+
+    #[test]
+    fn samples_reload_unchanged() {
+        let mut listings = Listings::load();
+        assert!(!listings.all().is_empty());
+        assert!(!listings.reload());
+    }
+
+    #[test]
+    fn edited_listings_reload() {
+        // Stores are only saved while someone is signed in
+        storage::set_account(Some("ada@example.com"));
+        let key_file = glib::KeyFile::new();
+        key_file.set_string("job:1", "title", "Rust Developer");
+        key_file.set_int64("job:1", "posted_at", 10);
+        storage::save_key_file(LISTINGS_FILE, &key_file);
+
+        let mut listings = Listings::load();
+        assert_eq!(listings.of_kind(ListingKind::Job).count(), 1);
+        assert!(!listings.reload());
+
+        key_file.set_string("job:1", "title", "Senior Rust Developer");
+        storage::save_key_file(LISTINGS_FILE, &key_file);
+        assert!(listings.reload());
+        assert_eq!(listings.all()[0].title, "Senior Rust Developer");
+    }
+}
@@ -0,0 +1,682 @@
+// Locations: offline map tiles, geocoding and static map previews
+use crate::i18n::tr_with;
+use crate::storage;
+use gtk4::{gdk_pixbuf, gio, glib};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::path::{Path, PathBuf};
+
+const LOCATION_FILE: &str = "location.ini";
+const PLACES_FILE: &str = "places.csv";
+
+pub const TILE_SIZE: i32 = 256;
+pub const MIN_ZOOM: u8 = 1;
+pub const DEFAULT_ZOOM: u8 = 14;
+/// Deepest zoom level used, whatever a tile source claims; OpenStreetMap
+/// stops at 19 and the tile arithmetic overflows not far past this.
+pub const MAX_ZOOM: u8 = 22;
+/// Web Mercator can't show the poles; latitudes are clamped to this.
+const MAX_LATITUDE: f64 = 85.051_128_78;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Location {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub label: String,
+}
+
+impl Location {
+    pub fn title(&self) -> String {
+        if self.label.is_empty() {
+            format_coordinates(self.latitude, self.longitude)
+        } else {
+            self.label.clone()
+        }
+    }
+
+    /// OpenStreetMap link for viewing the place online.
+    pub fn web_url(&self) -> String {
+        format!(
+            "https://www.openstreetmap.org/?mlat={lat:.5}&mlon={lon:.5}#map={DEFAULT_ZOOM}/{lat:.5}/{lon:.5}",
+            lat = self.latitude,
+            lon = self.longitude
+        )
+    }
+}
+
+pub fn format_coordinates(latitude: f64, longitude: f64) -> String {
+    format!("{latitude:.5}, {longitude:.5}")
+}
+
+/// Parses "48.8584, 2.2945" or "48.8584 2.2945" into latitude and longitude.
+pub fn parse_coordinates(text: &str) -> Option<(f64, f64)> {
+    let mut parts = text
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty());
+    let latitude: f64 = parts.next()?.parse().ok()?;
+    let longitude: f64 = parts.next()?.parse().ok()?;
+    if parts.next().is_some()
+        || !(-90.0..=90.0).contains(&latitude)
+        || !(-180.0..=180.0).contains(&longitude)
+    {
+        return None;
+    }
+    Some((latitude, longitude))
+}
+
+// ============================================================================
+// Web Mercator
+// ============================================================================
+
+/// Position in world pixels at `zoom`, where the world is
+/// `TILE_SIZE << zoom` pixels wide.
+pub fn project(latitude: f64, longitude: f64, zoom: u8) -> (f64, f64) {
+    let size = world_size(zoom);
+    let latitude = latitude.clamp(-MAX_LATITUDE, MAX_LATITUDE).to_radians();
+    let x = (longitude + 180.0) / 360.0 * size;
+    let y = (1.0 - (latitude.tan() + 1.0 / latitude.cos()).ln() / PI) / 2.0 * size;
+    (x, y)
+}
+
+/// Inverse of `project`; longitudes wrap around into -180..180.
+pub fn unproject(x: f64, y: f64, zoom: u8) -> (f64, f64) {
+    let size = world_size(zoom);
+    let longitude = (x / size * 360.0).rem_euclid(360.0) - 180.0;
+    let latitude = (PI - 2.0 * PI * y / size).sinh().atan().to_degrees();
+    (latitude, longitude)
+}
+
+fn world_size(zoom: u8) -> f64 {
+    f64::from(TILE_SIZE) * f64::from(zoom.min(MAX_ZOOM)).exp2()
+}
+
+// ============================================================================
+// Tile sources
+// ============================================================================
+
+/// A source of raster map tiles addressed as z/x/y (XYZ, y grows southwards).
+pub trait TileSource {
+    fn name(&self) -> String;
+    fn max_zoom(&self) -> u8;
+    /// Encoded image data (PNG, JPEG, …) for one tile, if the source has it.
+    fn tile(&self, zoom: u8, x: u32, y: u32) -> Option<Vec<u8>>;
+}
+
+/// Tiles stored as `<root>/<z>/<x>/<y>.png` (or `.jpg`), as written by most
+/// tile downloaders.
+pub struct TileDirectory {
+    root: PathBuf,
+    max_zoom: u8,
+}
+
+impl TileDirectory {
+    pub fn open(root: &Path) -> Result<Self, String> {
+        let max_zoom = std::fs::read_dir(root)
+            .map_err(|err| {
+                tr_with(
+                    "Can't read {path}: {error}",
+                    &[
+                        ("path", &root.display().to_string()),
+                        ("error", &err.to_string()),
+                    ],
+                )
+            })?
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u8>().ok())
+            .max()
+            .ok_or_else(|| {
+                tr_with(
+                    "{path} has no zoom level folders",
+                    &[("path", &root.display().to_string())],
+                )
+            })?
+            .min(MAX_ZOOM);
+        Ok(Self {
+            root: root.to_path_buf(),
+            max_zoom,
+        })
+    }
+}
+
+impl TileSource for TileDirectory {
+    fn name(&self) -> String {
+        self.root.file_name().map_or_else(
+            || self.root.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        )
+    }
+
+    fn max_zoom(&self) -> u8 {
+        self.max_zoom
+    }
+
+    fn tile(&self, zoom: u8, x: u32, y: u32) -> Option<Vec<u8>> {
+        let dir = self.root.join(zoom.to_string()).join(x.to_string());
+        ["png", "jpg", "jpeg", "webp"]
+            .iter()
+            .find_map(|ext| std::fs::read(dir.join(format!("{y}.{ext}"))).ok())
+    }
+}
+
+/// An MBTiles file: an SQLite database of tiles in TMS order (y grows
+/// northwards).
+pub struct MbTiles {
+    path: PathBuf,
+    connection: rusqlite::Connection,
+    max_zoom: u8,
+}
+
+impl MbTiles {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let connection =
+            rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+                .map_err(|err| {
+                    tr_with(
+                        "Can't open {path}: {error}",
+                        &[
+                            ("path", &path.display().to_string()),
+                            ("error", &err.to_string()),
+                        ],
+                    )
+                })?;
+
+        // Prefer the metadata table; fall back to scanning the tiles.
+        let from_metadata: Option<u8> = connection
+            .query_row(
+                "SELECT value FROM metadata WHERE name = 'maxzoom'",
+                [],
+                |row| row.get::<_, String>(0),
+            )
+            .ok()
+            .and_then(|value| value.trim().parse().ok());
+        let max_zoom = match from_metadata {
+            Some(zoom) => zoom,
+            None => connection
+                .query_row("SELECT MAX(zoom_level) FROM tiles", [], |row| {
+                    row.get::<_, Option<u8>>(0)
+                })
+                .map_err(|err| {
+                    tr_with(
+                        "{path} isn't an MBTiles file: {error}",
+                        &[
+                            ("path", &path.display().to_string()),
+                            ("error", &err.to_string()),
+                        ],
+                    )
+                })?
+                .ok_or_else(|| {
+                    tr_with(
+                        "{path} contains no tiles",
+                        &[("path", &path.display().to_string())],
+                    )
+                })?,
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            connection,
+            max_zoom: max_zoom.min(MAX_ZOOM),
+        })
+    }
+}
+
+impl TileSource for MbTiles {
+    fn name(&self) -> String {
+        self.path.file_name().map_or_else(
+            || self.path.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        )
+    }
+
+    fn max_zoom(&self) -> u8 {
+        self.max_zoom
+    }
+
+    fn tile(&self, zoom: u8, x: u32, y: u32) -> Option<Vec<u8>> {
+        let tms_row = 1u32
+            .checked_shl(zoom.into())?
+            .checked_sub(y.checked_add(1)?)?;
+        self.connection
+            .query_row(
+                "SELECT tile_data FROM tiles \
+                 WHERE zoom_level = ?1 AND tile_column = ?2 AND tile_row = ?3",
+                (zoom, x, tms_row),
+                |row| row.get(0),
+            )
+            .ok()
+    }
+}
+
+/// Opens `path` as an MBTiles file or, if it is a folder, a tile directory.
+pub fn open_tile_source(path: &Path) -> Result<Box<dyn TileSource>, String> {
+    if path.is_dir() {
+        Ok(Box::new(TileDirectory::open(path)?))
+    } else {
+        Ok(Box::new(MbTiles::open(path)?))
+    }
+}
+
+// ============================================================================
+// Geocoding
+// ============================================================================
+
+/// Turns place names into coordinates and back. The built-in implementation
+/// is an offline gazetteer; an online service can implement the same trait.
+pub trait Geocoder {
+    fn search(&self, query: &str) -> Vec<Location>;
+    fn reverse(&self, latitude: f64, longitude: f64) -> Option<Location>;
+}
+
+/// A fixed list of named places, extended by `places.csv` (name,lat,lon) in
+/// the data directory.
+pub struct Gazetteer {
+    places: Vec<Location>,
+}
+
+impl Gazetteer {
+    pub fn load() -> Self {
+        let place = |label: &str, latitude, longitude| Location {
+            latitude,
+            longitude,
+            label: label.to_string(),
+        };
+        let mut places = vec![
+            place("Amsterdam, Netherlands", 52.3676, 4.9041),
+            place("Bangalore, India", 12.9716, 77.5946),
+            place("Berlin, Germany", 52.5200, 13.4050),
+            place("Buenos Aires, Argentina", -34.6037, -58.3816),
+            place("Cairo, Egypt", 30.0444, 31.2357),
+            place("Cape Town, South Africa", -33.9249, 18.4241),
+            place("Lagos, Nigeria", 6.5244, 3.3792),
+            place("London, United Kingdom", 51.5072, -0.1276),
+            place("Mexico City, Mexico", 19.4326, -99.1332),
+            place("Mumbai, India", 19.0760, 72.8777),
+            place("Nairobi, Kenya", -1.2921, 36.8219),
+            place("New York, United States", 40.7128, -74.0060),
+            place("Paris, France", 48.8566, 2.3522),
+            place("San Francisco, United States", 37.7749, -122.4194),
+            place("São Paulo, Brazil", -23.5505, -46.6333),
+            place("Seoul, South Korea", 37.5665, 126.9780),
+            place("Singapore", 1.3521, 103.8198),
+            place("Stockholm, Sweden", 59.3293, 18.0686),
+            place("Sydney, Australia", -33.8688, 151.2093),
+            place("Tokyo, Japan", 35.6762, 139.6503),
+            place("Toronto, Canada", 43.6532, -79.3832),
+        ];
+
+        let path = storage::data_dir().join(PLACES_FILE);
+        if let Ok(text) = std::fs::read_to_string(&path) {
+            for line in text.lines() {
+                let mut fields = line.rsplitn(3, ',');
+                let (Some(lon), Some(lat), Some(name)) =
+                    (fields.next(), fields.next(), fields.next())
+                else {
+                    continue;
+                };
+                if let Some((latitude, longitude)) = parse_coordinates(&format!("{lat},{lon}")) {
+                    places.push(place(name.trim(), latitude, longitude));
+                }
+            }
+        }
+
+        Self { places }
+    }
+}
+
+impl Geocoder for Gazetteer {
+    fn search(&self, query: &str) -> Vec<Location> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let mut matches: Vec<&Location> = self
+            .places
+            .iter()
+            .filter(|place| place.label.to_lowercase().contains(&query))
+            .collect();
+        // Names that start with the query first
+        matches.sort_by_key(|place| !place.label.to_lowercase().starts_with(&query));
+        matches.into_iter().take(8).cloned().collect()
+    }
+
+    fn reverse(&self, latitude: f64, longitude: f64) -> Option<Location> {
+        // Nearest known place within ~25 km
+        self.places
+            .iter()
+            .map(|place| {
+                let d_lat = place.latitude - latitude;
+                let d_lon = (place.longitude - longitude) * latitude.to_radians().cos();
+                (d_lat * d_lat + d_lon * d_lon, place)
+            })
+            .filter(|(distance, _)| *distance < 0.25 * 0.25)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, place)| place.clone())
+    }
+}
+
+// ============================================================================
+// Map service
+// ============================================================================
+
+/// The configured tile source and geocoder, plus a cache of rendered previews.
+pub struct MapService {
+    source_path: Option<PathBuf>,
+    source: Option<Box<dyn TileSource>>,
+    pub geocoder: Box<dyn Geocoder>,
+    cache: HashMap<String, gdk_pixbuf::Pixbuf>,
+}
+
+impl MapService {
+    pub fn load() -> Self {
+        let source_path = storage::load_device_key_file(LOCATION_FILE)
+            .and_then(|key_file| key_file.string("map", "tile_source").ok())
+            .map(|path| PathBuf::from(path.as_str()));
+        let source = source_path
+            .as_deref()
+            .and_then(|path| match open_tile_source(path) {
+                Ok(source) => Some(source),
+                Err(err) => {
+                    eprintln!("Map tiles unavailable: {err}");
+                    None
+                }
+            });
+
+        Self {
+            source_path,
+            source,
+            geocoder: Box::new(Gazetteer::load()),
+            cache: HashMap::new(),
+        }
+    }
+
+    fn save(&self) {
+        let key_file = glib::KeyFile::new();
+        if let Some(path) = &self.source_path {
+            key_file.set_string("map", "tile_source", &path.to_string_lossy());
+        }
+        storage::save_device_key_file(LOCATION_FILE, &key_file);
+    }
+
+    /// Switches to the MBTiles file or tile folder at `path`.
+    pub fn set_source(&mut self, path: &Path) -> Result<(), String> {
+        self.source = Some(open_tile_source(path)?);
+        self.source_path = Some(path.to_path_buf());
+        self.cache.clear();
+        self.save();
+        Ok(())
+    }
+
+    pub fn source_name(&self) -> Option<String> {
+        self.source.as_ref().map(|source| source.name())
+    }
+
+    pub fn max_zoom(&self) -> u8 {
+        self.source
+            .as_ref()
+            .map_or(18, |source| source.max_zoom().max(MIN_ZOOM))
+    }
+
+    /// A `width`×`height` map centred on the location with a pin in the middle.
+    /// Without a tile source the map is a plain grid.
+    pub fn render(
+        &mut self,
+        latitude: f64,
+        longitude: f64,
+        zoom: u8,
+        width: i32,
+        height: i32,
+    ) -> Option<gdk_pixbuf::Pixbuf> {
+        let zoom = zoom.clamp(MIN_ZOOM, self.max_zoom());
+        let key = format!("{latitude:.6}:{longitude:.6}:{zoom}:{width}x{height}");
+        if let Some(pixbuf) = self.cache.get(&key) {
+            return Some(pixbuf.clone());
+        }
+
+        let map = gdk_pixbuf::Pixbuf::new(gdk_pixbuf::Colorspace::Rgb, true, 8, width, height)?;
+        map.fill(0xe8eef2ff);
+
+        let (center_x, center_y) = project(latitude, longitude, zoom);
+        let left = center_x.round() as i64 - i64::from(width / 2);
+        let top = center_y.round() as i64 - i64::from(height / 2);
+        let tiles = 1i64 << zoom;
+        let tile_size = i64::from(TILE_SIZE);
+
+        match &self.source {
+            Some(source) => {
+                for tile_y in tile_index(top)..=tile_index(top + i64::from(height) - 1) {
+                    if !(0..tiles).contains(&tile_y) {
+                        continue;
+                    }
+                    for tile_x in tile_index(left)..=tile_index(left + i64::from(width) - 1) {
+                        let wrapped_x = tile_x.rem_euclid(tiles);
+                        let Some(tile) = source
+                            .tile(zoom, wrapped_x as u32, tile_y as u32)
+                            .and_then(|data| decode_tile(&data))
+                        else {
+                            continue;
+                        };
+                        blit(
+                            &tile,
+                            &map,
+                            (tile_x * tile_size - left) as i32,
+                            (tile_y * tile_size - top) as i32,
+                        );
+                    }
+                }
+            }
+            None => draw_grid(&map, left, top),
+        }
+
+        draw_pin(&map, width / 2, height / 2);
+        self.cache.insert(key, map.clone());
+        Some(map)
+    }
+}
+
+fn tile_index(pixel: i64) -> i64 {
+    pixel.div_euclid(i64::from(TILE_SIZE))
+}
+
+fn decode_tile(data: &[u8]) -> Option<gdk_pixbuf::Pixbuf> {
+    let stream = gio::MemoryInputStream::from_bytes(&glib::Bytes::from(data));
+    let tile = gdk_pixbuf::Pixbuf::from_stream(&stream, gio::Cancellable::NONE).ok()?;
+    // High-DPI sources ship 512px tiles; scale them to the grid
+    if tile.width() != TILE_SIZE || tile.height() != TILE_SIZE {
+        return tile.scale_simple(TILE_SIZE, TILE_SIZE, gdk_pixbuf::InterpType::Bilinear);
+    }
+    Some(tile)
+}
+
+/// Copies `tile` onto `map` at (x, y), clipped to the map's bounds.
+fn blit(tile: &gdk_pixbuf::Pixbuf, map: &gdk_pixbuf::Pixbuf, x: i32, y: i32) {
+    let src_x = (-x).max(0);
+    let src_y = (-y).max(0);
+    let dest_x = x.max(0);
+    let dest_y = y.max(0);
+    let width = (tile.width() - src_x).min(map.width() - dest_x);
+    let height = (tile.height() - src_y).min(map.height() - dest_y);
+    if width <= 0 || height <= 0 {
+        return;
+    }
+    if tile.has_alpha() == map.has_alpha() {
+        tile.copy_area(src_x, src_y, width, height, map, dest_x, dest_y);
+    } else {
+        tile.composite(
+            map,
+            dest_x,
+            dest_y,
+            width,
+            height,
+            f64::from(x),
+            f64::from(y),
+            1.0,
+            1.0,
+            gdk_pixbuf::InterpType::Nearest,
+            255,
+        );
+    }
+}
+
+/// Faint lines on tile boundaries so an empty map still shows movement.
+fn draw_grid(map: &gdk_pixbuf::Pixbuf, left: i64, top: i64) {
+    let step = i64::from(TILE_SIZE / 4);
+    for y in 0..map.height() {
+        for x in 0..map.width() {
+            let on_line = (left + i64::from(x)).rem_euclid(step) == 0
+                || (top + i64::from(y)).rem_euclid(step) == 0;
+            if on_line {
+                map.put_pixel(x as u32, y as u32, 0xcf, 0xd8, 0xdc, 0xff);
+            }
+        }
+    }
+}
+
+/// A red pin with a white outline centred on (cx, cy).
+fn draw_pin(map: &gdk_pixbuf::Pixbuf, cx: i32, cy: i32) {
+    const RADIUS: i32 = 7;
+    for dy in -RADIUS..=RADIUS {
+        for dx in -RADIUS..=RADIUS {
+            let (x, y) = (cx + dx, cy + dy);
+            if x < 0 || y < 0 || x >= map.width() || y >= map.height() {
+                continue;
+            }
+            let distance = dx * dx + dy * dy;
+            if distance <= (RADIUS - 2) * (RADIUS - 2) {
+                map.put_pixel(x as u32, y as u32, 0xe5, 0x39, 0x35, 0xff);
+            } else if distance <= RADIUS * RADIUS {
+                map.put_pixel(x as u32, y as u32, 0xff, 0xff, 0xff, 0xff);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: (f64, f64), expected: (f64, f64)) {
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-6 && (actual.1 - expected.1).abs() < 1e-6,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    /// An MBTiles file in the test directory holding `tiles` as
+    /// (zoom, column, TMS row, data), with `maxzoom` metadata if given.
+    fn mbtiles(name: &str, maxzoom: Option<&str>, tiles: &[(u8, u32, u32, &[u8])]) -> PathBuf {
+        let dir = storage::data_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE metadata (name TEXT, value TEXT);
+                 CREATE TABLE tiles (zoom_level INTEGER, tile_column INTEGER,
+                                     tile_row INTEGER, tile_data BLOB);",
+            )
+            .unwrap();
+        if let Some(maxzoom) = maxzoom {
+            connection
+                .execute("INSERT INTO metadata VALUES ('maxzoom', ?1)", [maxzoom])
+                .unwrap();
+        }
+        for (zoom, column, row, data) in tiles {
+            connection
+                .execute(
+                    "INSERT INTO tiles VALUES (?1, ?2, ?3, ?4)",
+                    (zoom, column, row, data),
+                )
+                .unwrap();
+        }
+        path
+    }
+
+    #[test]
+    fn project_maps_known_points() {
+        assert_close(project(0.0, 0.0, 0), (128.0, 128.0));
+        assert_close(project(0.0, 0.0, 1), (256.0, 256.0));
+        assert_close(project(MAX_LATITUDE, -180.0, 2), (0.0, 0.0));
+        assert_close(project(-MAX_LATITUDE, 180.0, 2), (1024.0, 1024.0));
+        // The poles are clamped to the edge of the map
+        assert_close(project(90.0, 0.0, 0), project(MAX_LATITUDE, 0.0, 0));
+    }
+
+    #[test]
+    fn unproject_inverts_project() {
+        for (latitude, longitude) in [(51.5072, -0.1276), (-33.8688, 151.2093), (0.0, -179.5)] {
+            for zoom in [0, MIN_ZOOM, DEFAULT_ZOOM, MAX_ZOOM] {
+                let (x, y) = project(latitude, longitude, zoom);
+                assert_close(unproject(x, y, zoom), (latitude, longitude));
+            }
+        }
+        // Longitudes east of the antimeridian wrap around
+        let (x, y) = project(10.0, 170.0, 3);
+        assert_close(unproject(x + 2048.0 / 36.0 * 2.0, y, 3), (10.0, -170.0));
+    }
+
+    #[test]
+    fn zoom_is_capped() {
+        assert_eq!(
+            project(48.8584, 2.2945, u8::MAX),
+            project(48.8584, 2.2945, MAX_ZOOM)
+        );
+        assert_eq!(unproject(1.0, 2.0, u8::MAX), unproject(1.0, 2.0, MAX_ZOOM));
+    }
+
+    #[test]
+    fn mbtiles_rows_are_flipped() {
+        // At zoom 2, XYZ row 0 is TMS row 3
+        let path = mbtiles(
+            "flipped.mbtiles",
+            Some("2"),
+            &[
+                (2, 1, 3, b"north"),
+                (2, 1, 0, b"south"),
+                (0, 0, 0, b"world"),
+            ],
+        );
+        let source = MbTiles::open(&path).unwrap();
+        assert_eq!(source.name(), "flipped.mbtiles");
+        assert_eq!(source.max_zoom(), 2);
+        assert_eq!(source.tile(2, 1, 0).as_deref(), Some(&b"north"[..]));
+        assert_eq!(source.tile(2, 1, 3).as_deref(), Some(&b"south"[..]));
+        assert_eq!(source.tile(0, 0, 0).as_deref(), Some(&b"world"[..]));
+        assert_eq!(source.tile(2, 0, 0), None);
+    }
+
+    #[test]
+    fn mbtiles_lookups_out_of_range_find_nothing() {
+        let path = mbtiles("range.mbtiles", None, &[(1, 0, 0, b"tile")]);
+        let source = MbTiles::open(&path).unwrap();
+        assert_eq!(source.tile(1, 0, 1).as_deref(), Some(&b"tile"[..]));
+        assert_eq!(source.tile(1, 0, 2), None);
+        assert_eq!(source.tile(1, 0, u32::MAX), None);
+        assert_eq!(source.tile(40, 0, 0), None);
+    }
+
+    #[test]
+    fn mbtiles_max_zoom_comes_from_metadata_or_tiles() {
+        let path = mbtiles("scanned.mbtiles", None, &[(3, 0, 0, b""), (5, 0, 0, b"")]);
+        assert_eq!(MbTiles::open(&path).unwrap().max_zoom(), 5);
+
+        let path = mbtiles("deep.mbtiles", Some("30"), &[(3, 0, 0, b"")]);
+        assert_eq!(MbTiles::open(&path).unwrap().max_zoom(), MAX_ZOOM);
+
+        let path = mbtiles("empty.mbtiles", None, &[]);
+        assert!(MbTiles::open(&path).is_err());
+    }
+
+    #[test]
+    fn tile_directory_max_zoom_is_capped() {
+        let root = storage::data_dir().join("tiles");
+        for zoom in ["0", "12", "40", "not-a-zoom"] {
+            std::fs::create_dir_all(root.join(zoom)).unwrap();
+        }
+        std::fs::create_dir_all(root.join("12/7")).unwrap();
+        std::fs::write(root.join("12/7/9.png"), b"png").unwrap();
+
+        let source = TileDirectory::open(&root).unwrap();
+        assert_eq!(source.max_zoom(), MAX_ZOOM);
+        assert_eq!(source.tile(12, 7, 9).as_deref(), Some(&b"png"[..]));
+        assert_eq!(source.tile(12, 7, 8), None);
+    }
+}
@@ -0,0 +1,502 @@
+// Location picker, location dialog and static map previews
+use crate::i18n::{tr, tr_with};
+use crate::location::{self, Location, DEFAULT_ZOOM, MIN_ZOOM};
+use crate::state::AppState;
+use gtk4::prelude::*;
+use gtk4::{gdk, gio, glib};
+use gtk4::{
+    Box as GtkBox, Button, Entry, Frame, Label, ListBox, Orientation, Overlay, Picture,
+    SearchEntry, SpinButton,
+};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+const PICKER_WIDTH: i32 = 480;
+const PICKER_HEIGHT: i32 = 240;
+const PREVIEW_WIDTH: i32 = 360;
+const PREVIEW_HEIGHT: i32 = 160;
+
+/// Where the picker starts before anything is chosen.
+const DEFAULT_CENTER: (f64, f64) = (51.5072, -0.1276);
+
+struct LocationPicker {
+    state: Rc<AppState>,
+    latitude: SpinButton,
+    longitude: SpinButton,
+    label: Entry,
+    picture: Picture,
+    source_label: Label,
+    results: ListBox,
+    found: RefCell<Vec<Location>>,
+    zoom: Cell<u8>,
+    /// Set while the picker writes its own fields, to skip change handlers.
+    updating: Cell<bool>,
+    /// Whether the label came from reverse geocoding and may be replaced.
+    label_is_auto: Cell<bool>,
+    on_change: Box<dyn Fn(&Location)>,
+}
+
+// ============================================================================
+// Picker
+// ============================================================================
+
+/// A search box, an offline map to click on and coordinate fields.
+/// `on_change` runs with the current location whenever it changes.
+pub fn create_location_picker(
+    state: &Rc<AppState>,
+    on_change: impl Fn(&Location) + 'static,
+) -> GtkBox {
+    let root = GtkBox::new(Orientation::Vertical, 12);
+
+    // Tile source
+    let source_box = GtkBox::new(Orientation::Horizontal, 8);
+    let source_label = Label::new(None);
+    source_label.set_halign(gtk4::Align::Start);
+    source_label.set_hexpand(true);
+    source_label.set_ellipsize(gtk4::pango::EllipsizeMode::Middle);
+    source_label.add_css_class("upload-hint");
+    let mbtiles_btn = Button::with_label(&tr("Open MBTiles…"));
+    mbtiles_btn.add_css_class("action-button");
+    let folder_btn = Button::with_label(&tr("Open Tile Folder…"));
+    folder_btn.add_css_class("action-button");
+    source_box.append(&source_label);
+    source_box.append(&mbtiles_btn);
+    source_box.append(&folder_btn);
+    root.append(&source_box);
+
+    // Search by address or coordinates
+    let search = SearchEntry::new();
+    search.set_placeholder_text(Some(&tr("Search a place or enter “lat, lon”")));
+    root.append(&search);
+    let results = ListBox::new();
+    results.add_css_class("share-picker-list");
+    results.set_visible(false);
+    root.append(&results);
+
+    // Map
+    let picture = Picture::new();
+    picture.set_size_request(PICKER_WIDTH, PICKER_HEIGHT);
+    picture.set_can_shrink(false);
+    picture.set_halign(gtk4::Align::Center);
+    picture.set_cursor_from_name(Some("crosshair"));
+    picture.add_css_class("map-picture");
+
+    let overlay = Overlay::new();
+    overlay.set_child(Some(&picture));
+    overlay.set_halign(gtk4::Align::Center);
+    let zoom_box = GtkBox::new(Orientation::Vertical, 4);
+    zoom_box.set_halign(gtk4::Align::End);
+    zoom_box.set_valign(gtk4::Align::Start);
+    zoom_box.set_margin_top(8);
+    zoom_box.set_margin_end(8);
+    let zoom_in_btn = Button::with_label("+");
+    zoom_in_btn.add_css_class("map-zoom-button");
+    let zoom_out_btn = Button::with_label("−");
+    zoom_out_btn.add_css_class("map-zoom-button");
+    zoom_box.append(&zoom_in_btn);
+    zoom_box.append(&zoom_out_btn);
+    overlay.add_overlay(&zoom_box);
+    root.append(&overlay);
+
+    // Coordinates and label
+    let coordinates_box = GtkBox::new(Orientation::Horizontal, 12);
+    let latitude = SpinButton::with_range(-90.0, 90.0, 0.0001);
+    latitude.set_digits(5);
+    latitude.set_value(DEFAULT_CENTER.0);
+    latitude.set_hexpand(true);
+    let longitude = SpinButton::with_range(-180.0, 180.0, 0.0001);
+    longitude.set_digits(5);
+    longitude.set_value(DEFAULT_CENTER.1);
+    longitude.set_hexpand(true);
+    coordinates_box.append(&Label::new(Some(&tr("Lat"))));
+    coordinates_box.append(&latitude);
+    coordinates_box.append(&Label::new(Some(&tr("Lon"))));
+    coordinates_box.append(&longitude);
+    root.append(&coordinates_box);
+
+    let label = Entry::new();
+    label.set_placeholder_text(Some(&tr("Place name (optional)")));
+    root.append(&label);
+
+    let picker = Rc::new(LocationPicker {
+        state: state.clone(),
+        latitude: latitude.clone(),
+        longitude: longitude.clone(),
+        label: label.clone(),
+        picture: picture.clone(),
+        source_label,
+        results: results.clone(),
+        found: RefCell::new(Vec::new()),
+        zoom: Cell::new(DEFAULT_ZOOM),
+        updating: Cell::new(false),
+        label_is_auto: Cell::new(true),
+        on_change: Box::new(on_change),
+    });
+    picker.update_source_label(None);
+    picker.fill_label();
+    picker.refresh();
+
+    let p = picker.clone();
+    latitude.connect_value_changed(move |_| p.coordinates_edited());
+    let p = picker.clone();
+    longitude.connect_value_changed(move |_| p.coordinates_edited());
+    let p = picker.clone();
+    label.connect_changed(move |_| {
+        if !p.updating.get() {
+            p.label_is_auto.set(false);
+            p.refresh();
+        }
+    });
+
+    let p = picker.clone();
+    search.connect_search_changed(move |search| p.search(&search.text()));
+    let p = picker.clone();
+    results.connect_row_activated(move |_, row| {
+        let place = usize::try_from(row.index())
+            .ok()
+            .and_then(|index| p.found.borrow().get(index).cloned());
+        if let Some(place) = place {
+            p.set_location(&place);
+        }
+    });
+
+    // Click to move the pin, scroll to zoom
+    let click = gtk4::GestureClick::new();
+    let p = picker.clone();
+    click.connect_pressed(move |_, _, x, y| p.click(x, y));
+    picture.add_controller(click);
+    let scroll = gtk4::EventControllerScroll::new(gtk4::EventControllerScrollFlags::VERTICAL);
+    let p = picker.clone();
+    scroll.connect_scroll(move |_, _, dy| {
+        p.zoom_by(if dy < 0.0 { 1 } else { -1 });
+        glib::Propagation::Stop
+    });
+    picture.add_controller(scroll);
+
+    let p = picker.clone();
+    zoom_in_btn.connect_clicked(move |_| p.zoom_by(1));
+    let p = picker.clone();
+    zoom_out_btn.connect_clicked(move |_| p.zoom_by(-1));
+
+    let p = picker.clone();
+    mbtiles_btn.connect_clicked(move |button| {
+        let filter = gtk4::FileFilter::new();
+        filter.set_name(Some("MBTiles"));
+        filter.add_suffix("mbtiles");
+        let filters = gio::ListStore::new::<gtk4::FileFilter>();
+        filters.append(&filter);
+        let dialog = gtk4::FileDialog::builder()
+            .title(tr("Open MBTiles"))
+            .filters(&filters)
+            .modal(true)
+            .build();
+        let window = button.root().and_downcast::<gtk4::Window>();
+        let p = p.clone();
+        dialog.open(window.as_ref(), gio::Cancellable::NONE, move |result| {
+            if let Some(path) = result.ok().and_then(|file| file.path()) {
+                p.set_source(&path);
+            }
+        });
+    });
+
+    let p = picker.clone();
+    folder_btn.connect_clicked(move |button| {
+        let dialog = gtk4::FileDialog::builder()
+            .title(tr("Open Tile Folder"))
+            .modal(true)
+            .build();
+        let window = button.root().and_downcast::<gtk4::Window>();
+        let p = p.clone();
+        dialog.select_folder(window.as_ref(), gio::Cancellable::NONE, move |result| {
+            if let Some(path) = result.ok().and_then(|file| file.path()) {
+                p.set_source(&path);
+            }
+        });
+    });
+
+    root
+}
+
+impl LocationPicker {
+    fn current(&self) -> Location {
+        Location {
+            latitude: self.latitude.value(),
+            longitude: self.longitude.value(),
+            label: self.label.text().trim().to_string(),
+        }
+    }
+
+    fn refresh(&self) {
+        let location = self.current();
+        let pixbuf = self.state.maps.borrow_mut().render(
+            location.latitude,
+            location.longitude,
+            self.zoom.get(),
+            PICKER_WIDTH,
+            PICKER_HEIGHT,
+        );
+        if let Some(pixbuf) = pixbuf {
+            self.picture
+                .set_paintable(Some(&gdk::Texture::for_pixbuf(&pixbuf)));
+        }
+        (self.on_change)(&location);
+    }
+
+    fn set_location(&self, location: &Location) {
+        self.updating.set(true);
+        self.latitude.set_value(location.latitude);
+        self.longitude.set_value(location.longitude);
+        self.label.set_text(&location.label);
+        self.updating.set(false);
+        self.label_is_auto.set(true);
+        if location.label.is_empty() {
+            self.fill_label();
+        }
+        self.refresh();
+    }
+
+    fn coordinates_edited(&self) {
+        if self.updating.get() {
+            return;
+        }
+        self.fill_label();
+        self.refresh();
+    }
+
+    /// Names the place from the geocoder unless the user typed a label.
+    fn fill_label(&self) {
+        if !self.label_is_auto.get() {
+            return;
+        }
+        let place = self
+            .state
+            .maps
+            .borrow()
+            .geocoder
+            .reverse(self.latitude.value(), self.longitude.value());
+        self.updating.set(true);
+        self.label
+            .set_text(place.as_ref().map_or("", |place| place.label.as_str()));
+        self.updating.set(false);
+    }
+
+    fn click(&self, x: f64, y: f64) {
+        let zoom = self.zoom.get();
+        let (center_x, center_y) =
+            location::project(self.latitude.value(), self.longitude.value(), zoom);
+        let (latitude, longitude) = location::unproject(
+            center_x + x - f64::from(PICKER_WIDTH / 2),
+            center_y + y - f64::from(PICKER_HEIGHT / 2),
+            zoom,
+        );
+        self.set_location(&Location {
+            latitude,
+            longitude,
+            label: String::new(),
+        });
+    }
+
+    fn zoom_by(&self, step: i8) {
+        let max_zoom = self.state.maps.borrow().max_zoom();
+        let zoom = self
+            .zoom
+            .get()
+            .saturating_add_signed(step)
+            .clamp(MIN_ZOOM, max_zoom);
+        if zoom != self.zoom.get() {
+            self.zoom.set(zoom);
+            self.refresh();
+        }
+    }
+
+    fn search(&self, query: &str) {
+        let mut found = Vec::new();
+        if let Some((latitude, longitude)) = location::parse_coordinates(query) {
+            found.push(Location {
+                latitude,
+                longitude,
+                label: String::new(),
+            });
+        }
+        found.extend(self.state.maps.borrow().geocoder.search(query));
+
+        while let Some(child) = self.results.first_child() {
+            self.results.remove(&child);
+        }
+        for place in &found {
+            let text = if place.label.is_empty() {
+                tr_with(
+                    "📍 Use coordinates {coordinates}",
+                    &[("coordinates", &place.title())],
+                )
+            } else {
+                format!("📍 {}", place.label)
+            };
+            let row = Label::new(Some(&text));
+            row.set_halign(gtk4::Align::Start);
+            row.set_margin_start(8);
+            row.set_margin_end(8);
+            row.set_margin_top(6);
+            row.set_margin_bottom(6);
+            self.results.append(&row);
+        }
+        self.results.set_visible(!found.is_empty());
+        self.found.replace(found);
+    }
+
+    fn set_source(&self, path: &std::path::Path) {
+        let result = self.state.maps.borrow_mut().set_source(path);
+        self.update_source_label(result.err());
+        let max_zoom = self.state.maps.borrow().max_zoom();
+        self.zoom.set(self.zoom.get().min(max_zoom));
+        self.refresh();
+    }
+
+    fn update_source_label(&self, error: Option<String>) {
+        let text = match (error, self.state.maps.borrow().source_name()) {
+            (Some(error), _) => format!("⚠️ {error}"),
+            (None, Some(name)) => tr_with("🗺️ Offline map: {name}", &[("name", &name)]),
+            (None, None) => tr("🗺️ No offline map loaded — showing a plain grid"),
+        };
+        self.source_label.set_text(&text);
+    }
+}
+
+// ============================================================================
+// Dialog
+// ============================================================================
+
+/// A dialog with the picker and a note; `on_pick` gets the chosen location
+/// and note when the action button is pressed.
+pub fn open_location_dialog(
+    parent: &impl IsA<gtk4::Widget>,
+    state: &Rc<AppState>,
+    title: &str,
+    action_label: &str,
+    on_pick: impl Fn(Location, String) + 'static,
+) {
+    let window = gtk4::Window::builder()
+        .title(title)
+        .modal(true)
+        .default_width(540)
+        .build();
+    window.add_css_class("share-sheet");
+    if let Some(parent) = parent.root().and_downcast::<gtk4::Window>() {
+        window.set_transient_for(Some(&parent));
+    }
+
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_start(24);
+    content.set_margin_end(24);
+    content.set_margin_top(24);
+    content.set_margin_bottom(24);
+
+    let title_label = Label::new(Some(title));
+    title_label.set_halign(gtk4::Align::Start);
+    title_label.add_css_class("dialog-title");
+    content.append(&title_label);
+
+    let chosen: Rc<RefCell<Option<Location>>> = Rc::default();
+    let chosen_clone = chosen.clone();
+    content.append(&create_location_picker(state, move |location| {
+        chosen_clone.replace(Some(location.clone()));
+    }));
+
+    let note_entry = Entry::new();
+    note_entry.set_placeholder_text(Some(&tr("Add a message (optional)")));
+    content.append(&note_entry);
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 12);
+    buttons.set_halign(gtk4::Align::End);
+    let cancel_btn = Button::with_label(&tr("Cancel"));
+    cancel_btn.add_css_class("secondary-button");
+    let action_btn = Button::with_label(action_label);
+    action_btn.add_css_class("create-post-button");
+    buttons.append(&cancel_btn);
+    buttons.append(&action_btn);
+    content.append(&buttons);
+    window.set_child(Some(&content));
+
+    let window_clone = window.clone();
+    cancel_btn.connect_clicked(move |_| window_clone.close());
+
+    let window_clone = window.clone();
+    action_btn.connect_clicked(move |_| {
+        if let Some(location) = chosen.borrow().clone() {
+            on_pick(location, note_entry.text().trim().to_string());
+        }
+        window_clone.close();
+    });
+
+    window.present();
+}
+
+// ============================================================================
+// Preview
+// ============================================================================
+
+/// A static map with the place name, for feed posts and chat bubbles.
+pub fn create_map_preview(state: &Rc<AppState>, location: &Location) -> Frame {
+    let frame = Frame::new(None);
+    frame.add_css_class("map-preview");
+    frame.set_halign(gtk4::Align::Start);
+
+    let content = GtkBox::new(Orientation::Vertical, 0);
+
+    let picture = Picture::new();
+    picture.set_size_request(PREVIEW_WIDTH, PREVIEW_HEIGHT);
+    picture.set_can_shrink(false);
+    if let Some(pixbuf) = state.maps.borrow_mut().render(
+        location.latitude,
+        location.longitude,
+        DEFAULT_ZOOM,
+        PREVIEW_WIDTH,
+        PREVIEW_HEIGHT,
+    ) {
+        picture.set_paintable(Some(&gdk::Texture::for_pixbuf(&pixbuf)));
+    }
+    content.append(&picture);
+
+    let caption = GtkBox::new(Orientation::Horizontal, 8);
+    caption.set_margin_start(12);
+    caption.set_margin_end(8);
+    caption.set_margin_top(8);
+    caption.set_margin_bottom(8);
+
+    let text_box = GtkBox::new(Orientation::Vertical, 2);
+    text_box.set_hexpand(true);
+    let title = Label::new(Some(&format!("📍 {}", location.title())));
+    title.set_halign(gtk4::Align::Start);
+    title.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+    title.add_css_class("map-preview-title");
+    text_box.append(&title);
+    if !location.label.is_empty() {
+        let coordinates = Label::new(Some(&location::format_coordinates(
+            location.latitude,
+            location.longitude,
+        )));
+        coordinates.set_halign(gtk4::Align::Start);
+        coordinates.add_css_class("map-preview-coordinates");
+        text_box.append(&coordinates);
+    }
+    caption.append(&text_box);
+
+    let open_btn = Button::with_label("🌐");
+    open_btn.set_tooltip_text(Some(&tr("Open in OpenStreetMap")));
+    open_btn.add_css_class("action-button");
+    open_btn.set_valign(gtk4::Align::Center);
+    let url = location.web_url();
+    open_btn.connect_clicked(move |button| {
+        let window = button.root().and_downcast::<gtk4::Window>();
+        gtk4::UriLauncher::new(&url).launch(window.as_ref(), gio::Cancellable::NONE, |result| {
+            if let Err(err) = result {
+                eprintln!("Failed to open map: {err}");
+            }
+        });
+    });
+    caption.append(&open_btn);
+
+    content.append(&caption);
+    frame.set_child(Some(&content));
+    frame
+}
@@ -1,29 +1,137 @@
 // LinkWithMentor - Advanced GTK4 Social Media Application
+mod account_view;
+mod activity;
+mod assets;
+mod auth;
+mod community;
+mod desktop_notifications;
+mod feed;
+mod gallery;
+mod hashtag_view;
+mod hashtags;
+mod i18n;
+mod keyring;
+mod link_sheet;
+mod links;
+mod listings;
+mod location;
+mod location_view;
+mod media;
+mod navigation;
+mod notifications;
+mod notifications_view;
+mod poll;
+mod poll_view;
+mod preferences;
+mod profile;
+mod profile_view;
+mod qr;
+mod search;
+mod search_history;
+mod search_query;
+mod search_view;
+mod session;
+mod share;
+mod share_sheet;
+mod sizing;
+mod state;
+mod storage;
+mod theme;
+mod totp;
+mod two_factor_view;
+mod user_profile;
+mod util;
+mod viewer;
+
+use community::{Community, Conversation};
+use feed::Post;
 use gtk4::gdk;
+use gtk4::gio;
 use gtk4::glib;
 use gtk4::prelude::*;
 use gtk4::{
-    Application, ApplicationWindow, Box as GtkBox, Button, CssProvider, Entry, Frame, Label,
-    ListBox, Orientation, ScrolledWindow, Stack,
+    Application, ApplicationWindow, Box as GtkBox, Button, CheckButton, CssProvider, Entry, Frame,
+    Label, ListBox, Orientation, ScrolledWindow, Stack,
 };
+use i18n::{tr, tr_noop, tr_with};
+use links::LinkTarget;
+use listings::{Listing, ListingKind};
+use location::Location;
+use navigation::Navigator;
+use share::{ShareKind, ShareStatus};
+use state::{AppState, ListenerScope};
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::rc::Rc;
 
 const APP_ID: &str = "com.linkwithmentor";
 
 fn main() -> glib::ExitCode {
-    let app = Application::builder().application_id(APP_ID).build();
-
-    app.connect_startup(|_| {
+    assets::register();
+    let app = Application::builder()
+        .application_id(APP_ID)
+        .flags(gio::ApplicationFlags::HANDLES_OPEN)
+        .build();
+    let state = AppState::load();
+    session::restore(&state);
+    notifications::watch(&state);
+    search_history::watch(&state);
+    desktop_notifications::install(&app, &state);
+
+    let state_clone = state.clone();
+    app.connect_startup(move |_| {
+        let settings = state_clone.preferences.settings();
+        i18n::set_language(
+            &settings.map_or("system".into(), |settings| settings.string("language")),
+        );
         load_css();
+        // Without settings the bundled look is used as is
+        if let Some(settings) = settings {
+            theme::install(settings);
+            sizing::install(settings);
+        }
         // Icon is embedded in the executable via build.rs
     });
 
-    app.connect_activate(build_ui);
+    let state_clone = state.clone();
+    app.connect_activate(move |app| build_ui(app, &state_clone));
+
+    // linkwithmentor:// share links handed over by the desktop or command line
+    app.connect_open(move |app, files, _| {
+        build_ui(app, &state);
+        let Some(window) = app.active_window() else {
+            return;
+        };
+        let uris: Vec<String> = files.iter().map(|file| file.uri().to_string()).collect();
+        if state.session.borrow().is_some() {
+            for uri in &uris {
+                link_sheet::open_link(&window, &state, uri);
+            }
+            return;
+        }
+        // Not over the sign-in page; once signed in
+        let listener = Rc::new(Cell::new(None));
+        let state_clone = state.clone();
+        let listener_clone = listener.clone();
+        let id = state.auth_changed.connect(move || {
+            if state_clone.session.borrow().is_none() {
+                return;
+            }
+            if let Some(id) = listener_clone.take() {
+                state_clone.auth_changed.disconnect(id);
+            }
+            for uri in &uris {
+                link_sheet::open_link(&window, &state_clone, uri);
+            }
+        });
+        listener.set(Some(id));
+    });
     app.run()
 }
 
 fn load_css() {
     let provider = CssProvider::new();
-    provider.load_from_path("resources/style.css");
+    assets::load_css(&provider, "style.css");
 
     gtk4::style_context_add_provider_for_display(
         &gdk::Display::default().expect("Could not connect to display"),
@@ -32,7 +140,12 @@ fn load_css() {
     );
 }
 
-fn build_ui(app: &Application) {
+fn build_ui(app: &Application, state: &Rc<AppState>) {
+    if let Some(window) = app.active_window() {
+        window.present();
+        return;
+    }
+
     // Create main window
     let window = ApplicationWindow::builder()
         .application(app)
@@ -43,6 +156,83 @@ fn build_ui(app: &Application) {
 
     // Icon is embedded in executable via build.rs for Windows
 
+    // The listeners of what the window shows, for replacing it
+    let content_scope = Rc::new(Cell::new(None));
+    build_window_content(&window, state, &content_scope);
+
+    // Signing in or out swaps between the sign-in page and the app
+    let signed_in = Rc::new(Cell::new(state.session.borrow().is_some()));
+    let state_clone = state.clone();
+    let window_weak = window.downgrade();
+    let scope = content_scope.clone();
+    state.auth_changed.connect(move || {
+        let now_signed_in = state_clone.session.borrow().is_some();
+        if signed_in.replace(now_signed_in) == now_signed_in {
+            return;
+        }
+        // Not while the button that signed in or out is still handling it
+        let window_weak = window_weak.clone();
+        let state = state_clone.clone();
+        let scope = scope.clone();
+        glib::idle_add_local_once(move || {
+            if let Some(window) = window_weak.upgrade() {
+                build_window_content(&window, &state, &scope);
+            }
+        });
+    });
+
+    let logout = gio::SimpleAction::new("logout", None);
+    let state_clone = state.clone();
+    logout.connect_activate(move |_, _| session::sign_out(&state_clone));
+    window.add_action(&logout);
+
+    // Switching language rebuilds every page in the new one, then returns to
+    // Settings where the switch was made
+    let state_clone = state.clone();
+    let window_weak = window.downgrade();
+    if let Some(settings) = state.preferences.settings() {
+        settings.connect_changed(Some("language"), move |settings, key| {
+            i18n::set_language(&settings.string(key));
+            // Not while the language dropdown is still handling the change
+            let window_weak = window_weak.clone();
+            let state = state_clone.clone();
+            let scope = content_scope.clone();
+            glib::idle_add_local_once(move || {
+                if let Some(window) = window_weak.upgrade() {
+                    build_window_content(&window, &state, &scope);
+                    navigation::activate(&window, "show-page", Some(&"settings".to_variant()));
+                }
+            });
+        });
+    }
+
+    window.present();
+}
+
+/// Fills `window` with the app when signed in, or with the sign-in page.
+/// The listeners of what it showed before, kept in `scope`, are
+/// disconnected so the old pages can go.
+fn build_window_content(
+    window: &ApplicationWindow,
+    state: &Rc<AppState>,
+    scope: &Cell<Option<ListenerScope>>,
+) {
+    if let Some(old) = scope.take() {
+        state.disconnect_scope(old);
+    }
+    let ((), new) = state.scoped(|| {
+        if state.session.borrow().is_some() {
+            build_content(window, state);
+        } else {
+            window.set_child(Some(&account_view::create_sign_in_page(state)));
+        }
+    });
+    scope.set(Some(new));
+}
+
+/// Builds the sidebar and all pages into `window`, replacing any earlier
+/// ones, and installs the navigation actions.
+fn build_content(window: &ApplicationWindow, state: &Rc<AppState>) {
     // Main container
     let main_box = GtkBox::new(Orientation::Horizontal, 0);
 
@@ -53,59 +243,101 @@ fn build_ui(app: &Application) {
         .build();
 
     // Build all views
-    let home_view = create_home_view();
-    let chat_view = create_chat_view();
+    let home_view = create_home_view(state);
+    let (chat_view, select_conversation) = create_chat_view(state);
     let groups_view = create_groups_view();
-    let media_view = create_media_view();
-    let share_view = create_share_view();
-    let profile_view = create_profile_view();
-    let notifications_view = create_notifications_view();
-    let search_view = create_search_view();
-    let settings_view = create_settings_view();
+    let media_view = create_media_view(state);
+    let share_view = create_share_view(state);
+    let profile_view = create_profile_view(state);
+    let notifications_view = create_notifications_view(state);
+    let search_view = create_search_view(state);
+    let settings_view = create_settings_view(state);
     let video_call_view = create_video_call_view();
-    let create_post_view = create_create_post_view();
+    let create_post_view = create_create_post_view(state);
     let analytics_view = create_analytics_view();
-    let events_view = create_events_view();
-    let jobs_view = create_jobs_view();
+    let events_view = create_events_view(state);
+    let jobs_view = create_jobs_view(state);
     let achievements_view = create_achievements_view();
     let learning_view = create_learning_view();
     let discover_view = create_discover_view();
 
-    stack.add_titled(&home_view, Some("home"), "🏠 Home");
-    stack.add_titled(&chat_view, Some("chat"), "💬 Chat");
-    stack.add_titled(&groups_view, Some("groups"), "👥 Groups");
-    stack.add_titled(&media_view, Some("media"), "📸 Media");
-    stack.add_titled(&share_view, Some("share"), "📤 Share");
-    stack.add_titled(&profile_view, Some("profile"), "👤 Profile");
+    stack.add_titled(&home_view, Some("home"), &tr("🏠 Home"));
+    stack.add_titled(&chat_view, Some("chat"), &tr("💬 Chat"));
+    stack.add_titled(&groups_view, Some("groups"), &tr("👥 Groups"));
+    stack.add_titled(&media_view, Some("media"), &tr("📸 Media"));
+    stack.add_titled(&share_view, Some("share"), &tr("📤 Share"));
+    stack.add_titled(&profile_view, Some("profile"), &tr("👤 Profile"));
     stack.add_titled(
         &notifications_view,
         Some("notifications"),
-        "🔔 Notifications",
+        &tr("🔔 Notifications"),
+    );
+    stack.add_titled(&search_view, Some("search"), &tr("🔍 Search"));
+    stack.add_titled(&settings_view, Some("settings"), &tr("⚙️ Settings"));
+    stack.add_titled(&video_call_view, Some("videocall"), &tr("📹 Video Call"));
+    stack.add_titled(&create_post_view, Some("createpost"), &tr("✨ Create Post"));
+    stack.add_titled(&analytics_view, Some("analytics"), &tr("📊 Analytics"));
+    stack.add_titled(&events_view, Some("events"), &tr("🎮 Events"));
+    stack.add_titled(&jobs_view, Some("jobs"), &tr("💼 Jobs"));
+    stack.add_titled(
+        &achievements_view,
+        Some("achievements"),
+        &tr("🏆 Achievements"),
+    );
+    stack.add_titled(&learning_view, Some("learning"), &tr("📚 Learning"));
+    stack.add_titled(&discover_view, Some("discover"), &tr("🌐 Discover"));
+
+    // Profiles, conversations and hashtags can be opened from anywhere via
+    // actions
+    let navigator = Navigator::new(&stack);
+    let nav = navigator.clone();
+    let state_clone = state.clone();
+    let nav_for_tag = navigator.clone();
+    let state_for_tag = state.clone();
+    let nav_for_chat = navigator.clone();
+    let nav_for_reply = navigator.clone();
+    let select_for_reply = select_conversation.clone();
+    let nav_for_back = navigator.clone();
+    let nav_for_page = navigator.clone();
+    navigation::install_actions(
+        window,
+        move |page| nav_for_page.go_to(page),
+        move |user_id| {
+            if user_id == community::ME {
+                nav.go_to("profile");
+            } else {
+                let page = user_profile::create_user_profile_page(&state_clone, user_id);
+                nav.push(&format!("user:{user_id}"), &page);
+            }
+        },
+        move |conversation_id| {
+            select_conversation(conversation_id, false);
+            nav_for_chat.go_to("chat");
+        },
+        move |conversation_id| {
+            // The entry can only take focus once the chat page shows
+            nav_for_reply.go_to("chat");
+            select_for_reply(conversation_id, true);
+        },
+        move |tag| {
+            let page = hashtag_view::create_hashtag_page(&state_for_tag, tag);
+            nav_for_tag.push(&format!("tag:{tag}"), &page);
+        },
+        move || nav_for_back.back(),
     );
-    stack.add_titled(&search_view, Some("search"), "🔍 Search");
-    stack.add_titled(&settings_view, Some("settings"), "⚙️ Settings");
-    stack.add_titled(&video_call_view, Some("videocall"), "📹 Video Call");
-    stack.add_titled(&create_post_view, Some("createpost"), "✨ Create Post");
-    stack.add_titled(&analytics_view, Some("analytics"), "📊 Analytics");
-    stack.add_titled(&events_view, Some("events"), "🎮 Events");
-    stack.add_titled(&jobs_view, Some("jobs"), "💼 Jobs");
-    stack.add_titled(&achievements_view, Some("achievements"), "🏆 Achievements");
-    stack.add_titled(&learning_view, Some("learning"), "📚 Learning");
-    stack.add_titled(&discover_view, Some("discover"), "🌐 Discover");
 
     // Create custom sidebar
-    let sidebar = create_sidebar(&stack);
+    let sidebar = create_sidebar(&navigator, state);
 
     main_box.append(&sidebar);
     main_box.append(&stack);
 
     window.set_child(Some(&main_box));
-    window.present();
 }
 
-fn create_sidebar(stack: &Stack) -> GtkBox {
+fn create_sidebar(navigator: &Rc<Navigator>, state: &Rc<AppState>) -> GtkBox {
     let sidebar_box = GtkBox::new(Orientation::Vertical, 16);
-    sidebar_box.set_width_request(280);
+    sizing::set_size_request(&sidebar_box, 280, -1);
     sidebar_box.set_margin_start(12);
     sidebar_box.set_margin_end(12);
     sidebar_box.set_margin_top(24);
@@ -124,20 +356,30 @@ fn create_sidebar(stack: &Stack) -> GtkBox {
 
     // Navigation buttons
     let nav_items = vec![
-        ("home", "🏠", "Home", "home-page"),
-        ("discover", "🌐", "Discover", "discover-page"),
-        ("search", "🔍", "Search", "search-page"),
-        ("notifications", "🔔", "Notifications", "notifications-page"),
-        ("chat", "💬", "Chat", "chat-page"),
-        ("groups", "👥", "Groups", "groups-page"),
-        ("events", "🎮", "Events", "events-page"),
-        ("learning", "📚", "Learning", "learning-page"),
-        ("jobs", "💼", "Jobs", "jobs-page"),
-        ("achievements", "🏆", "Achievements", "achievements-page"),
-        ("analytics", "📊", "Analytics", "analytics-page"),
-        ("media", "📸", "Media", "media-page"),
-        ("share", "📤", "Share", "share-page"),
-        ("profile", "👤", "Profile", "profile-page"),
+        ("home", "🏠", tr("Home"), "home-page"),
+        ("discover", "🌐", tr("Discover"), "discover-page"),
+        ("search", "🔍", tr("Search"), "search-page"),
+        (
+            "notifications",
+            "🔔",
+            tr("Notifications"),
+            "notifications-page",
+        ),
+        ("chat", "💬", tr("Chat"), "chat-page"),
+        ("groups", "👥", tr("Groups"), "groups-page"),
+        ("events", "🎮", tr("Events"), "events-page"),
+        ("learning", "📚", tr("Learning"), "learning-page"),
+        ("jobs", "💼", tr("Jobs"), "jobs-page"),
+        (
+            "achievements",
+            "🏆",
+            tr("Achievements"),
+            "achievements-page",
+        ),
+        ("analytics", "📊", tr("Analytics"), "analytics-page"),
+        ("media", "📸", tr("Media"), "media-page"),
+        ("share", "📤", tr("Share"), "share-page"),
+        ("profile", "👤", tr("Profile"), "profile-page"),
     ];
 
     for (page_name, icon, label_text, css_class) in nav_items {
@@ -146,20 +388,24 @@ fn create_sidebar(stack: &Stack) -> GtkBox {
 
         let icon_label = Label::new(Some(icon));
         icon_label.add_css_class("nav-icon");
-        let text_label = Label::new(Some(label_text));
+        let text_label = Label::new(Some(&label_text));
         text_label.set_halign(gtk4::Align::Start);
         text_label.add_css_class("nav-text");
 
         button_box.append(&icon_label);
         button_box.append(&text_label);
+        if page_name == "notifications" {
+            text_label.set_hexpand(true);
+            button_box.append(&notifications_view::create_unread_badge(state));
+        }
         button.set_child(Some(&button_box));
         button.add_css_class("nav-button");
         button.add_css_class(css_class);
 
-        let stack_clone = stack.clone();
+        let navigator = navigator.clone();
         let page = page_name.to_string();
         button.connect_clicked(move |_| {
-            stack_clone.set_visible_child_name(&page);
+            navigator.show(&page);
         });
 
         sidebar_box.append(&button);
@@ -175,14 +421,14 @@ fn create_sidebar(stack: &Stack) -> GtkBox {
     let settings_box = GtkBox::new(Orientation::Horizontal, 12);
     let settings_icon = Label::new(Some("⚙️"));
     settings_icon.add_css_class("nav-icon");
-    let settings_text = Label::new(Some("Settings"));
+    let settings_text = Label::new(Some(&tr("Settings")));
     settings_text.add_css_class("nav-text");
     settings_box.append(&settings_icon);
     settings_box.append(&settings_text);
 
-    let stack_clone = stack.clone();
+    let navigator = navigator.clone();
     settings_btn.connect_clicked(move |_| {
-        stack_clone.set_visible_child_name("settings");
+        navigator.show("settings");
     });
     settings_btn.set_child(Some(&settings_box));
     settings_btn.add_css_class("nav-button");
@@ -191,7 +437,7 @@ fn create_sidebar(stack: &Stack) -> GtkBox {
     sidebar_box
 }
 
-fn create_home_view() -> ScrolledWindow {
+fn create_home_view(state: &Rc<AppState>) -> ScrolledWindow {
     let scroll = ScrolledWindow::builder()
         .hscrollbar_policy(gtk4::PolicyType::Never)
         .vexpand(true)
@@ -205,52 +451,63 @@ fn create_home_view() -> ScrolledWindow {
     feed_box.add_css_class("feed-container");
 
     // Header
-    let header = create_section_header("📰 Your Feed", "Stay updated with your mentors");
+    let header = create_section_header(&tr("📰 Your Feed"), &tr("Stay updated with your mentors"));
     feed_box.append(&header);
 
     // Create post button
-    let create_post_btn = Button::with_label("✨ Create New Post");
+    let create_post_btn = Button::with_label(&tr("✨ Create New Post"));
     create_post_btn.add_css_class("create-post-button");
     feed_box.append(&create_post_btn);
 
-    // Sample posts
-    let posts = vec![
-        (
-            "Alice Johnson",
-            "Just completed an amazing mentoring session on Rust! 🦀",
-            "2 hours ago",
-            "🎯",
-        ),
-        (
-            "Bob Smith",
-            "Looking for mentors in Machine Learning. Any recommendations?",
-            "5 hours ago",
-            "🤖",
-        ),
-        (
-            "Carol Williams",
-            "Sharing my latest project: A GTK4 social media app! Check it out 🚀",
-            "1 day ago",
-            "💻",
-        ),
-        (
-            "David Brown",
-            "Thanks to my mentor, I finally understood async/await! 🎉",
-            "2 days ago",
-            "⚡",
-        ),
-    ];
-
-    for (user, content, time, emoji) in posts {
-        let post = create_post_card(user, content, time, emoji);
-        feed_box.append(&post);
-    }
+    let posts_box = GtkBox::new(Orientation::Vertical, 20);
+    feed_box.append(&posts_box);
+    // Posts whose comments are expanded survive feed rebuilds
+    let open_comments: Rc<RefCell<HashSet<String>>> = Rc::default();
+    let populate = {
+        let state = state.clone();
+        move || {
+            while let Some(child) = posts_box.first_child() {
+                posts_box.remove(&child);
+            }
+            let posts: Vec<Post> = {
+                let community = state.community.borrow();
+                state
+                    .feed
+                    .borrow()
+                    .posts()
+                    .iter()
+                    .filter(|post| !community.hidden_from_feed(&post.author))
+                    .cloned()
+                    .collect()
+            };
+            for post in &posts {
+                let post_card = create_post_card(&state, post, &open_comments);
+                posts_box.append(&post_card);
+            }
+        }
+    };
+    populate();
+    state.feed_changed.connect(populate);
 
     scroll.set_child(Some(&feed_box));
     scroll
 }
 
-fn create_post_card(user: &str, content: &str, time: &str, emoji: &str) -> Frame {
+fn create_post_card(
+    state: &Rc<AppState>,
+    post: &Post,
+    open_comments: &Rc<RefCell<HashSet<String>>>,
+) -> Frame {
+    let (user, avatar) = {
+        let community = state.community.borrow();
+        let author = community.user(&post.author);
+        (
+            author.map_or(post.author.clone(), |user| user.name.clone()),
+            profile_view::create_avatar(author, "avatar", 48),
+        )
+    };
+    let time = util::format_relative_time(post.created_at, util::now());
+
     let frame = Frame::new(None);
     frame.add_css_class("post-card");
 
@@ -262,53 +519,166 @@ fn create_post_card(user: &str, content: &str, time: &str, emoji: &str) -> Frame
 
     // User header
     let user_box = GtkBox::new(Orientation::Horizontal, 12);
-    let avatar = Label::new(Some(emoji));
-    avatar.add_css_class("avatar");
     let user_info = GtkBox::new(Orientation::Vertical, 4);
-    let user_label = Label::new(Some(user));
+    let user_label = Label::new(Some(&user));
     user_label.set_halign(gtk4::Align::Start);
     user_label.add_css_class("post-user");
-    let time_label = Label::new(Some(time));
+    let time_label = Label::new(Some(&time));
     time_label.set_halign(gtk4::Align::Start);
     time_label.add_css_class("post-time");
     user_info.append(&user_label);
     user_info.append(&time_label);
+    navigation::link_to_profile(&avatar, &post.author);
+    navigation::link_to_profile(&user_label, &post.author);
     user_box.append(&avatar);
     user_box.append(&user_info);
+    user_info.set_hexpand(true);
+    user_box.append(&profile_view::create_relationship_menu(state, &post.author));
 
     // Content
-    let content_label = Label::new(Some(content));
+    let content_label = Label::new(None);
+    sizing::set_markup(&content_label, &util::linkify_markup(&post.text));
     content_label.set_wrap(true);
     content_label.set_halign(gtk4::Align::Start);
     content_label.add_css_class("post-content");
+    link_sheet::connect_links(&content_label, state);
 
     // Actions
     let actions_box = GtkBox::new(Orientation::Horizontal, 12);
-    let like_btn = Button::with_label("👍 Like");
+    let like_btn = Button::with_label(&match post.likes.len() {
+        0 => tr("👍 Like"),
+        count => format!("👍 {}", i18n::number(count)),
+    });
     like_btn.add_css_class("action-button");
-    let comment_btn = Button::with_label("💬 Comment");
+    if post.liked_by(community::ME) {
+        like_btn.add_css_class("liked");
+    }
+    let state_clone = state.clone();
+    let post_id = post.id.clone();
+    like_btn.connect_clicked(move |_| {
+        if state_clone.feed.borrow_mut().toggle_like(&post_id) {
+            state_clone.feed_changed.emit();
+        }
+    });
+    let comment_btn = Button::with_label(&match post.comments.len() {
+        0 => tr("💬 Comment"),
+        count => format!("💬 {}", i18n::number(count)),
+    });
     comment_btn.add_css_class("action-button");
-    let share_btn = Button::with_label("🔗 Share");
+    let share_btn = Button::with_label(&tr("🔗 Share"));
     share_btn.add_css_class("action-button");
 
     actions_box.append(&like_btn);
     actions_box.append(&comment_btn);
     actions_box.append(&share_btn);
 
+    let state_clone = state.clone();
+    let target = LinkTarget::Post {
+        id: post.id.clone(),
+        title: post.title(),
+    };
+    share_btn.connect_clicked(move |button| {
+        link_sheet::open_link_dialog(button, &state_clone, Some(target.clone()));
+    });
+
     card_box.append(&user_box);
-    card_box.append(&content_label);
+    if !post.text.is_empty() {
+        card_box.append(&content_label);
+    }
+    if let Some(poll) = &post.poll {
+        card_box.append(&poll_view::create_poll_widget(state, post, poll));
+    }
+    if let Some(location) = &post.location {
+        card_box.append(&location_view::create_map_preview(state, location));
+    }
     card_box.append(&actions_box);
 
+    let comments_box = create_comments_box(state, post);
+    comments_box.set_visible(open_comments.borrow().contains(&post.id));
+    card_box.append(&comments_box);
+    let open_comments = open_comments.clone();
+    let post_id = post.id.clone();
+    comment_btn.connect_clicked(move |_| {
+        let visible = !comments_box.is_visible();
+        comments_box.set_visible(visible);
+        if visible {
+            open_comments.borrow_mut().insert(post_id.clone());
+        } else {
+            open_comments.borrow_mut().remove(&post_id);
+        }
+    });
+
     frame.set_child(Some(&card_box));
     frame
 }
 
-fn create_chat_view() -> GtkBox {
+fn create_comments_box(state: &Rc<AppState>, post: &Post) -> GtkBox {
+    let comments_box = GtkBox::new(Orientation::Vertical, 8);
+    comments_box.add_css_class("post-comments");
+
+    let now = util::now();
+    {
+        let community = state.community.borrow();
+        for comment in &post.comments {
+            if community.is_blocked(&comment.author) {
+                continue;
+            }
+            let row = GtkBox::new(Orientation::Horizontal, 8);
+            let avatar =
+                profile_view::create_avatar(community.user(&comment.author), "comment-avatar", 24);
+            navigation::link_to_profile(&avatar, &comment.author);
+            row.append(&avatar);
+            let text = Label::new(None);
+            sizing::set_markup(
+                &text,
+                &format!(
+                    "<b>{}</b>  {}",
+                    glib::markup_escape_text(&community.user_name(&comment.author)),
+                    util::linkify_markup(&comment.text)
+                ),
+            );
+            link_sheet::connect_links(&text, state);
+            text.set_wrap(true);
+            text.set_xalign(0.0);
+            text.set_hexpand(true);
+            text.add_css_class("comment-text");
+            let time = Label::new(Some(&util::format_relative_time(comment.created_at, now)));
+            time.add_css_class("post-time");
+            row.append(&text);
+            row.append(&time);
+            comments_box.append(&row);
+        }
+    }
+
+    let entry = Entry::new();
+    entry.set_placeholder_text(Some(&tr("Write a comment...")));
+    entry.add_css_class("message-entry");
+    let state = state.clone();
+    let post_id = post.id.clone();
+    entry.connect_activate(move |entry| {
+        let text = entry.text().trim().to_string();
+        if text.is_empty() {
+            return;
+        }
+        if state.feed.borrow_mut().add_comment(&post_id, &text) {
+            state.feed_changed.emit();
+        }
+    });
+    comments_box.append(&entry);
+
+    comments_box
+}
+
+/// Selects a conversation on the chat page by id, focusing the message
+/// entry when replying.
+type SelectConversation = Rc<dyn Fn(&str, bool)>;
+
+fn create_chat_view(state: &Rc<AppState>) -> (GtkBox, SelectConversation) {
     let main_box = GtkBox::new(Orientation::Horizontal, 0);
 
     // Chat list sidebar
     let chat_list_box = GtkBox::new(Orientation::Vertical, 8);
-    chat_list_box.set_width_request(320);
+    sizing::set_size_request(&chat_list_box, 320, -1);
     chat_list_box.add_css_class("chat-sidebar");
     chat_list_box.set_margin_start(12);
     chat_list_box.set_margin_end(12);
@@ -316,7 +686,7 @@ fn create_chat_view() -> GtkBox {
     chat_list_box.set_margin_bottom(12);
 
     let search_entry = Entry::new();
-    search_entry.set_placeholder_text(Some("🔍 Search conversations..."));
+    search_entry.set_placeholder_text(Some(&tr("🔍 Search conversations...")));
     search_entry.add_css_class("search-entry");
     chat_list_box.append(&search_entry);
 
@@ -324,24 +694,6 @@ fn create_chat_view() -> GtkBox {
     let chat_list = ListBox::new();
     chat_list.add_css_class("chat-list");
 
-    // Sample chats
-    let chats = vec![
-        (
-            "Alice Johnson",
-            "Great! Let's schedule for tomorrow",
-            "2m",
-            true,
-        ),
-        ("Bob Smith", "Thanks for the resources!", "1h", false),
-        ("Carol Williams", "Can you review my code?", "3h", true),
-        ("David Brown", "See you in the meeting!", "1d", false),
-    ];
-
-    for (name, message, time, unread) in chats {
-        let chat_item = create_chat_item(name, message, time, unread);
-        chat_list.append(&chat_item);
-    }
-
     chat_scroll.set_child(Some(&chat_list));
     chat_scroll.set_vexpand(true);
     chat_list_box.append(&chat_scroll);
@@ -359,9 +711,8 @@ fn create_chat_view() -> GtkBox {
     chat_header.set_margin_top(12);
     chat_header.set_margin_bottom(12);
 
-    let header_avatar = Label::new(Some("👤"));
-    header_avatar.add_css_class("chat-avatar");
-    let header_name = Label::new(Some("Alice Johnson"));
+    let header_avatar = GtkBox::new(Orientation::Horizontal, 0);
+    let header_name = Label::new(None);
     header_name.add_css_class("chat-header-name");
     chat_header.append(&header_avatar);
     chat_header.append(&header_name);
@@ -370,10 +721,12 @@ fn create_chat_view() -> GtkBox {
     let spacer = GtkBox::new(Orientation::Horizontal, 0);
     spacer.set_hexpand(true);
     chat_header.append(&spacer);
+    let header_menu = GtkBox::new(Orientation::Horizontal, 0);
+    chat_header.append(&header_menu);
 
-    let audio_btn = Button::with_label("🎤 Audio");
+    let audio_btn = Button::with_label(&tr("🎤 Audio"));
     audio_btn.add_css_class("call-button");
-    let video_btn = Button::with_label("📹 Video");
+    let video_btn = Button::with_label(&tr("📹 Video"));
     video_btn.add_css_class("call-button");
     chat_header.append(&audio_btn);
     chat_header.append(&video_btn);
@@ -387,19 +740,6 @@ fn create_chat_view() -> GtkBox {
     messages_box.set_margin_top(20);
     messages_box.add_css_class("messages-container");
 
-    // Sample messages
-    let messages = vec![
-        ("Alice", "Hey! How's your project going?", false),
-        ("You", "It's going great! Just implemented the UI", true),
-        ("Alice", "Awesome! Can I see a demo?", false),
-        ("You", "Sure! Let me share my screen", true),
-    ];
-
-    for (sender, text, is_own) in messages {
-        let msg = create_message_bubble(sender, text, is_own);
-        messages_box.append(&msg);
-    }
-
     messages_scroll.set_child(Some(&messages_box));
 
     // Input area
@@ -410,13 +750,23 @@ fn create_chat_view() -> GtkBox {
     input_box.set_margin_top(12);
     input_box.set_margin_bottom(12);
 
-    let attach_btn = Button::with_label("📎");
+    let attach_btn = gtk4::MenuButton::new();
+    attach_btn.set_label("📎");
     attach_btn.add_css_class("attach-button");
+    attach_btn.set_tooltip_text(Some(&tr("Attach a file, link or location")));
+    let attach_popover = gtk4::Popover::new();
+    let attach_items = GtkBox::new(Orientation::Vertical, 4);
+    attach_items.set_margin_start(4);
+    attach_items.set_margin_end(4);
+    attach_items.set_margin_top(4);
+    attach_items.set_margin_bottom(4);
+    attach_popover.set_child(Some(&attach_items));
+    attach_btn.set_popover(Some(&attach_popover));
     let message_entry = Entry::new();
-    message_entry.set_placeholder_text(Some("Type a message..."));
+    message_entry.set_placeholder_text(Some(&tr("Type a message...")));
     message_entry.set_hexpand(true);
     message_entry.add_css_class("message-entry");
-    let send_btn = Button::with_label("Send 🚀");
+    let send_btn = Button::with_label(&tr("Send 🚀"));
     send_btn.add_css_class("send-button");
 
     input_box.append(&attach_btn);
@@ -429,10 +779,298 @@ fn create_chat_view() -> GtkBox {
 
     main_box.append(&chat_list_box);
     main_box.append(&conversation_box);
-    main_box
+
+    // Conversations come from the shared community state
+    let selected: Rc<RefCell<Option<String>>> = Rc::new(RefCell::new(
+        state
+            .community
+            .borrow()
+            .conversations()
+            .first()
+            .map(|conversation| conversation.id.clone()),
+    ));
+    let row_ids: Rc<RefCell<Vec<String>>> = Rc::default();
+    // The other person in a direct chat; their name opens their profile
+    let header_person: Rc<RefCell<Option<String>>> = Rc::default();
+    let header_click = gtk4::GestureClick::new();
+    let person = header_person.clone();
+    header_click.connect_released(move |gesture, _, _, _| {
+        if let (Some(widget), Some(id)) = (gesture.widget(), person.borrow().clone()) {
+            navigation::activate(&widget, "show-profile", Some(&id.to_variant()));
+        }
+    });
+    header_name.add_controller(header_click);
+
+    let refresh: Rc<dyn Fn()> = {
+        let state = state.clone();
+        let selected = selected.clone();
+        let row_ids = row_ids.clone();
+        let chat_list = chat_list.clone();
+        let search_entry = search_entry.clone();
+        let messages_scroll = messages_scroll.clone();
+        Rc::new(move || {
+            if let Some(id) = selected.borrow().as_deref() {
+                state.community.borrow_mut().mark_read(id);
+            }
+
+            let community = state.community.borrow();
+            let query = search_entry.text().trim().to_lowercase();
+            let now = util::now();
+            while let Some(child) = chat_list.first_child() {
+                chat_list.remove(&child);
+            }
+            row_ids.borrow_mut().clear();
+            for conversation in community.conversations() {
+                if community.is_blocked_conversation(conversation) {
+                    continue;
+                }
+                let title = community.conversation_title(conversation);
+                if !query.is_empty() && !title.to_lowercase().contains(&query) {
+                    continue;
+                }
+                let avatar = conversation_avatar(&community, conversation, "chat-item-avatar", 36);
+                let (preview, time) = conversation.messages.last().map_or(
+                    (String::new(), String::new()),
+                    |message| {
+                        (
+                            message.text.clone(),
+                            util::format_short_time(message.sent_at, now),
+                        )
+                    },
+                );
+                let person = match conversation.participants.as_slice() {
+                    [id] if conversation.title.is_none() => Some(id.as_str()),
+                    _ => None,
+                };
+                let chat_item = create_chat_item(
+                    &title,
+                    &preview,
+                    &time,
+                    conversation.unread,
+                    &avatar,
+                    person,
+                );
+                chat_list.append(&chat_item);
+                row_ids.borrow_mut().push(conversation.id.clone());
+            }
+
+            while let Some(child) = messages_box.first_child() {
+                messages_box.remove(&child);
+            }
+            while let Some(child) = header_menu.first_child() {
+                header_menu.remove(&child);
+            }
+            header_person.replace(None);
+            header_name.set_cursor_from_name(None);
+            let Some(conversation) = selected
+                .borrow()
+                .as_deref()
+                .and_then(|id| community.conversation(id))
+                .filter(|conversation| !community.is_blocked_conversation(conversation))
+            else {
+                header_name.set_text(&tr("Select a conversation"));
+                while let Some(child) = header_avatar.first_child() {
+                    header_avatar.remove(&child);
+                }
+                return;
+            };
+            if let [id] = conversation.participants.as_slice() {
+                if conversation.title.is_none() {
+                    header_menu.append(&profile_view::create_relationship_menu(&state, id));
+                    header_person.replace(Some(id.clone()));
+                    header_name.set_cursor_from_name(Some("pointer"));
+                }
+            }
+            header_name.set_text(&community.conversation_title(conversation));
+            while let Some(child) = header_avatar.first_child() {
+                header_avatar.remove(&child);
+            }
+            header_avatar.append(&conversation_avatar(
+                &community,
+                conversation,
+                "chat-avatar",
+                40,
+            ));
+            for message in &conversation.messages {
+                if community.is_blocked(&message.sender) {
+                    continue;
+                }
+                let is_own = message.sender == community::ME;
+                let sender = if is_own {
+                    tr("You")
+                } else {
+                    community.user_name(&message.sender)
+                };
+                let msg = create_message_bubble(
+                    &state,
+                    &sender,
+                    &message.text,
+                    message.location.as_ref(),
+                    is_own,
+                );
+                messages_box.append(&msg);
+            }
+
+            // Keep the newest message in view
+            let adjustment = messages_scroll.vadjustment();
+            glib::idle_add_local_once(move || {
+                adjustment.set_value(adjustment.upper() - adjustment.page_size());
+            });
+        })
+    };
+    refresh();
+    let refresh_clone = refresh.clone();
+    state.community_changed.connect(move || refresh_clone());
+
+    let refresh_clone = refresh.clone();
+    search_entry.connect_changed(move |_| refresh_clone());
+
+    let selected_for_send = selected.clone();
+    let selected_for_attach = selected.clone();
+    let selected_for_open = selected.clone();
+    let state_clone = state.clone();
+    chat_list.connect_row_activated(move |_, row| {
+        let Some(id) = usize::try_from(row.index())
+            .ok()
+            .and_then(|index| row_ids.borrow().get(index).cloned())
+        else {
+            return;
+        };
+        selected.replace(Some(id));
+        state_clone.community_changed.emit();
+    });
+
+    let select_conversation: SelectConversation = {
+        let state = state.clone();
+        let selected = selected_for_open;
+        let message_entry = message_entry.clone();
+        Rc::new(move |id, reply| {
+            selected.replace(Some(id.to_string()));
+            state.community_changed.emit();
+            if reply {
+                message_entry.grab_focus();
+            }
+        })
+    };
+
+    let send = {
+        let state = state.clone();
+        let message_entry = message_entry.clone();
+        move || {
+            let text = message_entry.text().trim().to_string();
+            let Some(id) = selected_for_send.borrow().clone() else {
+                return;
+            };
+            if text.is_empty() {
+                return;
+            }
+            if state
+                .community
+                .borrow_mut()
+                .send_message(&id, community::ME, &text, None)
+            {
+                message_entry.set_text("");
+                state.community_changed.emit();
+            }
+        }
+    };
+    let send = Rc::new(send);
+    let send_clone = send.clone();
+    send_btn.connect_clicked(move |_| send_clone());
+    message_entry.connect_activate(move |_| send());
+
+    // Files and links go through the share sheet with the open conversation
+    // already chosen; locations have a dialog of their own
+    for kind in [
+        ShareKind::Image,
+        ShareKind::Video,
+        ShareKind::Document,
+        ShareKind::Link,
+        ShareKind::Location,
+    ] {
+        let item = Button::with_label(&format!("{} {}", kind.icon(), tr(kind.label())));
+        item.add_css_class("flat");
+        let state = state.clone();
+        let selected = selected_for_attach.clone();
+        let popover = attach_popover.clone();
+        let attach_btn = attach_btn.clone();
+        item.connect_clicked(move |_| {
+            popover.popdown();
+            let Some(id) = selected.borrow().clone() else {
+                return;
+            };
+            if kind == ShareKind::Location {
+                send_location(&attach_btn, &state, id);
+                return;
+            }
+            let recipient = state
+                .community
+                .borrow()
+                .conversation(&id)
+                .map(share::Recipient::for_conversation);
+            if let Some(recipient) = recipient {
+                share_sheet::open_share_sheet_to(&attach_btn, &state, kind, recipient);
+            }
+        });
+        attach_items.append(&item);
+    }
+
+    (main_box, select_conversation)
+}
+
+/// Asks for a location and sends it into conversation `id`.
+fn send_location(parent: &impl IsA<gtk4::Widget>, state: &Rc<AppState>, id: String) {
+    let state_clone = state.clone();
+    location_view::open_location_dialog(
+        parent,
+        state,
+        &tr("📍 Send Location"),
+        &tr("Send"),
+        move |location, note| {
+            let text = if note.is_empty() {
+                format!("📍 {}", location.title())
+            } else {
+                note
+            };
+            if state_clone.community.borrow_mut().send_message(
+                &id,
+                community::ME,
+                &text,
+                Some(location),
+            ) {
+                state_clone.community_changed.emit();
+            }
+        },
+    );
+}
+
+/// The other person's avatar for direct chats, a group icon otherwise.
+fn conversation_avatar(
+    community: &Community,
+    conversation: &Conversation,
+    css_class: &str,
+    size: i32,
+) -> gtk4::Widget {
+    match conversation.participants.as_slice() {
+        [id] if conversation.title.is_none() => {
+            profile_view::create_avatar(community.user(id), css_class, size)
+        }
+        _ => {
+            let label = Label::new(Some("👥"));
+            label.add_css_class(css_class);
+            label.upcast()
+        }
+    }
 }
 
-fn create_chat_item(name: &str, message: &str, time: &str, unread: bool) -> GtkBox {
+fn create_chat_item(
+    name: &str,
+    message: &str,
+    time: &str,
+    unread: bool,
+    avatar: &gtk4::Widget,
+    person: Option<&str>,
+) -> GtkBox {
     let item_box = GtkBox::new(Orientation::Horizontal, 12);
     item_box.add_css_class("chat-item");
     if unread {
@@ -443,9 +1081,6 @@ fn create_chat_item(name: &str, message: &str, time: &str, unread: bool) -> GtkB
     item_box.set_margin_top(6);
     item_box.set_margin_bottom(6);
 
-    let avatar = Label::new(Some("👤"));
-    avatar.add_css_class("chat-item-avatar");
-
     let content_box = GtkBox::new(Orientation::Vertical, 4);
     content_box.set_hexpand(true);
 
@@ -458,20 +1093,32 @@ fn create_chat_item(name: &str, message: &str, time: &str, unread: bool) -> GtkB
     message_label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
     message_label.add_css_class("chat-item-message");
 
+    // In direct chats the avatar and name lead to the other person's profile
+    if let Some(user_id) = person {
+        navigation::link_to_profile(avatar, user_id);
+        navigation::link_to_profile(&name_label, user_id);
+    }
+
     content_box.append(&name_label);
     content_box.append(&message_label);
 
     let time_label = Label::new(Some(time));
     time_label.add_css_class("chat-item-time");
 
-    item_box.append(&avatar);
+    item_box.append(avatar);
     item_box.append(&content_box);
     item_box.append(&time_label);
 
     item_box
 }
 
-fn create_message_bubble(sender: &str, text: &str, is_own: bool) -> GtkBox {
+fn create_message_bubble(
+    state: &Rc<AppState>,
+    sender: &str,
+    text: &str,
+    location: Option<&Location>,
+    is_own: bool,
+) -> GtkBox {
     let container = GtkBox::new(Orientation::Horizontal, 0);
 
     let bubble = GtkBox::new(Orientation::Vertical, 4);
@@ -480,6 +1127,8 @@ fn create_message_bubble(sender: &str, text: &str, is_own: bool) -> GtkBox {
     bubble.set_margin_top(8);
     bubble.set_margin_bottom(8);
 
+    // Start and End follow the text direction, so in right-to-left
+    // languages own messages sit on the left
     if is_own {
         bubble.add_css_class("message-bubble-own");
         container.set_halign(gtk4::Align::End);
@@ -492,13 +1141,18 @@ fn create_message_bubble(sender: &str, text: &str, is_own: bool) -> GtkBox {
     sender_label.set_halign(gtk4::Align::Start);
     sender_label.add_css_class("message-sender");
 
-    let text_label = Label::new(Some(text));
+    let text_label = Label::new(None);
+    sizing::set_markup(&text_label, &util::linkify_markup(text));
     text_label.set_wrap(true);
     text_label.set_halign(gtk4::Align::Start);
     text_label.add_css_class("message-text");
+    link_sheet::connect_links(&text_label, state);
 
     bubble.append(&sender_label);
     bubble.append(&text_label);
+    if let Some(location) = location {
+        bubble.append(&location_view::create_map_preview(state, location));
+    }
     container.append(&bubble);
 
     container
@@ -512,43 +1166,47 @@ fn create_groups_view() -> ScrolledWindow {
     groups_box.set_margin_top(30);
     groups_box.set_margin_bottom(30);
 
-    let header = create_section_header("👥 Groups", "Connect with communities");
+    let header = create_section_header(&tr("👥 Groups"), &tr("Connect with communities"));
     groups_box.append(&header);
 
-    let create_group_btn = Button::with_label("➕ Create New Group");
+    let create_group_btn = Button::with_label(&tr("➕ Create New Group"));
     create_group_btn.add_css_class("create-post-button");
     groups_box.append(&create_group_btn);
 
     // Sample groups
     let groups = vec![
         (
-            "Rust Developers",
-            "2.5k members",
+            tr("Rust Developers"),
+            2_500,
             "🦀",
-            "Discuss Rust programming",
+            tr("Discuss Rust programming"),
         ),
         (
-            "UI/UX Design",
-            "1.8k members",
+            tr("UI/UX Design"),
+            1_800,
             "🎨",
-            "Share design resources",
+            tr("Share design resources"),
         ),
         (
-            "Career Mentorship",
-            "3.2k members",
+            tr("Career Mentorship"),
+            3_200,
             "🎯",
-            "Get career guidance",
+            tr("Get career guidance"),
         ),
         (
-            "Open Source",
-            "4.1k members",
+            tr("Open Source"),
+            4_100,
             "💻",
-            "Collaborate on projects",
+            tr("Collaborate on projects"),
         ),
     ];
 
     for (name, members, emoji, description) in groups {
-        let group_card = create_group_card(name, members, emoji, description);
+        let members = tr_with(
+            "{count} members",
+            &[("count", &util::format_count(members))],
+        );
+        let group_card = create_group_card(&name, &members, emoji, &description);
         groups_box.append(&group_card);
     }
 
@@ -588,7 +1246,7 @@ fn create_group_card(name: &str, members: &str, emoji: &str, description: &str)
     info_box.append(&desc_label);
     info_box.append(&members_label);
 
-    let join_btn = Button::with_label("Join");
+    let join_btn = Button::with_label(&tr("Join"));
     join_btn.add_css_class("join-button");
     join_btn.set_valign(gtk4::Align::Center);
 
@@ -600,7 +1258,7 @@ fn create_group_card(name: &str, members: &str, emoji: &str, description: &str)
     frame
 }
 
-fn create_media_view() -> ScrolledWindow {
+fn create_media_view(state: &Rc<AppState>) -> ScrolledWindow {
     let scroll = ScrolledWindow::new();
     let media_box = GtkBox::new(Orientation::Vertical, 20);
     media_box.set_margin_start(40);
@@ -608,135 +1266,168 @@ fn create_media_view() -> ScrolledWindow {
     media_box.set_margin_top(30);
     media_box.set_margin_bottom(30);
 
-    let header = create_section_header("📸 Media Gallery", "Your shared moments");
+    let header = create_section_header(&tr("📸 Media Gallery"), &tr("Your shared moments"));
     media_box.append(&header);
 
-    let upload_btn = Button::with_label("📤 Upload Media");
+    // Upload controls
+    let upload_box = GtkBox::new(Orientation::Horizontal, 16);
+    let upload_btn = Button::with_label(&tr("📤 Upload Media"));
     upload_btn.add_css_class("create-post-button");
-    media_box.append(&upload_btn);
-
-    // Grid for media items
-    let grid = gtk4::Grid::new();
-    grid.set_row_spacing(16);
-    grid.set_column_spacing(16);
-    grid.add_css_class("media-grid");
-
-    let media_items = [
-        ("🖼️", "Project Screenshot", "2 days ago"),
-        ("📹", "Tutorial Video", "5 days ago"),
-        ("📄", "Resume.pdf", "1 week ago"),
-        ("🎵", "Podcast Episode", "2 weeks ago"),
-        ("🖼️", "Team Photo", "3 weeks ago"),
-        ("📹", "Demo Recording", "1 month ago"),
-    ];
-
-    for (i, (icon, title, time)) in media_items.iter().enumerate() {
-        let media_card = create_media_card(icon, title, time);
-        grid.attach(&media_card, (i % 3) as i32, (i / 3) as i32, 1, 1);
-    }
-
-    media_box.append(&grid);
-    scroll.set_child(Some(&media_box));
-    scroll
-}
 
-fn create_media_card(icon: &str, title: &str, time: &str) -> Frame {
-    let frame = Frame::new(None);
-    frame.add_css_class("media-card");
-    frame.set_width_request(250);
-    frame.set_height_request(200);
-
-    let card_box = GtkBox::new(Orientation::Vertical, 12);
-    card_box.set_margin_start(16);
-    card_box.set_margin_end(16);
-    card_box.set_margin_top(16);
-    card_box.set_margin_bottom(16);
-    card_box.set_valign(gtk4::Align::Center);
-    card_box.set_halign(gtk4::Align::Center);
-
-    let icon_label = Label::new(Some(icon));
-    icon_label.add_css_class("media-icon");
-
-    let title_label = Label::new(Some(title));
-    title_label.add_css_class("media-title");
+    let downscale_check = CheckButton::with_label(&tr("Downscale large images"));
+    downscale_check.add_css_class("upload-option");
+    downscale_check.set_active(state.media.borrow().downscale_large_images);
+    let state_clone = state.clone();
+    downscale_check.connect_toggled(move |check| {
+        let mut library = state_clone.media.borrow_mut();
+        library.downscale_large_images = check.is_active();
+        library.save();
+    });
 
-    let time_label = Label::new(Some(time));
-    time_label.add_css_class("media-time");
+    let hint = Label::new(Some(&tr("or drop files onto the gallery")));
+    hint.add_css_class("upload-hint");
+
+    upload_box.append(&upload_btn);
+    upload_box.append(&downscale_check);
+    upload_box.append(&hint);
+    media_box.append(&upload_box);
+
+    let upload_panel = media::create_upload_panel();
+    media_box.append(&upload_panel);
+
+    let state_clone = state.clone();
+    let panel_clone = upload_panel.clone();
+    upload_btn.connect_clicked(move |button| {
+        let dialog = gtk4::FileDialog::builder()
+            .title(tr("Upload Media"))
+            .modal(true)
+            .build();
+        let window = button.root().and_downcast::<gtk4::Window>();
+        let state = state_clone.clone();
+        let panel = panel_clone.clone();
+        dialog.open_multiple(window.as_ref(), gio::Cancellable::NONE, move |result| {
+            if let Ok(list) = result {
+                let files = list.iter::<gio::File>().filter_map(Result::ok).collect();
+                media::start_uploads(&state, &panel, files);
+            }
+        });
+    });
 
-    card_box.append(&icon_label);
-    card_box.append(&title_label);
-    card_box.append(&time_label);
+    // Drag and drop onto the gallery
+    let drop_target = gtk4::DropTarget::new(gdk::FileList::static_type(), gdk::DragAction::COPY);
+    let media_box_clone = media_box.clone();
+    drop_target.connect_enter(move |_, _, _| {
+        media_box_clone.add_css_class("drop-active");
+        gdk::DragAction::COPY
+    });
+    let media_box_clone = media_box.clone();
+    drop_target.connect_leave(move |_| {
+        media_box_clone.remove_css_class("drop-active");
+    });
+    let state_clone = state.clone();
+    let panel_clone = upload_panel.clone();
+    let media_box_clone = media_box.clone();
+    drop_target.connect_drop(move |_, value, _, _| {
+        media_box_clone.remove_css_class("drop-active");
+        let Ok(file_list) = value.get::<gdk::FileList>() else {
+            return false;
+        };
+        media::start_uploads(&state_clone, &panel_clone, file_list.files());
+        true
+    });
+    scroll.add_controller(drop_target);
 
-    frame.set_child(Some(&card_box));
-    frame
+    media_box.append(&gallery::create_gallery(state));
+    scroll.set_child(Some(&media_box));
+    scroll
 }
 
-fn create_share_view() -> GtkBox {
+fn create_share_view(state: &Rc<AppState>) -> ScrolledWindow {
+    let scroll = ScrolledWindow::new();
     let main_box = GtkBox::new(Orientation::Vertical, 20);
     main_box.set_margin_start(40);
     main_box.set_margin_end(40);
     main_box.set_margin_top(30);
     main_box.set_margin_bottom(30);
 
-    let header = create_section_header("📤 Share Content", "Share files, links, and more");
+    let header =
+        create_section_header(&tr("📤 Share Content"), &tr("Share files, links, and more"));
     main_box.append(&header);
 
     // Share options grid
     let options_box = GtkBox::new(Orientation::Horizontal, 20);
     options_box.set_halign(gtk4::Align::Center);
 
-    let share_options = [
-        ("📄", "Documents"),
-        ("🖼️", "Images"),
-        ("📹", "Videos"),
-        ("🔗", "Links"),
-        ("📍", "Location"),
-        ("📊", "Polls"),
-    ];
-
-    for (icon, label) in share_options {
-        let option_card = create_share_option(icon, label);
+    for kind in ShareKind::ALL {
+        let option_card = create_share_option(kind.icon(), &tr(kind.label()));
+        let state_clone = state.clone();
+        let click = gtk4::GestureClick::new();
+        click.connect_released(move |gesture, _, _, _| {
+            if let Some(widget) = gesture.widget() {
+                share_sheet::open_share_sheet(&widget, &state_clone, kind);
+            }
+        });
+        option_card.add_controller(click);
         options_box.append(&option_card);
     }
 
     main_box.append(&options_box);
 
     // Recent shares
-    let recent_label = Label::new(Some("Recent Shares"));
+    let recent_label = Label::new(Some(&tr("Recent Shares")));
     recent_label.add_css_class("section-subtitle");
     recent_label.set_halign(gtk4::Align::Start);
     recent_label.set_margin_top(20);
     main_box.append(&recent_label);
 
-    let shares = vec![
-        (
-            "Project Proposal.pdf",
-            "Shared with Alice",
-            "1 hour ago",
-            "📄",
-        ),
-        (
-            "Screenshot.png",
-            "Shared in Rust Group",
-            "3 hours ago",
-            "🖼️",
-        ),
-        ("Tutorial.mp4", "Shared with Bob", "Yesterday", "📹"),
-    ];
+    let recent_box = GtkBox::new(Orientation::Vertical, 12);
+    main_box.append(&recent_box);
+
+    let populate = {
+        let state = state.clone();
+        move || {
+            while let Some(child) = recent_box.first_child() {
+                recent_box.remove(&child);
+            }
+            let now = util::now();
+            let records = state.shares.borrow().records().to_vec();
+            if records.is_empty() {
+                let empty = Label::new(Some(&tr("Nothing shared yet")));
+                empty.add_css_class("share-empty");
+                recent_box.append(&empty);
+            }
+            for record in records {
+                let shared_with = share::describe_recipients(&state, &record.recipients);
+                let share_item = create_share_item(
+                    &record.content.title(),
+                    &shared_with,
+                    &util::format_relative_time(record.created_at, now),
+                    record.content.icon(),
+                    &record.status,
+                );
+                if !record.message.is_empty() {
+                    share_item.set_tooltip_text(Some(&record.message));
+                }
+                recent_box.append(&share_item);
+            }
+        }
+    };
+    populate();
+    state.shares_changed.connect(populate);
 
-    for (file, shared_with, time, icon) in shares {
-        let share_item = create_share_item(file, shared_with, time, icon);
-        main_box.append(&share_item);
-    }
+    let links_panel = link_sheet::create_links_panel(state);
+    links_panel.set_margin_top(20);
+    main_box.append(&links_panel);
 
-    main_box
+    scroll.set_child(Some(&main_box));
+    scroll
 }
 
 fn create_share_option(icon: &str, label: &str) -> Frame {
     let frame = Frame::new(None);
     frame.add_css_class("share-option");
-    frame.set_width_request(120);
-    frame.set_height_request(120);
+    frame.set_cursor_from_name(Some("pointer"));
+    sizing::set_size_request(&frame, 120, 120);
 
     let box_content = GtkBox::new(Orientation::Vertical, 8);
     box_content.set_valign(gtk4::Align::Center);
@@ -755,7 +1446,13 @@ fn create_share_option(icon: &str, label: &str) -> Frame {
     frame
 }
 
-fn create_share_item(file: &str, shared_with: &str, time: &str, icon: &str) -> Frame {
+fn create_share_item(
+    file: &str,
+    shared_with: &str,
+    time: &str,
+    icon: &str,
+    status: &ShareStatus,
+) -> Frame {
     let frame = Frame::new(None);
     frame.add_css_class("share-item");
 
@@ -782,18 +1479,24 @@ fn create_share_item(file: &str, shared_with: &str, time: &str, icon: &str) -> F
     info_box.append(&file_label);
     info_box.append(&shared_label);
 
+    let status_label = Label::new(Some(&status.label()));
+    status_label.add_css_class("share-status");
+    status_label.add_css_class(status.css_class());
+    status_label.set_valign(gtk4::Align::Center);
+
     let time_label = Label::new(Some(time));
     time_label.add_css_class("share-item-time");
 
     item_box.append(&icon_label);
     item_box.append(&info_box);
+    item_box.append(&status_label);
     item_box.append(&time_label);
 
     frame.set_child(Some(&item_box));
     frame
 }
 
-fn create_profile_view() -> ScrolledWindow {
+fn create_profile_view(state: &Rc<AppState>) -> ScrolledWindow {
     let scroll = ScrolledWindow::new();
     let profile_box = GtkBox::new(Orientation::Vertical, 24);
     profile_box.set_margin_start(40);
@@ -802,105 +1505,42 @@ fn create_profile_view() -> ScrolledWindow {
     profile_box.set_margin_bottom(30);
 
     // Profile header
-    let header_box = GtkBox::new(Orientation::Vertical, 16);
-    header_box.add_css_class("profile-header");
-    header_box.set_margin_start(20);
-    header_box.set_margin_end(20);
-    header_box.set_margin_top(20);
-    header_box.set_margin_bottom(20);
-
-    let avatar = Label::new(Some("👤"));
-    avatar.add_css_class("profile-avatar");
-
-    let name = Label::new(Some("Your Name"));
-    name.add_css_class("profile-name");
-
-    let bio = Label::new(Some("Software Developer | Mentor | Open Source Enthusiast"));
-    bio.add_css_class("profile-bio");
-    bio.set_wrap(true);
-
-    let stats_box = GtkBox::new(Orientation::Horizontal, 40);
-    stats_box.set_halign(gtk4::Align::Center);
-    stats_box.set_margin_top(16);
-
-    let stats = vec![("Posts", "42"), ("Followers", "1.2k"), ("Following", "856")];
-
-    for (label, value) in stats {
-        let stat_box = GtkBox::new(Orientation::Vertical, 4);
-        let value_label = Label::new(Some(value));
-        value_label.add_css_class("stat-value");
-        let label_label = Label::new(Some(label));
-        label_label.add_css_class("stat-label");
-        stat_box.append(&value_label);
-        stat_box.append(&label_label);
-        stats_box.append(&stat_box);
-    }
+    let header_box = profile_view::create_profile_header(state);
+    let stats_box = profile_view::create_profile_stats(state);
 
-    let edit_btn = Button::with_label("✏️ Edit Profile");
+    let edit_btn = Button::with_label(&tr("✏️ Edit Profile"));
     edit_btn.add_css_class("edit-profile-button");
     edit_btn.set_halign(gtk4::Align::Center);
     edit_btn.set_margin_top(16);
+    let state_clone = state.clone();
+    edit_btn.connect_clicked(move |button| {
+        profile_view::open_profile_editor(button, &state_clone);
+    });
+
+    let hidden_btn = Button::with_label(&tr("🚫 Blocked & Muted"));
+    hidden_btn.add_css_class("stat-button");
+    hidden_btn.set_halign(gtk4::Align::Center);
+    let state_clone = state.clone();
+    hidden_btn.connect_clicked(move |button| {
+        profile_view::open_people_list(button, &state_clone, profile_view::PeopleList::Hidden);
+    });
 
-    header_box.append(&avatar);
-    header_box.append(&name);
-    header_box.append(&bio);
     header_box.append(&stats_box);
     header_box.append(&edit_btn);
+    header_box.append(&hidden_btn);
 
     profile_box.append(&header_box);
 
     // Activity section
-    let activity_label = Label::new(Some("Recent Activity"));
-    activity_label.add_css_class("section-subtitle");
-    activity_label.set_halign(gtk4::Align::Start);
-    activity_label.set_margin_top(20);
-    profile_box.append(&activity_label);
-
-    let activities = vec![
-        ("Posted in Rust Developers", "2 hours ago", "📝"),
-        ("Joined UI/UX Design group", "1 day ago", "👥"),
-        ("Shared a video", "3 days ago", "📹"),
-        ("Commented on Alice's post", "5 days ago", "💬"),
-    ];
-
-    for (activity, time, icon) in activities {
-        let activity_item = create_activity_item(activity, time, icon);
-        profile_box.append(&activity_item);
-    }
+    profile_box.append(&profile_view::create_activity_timeline(
+        state,
+        community::ME,
+    ));
 
     scroll.set_child(Some(&profile_box));
     scroll
 }
 
-fn create_activity_item(activity: &str, time: &str, icon: &str) -> Frame {
-    let frame = Frame::new(None);
-    frame.add_css_class("activity-item");
-
-    let item_box = GtkBox::new(Orientation::Horizontal, 16);
-    item_box.set_margin_start(16);
-    item_box.set_margin_end(16);
-    item_box.set_margin_top(12);
-    item_box.set_margin_bottom(12);
-
-    let icon_label = Label::new(Some(icon));
-    icon_label.add_css_class("activity-icon");
-
-    let activity_label = Label::new(Some(activity));
-    activity_label.set_halign(gtk4::Align::Start);
-    activity_label.set_hexpand(true);
-    activity_label.add_css_class("activity-text");
-
-    let time_label = Label::new(Some(time));
-    time_label.add_css_class("activity-time");
-
-    item_box.append(&icon_label);
-    item_box.append(&activity_label);
-    item_box.append(&time_label);
-
-    frame.set_child(Some(&item_box));
-    frame
-}
-
 fn create_section_header(title: &str, subtitle: &str) -> GtkBox {
     let header_box = GtkBox::new(Orientation::Vertical, 8);
 
@@ -922,7 +1562,7 @@ fn create_section_header(title: &str, subtitle: &str) -> GtkBox {
 // NEW ADVANCED VIEWS
 // ============================================================================
 
-fn create_notifications_view() -> ScrolledWindow {
+fn create_notifications_view(state: &Rc<AppState>) -> ScrolledWindow {
     let scroll = ScrolledWindow::new();
     let notif_box = GtkBox::new(Orientation::Vertical, 20);
     notif_box.set_margin_start(40);
@@ -930,236 +1570,37 @@ fn create_notifications_view() -> ScrolledWindow {
     notif_box.set_margin_top(30);
     notif_box.set_margin_bottom(30);
 
-    let header = create_section_header("🔔 Notifications", "Stay updated with your activity");
+    let header = create_section_header(
+        &tr("🔔 Notifications"),
+        &tr("Stay updated with your activity"),
+    );
     notif_box.append(&header);
 
-    // Filter buttons
-    let filter_box = GtkBox::new(Orientation::Horizontal, 12);
-    let filters = ["All", "Mentions", "Likes", "Comments", "Follows"];
-
-    for filter in filters {
-        let filter_btn = Button::with_label(filter);
-        filter_btn.add_css_class("filter-button");
-        if filter == "All" {
-            filter_btn.add_css_class("filter-active");
-        }
-        filter_box.append(&filter_btn);
-    }
-    notif_box.append(&filter_box);
-
-    // Notifications
-    let notifications = vec![
-        ("👍", "Alice Johnson", "liked your post", "2 min ago", true),
-        (
-            "💬",
-            "Bob Smith",
-            "commented: 'Great work!'",
-            "15 min ago",
-            true,
-        ),
-        (
-            "👥",
-            "Carol Williams",
-            "joined your group 'Rust Developers'",
-            "1 hour ago",
-            false,
-        ),
-        (
-            "🔗",
-            "David Brown",
-            "shared your post",
-            "2 hours ago",
-            false,
-        ),
-        (
-            "👤",
-            "Emma Davis",
-            "started following you",
-            "5 hours ago",
-            false,
-        ),
-        (
-            "💬",
-            "Frank Miller",
-            "mentioned you in a comment",
-            "1 day ago",
-            false,
-        ),
-        ("👍", "Grace Lee", "liked your comment", "2 days ago", false),
-    ];
-
-    for (icon, user, action, time, unread) in notifications {
-        let notif_item = create_notification_item(icon, user, action, time, unread);
-        notif_box.append(&notif_item);
-    }
+    notif_box.append(&profile_view::create_follow_requests(state));
+    notif_box.append(&notifications_view::create_notification_list(state));
 
     scroll.set_child(Some(&notif_box));
     scroll
 }
 
-fn create_notification_item(
-    icon: &str,
-    user: &str,
-    action: &str,
-    time: &str,
-    unread: bool,
-) -> Frame {
-    let frame = Frame::new(None);
-    frame.add_css_class("notification-item");
-    if unread {
-        frame.add_css_class("notification-unread");
-    }
-
-    let item_box = GtkBox::new(Orientation::Horizontal, 16);
-    item_box.set_margin_start(16);
-    item_box.set_margin_end(16);
-    item_box.set_margin_top(12);
-    item_box.set_margin_bottom(12);
-
-    let icon_label = Label::new(Some(icon));
-    icon_label.add_css_class("notification-icon");
-
-    let content_box = GtkBox::new(Orientation::Vertical, 4);
-    content_box.set_hexpand(true);
-
-    let user_label = Label::new(Some(user));
-    user_label.set_halign(gtk4::Align::Start);
-    user_label.add_css_class("notification-user");
-
-    let action_label = Label::new(Some(action));
-    action_label.set_halign(gtk4::Align::Start);
-    action_label.add_css_class("notification-action");
-
-    content_box.append(&user_label);
-    content_box.append(&action_label);
-
-    let time_label = Label::new(Some(time));
-    time_label.add_css_class("notification-time");
-
-    item_box.append(&icon_label);
-    item_box.append(&content_box);
-    item_box.append(&time_label);
-
-    frame.set_child(Some(&item_box));
-    frame
-}
-
-fn create_search_view() -> GtkBox {
+fn create_search_view(state: &Rc<AppState>) -> GtkBox {
     let main_box = GtkBox::new(Orientation::Vertical, 20);
     main_box.set_margin_start(40);
     main_box.set_margin_end(40);
     main_box.set_margin_top(30);
     main_box.set_margin_bottom(30);
 
-    let header = create_section_header("🔍 Search", "Find people, groups, and content");
+    let header = create_section_header(&tr("🔍 Search"), &tr("Find people, groups, and content"));
     main_box.append(&header);
 
-    // Search bar
-    let search_box = GtkBox::new(Orientation::Horizontal, 12);
-    let search_entry = Entry::new();
-    search_entry.set_placeholder_text(Some("Search for anything..."));
-    search_entry.set_hexpand(true);
-    search_entry.add_css_class("search-entry-large");
-
-    let search_btn = Button::with_label("🔍 Search");
-    search_btn.add_css_class("search-button");
-
-    search_box.append(&search_entry);
-    search_box.append(&search_btn);
-    main_box.append(&search_box);
-
-    // Filter tabs
-    let tabs_box = GtkBox::new(Orientation::Horizontal, 12);
-    let tabs = ["All", "People", "Groups", "Posts", "Media"];
-
-    for tab in tabs {
-        let tab_btn = Button::with_label(tab);
-        tab_btn.add_css_class("tab-button");
-        if tab == "All" {
-            tab_btn.add_css_class("tab-active");
-        }
-        tabs_box.append(&tab_btn);
-    }
-    main_box.append(&tabs_box);
-
-    // Search results
-    let results_label = Label::new(Some("Recent Searches"));
-    results_label.add_css_class("section-subtitle");
-    results_label.set_halign(gtk4::Align::Start);
-    results_label.set_margin_top(20);
-    main_box.append(&results_label);
-
-    let recent_searches = [
-        ("👤", "Alice Johnson", "User"),
-        ("👥", "Rust Developers", "Group"),
-        ("📝", "GTK4 tutorial", "Post"),
-        ("👤", "Bob Smith", "User"),
-    ];
+    main_box.append(&search_view::create_search_panel(state));
 
-    for (icon, title, category) in recent_searches {
-        let search_result = create_search_result(icon, title, category);
-        main_box.append(&search_result);
-    }
-
-    // Trending section
-    let trending_label = Label::new(Some("🔥 Trending Topics"));
-    trending_label.add_css_class("section-subtitle");
-    trending_label.set_halign(gtk4::Align::Start);
-    trending_label.set_margin_top(20);
-    main_box.append(&trending_label);
-
-    let trending_box = GtkBox::new(Orientation::Horizontal, 12);
-    let trending_topics = ["#Rust", "#GTK4", "#OpenSource", "#Mentorship", "#WebDev"];
-
-    for topic in trending_topics {
-        let topic_btn = Button::with_label(topic);
-        topic_btn.add_css_class("trending-tag");
-        trending_box.append(&topic_btn);
-    }
-    main_box.append(&trending_box);
+    main_box.append(&hashtag_view::create_trending_section(state));
 
     main_box
 }
 
-fn create_search_result(icon: &str, title: &str, category: &str) -> Frame {
-    let frame = Frame::new(None);
-    frame.add_css_class("search-result");
-
-    let result_box = GtkBox::new(Orientation::Horizontal, 16);
-    result_box.set_margin_start(16);
-    result_box.set_margin_end(16);
-    result_box.set_margin_top(12);
-    result_box.set_margin_bottom(12);
-
-    let icon_label = Label::new(Some(icon));
-    icon_label.add_css_class("search-result-icon");
-
-    let content_box = GtkBox::new(Orientation::Vertical, 4);
-    content_box.set_hexpand(true);
-
-    let title_label = Label::new(Some(title));
-    title_label.set_halign(gtk4::Align::Start);
-    title_label.add_css_class("search-result-title");
-
-    let category_label = Label::new(Some(category));
-    category_label.set_halign(gtk4::Align::Start);
-    category_label.add_css_class("search-result-category");
-
-    content_box.append(&title_label);
-    content_box.append(&category_label);
-
-    let view_btn = Button::with_label("View");
-    view_btn.add_css_class("view-button");
-
-    result_box.append(&icon_label);
-    result_box.append(&content_box);
-    result_box.append(&view_btn);
-
-    frame.set_child(Some(&result_box));
-    frame
-}
-
-fn create_settings_view() -> ScrolledWindow {
+fn create_settings_view(state: &Rc<AppState>) -> ScrolledWindow {
     let scroll = ScrolledWindow::new();
     let settings_box = GtkBox::new(Orientation::Vertical, 24);
     settings_box.set_margin_start(40);
@@ -1167,51 +1608,92 @@ fn create_settings_view() -> ScrolledWindow {
     settings_box.set_margin_top(30);
     settings_box.set_margin_bottom(30);
 
-    let header = create_section_header("⚙️ Settings", "Customize your experience");
+    let header = create_section_header(&tr("⚙️ Settings"), &tr("Customize your experience"));
     settings_box.append(&header);
 
     // Account section
-    let account_section = create_settings_section(
-        "👤 Account",
-        vec![
-            ("Email", "user@example.com", true),
-            ("Password", "••••••••", true),
-            ("Phone", "+1 234 567 8900", true),
-        ],
-    );
+    let account_section = create_settings_section(&tr("👤 Account"));
+    let email_label = Label::new(Some(&session::email(state)));
+    email_label.add_css_class("settings-value");
+    account_section.append(&create_settings_row(&tr("Email"), &email_label));
+    account_section.append(&create_settings_row(
+        &tr("Password"),
+        &account_view::create_password_button(state),
+    ));
+    let sign_out_btn = Button::with_label(&tr("Sign Out"));
+    sign_out_btn.add_css_class("danger-button");
+    sign_out_btn.set_valign(gtk4::Align::Center);
+    sign_out_btn.connect_clicked(|button| navigation::activate(button, "logout", None));
+    let account_name = state
+        .session
+        .borrow()
+        .as_ref()
+        .map(|session| session.name.clone())
+        .unwrap_or_default();
+    account_section.append(&create_settings_row(
+        &tr_with("Signed in as {name}", &[("name", &account_name)]),
+        &sign_out_btn,
+    ));
     settings_box.append(&account_section);
+    settings_box.append(&account_view::create_sessions_section(state));
 
-    // Preferences section
-    let preferences_section = create_settings_section(
-        "🎨 Preferences",
-        vec![
-            ("Theme", "Auto (System)", false),
-            ("Language", "English", false),
-            ("Font Size", "Medium", false),
-        ],
-    );
-    settings_box.append(&preferences_section);
-
-    // Notifications section
-    let notif_section = create_settings_section(
-        "🔔 Notifications",
-        vec![
-            ("Push Notifications", "Enabled", false),
-            ("Email Notifications", "Enabled", false),
-            ("Sound", "Enabled", false),
-        ],
-    );
-    settings_box.append(&notif_section);
+    match state.preferences.settings() {
+        Some(settings) => {
+            settings_box.append(&create_preferences_section(settings));
+            settings_box.append(&create_notification_settings(settings));
+        }
+        None => {
+            let unavailable = Label::new(Some(&tr(
+                "Preferences can't be changed: the settings schema couldn't be installed.",
+            )));
+            unavailable.add_css_class("settings-value");
+            unavailable.set_wrap(true);
+            settings_box.append(&unavailable);
+        }
+    }
 
     // Privacy section
-    let privacy_section = create_settings_section(
-        "🔒 Privacy & Security",
-        vec![
-            ("Profile Visibility", "Public", false),
-            ("Two-Factor Auth", "Disabled", true),
-            ("Data Sharing", "Limited", false),
-        ],
+    let privacy_section = create_settings_section(&tr("🔒 Privacy & Security"));
+    privacy_section.append(&create_settings_row(
+        &tr("Two-Factor Auth"),
+        &two_factor_view::create_status(state),
+    ));
+    // Visibility belongs to the profile, so it is kept with the community
+    let visibility_dropdown =
+        gtk4::DropDown::from_strings(&[&tr("Public"), &tr("Private (approve followers)")]);
+    visibility_dropdown.set_valign(gtk4::Align::Center);
+    let update_visibility = {
+        let state = state.clone();
+        let dropdown = visibility_dropdown.clone();
+        move || dropdown.set_selected(u32::from(state.community.borrow().is_private()))
+    };
+    update_visibility();
+    state.community_changed.connect(update_visibility);
+    let state_clone = state.clone();
+    visibility_dropdown.connect_selected_notify(move |dropdown| {
+        let private = dropdown.selected() == 1;
+        if state_clone.community.borrow().is_private() == private {
+            return;
+        }
+        state_clone.community.borrow_mut().set_private(private);
+        state_clone.community_changed.emit();
+        state_clone.feed_changed.emit();
+    });
+    privacy_section.insert_child_after(
+        &create_settings_row(&tr("Profile Visibility"), &visibility_dropdown),
+        privacy_section.first_child().as_ref(),
     );
+    if let Some(settings) = state.preferences.settings() {
+        privacy_section.append(&create_settings_row(
+            &tr("Data Sharing"),
+            &create_settings_choice(settings, "data-sharing", &preferences::DATA_SHARING),
+        ));
+        privacy_section.append(&create_settings_switch(
+            &tr("Save Search History"),
+            settings,
+            "save-search-history",
+        ));
+    }
     settings_box.append(&privacy_section);
 
     // About section
@@ -1219,15 +1701,15 @@ fn create_settings_view() -> ScrolledWindow {
     about_box.add_css_class("settings-section");
     about_box.set_margin_top(20);
 
-    let about_title = Label::new(Some("ℹ️ About"));
+    let about_title = Label::new(Some(&tr("ℹ️ About")));
     about_title.add_css_class("settings-section-title");
     about_title.set_halign(gtk4::Align::Start);
 
-    let version_label = Label::new(Some("Version 1.0.0"));
+    let version_label = Label::new(Some(&tr_with("Version {version}", &[("version", "1.0.0")])));
     version_label.set_halign(gtk4::Align::Start);
     version_label.add_css_class("settings-value");
 
-    let copyright_label = Label::new(Some("© 2025 LinkWithMentor. Built with GTK4 & Rust."));
+    let copyright_label = Label::new(Some(&tr("© 2025 LinkWithMentor. Built with GTK4 & Rust.")));
     copyright_label.set_halign(gtk4::Align::Start);
     copyright_label.add_css_class("settings-value");
 
@@ -1240,7 +1722,77 @@ fn create_settings_view() -> ScrolledWindow {
     scroll
 }
 
-fn create_settings_section(title: &str, items: Vec<(&str, &str, bool)>) -> GtkBox {
+fn create_preferences_section(settings: &gio::Settings) -> GtkBox {
+    let preferences_section = create_settings_section(&tr("🎨 Preferences"));
+    for (label, key, choices) in [
+        (tr_noop("Theme"), "theme", &preferences::THEMES[..]),
+        (tr_noop("Language"), "language", &preferences::LANGUAGES[..]),
+    ] {
+        preferences_section.append(&create_settings_row(
+            &tr(label),
+            &create_settings_choice(settings, key, choices),
+        ));
+    }
+    preferences_section.insert_child_after(
+        &create_settings_row(&tr("Accent Color"), &create_accent_picker(settings)),
+        preferences_section
+            .first_child()
+            .and_then(|title| title.next_sibling())
+            .as_ref(),
+    );
+    preferences_section.append(&create_settings_row(
+        &tr("Text Size"),
+        &create_text_scale(settings),
+    ));
+    preferences_section.append(&create_settings_row(
+        &tr("Density"),
+        &create_settings_choice(settings, "density", &preferences::DENSITIES),
+    ));
+    preferences_section
+}
+
+fn create_notification_settings(settings: &gio::Settings) -> GtkBox {
+    let notif_section = create_settings_section(&tr("🔔 Notifications"));
+    for (label, key) in [
+        (tr("Push Notifications"), "push-notifications"),
+        (tr("Email Notifications"), "email-notifications"),
+        (tr("Sound"), "notification-sound"),
+        (tr("Do Not Disturb"), "do-not-disturb"),
+        (tr("Quiet Hours"), "quiet-hours"),
+    ] {
+        notif_section.append(&create_settings_switch(&label, settings, key));
+    }
+
+    // Quiet hours range, in whole hours
+    let hours_box = GtkBox::new(Orientation::Horizontal, 8);
+    let hour_spin = |key: &str| {
+        let spin = gtk4::SpinButton::with_range(0.0, 23.0, 1.0);
+        spin.set_wrap(true);
+        settings.bind(key, &spin, "value").build();
+        settings
+            .bind("quiet-hours", &spin, "sensitive")
+            .get_only()
+            .build();
+        spin
+    };
+    hours_box.append(&Label::new(Some(&tr("From"))));
+    hours_box.append(&hour_spin("quiet-hours-start"));
+    hours_box.append(&Label::new(Some(&tr("to"))));
+    hours_box.append(&hour_spin("quiet-hours-end"));
+    notif_section.append(&create_settings_row(&tr("Quiet Hours Range"), &hours_box));
+
+    let digest_choices: Vec<(&str, &str)> = preferences::DigestMode::ALL
+        .iter()
+        .map(|mode| (mode.key(), mode.label()))
+        .collect();
+    notif_section.append(&create_settings_row(
+        &tr("Activity Digest"),
+        &create_settings_choice(settings, "activity-digest", &digest_choices),
+    ));
+    notif_section
+}
+
+fn create_settings_section(title: &str) -> GtkBox {
     let section_box = GtkBox::new(Orientation::Vertical, 12);
     section_box.add_css_class("settings-section");
 
@@ -1249,31 +1801,144 @@ fn create_settings_section(title: &str, items: Vec<(&str, &str, bool)>) -> GtkBo
     title_label.set_halign(gtk4::Align::Start);
     section_box.append(&title_label);
 
-    for (label, value, has_edit) in items {
-        let item_box = GtkBox::new(Orientation::Horizontal, 12);
-        item_box.add_css_class("settings-item");
+    section_box
+}
 
-        let label_label = Label::new(Some(label));
-        label_label.set_halign(gtk4::Align::Start);
-        label_label.set_hexpand(true);
-        label_label.add_css_class("settings-label");
+/// A settings row with a switch bound to the boolean `key`.
+fn create_settings_switch(label: &str, settings: &gio::Settings, key: &str) -> GtkBox {
+    let switch = gtk4::Switch::new();
+    switch.set_valign(gtk4::Align::Center);
+    settings.bind(key, &switch, "active").build();
+    create_settings_row(label, &switch)
+}
 
-        let value_label = Label::new(Some(value));
-        value_label.add_css_class("settings-value");
+/// Swatches for the preset accents, plus a color button for a custom one.
+fn create_accent_picker(settings: &gio::Settings) -> GtkBox {
+    let picker = GtkBox::new(Orientation::Horizontal, 8);
+    picker.set_valign(gtk4::Align::Center);
+
+    let mut swatches = Vec::new();
+    for accent in &theme::ACCENTS {
+        let swatch = Button::new();
+        swatch.add_css_class("accent-swatch");
+        swatch.add_css_class(&format!("accent-swatch-{}", accent.key));
+        swatch.set_tooltip_text(Some(&tr(accent.label)));
+        let settings = settings.clone();
+        let key = accent.key;
+        swatch.connect_clicked(move |_| {
+            let _ = settings.set_string("accent-color", key);
+        });
+        picker.append(&swatch);
+        swatches.push((key, swatch));
+    }
 
-        item_box.append(&label_label);
-        item_box.append(&value_label);
+    let dialog = gtk4::ColorDialog::builder()
+        .title(tr("Accent Color"))
+        .with_alpha(false)
+        .build();
+    let custom = gtk4::ColorDialogButton::new(Some(dialog));
+    custom.set_tooltip_text(Some(&tr("Custom color")));
+    picker.append(&custom);
+
+    let update = {
+        let custom = custom.clone();
+        move |settings: &gio::Settings| {
+            let value = settings.string("accent-color");
+            for (key, swatch) in &swatches {
+                if *key == value.as_str() {
+                    swatch.add_css_class("accent-swatch-selected");
+                } else {
+                    swatch.remove_css_class("accent-swatch-selected");
+                }
+            }
+            custom.set_rgba(&theme::accent_rgba(&value));
+        }
+    };
+    update(settings);
+    let handler = RefCell::new(Some(
+        settings.connect_changed(Some("accent-color"), move |settings, _| update(settings)),
+    ));
+    let settings_clone = settings.clone();
+    picker.connect_destroy(move |_| {
+        if let Some(handler) = handler.take() {
+            settings_clone.disconnect(handler);
+        }
+    });
 
-        if has_edit {
-            let edit_btn = Button::with_label("Edit");
-            edit_btn.add_css_class("edit-button");
-            item_box.append(&edit_btn);
+    let settings = settings.clone();
+    custom.connect_rgba_notify(move |custom| {
+        // Ignore the button following a preset; only a picked color counts
+        let picked = theme::hex(&custom.rgba());
+        if picked != theme::hex(&theme::accent_rgba(&settings.string("accent-color"))) {
+            let _ = settings.set_string("accent-color", &picked);
         }
+    });
+    picker
+}
 
-        section_box.append(&item_box);
-    }
+/// A slider for the "text-scale" setting, in percent.
+fn create_text_scale(settings: &gio::Settings) -> gtk4::Scale {
+    let scale = gtk4::Scale::with_range(Orientation::Horizontal, 0.8, 1.6, 0.1);
+    sizing::set_size_request(&scale, 200, -1);
+    scale.set_valign(gtk4::Align::Center);
+    scale.set_draw_value(true);
+    scale.set_value_pos(gtk4::PositionType::Right);
+    scale.add_mark(1.0, gtk4::PositionType::Bottom, None);
+    scale.set_format_value_func(|_, value| {
+        tr_with(
+            "{percent}%",
+            &[("percent", &i18n::number((value * 100.0).round() as i64))],
+        )
+    });
+    // Snap to whole steps so dragging doesn't rescale at every pixel
+    scale.set_round_digits(1);
+    settings
+        .bind("text-scale", &scale.adjustment(), "value")
+        .build();
+    scale
+}
 
-    section_box
+/// A dropdown bound to the string `key`, listing `choices` as (value,
+/// label) pairs. Labels are translated.
+fn create_settings_choice(
+    settings: &gio::Settings,
+    key: &str,
+    choices: &[(&'static str, &str)],
+) -> gtk4::DropDown {
+    let labels: Vec<String> = choices.iter().map(|(_, label)| tr(label)).collect();
+    let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+    let values: Rc<Vec<&'static str>> = Rc::new(choices.iter().map(|(value, _)| *value).collect());
+    let dropdown = gtk4::DropDown::from_strings(&labels);
+    dropdown.set_valign(gtk4::Align::Center);
+    let values_clone = values.clone();
+    settings
+        .bind(key, &dropdown, "selected")
+        .mapping(move |variant, _| {
+            let value = variant.str()?;
+            let index = values_clone.iter().position(|choice| *choice == value)?;
+            Some((index as u32).to_value())
+        })
+        .set_mapping(move |value, _| {
+            let index = value.get::<u32>().ok()? as usize;
+            values.get(index).map(|choice| choice.to_variant())
+        })
+        .build();
+    dropdown
+}
+
+/// A settings item with its label on the left and `control` on the right.
+fn create_settings_row(label: &str, control: &impl IsA<gtk4::Widget>) -> GtkBox {
+    let item_box = GtkBox::new(Orientation::Horizontal, 12);
+    item_box.add_css_class("settings-item");
+
+    let label_label = Label::new(Some(label));
+    label_label.set_halign(gtk4::Align::Start);
+    label_label.set_hexpand(true);
+    label_label.add_css_class("settings-label");
+
+    item_box.append(&label_label);
+    item_box.append(control);
+    item_box
 }
 
 fn create_video_call_view() -> GtkBox {
@@ -1297,7 +1962,7 @@ fn create_video_call_view() -> GtkBox {
     let remote_name = Label::new(Some("Alice Johnson"));
     remote_name.add_css_class("video-name");
 
-    let call_status = Label::new(Some("Connecting..."));
+    let call_status = Label::new(Some(&tr("Connecting...")));
     call_status.add_css_class("call-status");
 
     remote_video.append(&remote_avatar);
@@ -1313,8 +1978,7 @@ fn create_video_call_view() -> GtkBox {
     local_preview.set_valign(gtk4::Align::Start);
     local_preview.set_margin_top(20);
     local_preview.set_margin_end(20);
-    local_preview.set_width_request(200);
-    local_preview.set_height_request(150);
+    sizing::set_size_request(&local_preview, 200, 150);
 
     let local_box = GtkBox::new(Orientation::Vertical, 8);
     local_box.set_valign(gtk4::Align::Center);
@@ -1323,7 +1987,7 @@ fn create_video_call_view() -> GtkBox {
     let local_avatar = Label::new(Some("📹"));
     local_avatar.add_css_class("local-avatar");
 
-    let local_label = Label::new(Some("You"));
+    let local_label = Label::new(Some(&tr("You")));
     local_label.add_css_class("local-label");
 
     local_box.append(&local_avatar);
@@ -1337,16 +2001,16 @@ fn create_video_call_view() -> GtkBox {
     controls_box.set_margin_bottom(20);
     controls_box.add_css_class("call-controls");
 
-    let mute_btn = Button::with_label("🎤 Mute");
+    let mute_btn = Button::with_label(&tr("🎤 Mute"));
     mute_btn.add_css_class("call-control-button");
 
-    let video_btn = Button::with_label("📹 Video");
+    let video_btn = Button::with_label(&tr("📹 Video"));
     video_btn.add_css_class("call-control-button");
 
-    let screen_btn = Button::with_label("🖥️ Share");
+    let screen_btn = Button::with_label(&tr("🖥️ Share"));
     screen_btn.add_css_class("call-control-button");
 
-    let end_btn = Button::with_label("📞 End Call");
+    let end_btn = Button::with_label(&tr("📞 End Call"));
     end_btn.add_css_class("end-call-button");
 
     let settings_btn = Button::with_label("⚙️");
@@ -1365,7 +2029,7 @@ fn create_video_call_view() -> GtkBox {
     main_box
 }
 
-fn create_create_post_view() -> ScrolledWindow {
+fn create_create_post_view(state: &Rc<AppState>) -> ScrolledWindow {
     let scroll = ScrolledWindow::new();
     let post_box = GtkBox::new(Orientation::Vertical, 24);
     post_box.set_margin_start(40);
@@ -1373,7 +2037,10 @@ fn create_create_post_view() -> ScrolledWindow {
     post_box.set_margin_top(30);
     post_box.set_margin_bottom(30);
 
-    let header = create_section_header("✨ Create Post", "Share your thoughts with the community");
+    let header = create_section_header(
+        &tr("✨ Create Post"),
+        &tr("Share your thoughts with the community"),
+    );
     post_box.append(&header);
 
     // Post content area
@@ -1388,20 +2055,30 @@ fn create_create_post_view() -> ScrolledWindow {
 
     // User info
     let user_box = GtkBox::new(Orientation::Horizontal, 12);
-    let user_avatar = Label::new(Some("👤"));
-    user_avatar.add_css_class("create-post-avatar");
-    let user_name = Label::new(Some("Your Name"));
-    user_name.add_css_class("create-post-user");
-    user_box.append(&user_avatar);
-    user_box.append(&user_name);
     content_box.append(&user_box);
+    let populate_user = {
+        let state = state.clone();
+        move || {
+            while let Some(child) = user_box.first_child() {
+                user_box.remove(&child);
+            }
+            let community = state.community.borrow();
+            let me = community.user(community::ME);
+            user_box.append(&profile_view::create_avatar(me, "create-post-avatar", 48));
+            let user_name = Label::new(Some(&community.user_name(community::ME)));
+            user_name.add_css_class("create-post-user");
+            user_box.append(&user_name);
+        }
+    };
+    populate_user();
+    state.profile_changed.connect(populate_user);
 
     // Text entry (simulated with label - GTK4 TextView would be better)
     let text_frame = Frame::new(None);
     text_frame.add_css_class("post-text-area");
-    text_frame.set_height_request(150);
+    sizing::set_size_request(&text_frame, -1, 150);
 
-    let text_label = Label::new(Some("What's on your mind?"));
+    let text_label = Label::new(Some(&tr("What's on your mind?")));
     text_label.set_halign(gtk4::Align::Start);
     text_label.set_valign(gtk4::Align::Start);
     text_label.add_css_class("post-placeholder");
@@ -1409,7 +2086,7 @@ fn create_create_post_view() -> ScrolledWindow {
     content_box.append(&text_frame);
 
     // Media attachments
-    let media_label = Label::new(Some("📎 Attachments"));
+    let media_label = Label::new(Some(&tr("📎 Attachments")));
     media_label.set_halign(gtk4::Align::Start);
     media_label.add_css_class("section-subtitle");
     content_box.append(&media_label);
@@ -1419,12 +2096,12 @@ fn create_create_post_view() -> ScrolledWindow {
     media_grid.set_column_spacing(12);
 
     let media_types = [
-        ("🖼️", "Photo"),
-        ("📹", "Video"),
-        ("📄", "Document"),
-        ("📍", "Location"),
-        ("📊", "Poll"),
-        ("😊", "Emoji"),
+        ("🖼️", tr_noop("Photo")),
+        ("📹", tr_noop("Video")),
+        ("📄", tr_noop("Document")),
+        ("📍", tr_noop("Location")),
+        ("📊", tr_noop("Poll")),
+        ("😊", tr_noop("Emoji")),
     ];
 
     for (i, (icon, label)) in media_types.iter().enumerate() {
@@ -1434,13 +2111,34 @@ fn create_create_post_view() -> ScrolledWindow {
         let icon_label = Label::new(Some(icon));
         icon_label.add_css_class("media-type-icon");
 
-        let text_label = Label::new(Some(label));
+        let text_label = Label::new(Some(&tr(label)));
         text_label.add_css_class("media-type-label");
 
         btn_box.append(&icon_label);
         btn_box.append(&text_label);
         media_btn.set_child(Some(&btn_box));
         media_btn.add_css_class("media-type-button");
+        if *label == "Poll" {
+            let state_clone = state.clone();
+            media_btn.connect_clicked(move |button| {
+                poll_view::open_poll_composer(button, &state_clone);
+            });
+        } else if *label == "Location" {
+            let state_clone = state.clone();
+            media_btn.connect_clicked(move |button| {
+                let state = state_clone.clone();
+                location_view::open_location_dialog(
+                    button,
+                    &state_clone,
+                    &tr("📍 Post a Location"),
+                    &tr("Post"),
+                    move |location, note| {
+                        state.feed.borrow_mut().publish(&note, None, Some(location));
+                        state.feed_changed.emit();
+                    },
+                );
+            });
+        }
 
         media_grid.attach(&media_btn, (i % 3) as i32, (i / 3) as i32, 1, 1);
     }
@@ -1453,19 +2151,19 @@ fn create_create_post_view() -> ScrolledWindow {
     let options_box = GtkBox::new(Orientation::Horizontal, 16);
     options_box.set_margin_top(12);
 
-    let visibility_label = Label::new(Some("👁️ Visibility:"));
+    let visibility_label = Label::new(Some(&tr("👁️ Visibility:")));
     visibility_label.add_css_class("option-label");
 
-    let visibility_btn = Button::with_label("Public 🌍");
+    let visibility_btn = Button::with_label(&tr("Public 🌍"));
     visibility_btn.add_css_class("option-button");
 
     let spacer = GtkBox::new(Orientation::Horizontal, 0);
     spacer.set_hexpand(true);
 
-    let draft_btn = Button::with_label("💾 Save Draft");
+    let draft_btn = Button::with_label(&tr("💾 Save Draft"));
     draft_btn.add_css_class("secondary-button");
 
-    let post_btn = Button::with_label("🚀 Post");
+    let post_btn = Button::with_label(&tr("🚀 Post"));
     post_btn.add_css_class("create-post-button");
 
     options_box.append(&visibility_label);
@@ -1489,10 +2187,11 @@ fn create_analytics_view() -> ScrolledWindow {
     box_container.set_margin_bottom(30);
     box_container.add_css_class("feed-container");
 
-    let header = create_section_header("📊 Analytics", "Track your engagement and growth");
+    let header =
+        create_section_header(&tr("📊 Analytics"), &tr("Track your engagement and growth"));
     box_container.append(&header);
 
-    let label = Label::new(Some("Analytics Dashboard Coming Soon..."));
+    let label = Label::new(Some(&tr("Analytics Dashboard Coming Soon...")));
     label.add_css_class("section-subtitle");
     box_container.append(&label);
 
@@ -1500,7 +2199,7 @@ fn create_analytics_view() -> ScrolledWindow {
     scroll
 }
 
-fn create_events_view() -> ScrolledWindow {
+fn create_events_view(state: &Rc<AppState>) -> ScrolledWindow {
     let scroll = ScrolledWindow::new();
     let box_container = GtkBox::new(Orientation::Vertical, 20);
     box_container.set_margin_start(40);
@@ -1509,18 +2208,16 @@ fn create_events_view() -> ScrolledWindow {
     box_container.set_margin_bottom(30);
     box_container.add_css_class("feed-container");
 
-    let header = create_section_header("🎮 Events", "Discover and join upcoming events");
+    let header = create_section_header(&tr("🎮 Events"), &tr("Discover and join upcoming events"));
     box_container.append(&header);
 
-    let label = Label::new(Some("Events Calendar Coming Soon..."));
-    label.add_css_class("section-subtitle");
-    box_container.append(&label);
+    box_container.append(&create_listing_list(state, ListingKind::Event));
 
     scroll.set_child(Some(&box_container));
     scroll
 }
 
-fn create_jobs_view() -> ScrolledWindow {
+fn create_jobs_view(state: &Rc<AppState>) -> ScrolledWindow {
     let scroll = ScrolledWindow::new();
     let box_container = GtkBox::new(Orientation::Vertical, 20);
     box_container.set_margin_start(40);
@@ -1529,17 +2226,89 @@ fn create_jobs_view() -> ScrolledWindow {
     box_container.set_margin_bottom(30);
     box_container.add_css_class("feed-container");
 
-    let header = create_section_header("💼 Jobs", "Find your next career opportunity");
+    let header = create_section_header(&tr("💼 Jobs"), &tr("Find your next career opportunity"));
     box_container.append(&header);
 
-    let label = Label::new(Some("Job Board Coming Soon..."));
-    label.add_css_class("section-subtitle");
-    box_container.append(&label);
+    box_container.append(&create_listing_list(state, ListingKind::Job));
 
     scroll.set_child(Some(&box_container));
     scroll
 }
 
+/// The listings of `kind`, newest first, kept up to date.
+fn create_listing_list(state: &Rc<AppState>, kind: ListingKind) -> GtkBox {
+    let list = GtkBox::new(Orientation::Vertical, 16);
+    let populate = {
+        let list = list.clone();
+        let state = state.clone();
+        move || {
+            while let Some(child) = list.first_child() {
+                list.remove(&child);
+            }
+            let listings = state.listings.borrow();
+            for listing in listings.of_kind(kind) {
+                list.append(&create_listing_card(listing));
+            }
+            if list.first_child().is_none() {
+                let empty = Label::new(Some(&match kind {
+                    ListingKind::Job => tr("No job openings right now"),
+                    ListingKind::Event => tr("No upcoming events"),
+                }));
+                empty.add_css_class("section-subtitle");
+                list.append(&empty);
+            }
+        }
+    };
+    populate();
+    state.listings_changed.connect(populate);
+    list
+}
+
+fn create_listing_card(listing: &Listing) -> Frame {
+    let frame = Frame::new(None);
+    frame.add_css_class("group-card");
+
+    let info_box = GtkBox::new(Orientation::Vertical, 6);
+    info_box.set_margin_start(20);
+    info_box.set_margin_end(20);
+    info_box.set_margin_top(16);
+    info_box.set_margin_bottom(16);
+
+    let title = Label::new(Some(&listing.title));
+    title.set_halign(gtk4::Align::Start);
+    title.add_css_class("group-name");
+
+    let details = match listing.starts_at {
+        Some(starts_at) => tr_with(
+            "{date} · {place} · by {host}",
+            &[
+                ("date", &util::format_date_time(starts_at)),
+                ("place", &listing.place),
+                ("host", &listing.host),
+            ],
+        ),
+        None => tr_with(
+            "{host} · {place}",
+            &[("host", &listing.host), ("place", &listing.place)],
+        ),
+    };
+    let details = Label::new(Some(&details));
+    details.set_halign(gtk4::Align::Start);
+    details.add_css_class("group-members");
+
+    let description = Label::new(Some(&listing.description));
+    description.set_halign(gtk4::Align::Start);
+    description.set_wrap(true);
+    description.set_xalign(0.0);
+    description.add_css_class("group-description");
+
+    info_box.append(&title);
+    info_box.append(&details);
+    info_box.append(&description);
+    frame.set_child(Some(&info_box));
+    frame
+}
+
 fn create_achievements_view() -> ScrolledWindow {
     let scroll = ScrolledWindow::new();
     let box_container = GtkBox::new(Orientation::Vertical, 20);
@@ -1549,10 +2318,10 @@ fn create_achievements_view() -> ScrolledWindow {
     box_container.set_margin_bottom(30);
     box_container.add_css_class("feed-container");
 
-    let header = create_section_header("🏆 Achievements", "Your badges and milestones");
+    let header = create_section_header(&tr("🏆 Achievements"), &tr("Your badges and milestones"));
     box_container.append(&header);
 
-    let label = Label::new(Some("Achievements System Coming Soon..."));
+    let label = Label::new(Some(&tr("Achievements System Coming Soon...")));
     label.add_css_class("section-subtitle");
     box_container.append(&label);
 
@@ -1569,10 +2338,10 @@ fn create_learning_view() -> ScrolledWindow {
     box_container.set_margin_bottom(30);
     box_container.add_css_class("feed-container");
 
-    let header = create_section_header("📚 Learning", "Expand your skills with courses");
+    let header = create_section_header(&tr("📚 Learning"), &tr("Expand your skills with courses"));
     box_container.append(&header);
 
-    let label = Label::new(Some("Learning Platform Coming Soon..."));
+    let label = Label::new(Some(&tr("Learning Platform Coming Soon...")));
     label.add_css_class("section-subtitle");
     box_container.append(&label);
 
@@ -1589,10 +2358,10 @@ fn create_discover_view() -> ScrolledWindow {
     box_container.set_margin_bottom(30);
     box_container.add_css_class("feed-container");
 
-    let header = create_section_header("🌐 Discover", "Explore new content and people");
+    let header = create_section_header(&tr("🌐 Discover"), &tr("Explore new content and people"));
     box_container.append(&header);
 
-    let label = Label::new(Some("Discovery Feed Coming Soon..."));
+    let label = Label::new(Some(&tr("Discovery Feed Coming Soon...")));
     label.add_css_class("section-subtitle");
     box_container.append(&label);
 
@@ -0,0 +1,1158 @@
+// Media library and upload pipeline
+use crate::i18n::{tr, tr_noop, tr_with};
+use crate::state::AppState;
+use crate::storage;
+use crate::util::{self, DAY, WEEK};
+use gtk4::prelude::*;
+use gtk4::{gdk_pixbuf, gio, glib};
+use gtk4::{Box as GtkBox, Button, Label, Orientation, ProgressBar};
+use std::collections::{BTreeSet, HashSet};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+const LIBRARY_FILE: &str = "media.ini";
+const SETTINGS_GROUP: &str = "library";
+const ITEM_PREFIX: &str = "item:";
+/// `shared_by` of the signed-in user's own uploads.
+const SHARED_BY_YOU: &str = tr_noop("You");
+
+/// Longest edge used when "Downscale large images" is enabled.
+pub const DEFAULT_MAX_DIMENSION: i32 = 2560;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MediaKind {
+    Image,
+    Video,
+    Document,
+    Audio,
+}
+
+impl MediaKind {
+    pub fn icon(self) -> &'static str {
+        match self {
+            MediaKind::Image => "🖼️",
+            MediaKind::Video => "📹",
+            MediaKind::Document => "📄",
+            MediaKind::Audio => "🎵",
+        }
+    }
+
+    pub fn id(self) -> &'static str {
+        match self {
+            MediaKind::Image => "image",
+            MediaKind::Video => "video",
+            MediaKind::Document => "document",
+            MediaKind::Audio => "audio",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Self {
+        match id {
+            "image" => MediaKind::Image,
+            "video" => MediaKind::Video,
+            "audio" => MediaKind::Audio,
+            _ => MediaKind::Document,
+        }
+    }
+
+    pub fn from_mime(mime: &str) -> Self {
+        match mime.split('/').next() {
+            Some("image") => MediaKind::Image,
+            Some("video") => MediaKind::Video,
+            Some("audio") => MediaKind::Audio,
+            _ => MediaKind::Document,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MediaItem {
+    pub id: String,
+    pub title: String,
+    pub kind: MediaKind,
+    pub mime: String,
+    /// SHA-256 of the original upload, used for de-duplication.
+    pub hash: Option<String>,
+    /// File name inside the library directory; sample items have none.
+    pub file_name: Option<String>,
+    pub size: u64,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub uploaded_at: i64,
+    pub shared_by: String,
+    pub album: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl MediaItem {
+    /// Who shared the item, as shown in the interface.
+    pub fn shared_by_name(&self) -> String {
+        if self.shared_by == SHARED_BY_YOU {
+            tr(SHARED_BY_YOU)
+        } else {
+            self.shared_by.clone()
+        }
+    }
+
+    pub fn path(&self) -> Option<PathBuf> {
+        self.file_name
+            .as_ref()
+            .map(|name| MediaLibrary::files_dir().join(name))
+    }
+}
+
+pub struct MediaLibrary {
+    items: Vec<MediaItem>,
+    albums: Vec<String>,
+    pub downscale_large_images: bool,
+}
+
+/// Which items the gallery shows.
+#[derive(Clone, Debug, Default)]
+pub struct MediaFilter {
+    /// Kinds to show; empty means all.
+    pub kinds: HashSet<MediaKind>,
+    pub album: AlbumFilter,
+    pub tag: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum AlbumFilter {
+    #[default]
+    Any,
+    Unsorted,
+    Named(String),
+}
+
+impl MediaFilter {
+    pub fn matches(&self, item: &MediaItem) -> bool {
+        let kind_ok = self.kinds.is_empty() || self.kinds.contains(&item.kind);
+        let album_ok = match &self.album {
+            AlbumFilter::Any => true,
+            AlbumFilter::Unsorted => item.album.is_none(),
+            AlbumFilter::Named(name) => item.album.as_deref() == Some(name),
+        };
+        let tag_ok = self
+            .tag
+            .as_ref()
+            .is_none_or(|tag| item.tags.iter().any(|t| t == tag));
+        kind_ok && album_ok && tag_ok
+    }
+}
+
+/// Normalizes a free-form tag: lower case, no leading '#', spaces become '-'.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag: String = tag
+        .trim()
+        .trim_start_matches('#')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase();
+    (!tag.is_empty()).then_some(tag)
+}
+
+impl MediaLibrary {
+    pub fn files_dir() -> PathBuf {
+        storage::account_path("media")
+    }
+
+    pub fn load() -> Self {
+        let Some(key_file) = storage::load_key_file(LIBRARY_FILE) else {
+            return Self::with_samples();
+        };
+
+        let mut items = Vec::new();
+        for (id, group) in storage::groups_with_prefix(&key_file, ITEM_PREFIX) {
+            let group = group.as_str();
+            let optional = |key: &str| key_file.string(group, key).ok().map(String::from);
+            let dimension = |key: &str| key_file.integer(group, key).ok();
+            items.push(MediaItem {
+                id: id.clone(),
+                title: storage::string_or(&key_file, group, "title", &id),
+                kind: MediaKind::from_id(&storage::string_or(&key_file, group, "kind", "")),
+                mime: storage::string_or(&key_file, group, "mime", "application/octet-stream"),
+                hash: optional("hash"),
+                file_name: optional("file"),
+                size: key_file.uint64(group, "size").unwrap_or_default(),
+                width: dimension("width"),
+                height: dimension("height"),
+                uploaded_at: key_file.int64(group, "uploaded_at").unwrap_or_default(),
+                shared_by: storage::string_or(&key_file, group, "shared_by", SHARED_BY_YOU),
+                album: optional("album"),
+                tags: storage::list(&key_file, group, "tags"),
+            });
+        }
+        items.sort_by_key(|item| std::cmp::Reverse(item.uploaded_at));
+
+        Self {
+            items,
+            albums: storage::list(&key_file, SETTINGS_GROUP, "albums"),
+            downscale_large_images: key_file
+                .boolean(SETTINGS_GROUP, "downscale_large_images")
+                .unwrap_or(true),
+        }
+    }
+
+    fn with_samples() -> Self {
+        let now = util::now();
+        let samples = [
+            (
+                MediaKind::Image,
+                "Project Screenshot",
+                2 * DAY,
+                SHARED_BY_YOU,
+                "projects",
+            ),
+            (
+                MediaKind::Video,
+                "Tutorial Video",
+                5 * DAY,
+                "Alice Johnson",
+                "rust",
+            ),
+            (
+                MediaKind::Document,
+                "Resume.pdf",
+                WEEK,
+                SHARED_BY_YOU,
+                "career",
+            ),
+            (
+                MediaKind::Audio,
+                "Podcast Episode",
+                2 * WEEK,
+                "Bob Smith",
+                "career",
+            ),
+            (
+                MediaKind::Image,
+                "Team Photo",
+                3 * WEEK,
+                "Carol Williams",
+                "team",
+            ),
+            (
+                MediaKind::Video,
+                "Demo Recording",
+                30 * DAY,
+                SHARED_BY_YOU,
+                "projects",
+            ),
+        ];
+
+        let items = samples
+            .into_iter()
+            .enumerate()
+            .map(|(i, (kind, title, age, shared_by, tag))| MediaItem {
+                id: format!("sample-{i}"),
+                title: title.to_string(),
+                kind,
+                mime: String::new(),
+                hash: None,
+                file_name: None,
+                size: 0,
+                width: None,
+                height: None,
+                uploaded_at: now - age,
+                shared_by: shared_by.to_string(),
+                album: None,
+                tags: vec![tag.to_string()],
+            })
+            .collect();
+
+        Self {
+            items,
+            albums: Vec::new(),
+            downscale_large_images: true,
+        }
+    }
+
+    pub fn save(&self) {
+        let key_file = glib::KeyFile::new();
+        key_file.set_boolean(
+            SETTINGS_GROUP,
+            "downscale_large_images",
+            self.downscale_large_images,
+        );
+        storage::set_list(&key_file, SETTINGS_GROUP, "albums", &self.albums);
+
+        for item in &self.items {
+            let group = format!("{ITEM_PREFIX}{}", item.id);
+            key_file.set_string(&group, "title", &item.title);
+            key_file.set_string(&group, "kind", item.kind.id());
+            key_file.set_string(&group, "mime", &item.mime);
+            if let Some(hash) = &item.hash {
+                key_file.set_string(&group, "hash", hash);
+            }
+            if let Some(file_name) = &item.file_name {
+                key_file.set_string(&group, "file", file_name);
+            }
+            key_file.set_uint64(&group, "size", item.size);
+            if let (Some(width), Some(height)) = (item.width, item.height) {
+                key_file.set_integer(&group, "width", width);
+                key_file.set_integer(&group, "height", height);
+            }
+            key_file.set_int64(&group, "uploaded_at", item.uploaded_at);
+            key_file.set_string(&group, "shared_by", &item.shared_by);
+            if let Some(album) = &item.album {
+                key_file.set_string(&group, "album", album);
+            }
+            storage::set_list(&key_file, &group, "tags", &item.tags);
+        }
+
+        storage::save_key_file(LIBRARY_FILE, &key_file);
+    }
+
+    /// Items ordered newest first.
+    pub fn items(&self) -> &[MediaItem] {
+        &self.items
+    }
+
+    pub fn find_by_hash(&self, hash: &str) -> Option<&MediaItem> {
+        self.items
+            .iter()
+            .find(|item| item.hash.as_deref() == Some(hash))
+    }
+
+    pub fn insert(&mut self, item: MediaItem) {
+        self.items.insert(0, item);
+        self.save();
+    }
+
+    pub fn albums(&self) -> &[String] {
+        &self.albums
+    }
+
+    /// Every tag in use, sorted alphabetically.
+    pub fn tags(&self) -> Vec<String> {
+        let tags: BTreeSet<&String> = self.items.iter().flat_map(|item| &item.tags).collect();
+        tags.into_iter().cloned().collect()
+    }
+
+    pub fn create_album(&mut self, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(tr("Album name cannot be empty"));
+        }
+        if self
+            .albums
+            .iter()
+            .any(|album| album.eq_ignore_ascii_case(name))
+        {
+            return Err(tr_with(
+                "An album named “{name}” already exists",
+                &[("name", name)],
+            ));
+        }
+        self.albums.push(name.to_string());
+        self.save();
+        Ok(())
+    }
+
+    /// Deletes an album; its items become unsorted.
+    pub fn delete_album(&mut self, name: &str) {
+        self.albums.retain(|album| album != name);
+        for item in &mut self.items {
+            if item.album.as_deref() == Some(name) {
+                item.album = None;
+            }
+        }
+        self.save();
+    }
+
+    pub fn move_to_album(&mut self, ids: &HashSet<String>, album: Option<&str>) {
+        for item in self.items.iter_mut().filter(|item| ids.contains(&item.id)) {
+            item.album = album.map(String::from);
+        }
+        self.save();
+    }
+
+    pub fn add_tag(&mut self, ids: &HashSet<String>, tag: &str) {
+        let Some(tag) = normalize_tag(tag) else {
+            return;
+        };
+        for item in self.items.iter_mut().filter(|item| ids.contains(&item.id)) {
+            if !item.tags.contains(&tag) {
+                item.tags.push(tag.clone());
+            }
+        }
+        self.save();
+    }
+
+    pub fn remove_tag(&mut self, ids: &HashSet<String>, tag: &str) {
+        let Some(tag) = normalize_tag(tag) else {
+            return;
+        };
+        for item in self.items.iter_mut().filter(|item| ids.contains(&item.id)) {
+            item.tags.retain(|t| *t != tag);
+        }
+        self.save();
+    }
+
+    /// Removes items and their stored files.
+    pub fn delete(&mut self, ids: &HashSet<String>) {
+        for item in self.items.iter().filter(|item| ids.contains(&item.id)) {
+            if let Some(path) = item.path() {
+                if let Err(err) = std::fs::remove_file(&path) {
+                    eprintln!("Could not delete {}: {err}", path.display());
+                }
+            }
+        }
+        self.items.retain(|item| !ids.contains(&item.id));
+        self.save();
+    }
+}
+
+// ============================================================================
+// UPLOAD PIPELINE
+// ============================================================================
+
+#[derive(Clone, Copy, Debug)]
+pub struct UploadOptions {
+    pub max_dimension: Option<i32>,
+}
+
+/// The result of preparing a file for the library.
+pub struct ProcessedUpload {
+    pub data: Vec<u8>,
+    pub mime: String,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub downscaled: bool,
+}
+
+pub fn content_hash(data: &[u8]) -> String {
+    glib::compute_checksum_for_data(glib::ChecksumType::Sha256, data)
+        .map(|hash| hash.to_string())
+        .unwrap_or_default()
+}
+
+/// Guesses the MIME type from the file contents, using the name only as a hint.
+pub fn sniff_mime(file_name: &str, data: &[u8]) -> String {
+    let head = &data[..data.len().min(4096)];
+    let (content_type, _uncertain) = gio::content_type_guess(Some(file_name), head);
+    gio::content_type_get_mime_type(&content_type)
+        .map(|mime| mime.to_string())
+        .unwrap_or_else(|| "application/octet-stream".to_string())
+}
+
+/// Removes location and other metadata without re-encoding: EXIF, XMP,
+/// IPTC and comments from JPEG, PNG and GIF images, and the metadata boxes
+/// of MP4 and QuickTime files. None when the format is another one or the
+/// file doesn't parse, so nothing can slip through unstripped.
+pub fn strip_metadata(mime: &str, data: &[u8]) -> Option<Vec<u8>> {
+    match mime {
+        "image/jpeg" => strip_jpeg_metadata(data),
+        "image/png" => strip_png_metadata(data),
+        "image/gif" => strip_gif_metadata(data),
+        _ if is_mp4(mime) => strip_mp4_metadata(data),
+        _ => None,
+    }
+}
+
+fn is_mp4(mime: &str) -> bool {
+    matches!(
+        mime,
+        "video/mp4" | "video/quicktime" | "video/x-m4v" | "audio/mp4" | "audio/x-m4a"
+    )
+}
+
+/// Keeps the segments needed to show the image: APP0 (JFIF), APP14
+/// (Adobe color) and ICC profiles in APP2. Other APPn segments, comments
+/// and anything after the end of the image go.
+fn strip_jpeg_metadata(data: &[u8]) -> Option<Vec<u8>> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(&data[..2]);
+    let mut pos = 2;
+
+    loop {
+        if *data.get(pos)? != 0xFF {
+            return None;
+        }
+        // Any number of 0xFF fill bytes may come before a marker
+        while *data.get(pos + 1)? == 0xFF {
+            pos += 1;
+        }
+        let marker = data[pos + 1];
+        match marker {
+            // End of image
+            0xD9 => {
+                out.extend_from_slice(&[0xFF, 0xD9]);
+                return Some(out);
+            }
+            // Markers without a length
+            0x01 | 0xD0..=0xD7 => {
+                out.extend_from_slice(&data[pos..pos + 2]);
+                pos += 2;
+                continue;
+            }
+            _ => {}
+        }
+
+        let length = u16::from_be_bytes([*data.get(pos + 2)?, *data.get(pos + 3)?]) as usize;
+        let end = pos + 2 + length;
+        if length < 2 || end > data.len() {
+            return None;
+        }
+        let payload = &data[pos + 4..end];
+        let keep = match marker {
+            0xE0 | 0xEE => true,
+            0xE2 => payload.starts_with(b"ICC_PROFILE\0"),
+            0xE1..=0xEF | 0xFE => false,
+            _ => true,
+        };
+        if keep {
+            out.extend_from_slice(&data[pos..end]);
+        }
+        pos = end;
+
+        // Start of scan: entropy-coded data runs until the next marker,
+        // where 0xFF is followed by something other than a stuffed zero or
+        // a restart marker
+        if marker == 0xDA {
+            let scan_end = (pos..data.len().saturating_sub(1))
+                .find(|&i| data[i] == 0xFF && !matches!(data[i + 1], 0x00 | 0xD0..=0xD7))?;
+            out.extend_from_slice(&data[pos..scan_end]);
+            pos = scan_end;
+        }
+    }
+}
+
+/// Keeps only chunks known to describe the image itself, so unknown ones
+/// can't carry metadata through. Anything after IEND goes.
+fn strip_png_metadata(data: &[u8]) -> Option<Vec<u8>> {
+    const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    const IMAGE_CHUNKS: [&[u8; 4]; 19] = [
+        b"IHDR", b"PLTE", b"IDAT", b"IEND", b"tRNS", b"cHRM", b"gAMA", b"iCCP", b"sBIT", b"sRGB",
+        b"cICP", b"mDCv", b"cLLi", b"bKGD", b"pHYs", b"hIST", b"acTL", b"fcTL", b"fdAT",
+    ];
+    if !data.starts_with(&SIGNATURE) {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(&SIGNATURE);
+    let mut pos = SIGNATURE.len();
+
+    while pos + 12 <= data.len() {
+        let length =
+            u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as usize;
+        let chunk_type = &data[pos + 4..pos + 8];
+        let end = pos + 12 + length;
+        if end > data.len() {
+            return None;
+        }
+
+        if IMAGE_CHUNKS.iter().any(|known| &known[..] == chunk_type) {
+            out.extend_from_slice(&data[pos..end]);
+        }
+        pos = end;
+
+        if chunk_type == b"IEND" {
+            return Some(out);
+        }
+    }
+
+    None
+}
+
+/// Drops comment extensions and application extensions other than the
+/// animation loop count, which is where XMP lives in GIFs.
+fn strip_gif_metadata(data: &[u8]) -> Option<Vec<u8>> {
+    if !data.starts_with(b"GIF87a") && !data.starts_with(b"GIF89a") {
+        return None;
+    }
+    // Header, logical screen descriptor and global color table
+    let flags = *data.get(10)?;
+    let mut pos = 13 + color_table_len(flags);
+    let mut out = data.get(..pos)?.to_vec();
+
+    // A run of data sub-blocks ends with an empty one; returns the end
+    let sub_blocks_end = |mut pos: usize| -> Option<usize> {
+        loop {
+            let len = usize::from(*data.get(pos)?);
+            pos += 1 + len;
+            if len == 0 {
+                return (pos <= data.len()).then_some(pos);
+            }
+        }
+    };
+
+    loop {
+        match *data.get(pos)? {
+            // Trailer
+            0x3B => {
+                out.push(0x3B);
+                return Some(out);
+            }
+            // Image: descriptor, local color table, LZW code size, data
+            0x2C => {
+                let flags = *data.get(pos + 9)?;
+                let end = sub_blocks_end(pos + 10 + color_table_len(flags) + 1)?;
+                out.extend_from_slice(&data[pos..end]);
+                pos = end;
+            }
+            0x21 => {
+                let label = *data.get(pos + 1)?;
+                let end = sub_blocks_end(pos + 2)?;
+                let keep = match label {
+                    0xFE => false,
+                    0xFF => matches!(
+                        data.get(pos + 3..pos + 14),
+                        Some(b"NETSCAPE2.0") | Some(b"ANIMEXTS1.0")
+                    ),
+                    _ => true,
+                };
+                if keep {
+                    out.extend_from_slice(&data[pos..end]);
+                }
+                pos = end;
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// Bytes in a GIF color table, from the descriptor's packed flags.
+fn color_table_len(flags: u8) -> usize {
+    if flags & 0x80 == 0 {
+        0
+    } else {
+        3 << ((flags & 0x07) + 1)
+    }
+}
+
+/// Turns `udta`, `meta` and `uuid` boxes, which hold the location, XMP and
+/// other tags, into `free` boxes of the same size. Nothing moves, so the
+/// offsets the file keeps into its media data stay right.
+fn strip_mp4_metadata(data: &[u8]) -> Option<Vec<u8>> {
+    fn walk(data: &mut [u8], top_level: bool) -> Option<()> {
+        let mut pos = 0;
+        while pos < data.len() {
+            let header = data.get(pos..pos + 8)?;
+            let size32 = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+            let box_type: [u8; 4] = [header[4], header[5], header[6], header[7]];
+            let (size, header_len) = match size32 {
+                0 if top_level => (data.len() - pos, 8),
+                1 => {
+                    let large = data.get(pos + 8..pos + 16)?;
+                    let size = u64::from_be_bytes(large.try_into().ok()?);
+                    (usize::try_from(size).ok()?, 16)
+                }
+                size => (size as usize, 8),
+            };
+            let end = pos.checked_add(size)?;
+            if size < header_len || end > data.len() {
+                return None;
+            }
+            match &box_type {
+                b"udta" | b"meta" | b"uuid" => {
+                    data[pos + 4..pos + 8].copy_from_slice(b"free");
+                    data[pos + header_len..end].fill(0);
+                }
+                b"moov" | b"trak" | b"mdia" | b"minf" | b"moof" | b"traf" => {
+                    walk(&mut data[pos + header_len..end], false)?;
+                }
+                _ => {}
+            }
+            pos = end;
+        }
+        Some(())
+    }
+
+    if data.get(4..8)? != b"ftyp" {
+        return None;
+    }
+    let mut out = data.to_vec();
+    walk(&mut out, true)?;
+    Some(out)
+}
+
+/// Strips metadata and, for images, applies the EXIF orientation and the
+/// optional size limit. Images that can't be stripped as they are get
+/// re-encoded; other files that might carry a location but can't be
+/// stripped are refused. Runs on a worker thread.
+pub fn process_upload(
+    file_name: &str,
+    data: Vec<u8>,
+    options: UploadOptions,
+) -> Result<ProcessedUpload, String> {
+    let mime = sniff_mime(file_name, &data);
+    let unchanged = |data: Vec<u8>, mime: String| ProcessedUpload {
+        data,
+        mime,
+        width: None,
+        height: None,
+        downscaled: false,
+    };
+    match MediaKind::from_mime(&mime) {
+        MediaKind::Image => {}
+        MediaKind::Video => {
+            return strip_metadata(&mime, &data)
+                .map(|data| unchanged(data, mime))
+                .ok_or_else(|| {
+                    tr("Location data can't be removed from this kind of video. Convert it to MP4 first.")
+                });
+        }
+        // Of the rest, only MP4 audio carries the tags cameras write
+        MediaKind::Audio | MediaKind::Document => {
+            return match strip_metadata(&mime, &data) {
+                Some(stripped) => Ok(unchanged(stripped, mime)),
+                None if is_mp4(&mime) => Err(tr("This file couldn't be read.")),
+                None => Ok(unchanged(data, mime)),
+            };
+        }
+    }
+
+    let stream = gio::MemoryInputStream::from_bytes(&glib::Bytes::from(&data));
+    let Ok(pixbuf) = gdk_pixbuf::Pixbuf::from_stream(&stream, gio::Cancellable::NONE) else {
+        return strip_metadata(&mime, &data)
+            .map(|data| unchanged(data, mime))
+            .ok_or_else(|| tr("This image couldn't be read."));
+    };
+
+    let rotated = pixbuf
+        .option("orientation")
+        .is_some_and(|orientation| orientation != "1");
+    let pixbuf = pixbuf.apply_embedded_orientation().unwrap_or(pixbuf);
+    let (width, height) = (pixbuf.width(), pixbuf.height());
+    let too_large = options
+        .max_dimension
+        .is_some_and(|max| width.max(height) > max);
+
+    if !rotated && !too_large {
+        if let Some(stripped) = strip_metadata(&mime, &data) {
+            return Ok(ProcessedUpload {
+                data: stripped,
+                mime,
+                width: Some(width),
+                height: Some(height),
+                downscaled: false,
+            });
+        }
+    }
+
+    // Re-encoding drops all metadata, so no separate stripping is needed.
+    let (target_width, target_height) = match options.max_dimension {
+        Some(max) if too_large => {
+            let scale = max as f64 / width.max(height) as f64;
+            (
+                ((width as f64 * scale).round() as i32).max(1),
+                ((height as f64 * scale).round() as i32).max(1),
+            )
+        }
+        _ => (width, height),
+    };
+    let scaled = pixbuf
+        .scale_simple(
+            target_width,
+            target_height,
+            gdk_pixbuf::InterpType::Bilinear,
+        )
+        .unwrap_or(pixbuf);
+
+    let (format, out_mime, encode_options): (&str, &str, &[(&str, &str)]) = if mime == "image/jpeg"
+    {
+        ("jpeg", "image/jpeg", &[("quality", "90")])
+    } else {
+        ("png", "image/png", &[])
+    };
+
+    let encoded = scaled
+        .save_to_bufferv(format, encode_options)
+        .map_err(|_| tr("This image couldn't be converted."))?;
+    Ok(ProcessedUpload {
+        data: encoded,
+        mime: out_mime.to_string(),
+        width: Some(scaled.width()),
+        height: Some(scaled.height()),
+        downscaled: too_large,
+    })
+}
+
+fn extension_for(file_name: &str, mime: &str) -> String {
+    match mime {
+        "image/jpeg" => "jpg".to_string(),
+        "image/png" => "png".to_string(),
+        _ => std::path::Path::new(file_name)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase())
+            .unwrap_or_else(|| "bin".to_string()),
+    }
+}
+
+// ============================================================================
+// UPLOAD QUEUE UI
+// ============================================================================
+
+/// One row in the upload progress panel.
+struct UploadRow {
+    progress: ProgressBar,
+    status: Label,
+}
+
+impl UploadRow {
+    fn new(panel: &GtkBox, file_name: &str) -> Self {
+        let row = GtkBox::new(Orientation::Horizontal, 12);
+        row.add_css_class("upload-row");
+
+        let name_label = Label::new(Some(file_name));
+        name_label.set_halign(gtk4::Align::Start);
+        name_label.set_width_chars(24);
+        name_label.set_max_width_chars(24);
+        name_label.set_ellipsize(gtk4::pango::EllipsizeMode::Middle);
+        name_label.add_css_class("upload-name");
+
+        let progress = ProgressBar::new();
+        progress.set_hexpand(true);
+        progress.set_valign(gtk4::Align::Center);
+        progress.add_css_class("upload-progress");
+
+        let status = Label::new(Some(&tr("Queued")));
+        status.set_width_chars(16);
+        status.set_xalign(1.0);
+        status.add_css_class("upload-status");
+
+        row.append(&name_label);
+        row.append(&progress);
+        row.append(&status);
+        panel.append(&row);
+
+        Self { progress, status }
+    }
+
+    fn update(&self, fraction: f64, status: &str) {
+        self.progress.set_fraction(fraction);
+        self.status.set_text(status);
+    }
+
+    fn finish(&self, status: &str, css_class: &str) {
+        self.update(1.0, status);
+        self.status.add_css_class(css_class);
+    }
+}
+
+/// The "Uploads" panel shown above the gallery while files are queued.
+pub fn create_upload_panel() -> GtkBox {
+    let panel = GtkBox::new(Orientation::Vertical, 8);
+    panel.add_css_class("upload-panel");
+    panel.set_visible(false);
+
+    let header = GtkBox::new(Orientation::Horizontal, 12);
+    let title = Label::new(Some(&tr("Uploads")));
+    title.set_halign(gtk4::Align::Start);
+    title.set_hexpand(true);
+    title.add_css_class("upload-panel-title");
+
+    let clear_btn = Button::with_label(&tr("Clear"));
+    clear_btn.add_css_class("secondary-button");
+
+    let panel_clone = panel.clone();
+    let header_clone = header.clone();
+    clear_btn.connect_clicked(move |_| {
+        while let Some(child) = header_clone.next_sibling() {
+            panel_clone.remove(&child);
+        }
+        panel_clone.set_visible(false);
+    });
+
+    header.append(&title);
+    header.append(&clear_btn);
+    panel.append(&header);
+    panel
+}
+
+/// Queues `files` for upload, showing progress in `panel`. Files are handled
+/// one at a time; the library is updated and listeners notified after each.
+pub fn start_uploads(state: &Rc<AppState>, panel: &GtkBox, files: Vec<gio::File>) {
+    if files.is_empty() {
+        return;
+    }
+    panel.set_visible(true);
+
+    let jobs: Vec<(gio::File, UploadRow)> = files
+        .into_iter()
+        .map(|file| {
+            let name = file
+                .basename()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| file.uri().to_string());
+            let row = UploadRow::new(panel, &name);
+            (file, row)
+        })
+        .collect();
+
+    let state = state.clone();
+    glib::spawn_future_local(async move {
+        for (file, row) in jobs {
+            upload_file(&state, &file, &row).await;
+        }
+    });
+}
+
+async fn upload_file(state: &Rc<AppState>, file: &gio::File, row: &UploadRow) {
+    let file_name = file
+        .basename()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "upload".to_string());
+
+    row.update(0.1, &tr("Reading…"));
+    let data = match file.load_contents_future().await {
+        Ok((data, _etag)) => data.to_vec(),
+        Err(err) => {
+            row.finish(
+                &tr_with("Failed: {error}", &[("error", err.message())]),
+                "upload-failed",
+            );
+            return;
+        }
+    };
+
+    row.update(0.35, &tr("Hashing…"));
+    let (hash, data) = match gio::spawn_blocking(move || (content_hash(&data), data)).await {
+        Ok(result) => result,
+        Err(_) => {
+            row.finish(&tr("Failed"), "upload-failed");
+            return;
+        }
+    };
+
+    if let Some(existing) = state.media.borrow().find_by_hash(&hash) {
+        row.finish(
+            &tr_with("Duplicate of {title}", &[("title", &existing.title)]),
+            "upload-duplicate",
+        );
+        return;
+    }
+
+    row.update(0.6, &tr("Processing…"));
+    let options = UploadOptions {
+        max_dimension: state
+            .media
+            .borrow()
+            .downscale_large_images
+            .then_some(DEFAULT_MAX_DIMENSION),
+    };
+    let name = file_name.clone();
+    let processed = match gio::spawn_blocking(move || process_upload(&name, data, options)).await {
+        Ok(Ok(processed)) => processed,
+        Ok(Err(message)) => {
+            row.finish(&message, "upload-failed");
+            return;
+        }
+        Err(_) => {
+            row.finish(&tr("Failed"), "upload-failed");
+            return;
+        }
+    };
+
+    row.update(0.85, &tr("Saving…"));
+    let stored_name = format!(
+        "{}.{}",
+        &hash[..16],
+        extension_for(&file_name, &processed.mime)
+    );
+    let dir = MediaLibrary::files_dir();
+    let written = std::fs::create_dir_all(&dir)
+        .and_then(|_| std::fs::write(dir.join(&stored_name), &processed.data));
+    if let Err(err) = written {
+        row.finish(
+            &tr_with("Failed: {error}", &[("error", &err.to_string())]),
+            "upload-failed",
+        );
+        return;
+    }
+
+    let item = MediaItem {
+        id: hash[..16].to_string(),
+        title: file_name,
+        kind: MediaKind::from_mime(&processed.mime),
+        mime: processed.mime,
+        hash: Some(hash),
+        file_name: Some(stored_name),
+        size: processed.data.len() as u64,
+        width: processed.width,
+        height: processed.height,
+        uploaded_at: util::now(),
+        shared_by: SHARED_BY_YOU.to_string(),
+        album: None,
+        tags: Vec::new(),
+    };
+    state.media.borrow_mut().insert(item);
+    state.media_changed.emit();
+
+    let status = if processed.downscaled {
+        tr("Done (resized)")
+    } else {
+        tr("Done")
+    };
+    row.finish(&status, "upload-done");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jpeg_segment(marker: u8, payload: &[u8]) -> Vec<u8> {
+        let mut segment = vec![0xFF, marker];
+        segment.extend_from_slice(&(payload.len() as u16 + 2).to_be_bytes());
+        segment.extend_from_slice(payload);
+        segment
+    }
+
+    fn png_chunk(chunk_type: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut chunk = (payload.len() as u32).to_be_bytes().to_vec();
+        chunk.extend_from_slice(chunk_type);
+        chunk.extend_from_slice(payload);
+        // The CRC isn't checked
+        chunk.extend_from_slice(&[0; 4]);
+        chunk
+    }
+
+    fn mp4_box(box_type: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut mp4_box = (payload.len() as u32 + 8).to_be_bytes().to_vec();
+        mp4_box.extend_from_slice(box_type);
+        mp4_box.extend_from_slice(payload);
+        mp4_box
+    }
+
+    #[test]
+    fn jpeg_metadata_is_removed() {
+        let jfif = jpeg_segment(0xE0, b"JFIF\0\x01\x02");
+        let icc = jpeg_segment(0xE2, b"ICC_PROFILE\0\x01\x01");
+        let quant = jpeg_segment(0xDB, &[0; 5]);
+        let scan_header = jpeg_segment(0xDA, &[1, 2, 3]);
+        // Stuffed 0xFF 0x00 and a restart marker belong to the scan
+        let scan = [0x12, 0xFF, 0x00, 0x34, 0xFF, 0xD0, 0x56];
+
+        let mut data = vec![0xFF, 0xD8];
+        data.extend(&jfif);
+        data.extend(jpeg_segment(0xE1, b"Exif\0\0GPS 52.52N 13.40E"));
+        data.extend(&icc);
+        data.extend(jpeg_segment(0xE2, b"MPF\0"));
+        data.extend(jpeg_segment(0xFE, b"comment"));
+        // Fill bytes before a marker
+        data.extend([0xFF, 0xFF]);
+        data.extend(&quant);
+        data.extend(&scan_header);
+        data.extend(scan);
+        data.extend(jpeg_segment(0xE1, b"http://ns.adobe.com/xap/1.0/"));
+        data.extend([0xFF, 0xD9]);
+        data.extend(b"trailing data");
+
+        let mut expected = vec![0xFF, 0xD8];
+        expected.extend(&jfif);
+        expected.extend(&icc);
+        expected.extend(&quant);
+        expected.extend(&scan_header);
+        expected.extend(scan);
+        expected.extend([0xFF, 0xD9]);
+
+        assert_eq!(strip_jpeg_metadata(&data), Some(expected));
+    }
+
+    #[test]
+    fn broken_jpegs_are_not_passed_through() {
+        let mut data = vec![0xFF, 0xD8];
+        data.extend(jpeg_segment(0xE1, b"Exif\0\0GPS"));
+        // Cut off in the middle of a segment
+        assert_eq!(strip_jpeg_metadata(&data[..data.len() - 2]), None);
+        // No end of image
+        assert_eq!(strip_jpeg_metadata(&data), None);
+        // Not a JPEG at all
+        assert_eq!(strip_jpeg_metadata(b"GIF89a"), None);
+        assert_eq!(strip_metadata("image/webp", &data), None);
+    }
+
+    #[test]
+    fn png_keeps_only_image_chunks() {
+        let header = png_chunk(b"IHDR", &[0; 13]);
+        let srgb = png_chunk(b"sRGB", &[0]);
+        let image = png_chunk(b"IDAT", &[1, 2, 3]);
+        let end = png_chunk(b"IEND", &[]);
+
+        let mut data = b"\x89PNG\r\n\x1a\n".to_vec();
+        data.extend(&header);
+        data.extend(png_chunk(b"eXIf", b"MM\0*GPS"));
+        data.extend(png_chunk(b"tEXt", b"Comment\0hello"));
+        data.extend(&srgb);
+        data.extend(png_chunk(b"caBX", b"unknown"));
+        data.extend(&image);
+        data.extend(&end);
+        data.extend(b"trailing data");
+
+        let mut expected = b"\x89PNG\r\n\x1a\n".to_vec();
+        expected.extend(&header);
+        expected.extend(&srgb);
+        expected.extend(&image);
+        expected.extend(&end);
+
+        assert_eq!(strip_png_metadata(&data), Some(expected));
+    }
+
+    #[test]
+    fn broken_pngs_are_not_passed_through() {
+        let mut data = b"\x89PNG\r\n\x1a\n".to_vec();
+        data.extend(png_chunk(b"IHDR", &[0; 13]));
+        data.extend(png_chunk(b"eXIf", b"MM\0*GPS"));
+        // No IEND
+        assert_eq!(strip_png_metadata(&data), None);
+        // A chunk longer than the file
+        assert_eq!(strip_png_metadata(&data[..data.len() - 3]), None);
+        assert_eq!(strip_png_metadata(b"\x89PNG"), None);
+    }
+
+    #[test]
+    fn gif_comments_and_xmp_are_removed() {
+        // Header and screen descriptor with a two-color global table
+        let mut head = b"GIF89a\x01\x00\x01\x00\x80\x00\x00".to_vec();
+        head.extend([0, 0, 0, 255, 255, 255]);
+        let looping = b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00".to_vec();
+        let image = b"\x2C\x00\x00\x00\x00\x01\x00\x01\x00\x00\x02\x02\x44\x01\x00".to_vec();
+
+        let mut data = head.clone();
+        data.extend(b"\x21\xFE\x05hello\x00");
+        data.extend(b"\x21\xFF\x0BXMP DataXMP\x03GPS\x00");
+        data.extend(&looping);
+        data.extend(&image);
+        data.push(0x3B);
+
+        let mut expected = head;
+        expected.extend(&looping);
+        expected.extend(&image);
+        expected.push(0x3B);
+
+        assert_eq!(strip_gif_metadata(&data), Some(expected));
+        assert_eq!(strip_gif_metadata(&data[..data.len() - 1]), None);
+    }
+
+    #[test]
+    fn mp4_metadata_boxes_are_blanked_in_place() {
+        let udta = mp4_box(b"udta", &mp4_box(b"\xA9xyz", b"+52.52+013.40/"));
+        let trak = mp4_box(b"trak", &mp4_box(b"meta", b"tags"));
+        let mut moov_payload = mp4_box(b"mvhd", &[7; 4]);
+        moov_payload.extend(&udta);
+        moov_payload.extend(&trak);
+
+        let mut data = mp4_box(b"ftyp", b"isom");
+        data.extend(mp4_box(b"moov", &moov_payload));
+        data.extend(mp4_box(b"mdat", &[9; 6]));
+
+        let stripped = strip_mp4_metadata(&data).unwrap();
+        assert_eq!(stripped.len(), data.len());
+        let text = String::from_utf8_lossy(&stripped);
+        assert!(!text.contains("udta") && !text.contains("meta") && !text.contains("52.52"));
+        assert!(text.contains("mvhd") && text.contains("mdat"));
+        assert!(stripped.ends_with(&[9; 6]));
+
+        // A box running past the end of the file
+        assert_eq!(strip_mp4_metadata(&data[..data.len() - 1]), None);
+        assert_eq!(strip_metadata("video/webm", &data), None);
+    }
+}
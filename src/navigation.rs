@@ -0,0 +1,209 @@
+// Page navigation with back history, and window actions to reach pages
+// from anywhere in the widget tree
+use crate::state::AppState;
+use gtk4::prelude::*;
+use gtk4::{gio, glib, Stack};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+/// Pages pushed at runtime are named "<prefix><id>" and dropped once they
+/// leave the history.
+const PUSHED_PREFIX: &str = "pushed:";
+
+pub struct Navigator {
+    stack: Stack,
+    history: RefCell<Vec<String>>,
+}
+
+impl Navigator {
+    pub fn new(stack: &Stack) -> Rc<Self> {
+        Rc::new(Self {
+            stack: stack.clone(),
+            history: RefCell::new(Vec::new()),
+        })
+    }
+
+    /// Shows a top-level page, e.g. from the sidebar; forgets the history.
+    pub fn show(&self, name: &str) {
+        self.history.borrow_mut().clear();
+        self.stack.set_visible_child_name(name);
+        self.drop_unused_pages();
+    }
+
+    /// Shows an existing page, remembering the current one for "Back".
+    pub fn go_to(&self, name: &str) {
+        if self.stack.visible_child_name().as_deref() == Some(name) {
+            return;
+        }
+        self.remember_current();
+        self.stack.set_visible_child_name(name);
+    }
+
+    /// Adds `page` under `name` (replacing an older copy) and shows it.
+    pub fn push(&self, name: &str, page: &impl IsA<gtk4::Widget>) {
+        let name = format!("{PUSHED_PREFIX}{name}");
+        if self.stack.visible_child_name().as_deref() != Some(name.as_str()) {
+            self.remember_current();
+        }
+        if let Some(old) = self.stack.child_by_name(&name) {
+            self.stack.remove(&old);
+        }
+        self.stack.add_named(page, Some(&name));
+        self.stack.set_visible_child_name(&name);
+    }
+
+    pub fn back(&self) {
+        let Some(previous) = self.history.borrow_mut().pop() else {
+            return;
+        };
+        self.stack.set_visible_child_name(&previous);
+        self.drop_unused_pages();
+    }
+
+    fn remember_current(&self) {
+        if let Some(current) = self.stack.visible_child_name() {
+            self.history.borrow_mut().push(current.to_string());
+        }
+    }
+
+    fn drop_unused_pages(&self) {
+        let visible = self.stack.visible_child_name();
+        let history = self.history.borrow();
+        let mut child = self.stack.first_child();
+        while let Some(page) = child {
+            child = page.next_sibling();
+            let name = self.stack.page(&page).name();
+            let Some(name) = name.filter(|name| name.starts_with(PUSHED_PREFIX)) else {
+                continue;
+            };
+            if visible.as_deref() != Some(name.as_str()) && !history.contains(&name.to_string()) {
+                self.stack.remove(&page);
+            }
+        }
+    }
+}
+
+/// Registers the navigation actions on the main window:
+/// `win.show-page(page name)`, `win.show-profile(user id)`,
+/// `win.open-conversation(conversation id)`,
+/// `win.reply-conversation(conversation id)`, `win.show-hashtag(tag)` and
+/// `win.navigate-back`.
+pub fn install_actions(
+    window: &gtk4::ApplicationWindow,
+    show_page: impl Fn(&str) + 'static,
+    show_profile: impl Fn(&str) + 'static,
+    open_conversation: impl Fn(&str) + 'static,
+    reply_conversation: impl Fn(&str) + 'static,
+    show_hashtag: impl Fn(&str) + 'static,
+    back: impl Fn() + 'static,
+) {
+    let action = gio::SimpleAction::new("show-page", Some(glib::VariantTy::STRING));
+    action.connect_activate(move |_, parameter| {
+        if let Some(name) = parameter.and_then(|parameter| parameter.str()) {
+            show_page(name);
+        }
+    });
+    window.add_action(&action);
+
+    let action = gio::SimpleAction::new("show-profile", Some(glib::VariantTy::STRING));
+    action.connect_activate(move |_, parameter| {
+        if let Some(id) = parameter.and_then(|parameter| parameter.str()) {
+            show_profile(id);
+        }
+    });
+    window.add_action(&action);
+
+    let action = gio::SimpleAction::new("open-conversation", Some(glib::VariantTy::STRING));
+    action.connect_activate(move |_, parameter| {
+        if let Some(id) = parameter.and_then(|parameter| parameter.str()) {
+            open_conversation(id);
+        }
+    });
+    window.add_action(&action);
+
+    let action = gio::SimpleAction::new("reply-conversation", Some(glib::VariantTy::STRING));
+    action.connect_activate(move |_, parameter| {
+        if let Some(id) = parameter.and_then(|parameter| parameter.str()) {
+            reply_conversation(id);
+        }
+    });
+    window.add_action(&action);
+
+    let action = gio::SimpleAction::new("show-hashtag", Some(glib::VariantTy::STRING));
+    action.connect_activate(move |_, parameter| {
+        if let Some(tag) = parameter.and_then(|parameter| parameter.str()) {
+            show_hashtag(tag);
+        }
+    });
+    window.add_action(&action);
+
+    let action = gio::SimpleAction::new("navigate-back", None);
+    action.connect_activate(move |_, _| back());
+    window.add_action(&action);
+}
+
+/// Activates a `win.` action from `widget`. Widgets inside dialogs reach
+/// the main window through their transient parent, and the dialog closes.
+pub fn activate(widget: &impl IsA<gtk4::Widget>, action: &str, parameter: Option<&glib::Variant>) {
+    let name = format!("win.{action}");
+    if widget.activate_action(&name, parameter).is_ok() {
+        return;
+    }
+    let Some(dialog) = widget.root().and_downcast::<gtk4::Window>() else {
+        return;
+    };
+    if let Some(parent) = dialog.transient_for() {
+        if parent.activate_action(&name, parameter).is_ok() {
+            dialog.close();
+        }
+    }
+}
+
+/// Makes `widget` open `user_id`'s profile when clicked.
+pub fn link_to_profile(widget: &impl IsA<gtk4::Widget>, user_id: &str) {
+    let click = gtk4::GestureClick::new();
+    // Claim the press so rows and buttons underneath don't react as well
+    click.connect_pressed(|gesture, _, _, _| {
+        gesture.set_state(gtk4::EventSequenceState::Claimed);
+    });
+    let user_id = user_id.to_string();
+    click.connect_released(move |gesture, _, _, _| {
+        if let Some(widget) = gesture.widget() {
+            activate(&widget, "show-profile", Some(&user_id.to_variant()));
+        }
+    });
+    widget.add_controller(click);
+    widget.set_cursor_from_name(Some("pointer"));
+    widget.add_css_class("profile-link-target");
+}
+
+/// Fills `page` each time it is shown, and refreshes it on community, feed
+/// and share changes while it stays on screen.
+pub fn refresh_while_mapped(
+    page: &impl IsA<gtk4::Widget>,
+    state: &Rc<AppState>,
+    populate: Rc<dyn Fn()>,
+) {
+    let listeners = Rc::new(Cell::new(None));
+
+    let state_clone = state.clone();
+    let listeners_clone = listeners.clone();
+    page.connect_map(move |_| {
+        populate();
+        let (p1, p2, p3) = (populate.clone(), populate.clone(), populate.clone());
+        listeners_clone.set(Some((
+            state_clone.community_changed.connect(move || p1()),
+            state_clone.feed_changed.connect(move || p2()),
+            state_clone.shares_changed.connect(move || p3()),
+        )));
+    });
+
+    let state = state.clone();
+    page.connect_unmap(move |_| {
+        if let Some((community, feed, shares)) = listeners.take() {
+            state.community_changed.disconnect(community);
+            state.feed_changed.disconnect(feed);
+            state.shares_changed.disconnect(shares);
+        }
+    });
+}
@@ -0,0 +1,674 @@
+// Notifications generated from likes, comments, mentions, follows, group
+// joins and shared links, with read and dismissed state
+use crate::community::{Community, ME};
+use crate::feed::Feed;
+use crate::i18n::{tr, tr_noop, tr_with, trn};
+use crate::links;
+use crate::state::AppState;
+use crate::storage;
+use crate::util;
+use gtk4::glib;
+use std::rc::Rc;
+
+const NOTIFICATIONS_FILE: &str = "notifications.ini";
+const NOTIFICATION_PREFIX: &str = "notification:";
+const DIGEST_GROUP: &str = "digest";
+/// Longest quote of a comment or message shown in a notification.
+const QUOTE_LENGTH: usize = 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotificationKind {
+    Like,
+    Comment,
+    Mention,
+    Follow,
+    GroupJoin,
+    Share,
+}
+
+impl NotificationKind {
+    const ALL: [NotificationKind; 6] = [
+        NotificationKind::Like,
+        NotificationKind::Comment,
+        NotificationKind::Mention,
+        NotificationKind::Follow,
+        NotificationKind::GroupJoin,
+        NotificationKind::Share,
+    ];
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            NotificationKind::Like => "👍",
+            NotificationKind::Comment => "💬",
+            NotificationKind::Mention => "📣",
+            NotificationKind::Follow => "👤",
+            NotificationKind::GroupJoin => "👥",
+            NotificationKind::Share => "🔗",
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            NotificationKind::Like => "like",
+            NotificationKind::Comment => "comment",
+            NotificationKind::Mention => "mention",
+            NotificationKind::Follow => "follow",
+            NotificationKind::GroupJoin => "group-join",
+            NotificationKind::Share => "share",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.key() == key)
+    }
+
+    /// Likes, follows and joins about the same thing collapse into one item;
+    /// comments and mentions each say something different.
+    fn collapses(self) -> bool {
+        matches!(
+            self,
+            NotificationKind::Like | NotificationKind::Follow | NotificationKind::GroupJoin
+        )
+    }
+
+    /// "3 likes", "1 new follower"…
+    fn count_text(self, count: usize) -> String {
+        let count = count as u64;
+        match self {
+            NotificationKind::Like => trn("{n} like", "{n} likes", count),
+            NotificationKind::Comment => trn("{n} comment", "{n} comments", count),
+            NotificationKind::Mention => trn("{n} mention", "{n} mentions", count),
+            NotificationKind::Follow => trn("{n} new follower", "{n} new followers", count),
+            NotificationKind::GroupJoin => trn("{n} group join", "{n} group joins", count),
+            NotificationKind::Share => trn("{n} shared link", "{n} shared links", count),
+        }
+    }
+}
+
+/// The filter buttons above the notification list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotificationFilter {
+    All,
+    Mentions,
+    Likes,
+    Comments,
+    Follows,
+}
+
+impl NotificationFilter {
+    pub const ALL: [NotificationFilter; 5] = [
+        NotificationFilter::All,
+        NotificationFilter::Mentions,
+        NotificationFilter::Likes,
+        NotificationFilter::Comments,
+        NotificationFilter::Follows,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            NotificationFilter::All => tr_noop("All"),
+            NotificationFilter::Mentions => tr_noop("Mentions"),
+            NotificationFilter::Likes => tr_noop("Likes"),
+            NotificationFilter::Comments => tr_noop("Comments"),
+            NotificationFilter::Follows => tr_noop("Follows"),
+        }
+    }
+
+    pub fn matches(self, kind: NotificationKind) -> bool {
+        match self {
+            NotificationFilter::All => true,
+            NotificationFilter::Mentions => kind == NotificationKind::Mention,
+            NotificationFilter::Likes => kind == NotificationKind::Like,
+            NotificationFilter::Comments => kind == NotificationKind::Comment,
+            NotificationFilter::Follows => kind == NotificationKind::Follow,
+        }
+    }
+}
+
+/// What a notification leads to when opened.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NotificationTarget {
+    Post(String),
+    Profile(String),
+    Group(String),
+    Conversation(String),
+}
+
+impl NotificationTarget {
+    fn key(&self) -> String {
+        match self {
+            NotificationTarget::Post(id) => format!("post:{id}"),
+            NotificationTarget::Profile(id) => format!("user:{id}"),
+            NotificationTarget::Group(id) => format!("group:{id}"),
+            NotificationTarget::Conversation(id) => format!("conversation:{id}"),
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        let (kind, id) = key.split_once(':')?;
+        let id = id.to_string();
+        match kind {
+            "post" => Some(NotificationTarget::Post(id)),
+            "user" => Some(NotificationTarget::Profile(id)),
+            "group" => Some(NotificationTarget::Group(id)),
+            "conversation" => Some(NotificationTarget::Conversation(id)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Notification {
+    /// Names the event, e.g. "like:<post>:<user>", so it is only recorded
+    /// once.
+    pub key: String,
+    pub kind: NotificationKind,
+    /// User id of whoever caused it.
+    pub actor: String,
+    /// What happened, after the actor's name: "liked your post …".
+    pub text: String,
+    pub target: NotificationTarget,
+    pub created_at: i64,
+    pub read: bool,
+    /// Dismissed notifications are kept so they don't come back.
+    pub dismissed: bool,
+}
+
+/// Notifications shown as one item, e.g. every like on the same post.
+#[derive(Clone, Debug)]
+pub struct NotificationGroup {
+    pub kind: NotificationKind,
+    /// The newest notification's text and target.
+    pub text: String,
+    pub target: NotificationTarget,
+    /// Newest first, each person once.
+    pub actors: Vec<String>,
+    pub keys: Vec<String>,
+    pub latest: i64,
+    pub unread: bool,
+}
+
+/// Collapses `notifications` (newest first) into groups, keeping the order.
+pub fn group(notifications: &[Notification]) -> Vec<NotificationGroup> {
+    let mut groups: Vec<NotificationGroup> = Vec::new();
+    for notification in notifications {
+        // All new followers share one group
+        let same = |group: &NotificationGroup| {
+            notification.kind.collapses()
+                && group.kind == notification.kind
+                && (notification.kind == NotificationKind::Follow
+                    || group.target == notification.target)
+        };
+        if let Some(group) = groups.iter_mut().find(|group| same(group)) {
+            if !group.actors.contains(&notification.actor) {
+                group.actors.push(notification.actor.clone());
+            }
+            group.keys.push(notification.key.clone());
+            group.unread |= !notification.read;
+            continue;
+        }
+        groups.push(NotificationGroup {
+            kind: notification.kind,
+            text: notification.text.clone(),
+            target: notification.target.clone(),
+            actors: vec![notification.actor.clone()],
+            keys: vec![notification.key.clone()],
+            latest: notification.created_at,
+            unread: !notification.read,
+        });
+    }
+    groups
+}
+
+/// One line for a digest: "5 likes · 2 comments · 1 new follower".
+pub fn summarize(notifications: &[Notification]) -> String {
+    NotificationKind::ALL
+        .into_iter()
+        .filter_map(|kind| {
+            let count = notifications
+                .iter()
+                .filter(|notification| notification.kind == kind)
+                .count();
+            (count > 0).then(|| kind.count_text(count))
+        })
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+pub struct NotificationCenter {
+    notifications: Vec<Notification>,
+    /// When the last desktop digest went out; 0 before the first one.
+    last_digest_at: i64,
+}
+
+impl NotificationCenter {
+    pub fn load() -> Self {
+        let key_file = storage::load_key_file(NOTIFICATIONS_FILE);
+        let notifications = key_file
+            .as_ref()
+            .map(|key_file| {
+                storage::groups_with_prefix(key_file, NOTIFICATION_PREFIX)
+                    .into_iter()
+                    .filter_map(|(key, group)| {
+                        Some(Notification {
+                            kind: NotificationKind::from_key(
+                                &key_file.string(&group, "kind").ok()?,
+                            )?,
+                            actor: storage::string_or(key_file, &group, "actor", ""),
+                            text: storage::string_or(key_file, &group, "text", ""),
+                            target: NotificationTarget::from_key(
+                                &key_file.string(&group, "target").ok()?,
+                            )?,
+                            created_at: key_file.int64(&group, "created_at").unwrap_or_default(),
+                            read: key_file.boolean(&group, "read").unwrap_or_default(),
+                            dismissed: key_file.boolean(&group, "dismissed").unwrap_or_default(),
+                            key,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let last_digest_at = key_file
+            .and_then(|key_file| key_file.int64(DIGEST_GROUP, "last_sent").ok())
+            .unwrap_or_default();
+        let mut center = Self {
+            notifications,
+            last_digest_at,
+        };
+        center.sort();
+        center
+    }
+
+    fn save(&self) {
+        let key_file = glib::KeyFile::new();
+        for notification in &self.notifications {
+            let group = format!("{NOTIFICATION_PREFIX}{}", notification.key);
+            key_file.set_string(&group, "kind", notification.kind.key());
+            key_file.set_string(&group, "actor", &notification.actor);
+            key_file.set_string(&group, "text", &notification.text);
+            key_file.set_string(&group, "target", &notification.target.key());
+            key_file.set_int64(&group, "created_at", notification.created_at);
+            key_file.set_boolean(&group, "read", notification.read);
+            key_file.set_boolean(&group, "dismissed", notification.dismissed);
+        }
+        key_file.set_int64(DIGEST_GROUP, "last_sent", self.last_digest_at);
+        storage::save_key_file(NOTIFICATIONS_FILE, &key_file);
+    }
+
+    fn sort(&mut self) {
+        self.notifications
+            .sort_by_key(|notification| std::cmp::Reverse(notification.created_at));
+    }
+
+    /// Notifications to show for `filter`, newest first. Dismissed ones and
+    /// those from blocked or muted people are left out.
+    pub fn visible(&self, community: &Community, filter: NotificationFilter) -> Vec<Notification> {
+        self.notifications
+            .iter()
+            .filter(|notification| {
+                !notification.dismissed
+                    && filter.matches(notification.kind)
+                    && !community.hidden_from_feed(&notification.actor)
+            })
+            .cloned()
+            .collect()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Notification> {
+        self.notifications
+            .iter()
+            .find(|notification| notification.key == key)
+    }
+
+    pub fn unread_count(&self, community: &Community) -> usize {
+        self.visible(community, NotificationFilter::All)
+            .iter()
+            .filter(|notification| !notification.read)
+            .count()
+    }
+
+    pub fn mark_read(&mut self, keys: &[String]) {
+        for notification in &mut self.notifications {
+            if keys.contains(&notification.key) {
+                notification.read = true;
+            }
+        }
+        self.save();
+    }
+
+    pub fn mark_all_read(&mut self) {
+        for notification in &mut self.notifications {
+            notification.read = true;
+        }
+        self.save();
+    }
+
+    pub fn dismiss(&mut self, keys: &[String]) {
+        for notification in &mut self.notifications {
+            if keys.contains(&notification.key) {
+                notification.dismissed = true;
+                notification.read = true;
+            }
+        }
+        self.save();
+    }
+
+    pub fn last_digest_at(&self) -> i64 {
+        self.last_digest_at
+    }
+
+    pub fn set_last_digest_at(&mut self, timestamp: i64) {
+        self.last_digest_at = timestamp;
+        self.save();
+    }
+
+    /// Records events in the feed and community that aren't known yet and
+    /// returns how many were new. Known ones get their text refreshed, so it
+    /// follows the interface language.
+    pub fn sync(&mut self, feed: &Feed, community: &Community) -> usize {
+        let events = collect_events(feed, community);
+        let mut added = 0;
+        for event in events {
+            if let Some(known) = self
+                .notifications
+                .iter_mut()
+                .find(|notification| notification.key == event.key)
+            {
+                known.text = event.text;
+                continue;
+            }
+            self.notifications.push(event);
+            added += 1;
+        }
+        if added > 0 {
+            self.sort();
+            self.save();
+        }
+        added
+    }
+}
+
+/// Every event the signed-in user would be notified about, as unread
+/// notifications.
+fn collect_events(feed: &Feed, community: &Community) -> Vec<Notification> {
+    let now = util::now();
+    let mut events = Vec::new();
+    let mut event = |key: String,
+                     kind: NotificationKind,
+                     actor: &str,
+                     text: String,
+                     target: NotificationTarget,
+                     created_at: i64| {
+        if actor != ME {
+            events.push(Notification {
+                key,
+                kind,
+                actor: actor.to_string(),
+                text,
+                target,
+                created_at,
+                read: false,
+                dismissed: false,
+            });
+        }
+    };
+    let handle = community
+        .user(ME)
+        .map(|me| format!("@{}", me.handle.to_lowercase()));
+    let mentions_me = |text: &str| {
+        handle
+            .as_ref()
+            .is_some_and(|handle| mentions(&text.to_lowercase(), handle))
+    };
+
+    for post in feed.posts() {
+        let target = || NotificationTarget::Post(post.id.clone());
+        if post.author == ME {
+            for like in &post.likes {
+                event(
+                    format!("like:{}:{}", post.id, like.user),
+                    NotificationKind::Like,
+                    &like.user,
+                    tr_with("liked your post “{title}”", &[("title", &post.title())]),
+                    target(),
+                    like.liked_at,
+                );
+            }
+        } else if mentions_me(&post.text) {
+            event(
+                format!("mention:{}", post.id),
+                NotificationKind::Mention,
+                &post.author,
+                tr_with("mentioned you in “{title}”", &[("title", &post.title())]),
+                target(),
+                post.created_at,
+            );
+        }
+        for (index, comment) in post.comments.iter().enumerate() {
+            let quote = quote(&comment.text);
+            let (kind, text) = if mentions_me(&comment.text) {
+                (
+                    NotificationKind::Mention,
+                    tr_with(
+                        "mentioned you in a comment: “{quote}”",
+                        &[("quote", &quote)],
+                    ),
+                )
+            } else if post.author == ME {
+                (
+                    NotificationKind::Comment,
+                    tr_with("commented: “{quote}”", &[("quote", &quote)]),
+                )
+            } else {
+                continue;
+            };
+            event(
+                format!("comment:{}:{index}", post.id),
+                kind,
+                &comment.author,
+                text,
+                target(),
+                comment.created_at,
+            );
+        }
+    }
+
+    // Follows and members carry no timestamp, so they date from when they
+    // were first seen
+    for follower in community.followers(ME) {
+        event(
+            format!("follow:{}", follower.id),
+            NotificationKind::Follow,
+            &follower.id,
+            tr("started following you"),
+            NotificationTarget::Profile(follower.id.clone()),
+            now,
+        );
+    }
+    for group in community.groups().iter().filter(|group| group.joined) {
+        for member in &group.members {
+            event(
+                format!("join:{}:{member}", group.id),
+                NotificationKind::GroupJoin,
+                member,
+                tr_with(
+                    "joined {group}",
+                    &[("group", &format!("{} {}", group.emoji, group.name))],
+                ),
+                NotificationTarget::Group(group.id.clone()),
+                now,
+            );
+        }
+    }
+
+    let link_prefix = format!("{}://", links::SCHEME);
+    for conversation in community.conversations() {
+        for (index, message) in conversation.messages.iter().enumerate() {
+            if !message.text.contains(&link_prefix) {
+                continue;
+            }
+            let text = match &conversation.title {
+                Some(title) => tr_with(
+                    "shared a link in {conversation}",
+                    &[("conversation", title)],
+                ),
+                None => tr("shared a link with you"),
+            };
+            event(
+                format!("share:{}:{index}", conversation.id),
+                NotificationKind::Share,
+                &message.sender,
+                text,
+                NotificationTarget::Conversation(conversation.id.clone()),
+                message.sent_at,
+            );
+        }
+    }
+
+    events
+}
+
+/// Whether `text` contains `handle` as a whole word; both lowercase. An
+/// address such as bob@alice.com doesn't mention @alice.
+fn mentions(text: &str, handle: &str) -> bool {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(handle).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + handle.len()..].chars().next();
+        !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+    })
+}
+
+fn quote(text: &str) -> String {
+    match text.char_indices().nth(QUOTE_LENGTH) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Keeps the notification list in step with the feed and community: syncs
+/// now, after every change to either and when another account signs in.
+pub fn watch(state: &Rc<AppState>) {
+    let sync = {
+        let state = state.clone();
+        move || {
+            let added = state
+                .notifications
+                .borrow_mut()
+                .sync(&state.feed.borrow(), &state.community.borrow());
+            if added > 0 {
+                state.notifications_changed.emit();
+            }
+        }
+    };
+    sync();
+    let sync = Rc::new(sync);
+    let s = sync.clone();
+    state.feed_changed.connect(move || s());
+    let s = sync.clone();
+    state.community_changed.connect(move || s());
+    state.auth_changed.connect(move || sync());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(
+        kind: NotificationKind,
+        actor: &str,
+        target: NotificationTarget,
+        read: bool,
+    ) -> Notification {
+        Notification {
+            key: format!("{}:{actor}:{}", kind.key(), target.key()),
+            kind,
+            actor: actor.to_string(),
+            text: format!("{} from {actor}", kind.key()),
+            target,
+            created_at: 0,
+            read,
+            dismissed: false,
+        }
+    }
+
+    fn post(id: &str) -> NotificationTarget {
+        NotificationTarget::Post(id.to_string())
+    }
+
+    fn profile(id: &str) -> NotificationTarget {
+        NotificationTarget::Profile(id.to_string())
+    }
+
+    #[test]
+    fn mentions_are_whole_words() {
+        for text in [
+            "@alice",
+            "hi @alice!",
+            "thanks, @alice.",
+            "(@alice)",
+            "cc:@alice",
+        ] {
+            assert!(mentions(text, "@alice"), "{text}");
+        }
+        for text in [
+            "bob@alice.com",
+            "x@alice",
+            "@alicecooper",
+            "@alice_b",
+            "_@alice",
+            "@alic",
+        ] {
+            assert!(!mentions(text, "@alice"), "{text}");
+        }
+        // A later match still counts after an address
+        assert!(mentions("bob@alice.com, ask @alice", "@alice"));
+    }
+
+    #[test]
+    fn likes_on_one_post_collapse() {
+        let groups = group(&[
+            notification(NotificationKind::Like, "bob", post("1"), false),
+            notification(NotificationKind::Like, "carol", post("1"), true),
+            notification(NotificationKind::Like, "bob", post("1"), true),
+            notification(NotificationKind::Like, "dave", post("2"), true),
+        ]);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].actors, ["bob", "carol"]);
+        assert_eq!(groups[0].keys.len(), 3);
+        assert!(groups[0].unread);
+        assert_eq!(groups[1].actors, ["dave"]);
+        assert!(!groups[1].unread);
+    }
+
+    #[test]
+    fn followers_share_a_group_and_comments_stay_apart() {
+        let groups = group(&[
+            notification(NotificationKind::Follow, "bob", profile("bob"), true),
+            notification(NotificationKind::Comment, "bob", post("1"), true),
+            notification(NotificationKind::Follow, "carol", profile("carol"), true),
+            notification(NotificationKind::Comment, "carol", post("1"), true),
+        ]);
+        let kinds: Vec<_> = groups.iter().map(|group| group.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                NotificationKind::Follow,
+                NotificationKind::Comment,
+                NotificationKind::Comment
+            ]
+        );
+        assert_eq!(groups[0].actors, ["bob", "carol"]);
+    }
+
+    #[test]
+    fn summaries_count_each_kind() {
+        let summary = summarize(&[
+            notification(NotificationKind::Follow, "bob", profile("bob"), false),
+            notification(NotificationKind::Like, "bob", post("1"), false),
+            notification(NotificationKind::Like, "carol", post("2"), false),
+        ]);
+        assert_eq!(summary, "2 likes · 1 new follower");
+        assert_eq!(summarize(&[]), "");
+    }
+}
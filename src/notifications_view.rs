@@ -0,0 +1,267 @@
+// Notification list with filters, read state and dismissing, and the
+// sidebar's unread badge
+use crate::i18n::{tr, tr_with, trn};
+use crate::navigation;
+use crate::notifications::{self, NotificationFilter, NotificationGroup, NotificationTarget};
+use crate::state::AppState;
+use crate::util;
+use gtk4::prelude::*;
+use gtk4::{Box as GtkBox, Button, Frame, Label, Orientation};
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Horizontal swipe speed, in pixels per second, that dismisses an item.
+const DISMISS_SWIPE_VELOCITY: f64 = 800.0;
+
+/// The unread count shown on the sidebar's notifications button; hidden
+/// when everything has been read.
+pub fn create_unread_badge(state: &Rc<AppState>) -> Label {
+    let badge = Label::new(None);
+    badge.add_css_class("nav-badge");
+    badge.set_valign(gtk4::Align::Center);
+
+    let update = {
+        let state = state.clone();
+        let badge = badge.clone();
+        move || {
+            let count = state
+                .notifications
+                .borrow()
+                .unread_count(&state.community.borrow());
+            badge.set_text(&if count > 99 {
+                "99+".to_string()
+            } else {
+                count.to_string()
+            });
+            badge.set_visible(count > 0);
+        }
+    };
+    update();
+    let update = Rc::new(update);
+    let u = update.clone();
+    state.notifications_changed.connect(move || u());
+    state.community_changed.connect(move || update());
+
+    badge
+}
+
+/// Filter buttons, "Mark all as read" and the notifications themselves.
+pub fn create_notification_list(state: &Rc<AppState>) -> GtkBox {
+    let list_box = GtkBox::new(Orientation::Vertical, 20);
+
+    let toolbar = GtkBox::new(Orientation::Horizontal, 12);
+    let filter_box = GtkBox::new(Orientation::Horizontal, 12);
+    filter_box.set_hexpand(true);
+    let mark_all_btn = Button::with_label(&tr("✓ Mark all as read"));
+    mark_all_btn.add_css_class("secondary-button");
+    toolbar.append(&filter_box);
+    toolbar.append(&mark_all_btn);
+    list_box.append(&toolbar);
+
+    let items_box = GtkBox::new(Orientation::Vertical, 12);
+    list_box.append(&items_box);
+
+    let filter = Rc::new(Cell::new(NotificationFilter::All));
+    let populate: Rc<dyn Fn()> = {
+        let state = state.clone();
+        let filter = filter.clone();
+        let mark_all_btn = mark_all_btn.clone();
+        Rc::new(move || {
+            while let Some(child) = items_box.first_child() {
+                items_box.remove(&child);
+            }
+            let (notifications, unread) = {
+                let center = state.notifications.borrow();
+                let community = state.community.borrow();
+                (
+                    center.visible(&community, filter.get()),
+                    center.unread_count(&community),
+                )
+            };
+            mark_all_btn.set_sensitive(unread > 0);
+
+            let now = util::now();
+            for group in notifications::group(&notifications) {
+                items_box.append(&create_notification_item(&state, &group, now));
+            }
+            if notifications.is_empty() {
+                let empty = Label::new(Some(&match filter.get() {
+                    NotificationFilter::All => tr("You're all caught up"),
+                    _ => tr("Nothing here yet"),
+                }));
+                empty.add_css_class("share-empty");
+                items_box.append(&empty);
+            }
+        })
+    };
+
+    let mut buttons: Vec<Button> = Vec::new();
+    for option in NotificationFilter::ALL {
+        let button = Button::with_label(&tr(option.label()));
+        button.add_css_class("filter-button");
+        if option == filter.get() {
+            button.add_css_class("filter-active");
+        }
+        filter_box.append(&button);
+        buttons.push(button);
+    }
+    for (button, option) in buttons.iter().zip(NotificationFilter::ALL) {
+        let buttons = buttons.clone();
+        let filter = filter.clone();
+        let populate = populate.clone();
+        button.connect_clicked(move |clicked| {
+            for button in &buttons {
+                button.remove_css_class("filter-active");
+            }
+            clicked.add_css_class("filter-active");
+            filter.set(option);
+            populate();
+        });
+    }
+
+    let state_clone = state.clone();
+    mark_all_btn.connect_clicked(move |_| {
+        state_clone.notifications.borrow_mut().mark_all_read();
+        state_clone.notifications_changed.emit();
+    });
+
+    populate();
+    let p = populate.clone();
+    state.notifications_changed.connect(move || p());
+    state.community_changed.connect(move || populate());
+
+    list_box
+}
+
+fn create_notification_item(state: &Rc<AppState>, group: &NotificationGroup, now: i64) -> Frame {
+    let frame = Frame::new(None);
+    frame.add_css_class("notification-item");
+    if group.unread {
+        frame.add_css_class("unread");
+    }
+
+    let item_box = GtkBox::new(Orientation::Horizontal, 16);
+    item_box.set_margin_start(16);
+    item_box.set_margin_end(16);
+    item_box.set_margin_top(12);
+    item_box.set_margin_bottom(12);
+
+    let icon_label = Label::new(Some(group.kind.icon()));
+    icon_label.add_css_class("notification-icon");
+
+    let content_box = GtkBox::new(Orientation::Vertical, 4);
+    content_box.set_hexpand(true);
+
+    let user_label = Label::new(Some(&actor_names(state, &group.actors)));
+    user_label.set_halign(gtk4::Align::Start);
+    user_label.add_css_class("notification-user");
+    navigation::link_to_profile(&user_label, &group.actors[0]);
+
+    let action_label = Label::new(Some(&group.text));
+    action_label.set_halign(gtk4::Align::Start);
+    action_label.set_wrap(true);
+    action_label.set_xalign(0.0);
+    action_label.add_css_class("notification-action");
+
+    content_box.append(&user_label);
+    content_box.append(&action_label);
+
+    let time_label = Label::new(Some(&util::format_relative_time(group.latest, now)));
+    time_label.add_css_class("notification-time");
+
+    item_box.append(&icon_label);
+    item_box.append(&content_box);
+    item_box.append(&time_label);
+
+    if group.unread {
+        let read_btn = Button::with_label("✓");
+        read_btn.set_tooltip_text(Some(&tr("Mark as read")));
+        read_btn.add_css_class("notification-button");
+        read_btn.set_valign(gtk4::Align::Center);
+        let state = state.clone();
+        let keys = group.keys.clone();
+        read_btn.connect_clicked(move |_| {
+            state.notifications.borrow_mut().mark_read(&keys);
+            state.notifications_changed.emit();
+        });
+        item_box.append(&read_btn);
+    }
+
+    let dismiss_btn = Button::with_label("✕");
+    dismiss_btn.set_tooltip_text(Some(&tr("Dismiss")));
+    dismiss_btn.add_css_class("notification-button");
+    dismiss_btn.set_valign(gtk4::Align::Center);
+    let dismiss = {
+        let state = state.clone();
+        let keys = group.keys.clone();
+        move || {
+            state.notifications.borrow_mut().dismiss(&keys);
+            state.notifications_changed.emit();
+        }
+    };
+    let dismiss = Rc::new(dismiss);
+    let d = dismiss.clone();
+    dismiss_btn.connect_clicked(move |_| d());
+    item_box.append(&dismiss_btn);
+
+    // Swiping the item sideways dismisses it too
+    let swipe = gtk4::GestureSwipe::new();
+    swipe.connect_swipe(move |_, velocity_x, velocity_y| {
+        if velocity_x.abs() > DISMISS_SWIPE_VELOCITY && velocity_x.abs() > velocity_y.abs() {
+            dismiss();
+        }
+    });
+    frame.add_controller(swipe);
+
+    // Clicking the item opens what it is about and marks it read
+    let click = gtk4::GestureClick::new();
+    let state = state.clone();
+    let keys = group.keys.clone();
+    let target = group.target.clone();
+    click.connect_released(move |gesture, _, _, _| {
+        if let Some(widget) = gesture.widget() {
+            open_target(&widget, &target);
+        }
+        state.notifications.borrow_mut().mark_read(&keys);
+        state.notifications_changed.emit();
+    });
+    frame.add_controller(click);
+    frame.set_cursor_from_name(Some("pointer"));
+
+    frame.set_child(Some(&item_box));
+    frame
+}
+
+/// "Alice Johnson", "Alice Johnson and Bob Smith" or "Alice Johnson and 12
+/// others".
+fn actor_names(state: &AppState, actors: &[String]) -> String {
+    let community = state.community.borrow();
+    let first = community.user_name(&actors[0]);
+    match actors.len() {
+        1 => first,
+        2 => tr_with(
+            "{first} and {second}",
+            &[
+                ("first", &first),
+                ("second", &community.user_name(&actors[1])),
+            ],
+        ),
+        count => trn(
+            "{first} and {n} other",
+            "{first} and {n} others",
+            count as u64 - 1,
+        )
+        .replace("{first}", &first),
+    }
+}
+
+/// Shows the page, profile or conversation a notification is about.
+pub fn open_target(widget: &impl IsA<gtk4::Widget>, target: &NotificationTarget) {
+    let (action, parameter) = match target {
+        NotificationTarget::Post(_) => ("show-page", "home".to_variant()),
+        NotificationTarget::Group(_) => ("show-page", "groups".to_variant()),
+        NotificationTarget::Profile(id) => ("show-profile", id.to_variant()),
+        NotificationTarget::Conversation(id) => ("open-conversation", id.to_variant()),
+    };
+    navigation::activate(widget, action, Some(&parameter));
+}
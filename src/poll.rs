@@ -0,0 +1,344 @@
+// Polls attached to feed posts: options, votes, results and CSV export
+use crate::community::Community;
+use crate::i18n::{tr, tr_with, trn};
+use crate::util;
+
+pub const MIN_OPTIONS: usize = 2;
+pub const MAX_OPTIONS: usize = 10;
+
+#[derive(Clone, Debug)]
+pub struct Vote {
+    pub user: String,
+    /// Indices into `Poll::options`.
+    pub choices: Vec<usize>,
+    pub voted_at: i64,
+}
+
+/// A poll. Voters are recorded even for anonymous polls so nobody votes
+/// twice; anonymity only hides who voted from the author and CSV export.
+#[derive(Clone, Debug)]
+pub struct Poll {
+    pub question: String,
+    pub options: Vec<String>,
+    pub multiple: bool,
+    pub anonymous: bool,
+    pub closes_at: Option<i64>,
+    pub votes: Vec<Vote>,
+}
+
+impl Poll {
+    /// Builds a poll from composer input, trimming blanks and checking the
+    /// option count.
+    pub fn new(
+        question: &str,
+        options: &[String],
+        multiple: bool,
+        anonymous: bool,
+        closes_at: Option<i64>,
+    ) -> Result<Self, String> {
+        let question = question.trim();
+        if question.is_empty() {
+            return Err(tr("Ask a question"));
+        }
+
+        let options: Vec<String> = options
+            .iter()
+            .map(|option| option.trim().to_string())
+            .filter(|option| !option.is_empty())
+            .collect();
+        if options.len() < MIN_OPTIONS {
+            return Err(trn(
+                "Add at least {n} option",
+                "Add at least {n} options",
+                MIN_OPTIONS as u64,
+            ));
+        }
+        if options.len() > MAX_OPTIONS {
+            return Err(trn(
+                "Polls can have at most {n} option",
+                "Polls can have at most {n} options",
+                MAX_OPTIONS as u64,
+            ));
+        }
+        for (index, option) in options.iter().enumerate() {
+            if options[..index]
+                .iter()
+                .any(|other| other.eq_ignore_ascii_case(option))
+            {
+                return Err(tr_with("“{option}” is listed twice", &[("option", option)]));
+            }
+        }
+
+        Ok(Self {
+            question: question.to_string(),
+            options,
+            multiple,
+            anonymous,
+            closes_at,
+            votes: Vec::new(),
+        })
+    }
+
+    pub fn is_closed(&self, now: i64) -> bool {
+        self.closes_at.is_some_and(|closes_at| closes_at <= now)
+    }
+
+    pub fn vote_of(&self, user: &str) -> Option<&Vote> {
+        self.votes.iter().find(|vote| vote.user == user)
+    }
+
+    /// Votes per option, in option order.
+    pub fn counts(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.options.len()];
+        for vote in &self.votes {
+            for &choice in &vote.choices {
+                if let Some(count) = counts.get_mut(choice) {
+                    *count += 1;
+                }
+            }
+        }
+        counts
+    }
+
+    /// Share of voters who picked each option, 0.0–1.0. With multiple choice
+    /// the shares can add up to more than one.
+    pub fn fractions(&self) -> Vec<f64> {
+        let voters = self.votes.len();
+        self.counts()
+            .into_iter()
+            .map(|count| {
+                if voters == 0 {
+                    0.0
+                } else {
+                    count as f64 / voters as f64
+                }
+            })
+            .collect()
+    }
+
+    /// Records or replaces `user`'s vote.
+    pub fn vote(&mut self, user: &str, choices: Vec<usize>, now: i64) -> Result<(), String> {
+        if self.is_closed(now) {
+            return Err(tr("This poll is closed"));
+        }
+        if choices.is_empty() {
+            return Err(tr("Pick an option"));
+        }
+        if !self.multiple && choices.len() > 1 {
+            return Err(tr("Pick only one option"));
+        }
+        if choices.iter().any(|&choice| choice >= self.options.len()) {
+            return Err(tr("Unknown option"));
+        }
+
+        self.votes.retain(|vote| vote.user != user);
+        self.votes.push(Vote {
+            user: user.to_string(),
+            choices,
+            voted_at: now,
+        });
+        Ok(())
+    }
+
+    pub fn retract(&mut self, user: &str, now: i64) -> Result<(), String> {
+        if self.is_closed(now) {
+            return Err(tr("This poll is closed"));
+        }
+        self.votes.retain(|vote| vote.user != user);
+        Ok(())
+    }
+
+    /// "12 votes · Closes in 2 days", "3 votes · Final results".
+    pub fn status_text(&self, now: i64) -> String {
+        let votes = trn("{n} vote", "{n} votes", self.votes.len() as u64);
+        let closing = match self.closes_at {
+            _ if self.is_closed(now) => tr("Final results"),
+            Some(closes_at) => tr_with(
+                "Closes {time}",
+                &[("time", &util::format_time_until(closes_at, now))],
+            ),
+            None => tr("No closing time"),
+        };
+        let mut parts = vec![votes, closing];
+        if self.multiple {
+            parts.push(tr("Multiple choice"));
+        }
+        if self.anonymous {
+            parts.push(tr("Anonymous"));
+        }
+        parts.join(" · ")
+    }
+
+    /// Results as CSV: one row per option, with voter names unless the poll
+    /// is anonymous.
+    pub fn results_csv(&self, community: &Community) -> String {
+        let mut csv = String::from("Option,Votes,Percent,Voters\n");
+        let counts = self.counts();
+        let fractions = self.fractions();
+        for (index, option) in self.options.iter().enumerate() {
+            let voters = if self.anonymous {
+                String::new()
+            } else {
+                self.votes
+                    .iter()
+                    .filter(|vote| vote.choices.contains(&index))
+                    .map(|vote| community.user_name(&vote.user))
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            };
+            csv.push_str(&format!(
+                "{},{},{:.1},{}\n",
+                csv_field(option),
+                counts[index],
+                fractions[index] * 100.0,
+                csv_field(&voters)
+            ));
+        }
+        csv
+    }
+}
+
+/// Quotes a CSV field when it contains a separator, quote or line break.
+/// Text that a spreadsheet would read as a formula gets a leading `'`.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{value}")
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(options: &[&str]) -> Vec<String> {
+        options.iter().map(|option| option.to_string()).collect()
+    }
+
+    fn poll(multiple: bool, closes_at: Option<i64>) -> Poll {
+        Poll::new(
+            "Lunch?",
+            &options(&["Pizza", "Sushi", "Tacos"]),
+            multiple,
+            false,
+            closes_at,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn new_trims_and_drops_blank_options() {
+        let poll = Poll::new(
+            "  Lunch? ",
+            &options(&[" Pizza ", "", "  ", "Sushi"]),
+            false,
+            false,
+            None,
+        )
+        .unwrap();
+        assert_eq!(poll.question, "Lunch?");
+        assert_eq!(poll.options, ["Pizza", "Sushi"]);
+    }
+
+    #[test]
+    fn new_checks_the_question_and_option_count() {
+        let two = options(&["Yes", "No"]);
+        assert!(Poll::new(" ", &two, false, false, None).is_err());
+        assert!(Poll::new("Ready?", &options(&["Yes", " "]), false, false, None).is_err());
+
+        let eleven: Vec<String> = (1..=MAX_OPTIONS + 1).map(|n| n.to_string()).collect();
+        assert!(Poll::new("Pick one", &eleven, false, false, None).is_err());
+        assert!(Poll::new("Pick one", &eleven[..MAX_OPTIONS], false, false, None).is_ok());
+    }
+
+    #[test]
+    fn new_rejects_duplicate_options() {
+        let duplicates = options(&["Rust", "Go", "rust"]);
+        assert!(Poll::new("Language?", &duplicates, false, false, None).is_err());
+    }
+
+    #[test]
+    fn single_choice_polls_take_one_option() {
+        let mut poll = poll(false, None);
+        assert!(poll.vote("alice", vec![0, 1], 0).is_err());
+        assert!(poll.vote("alice", Vec::new(), 0).is_err());
+        assert!(poll.vote("alice", vec![3], 0).is_err());
+        assert!(poll.vote("alice", vec![1], 0).is_ok());
+        assert_eq!(poll.counts(), [0, 1, 0]);
+    }
+
+    #[test]
+    fn multiple_choice_polls_take_several_options() {
+        let mut poll = poll(true, None);
+        poll.vote("alice", vec![0, 2], 0).unwrap();
+        poll.vote("bob", vec![0], 0).unwrap();
+        assert_eq!(poll.counts(), [2, 0, 1]);
+        assert_eq!(poll.fractions(), [1.0, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn voting_again_replaces_the_vote() {
+        let mut poll = poll(false, None);
+        poll.vote("alice", vec![0], 0).unwrap();
+        poll.vote("alice", vec![2], 1).unwrap();
+        assert_eq!(poll.votes.len(), 1);
+        assert_eq!(poll.counts(), [0, 0, 1]);
+        assert_eq!(poll.vote_of("alice").unwrap().voted_at, 1);
+    }
+
+    #[test]
+    fn closed_polls_refuse_votes() {
+        let mut poll = poll(false, Some(100));
+        poll.vote("alice", vec![0], 99).unwrap();
+        assert!(poll.vote("bob", vec![1], 100).is_err());
+        assert!(poll.retract("alice", 100).is_err());
+        assert_eq!(poll.counts(), [1, 0, 0]);
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        assert_eq!(csv_field("Pizza"), "Pizza");
+        assert_eq!(csv_field("Salt, pepper"), "\"Salt, pepper\"");
+        assert_eq!(csv_field("The \"best\""), "\"The \"\"best\"\"\"");
+        assert_eq!(csv_field("Two\nlines"), "\"Two\nlines\"");
+    }
+
+    #[test]
+    fn csv_fields_never_start_a_formula() {
+        assert_eq!(csv_field("=1+1"), "'=1+1");
+        assert_eq!(csv_field("+44"), "'+44");
+        assert_eq!(csv_field("-5"), "'-5");
+        assert_eq!(csv_field("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(csv_field("=A1,B1"), "\"'=A1,B1\"");
+        assert_eq!(csv_field("a=b"), "a=b");
+    }
+
+    #[test]
+    fn results_csv_lists_voters_unless_anonymous() {
+        let community = Community::load();
+        let mut poll = Poll::new(
+            "Best snack?",
+            &options(&["Chips, salted", "=HYPERLINK(\"x\")"]),
+            false,
+            false,
+            None,
+        )
+        .unwrap();
+        poll.vote("alice", vec![0], 0).unwrap();
+        assert_eq!(
+            poll.results_csv(&community),
+            "Option,Votes,Percent,Voters\n\
+             \"Chips, salted\",1,100.0,Alice Johnson\n\
+             \"'=HYPERLINK(\"\"x\"\")\",0,0.0,\n"
+        );
+
+        poll.anonymous = true;
+        assert!(!poll.results_csv(&community).contains("Alice"));
+    }
+}
@@ -0,0 +1,521 @@
+// Poll composer, feed poll widget and the author's results window
+use crate::community::ME;
+use crate::feed::Post;
+use crate::i18n::{self, tr, tr_noop, tr_with};
+use crate::poll::{Poll, MAX_OPTIONS, MIN_OPTIONS};
+use crate::state::AppState;
+use crate::util::{self, DAY, HOUR, WEEK};
+use gtk4::prelude::*;
+use gtk4::{gio, glib};
+use gtk4::{
+    Box as GtkBox, Button, CheckButton, DropDown, Entry, Label, Orientation, ProgressBar,
+    StringList,
+};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const CLOSING_CHOICES: [(&str, Option<i64>); 5] = [
+    (tr_noop("Never"), None),
+    (tr_noop("In 1 hour"), Some(HOUR)),
+    (tr_noop("In 1 day"), Some(DAY)),
+    (tr_noop("In 3 days"), Some(3 * DAY)),
+    (tr_noop("In 1 week"), Some(WEEK)),
+];
+
+// ============================================================================
+// Composer
+// ============================================================================
+
+/// Opens the dialog for publishing a poll to the feed.
+pub fn open_poll_composer(parent: &impl IsA<gtk4::Widget>, state: &Rc<AppState>) {
+    let window = gtk4::Window::builder()
+        .title(tr("Create Poll"))
+        .modal(true)
+        .default_width(480)
+        .build();
+    window.add_css_class("share-sheet");
+    if let Some(parent) = parent.root().and_downcast::<gtk4::Window>() {
+        window.set_transient_for(Some(&parent));
+    }
+
+    let form = GtkBox::new(Orientation::Vertical, 12);
+    form.set_margin_start(24);
+    form.set_margin_end(24);
+    form.set_margin_top(24);
+    form.set_margin_bottom(24);
+
+    let title = Label::new(Some(&tr("📊 Create Poll")));
+    title.set_halign(gtk4::Align::Start);
+    title.add_css_class("dialog-title");
+    form.append(&title);
+
+    let question_entry = Entry::new();
+    question_entry.set_placeholder_text(Some(&tr("Ask a question")));
+    form.append(&form_label(&tr("Question")));
+    form.append(&question_entry);
+
+    let note_entry = Entry::new();
+    note_entry.set_placeholder_text(Some(&tr("Add some context (optional)")));
+    form.append(&note_entry);
+
+    // Options: start with two, allow up to MAX_OPTIONS
+    form.append(&form_label(&tr("Options")));
+    let options_box = GtkBox::new(Orientation::Vertical, 8);
+    form.append(&options_box);
+    let add_btn = Button::with_label(&tr("➕ Add option"));
+    add_btn.add_css_class("secondary-button");
+    add_btn.set_halign(gtk4::Align::Start);
+    form.append(&add_btn);
+
+    let option_entries: Rc<RefCell<Vec<Entry>>> = Rc::default();
+    let add_option: Rc<dyn Fn()> = {
+        let options_box = options_box.clone();
+        let option_entries = option_entries.clone();
+        let add_btn = add_btn.clone();
+        Rc::new(move || {
+            let row = GtkBox::new(Orientation::Horizontal, 8);
+            let entry = Entry::new();
+            entry.set_hexpand(true);
+            let remove_btn = Button::with_label("✕");
+            remove_btn.set_tooltip_text(Some(&tr("Remove option")));
+            remove_btn.add_css_class("action-button");
+            row.append(&entry);
+            row.append(&remove_btn);
+            options_box.append(&row);
+            option_entries.borrow_mut().push(entry.clone());
+
+            let refresh = {
+                let option_entries = option_entries.clone();
+                let add_btn = add_btn.clone();
+                move || {
+                    let entries = option_entries.borrow();
+                    for (index, entry) in entries.iter().enumerate() {
+                        entry.set_placeholder_text(Some(&tr_with(
+                            "Option {number}",
+                            &[("number", &i18n::number(index + 1))],
+                        )));
+                        if let Some(remove_btn) = entry.next_sibling() {
+                            remove_btn.set_sensitive(entries.len() > MIN_OPTIONS);
+                        }
+                    }
+                    add_btn.set_sensitive(entries.len() < MAX_OPTIONS);
+                }
+            };
+            refresh();
+
+            let options_box = options_box.clone();
+            let option_entries = option_entries.clone();
+            remove_btn.connect_clicked(move |_| {
+                option_entries.borrow_mut().retain(|other| *other != entry);
+                options_box.remove(&row);
+                refresh();
+            });
+        })
+    };
+    for _ in 0..MIN_OPTIONS {
+        add_option();
+    }
+    add_btn.connect_clicked(move |_| add_option());
+
+    let multiple_check = CheckButton::with_label(&tr("Allow multiple answers"));
+    let anonymous_check = CheckButton::with_label(&tr("Anonymous voting"));
+    form.append(&multiple_check);
+    form.append(&anonymous_check);
+
+    let closing_names: Vec<String> = CLOSING_CHOICES.iter().map(|(name, _)| tr(name)).collect();
+    let closing_names: Vec<&str> = closing_names.iter().map(String::as_str).collect();
+    let closing_dropdown = DropDown::new(
+        Some(StringList::new(&closing_names)),
+        gtk4::Expression::NONE,
+    );
+    closing_dropdown.set_selected(2);
+    form.append(&form_label(&tr("Closes")));
+    form.append(&closing_dropdown);
+
+    let error_label = Label::new(None);
+    error_label.set_halign(gtk4::Align::Start);
+    error_label.add_css_class("form-error");
+    form.append(&error_label);
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 12);
+    buttons.set_halign(gtk4::Align::End);
+    let cancel_btn = Button::with_label(&tr("Cancel"));
+    cancel_btn.add_css_class("secondary-button");
+    let post_btn = Button::with_label(&tr("🚀 Post Poll"));
+    post_btn.add_css_class("create-post-button");
+    buttons.append(&cancel_btn);
+    buttons.append(&post_btn);
+    form.append(&buttons);
+    window.set_child(Some(&form));
+
+    let window_clone = window.clone();
+    cancel_btn.connect_clicked(move |_| window_clone.close());
+
+    let state = state.clone();
+    let window_clone = window.clone();
+    post_btn.connect_clicked(move |_| {
+        let options: Vec<String> = option_entries
+            .borrow()
+            .iter()
+            .map(|entry| entry.text().to_string())
+            .collect();
+        let closes_at = CLOSING_CHOICES
+            .get(closing_dropdown.selected() as usize)
+            .and_then(|(_, duration)| *duration)
+            .map(|duration| util::now() + duration);
+
+        match Poll::new(
+            &question_entry.text(),
+            &options,
+            multiple_check.is_active(),
+            anonymous_check.is_active(),
+            closes_at,
+        ) {
+            Ok(poll) => {
+                state
+                    .feed
+                    .borrow_mut()
+                    .publish(&note_entry.text(), Some(poll), None);
+                state.feed_changed.emit();
+                window_clone.close();
+            }
+            Err(message) => error_label.set_text(&message),
+        }
+    });
+
+    window.present();
+}
+
+fn form_label(text: &str) -> Label {
+    let label = Label::new(Some(text));
+    label.set_halign(gtk4::Align::Start);
+    label.add_css_class("form-label");
+    label
+}
+
+// ============================================================================
+// Feed widget
+// ============================================================================
+
+/// The poll section of a feed card: choices until the user votes, then
+/// live result bars.
+pub fn create_poll_widget(state: &Rc<AppState>, post: &Post, poll: &Poll) -> GtkBox {
+    let container = GtkBox::new(Orientation::Vertical, 8);
+    container.add_css_class("poll-card");
+
+    let question = Label::new(Some(&poll.question));
+    question.set_halign(gtk4::Align::Start);
+    question.set_wrap(true);
+    question.add_css_class("poll-question");
+    container.append(&question);
+
+    let now = util::now();
+    let my_vote = poll.vote_of(ME);
+    let is_author = post.author == ME;
+    let error_label = Label::new(None);
+    error_label.set_halign(gtk4::Align::Start);
+    error_label.add_css_class("form-error");
+
+    if my_vote.is_some() || poll.is_closed(now) || is_author {
+        let mine = my_vote
+            .map(|vote| vote.choices.as_slice())
+            .unwrap_or_default();
+        for (index, (option, (count, fraction))) in poll
+            .options
+            .iter()
+            .zip(poll.counts().into_iter().zip(poll.fractions()))
+            .enumerate()
+        {
+            container.append(&create_result_bar(
+                option,
+                count,
+                fraction,
+                mine.contains(&index),
+            ));
+        }
+    } else {
+        let checks: Vec<CheckButton> = poll
+            .options
+            .iter()
+            .map(|option| CheckButton::with_label(option))
+            .collect();
+        for check in &checks {
+            if !poll.multiple {
+                check.set_group(checks.first());
+            }
+            check.add_css_class("poll-choice");
+            container.append(check);
+        }
+
+        let vote_btn = Button::with_label(&tr("🗳️ Vote"));
+        vote_btn.add_css_class("action-button");
+        vote_btn.set_halign(gtk4::Align::Start);
+        container.append(&vote_btn);
+
+        let state = state.clone();
+        let post_id = post.id.clone();
+        let error_label = error_label.clone();
+        vote_btn.connect_clicked(move |_| {
+            let choices: Vec<usize> = checks
+                .iter()
+                .enumerate()
+                .filter(|(_, check)| check.is_active())
+                .map(|(index, _)| index)
+                .collect();
+            let result = state
+                .feed
+                .borrow_mut()
+                .update_poll(&post_id, |poll| poll.vote(ME, choices, util::now()));
+            match result {
+                Ok(()) => state.feed_changed.emit(),
+                Err(message) => error_label.set_text(&message),
+            }
+        });
+    }
+    container.append(&error_label);
+
+    let footer = GtkBox::new(Orientation::Horizontal, 12);
+    let status = Label::new(Some(&poll.status_text(now)));
+    status.set_halign(gtk4::Align::Start);
+    status.set_hexpand(true);
+    status.add_css_class("poll-status");
+    footer.append(&status);
+
+    if my_vote.is_some() && !poll.is_closed(now) {
+        let retract_btn = Button::with_label(&tr("↩️ Change vote"));
+        retract_btn.add_css_class("action-button");
+        footer.append(&retract_btn);
+
+        let state = state.clone();
+        let post_id = post.id.clone();
+        retract_btn.connect_clicked(move |_| {
+            let result = state
+                .feed
+                .borrow_mut()
+                .update_poll(&post_id, |poll| poll.retract(ME, util::now()));
+            match result {
+                Ok(()) => state.feed_changed.emit(),
+                Err(message) => error_label.set_text(&message),
+            }
+        });
+    }
+
+    if is_author {
+        let results_btn = Button::with_label(&tr("📊 Results"));
+        results_btn.add_css_class("action-button");
+        footer.append(&results_btn);
+
+        let state = state.clone();
+        let post_id = post.id.clone();
+        results_btn.connect_clicked(move |button| open_poll_results(button, &state, &post_id));
+    }
+    container.append(&footer);
+
+    container
+}
+
+fn create_result_bar(option: &str, count: usize, fraction: f64, mine: bool) -> GtkBox {
+    let row = GtkBox::new(Orientation::Vertical, 4);
+
+    let header = GtkBox::new(Orientation::Horizontal, 8);
+    let text = if mine {
+        format!("✓ {option}")
+    } else {
+        option.to_string()
+    };
+    let option_label = Label::new(Some(&text));
+    option_label.set_halign(gtk4::Align::Start);
+    option_label.set_hexpand(true);
+    option_label.set_wrap(true);
+    option_label.add_css_class("poll-option");
+    let count_label = Label::new(Some(&tr_with(
+        "{percent}% · {count}",
+        &[
+            ("percent", &i18n::decimal(fraction * 100.0, 0)),
+            ("count", &i18n::number(count)),
+        ],
+    )));
+    count_label.add_css_class("poll-count");
+    header.append(&option_label);
+    header.append(&count_label);
+
+    let bar = ProgressBar::new();
+    bar.set_fraction(fraction);
+    bar.add_css_class("poll-bar");
+    if mine {
+        bar.add_css_class("poll-bar-mine");
+    }
+
+    row.append(&header);
+    row.append(&bar);
+    row
+}
+
+// ============================================================================
+// Results window
+// ============================================================================
+
+/// Detailed results for the poll's author, with closing and CSV export.
+pub fn open_poll_results(parent: &impl IsA<gtk4::Widget>, state: &Rc<AppState>, post_id: &str) {
+    let window = gtk4::Window::builder()
+        .title(tr("Poll Results"))
+        .modal(true)
+        .default_width(520)
+        .default_height(560)
+        .build();
+    window.add_css_class("share-sheet");
+    if let Some(parent) = parent.root().and_downcast::<gtk4::Window>() {
+        window.set_transient_for(Some(&parent));
+    }
+
+    let root = GtkBox::new(Orientation::Vertical, 12);
+    root.set_margin_start(24);
+    root.set_margin_end(24);
+    root.set_margin_top(24);
+    root.set_margin_bottom(24);
+
+    let scroll = gtk4::ScrolledWindow::new();
+    scroll.set_vexpand(true);
+    let results_box = GtkBox::new(Orientation::Vertical, 12);
+    scroll.set_child(Some(&results_box));
+    root.append(&scroll);
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 12);
+    buttons.set_halign(gtk4::Align::End);
+    let close_poll_btn = Button::with_label(&tr("🔒 Close Poll Now"));
+    close_poll_btn.add_css_class("secondary-button");
+    let export_btn = Button::with_label(&tr("📥 Export CSV"));
+    export_btn.add_css_class("secondary-button");
+    let done_btn = Button::with_label(&tr("Done"));
+    done_btn.add_css_class("create-post-button");
+    buttons.append(&close_poll_btn);
+    buttons.append(&export_btn);
+    buttons.append(&done_btn);
+    root.append(&buttons);
+    window.set_child(Some(&root));
+
+    let populate = {
+        let state = state.clone();
+        let post_id = post_id.to_string();
+        let close_poll_btn = close_poll_btn.clone();
+        move || {
+            while let Some(child) = results_box.first_child() {
+                results_box.remove(&child);
+            }
+            let Some(poll) = state
+                .feed
+                .borrow()
+                .post(&post_id)
+                .and_then(|post| post.poll.clone())
+            else {
+                return;
+            };
+            let now = util::now();
+            close_poll_btn.set_sensitive(!poll.is_closed(now));
+
+            let title = Label::new(Some(&format!("📊 {}", poll.question)));
+            title.set_halign(gtk4::Align::Start);
+            title.set_wrap(true);
+            title.add_css_class("dialog-title");
+            let status = Label::new(Some(&poll.status_text(now)));
+            status.set_halign(gtk4::Align::Start);
+            status.add_css_class("poll-status");
+            results_box.append(&title);
+            results_box.append(&status);
+
+            let community = state.community.borrow();
+            for (index, (option, (count, fraction))) in poll
+                .options
+                .iter()
+                .zip(poll.counts().into_iter().zip(poll.fractions()))
+                .enumerate()
+            {
+                results_box.append(&create_result_bar(option, count, fraction, false));
+                if poll.anonymous || count == 0 {
+                    continue;
+                }
+                let voters: Vec<String> = poll
+                    .votes
+                    .iter()
+                    .filter(|vote| vote.choices.contains(&index))
+                    .map(|vote| community.user_name(&vote.user))
+                    .collect();
+                let voters_label = Label::new(Some(&voters.join(", ")));
+                voters_label.set_halign(gtk4::Align::Start);
+                voters_label.set_wrap(true);
+                voters_label.add_css_class("poll-voters");
+                results_box.append(&voters_label);
+            }
+        }
+    };
+    populate();
+    let listener = state.feed_changed.connect(populate);
+    let state_clone = state.clone();
+    window.connect_close_request(move |_| {
+        state_clone.feed_changed.disconnect(listener);
+        glib::Propagation::Proceed
+    });
+
+    let state_clone = state.clone();
+    let post_id_clone = post_id.to_string();
+    close_poll_btn.connect_clicked(move |_| {
+        let now = util::now();
+        let result = state_clone
+            .feed
+            .borrow_mut()
+            .update_poll(&post_id_clone, |poll| {
+                poll.closes_at = Some(now);
+                Ok(())
+            });
+        if result.is_ok() {
+            state_clone.feed_changed.emit();
+        }
+    });
+
+    let state_clone = state.clone();
+    let post_id_clone = post_id.to_string();
+    export_btn.connect_clicked(move |button| export_csv(button, &state_clone, &post_id_clone));
+
+    let window_clone = window.clone();
+    done_btn.connect_clicked(move |_| window_clone.close());
+
+    window.present();
+}
+
+fn export_csv(button: &Button, state: &Rc<AppState>, post_id: &str) {
+    let Some(csv) = state
+        .feed
+        .borrow()
+        .post(post_id)
+        .and_then(|post| post.poll.as_ref())
+        .map(|poll| poll.results_csv(&state.community.borrow()))
+    else {
+        return;
+    };
+
+    let dialog = gtk4::FileDialog::builder()
+        .title(tr("Export Poll Results"))
+        .initial_name("poll-results.csv")
+        .modal(true)
+        .build();
+    let window = button.root().and_downcast::<gtk4::Window>();
+    let button = button.clone();
+    dialog.save(window.as_ref(), gio::Cancellable::NONE, move |result| {
+        let Ok(file) = result else {
+            return;
+        };
+        match file.replace_contents(
+            csv.as_bytes(),
+            None,
+            false,
+            gio::FileCreateFlags::REPLACE_DESTINATION,
+            gio::Cancellable::NONE,
+        ) {
+            Ok(_) => button.set_label(&tr("✅ Exported")),
+            Err(err) => {
+                eprintln!("Failed to export poll results: {err}");
+                button.set_label(&tr("❌ Export failed"));
+            }
+        }
+    });
+}
@@ -0,0 +1,320 @@
+// App preferences, kept in GSettings and edited in the Settings view
+//
+// The schema lives in resources/com.linkwithmentor.gschema.xml. build.rs
+// compiles it and the result is embedded here, then installed under the
+// user data directory on startup so no system-wide install is needed.
+use crate::i18n::tr_noop;
+use crate::storage;
+use crate::util::{DAY, WEEK};
+use gtk4::prelude::*;
+use gtk4::{gio, glib};
+use std::path::Path;
+
+/// Same as the application id.
+const SCHEMA_ID: &str = "com.linkwithmentor";
+const COMPILED_SCHEMAS: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/gschemas.compiled"));
+const SCHEMAS_DIR: &str = "schemas";
+
+/// Where notification preferences were kept before GSettings.
+const LEGACY_FILE: &str = "preferences.ini";
+const LEGACY_GROUP: &str = "notifications";
+/// The search history switch used to live with the searches.
+const LEGACY_SEARCHES_FILE: &str = "searches.ini";
+
+/// Choices for the string keys, as (value, label) pairs. Labels are
+/// marked for translation.
+pub const THEMES: [(&str, &str); 4] = [
+    ("system", tr_noop("Auto (System)")),
+    ("light", tr_noop("Light")),
+    ("dark", tr_noop("Dark")),
+    ("high-contrast", tr_noop("High Contrast")),
+];
+/// Languages are listed by their own name.
+pub const LANGUAGES: [(&str, &str); 3] = [
+    ("system", tr_noop("System Default")),
+    ("en", "English"),
+    ("ar", "العربية"),
+];
+pub const DENSITIES: [(&str, &str); 2] = [
+    ("comfortable", tr_noop("Comfortable")),
+    ("compact", tr_noop("Compact")),
+];
+pub const DATA_SHARING: [(&str, &str); 3] = [
+    ("off", tr_noop("Off")),
+    ("limited", tr_noop("Limited")),
+    ("full", tr_noop("Full")),
+];
+
+/// How activity notifications (likes, mentions, follows…) reach the
+/// desktop: one by one, or bundled into a periodic summary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DigestMode {
+    Off,
+    Daily,
+    Weekly,
+}
+
+impl DigestMode {
+    pub const ALL: [DigestMode; 3] = [DigestMode::Off, DigestMode::Daily, DigestMode::Weekly];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DigestMode::Off => tr_noop("Off (notify right away)"),
+            DigestMode::Daily => tr_noop("Daily digest"),
+            DigestMode::Weekly => tr_noop("Weekly digest"),
+        }
+    }
+
+    /// Seconds between digests.
+    pub fn period(self) -> Option<i64> {
+        match self {
+            DigestMode::Off => None,
+            DigestMode::Daily => Some(DAY),
+            DigestMode::Weekly => Some(WEEK),
+        }
+    }
+
+    /// The value stored in the "activity-digest" key.
+    pub fn key(self) -> &'static str {
+        match self {
+            DigestMode::Off => "off",
+            DigestMode::Daily => "daily",
+            DigestMode::Weekly => "weekly",
+        }
+    }
+
+    fn from_key(key: &str) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.key() == key)
+            .unwrap_or(DigestMode::Off)
+    }
+}
+
+/// Typed access to the settings. Views bind their controls to
+/// `settings()` directly and listen for its changed signal.
+/// Without a schema there are no settings at all; the getters then give
+/// the schema's defaults and nothing can be changed.
+pub struct Preferences {
+    settings: Option<gio::Settings>,
+}
+
+impl Preferences {
+    pub fn load() -> Self {
+        let settings = open_settings();
+        if let Some(settings) = &settings {
+            migrate_legacy(settings);
+        }
+        Self { settings }
+    }
+
+    pub fn settings(&self) -> Option<&gio::Settings> {
+        self.settings.as_ref()
+    }
+
+    fn boolean(&self, key: &str, default: bool) -> bool {
+        self.settings
+            .as_ref()
+            .map_or(default, |settings| settings.boolean(key))
+    }
+
+    /// Desktop notifications while the window is hidden or unfocused.
+    pub fn push_notifications(&self) -> bool {
+        self.boolean("push-notifications", true)
+    }
+
+    /// Play the alert sound with desktop notifications.
+    pub fn sound(&self) -> bool {
+        self.boolean("notification-sound", true)
+    }
+
+    pub fn digest(&self) -> DigestMode {
+        self.settings.as_ref().map_or(DigestMode::Off, |settings| {
+            DigestMode::from_key(&settings.string("activity-digest"))
+        })
+    }
+
+    pub fn save_search_history(&self) -> bool {
+        self.boolean("save-search-history", true)
+    }
+
+    /// Whether desktop notifications are held back at `hour` (0–23) by Do
+    /// Not Disturb or quiet hours. They are still recorded in the list.
+    pub fn alerts_paused(&self, hour: u32) -> bool {
+        // Both default to off
+        let Some(settings) = &self.settings else {
+            return false;
+        };
+        if settings.boolean("do-not-disturb") {
+            return true;
+        }
+        let start = settings.int("quiet-hours-start") as u32;
+        let end = settings.int("quiet-hours-end") as u32;
+        if !settings.boolean("quiet-hours") || start == end {
+            return false;
+        }
+        if start < end {
+            (start..end).contains(&hour)
+        } else {
+            hour >= start || hour < end
+        }
+    }
+}
+
+/// Installs the embedded schema if it changed since the last run, and
+/// opens the settings from it. A schema installed system-wide is used as a
+/// fallback. Failing both, the settings live in memory until the app quits,
+/// and if the schema can't even be written to a temporary directory there
+/// are none.
+fn open_settings() -> Option<gio::Settings> {
+    let schema = install_schema(&storage::data_dir().join(SCHEMAS_DIR)).or_else(|| {
+        gio::SettingsSchemaSource::default().and_then(|source| source.lookup(SCHEMA_ID, true))
+    });
+    if let Some(schema) = schema {
+        return Some(gio::Settings::new_full(
+            &schema,
+            None::<&gio::SettingsBackend>,
+            None,
+        ));
+    }
+
+    let Some(schema) = install_temporary_schema() else {
+        eprintln!("Settings are unavailable; using the defaults");
+        return None;
+    };
+    eprintln!("Settings can't be saved; changes are kept until the app quits");
+    let backend = gio::memory_settings_backend_new();
+    Some(gio::Settings::new_full(&schema, Some(&backend), None))
+}
+
+/// Installs the schema in a new temporary directory.
+fn install_temporary_schema() -> Option<gio::SettingsSchema> {
+    install_schema(&std::env::temp_dir().join(format!(
+        "linkwithmentor-schemas-{}",
+        glib::uuid_string_random()
+    )))
+}
+
+/// Writes the embedded schema to `dir` unless it is already there, and
+/// looks ours up in it.
+fn install_schema(dir: &Path) -> Option<gio::SettingsSchema> {
+    let path = dir.join("gschemas.compiled");
+    if std::fs::read(&path).ok().as_deref() != Some(COMPILED_SCHEMAS) {
+        if let Err(err) =
+            std::fs::create_dir_all(dir).and_then(|()| std::fs::write(&path, COMPILED_SCHEMAS))
+        {
+            eprintln!(
+                "Could not install the settings schema in {}: {err}",
+                dir.display()
+            );
+            return None;
+        }
+    }
+    gio::SettingsSchemaSource::from_directory(dir, None, false)
+        .ok()?
+        .lookup(SCHEMA_ID, false)
+}
+
+/// Copies preferences saved by older versions into the settings, then
+/// drops the old copies so they don't override later changes.
+fn migrate_legacy(settings: &gio::Settings) {
+    if let Some(key_file) = storage::load_device_key_file(LEGACY_FILE) {
+        for (old, new) in [
+            ("push", "push-notifications"),
+            ("email", "email-notifications"),
+            ("sound", "notification-sound"),
+            ("do_not_disturb", "do-not-disturb"),
+            ("quiet_hours", "quiet-hours"),
+        ] {
+            if let Ok(value) = key_file.boolean(LEGACY_GROUP, old) {
+                let _ = settings.set_boolean(new, value);
+            }
+        }
+        for (old, new) in [
+            ("quiet_start", "quiet-hours-start"),
+            ("quiet_end", "quiet-hours-end"),
+        ] {
+            if let Ok(hour @ 0..=23) = key_file.integer(LEGACY_GROUP, old) {
+                let _ = settings.set_int(new, hour);
+            }
+        }
+        if let Ok(digest) = key_file.string(LEGACY_GROUP, "digest") {
+            let _ = settings.set_string("activity-digest", DigestMode::from_key(&digest).key());
+        }
+        let _ = std::fs::remove_file(storage::data_dir().join(LEGACY_FILE));
+    }
+
+    if let Some(key_file) = storage::load_device_key_file(LEGACY_SEARCHES_FILE) {
+        if let Ok(record) = key_file.boolean("history", "record") {
+            let _ = settings.set_boolean("save-search-history", record);
+            let _ = key_file.remove_key("history", "record");
+            storage::save_device_key_file(LEGACY_SEARCHES_FILE, &key_file);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Preferences kept in memory, so tests don't touch the real settings.
+    fn preferences() -> Preferences {
+        let schema = install_temporary_schema().unwrap();
+        let backend = gio::memory_settings_backend_new();
+        Preferences {
+            settings: Some(gio::Settings::new_full(&schema, Some(&backend), None)),
+        }
+    }
+
+    fn set_quiet_hours(preferences: &Preferences, start: i32, end: i32) {
+        let settings = preferences.settings().unwrap();
+        settings.set_boolean("quiet-hours", true).unwrap();
+        settings.set_int("quiet-hours-start", start).unwrap();
+        settings.set_int("quiet-hours-end", end).unwrap();
+    }
+
+    fn paused_hours(preferences: &Preferences) -> Vec<u32> {
+        (0..24)
+            .filter(|hour| preferences.alerts_paused(*hour))
+            .collect()
+    }
+
+    #[test]
+    fn nothing_is_paused_by_default() {
+        assert!(paused_hours(&preferences()).is_empty());
+        assert!(paused_hours(&Preferences { settings: None }).is_empty());
+    }
+
+    #[test]
+    fn quiet_hours_within_a_day() {
+        let preferences = preferences();
+        set_quiet_hours(&preferences, 9, 12);
+        assert_eq!(paused_hours(&preferences), [9, 10, 11]);
+    }
+
+    #[test]
+    fn quiet_hours_past_midnight() {
+        let preferences = preferences();
+        set_quiet_hours(&preferences, 22, 7);
+        assert_eq!(paused_hours(&preferences), [0, 1, 2, 3, 4, 5, 6, 22, 23]);
+    }
+
+    #[test]
+    fn quiet_hours_need_the_switch_and_a_range() {
+        let preferences = preferences();
+        set_quiet_hours(&preferences, 8, 8);
+        assert!(paused_hours(&preferences).is_empty());
+        set_quiet_hours(&preferences, 22, 7);
+        let settings = preferences.settings().unwrap();
+        settings.set_boolean("quiet-hours", false).unwrap();
+        assert!(paused_hours(&preferences).is_empty());
+    }
+
+    #[test]
+    fn do_not_disturb_pauses_every_hour() {
+        let preferences = preferences();
+        let settings = preferences.settings().unwrap();
+        settings.set_boolean("do-not-disturb", true).unwrap();
+        assert_eq!(paused_hours(&preferences).len(), 24);
+    }
+}
@@ -0,0 +1,366 @@
+// The signed-in user's profile: headline, bio, images, skills and links
+use crate::i18n::{tr, tr_noop, tr_with, trn};
+use crate::storage;
+use crate::util;
+use gtk4::{gdk_pixbuf, glib};
+use std::path::{Path, PathBuf};
+
+const PROFILE_FILE: &str = "profile.ini";
+const PROFILE_GROUP: &str = "profile";
+const SKILL_PREFIX: &str = "skill:";
+const LINK_PREFIX: &str = "link:";
+/// Cropped avatar and cover images live here, under the data directory.
+const IMAGE_DIR: &str = "profile";
+/// The display name until the user sets one; signing in replaces it with
+/// the account's name.
+pub const PLACEHOLDER_NAME: &str = "Your Name";
+
+pub const MAX_NAME_LENGTH: usize = 50;
+pub const MAX_HEADLINE_LENGTH: usize = 100;
+pub const MAX_BIO_LENGTH: usize = 500;
+pub const MAX_SKILLS: usize = 20;
+pub const MAX_LINKS: usize = 8;
+pub const MAX_LANGUAGES: usize = 10;
+
+pub const AVATAR_SIZE: i32 = 256;
+pub const COVER_WIDTH: i32 = 1200;
+pub const COVER_HEIGHT: i32 = 400;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Proficiency {
+    Beginner,
+    Intermediate,
+    Advanced,
+    Expert,
+}
+
+impl Proficiency {
+    pub const ALL: [Proficiency; 4] = [
+        Proficiency::Beginner,
+        Proficiency::Intermediate,
+        Proficiency::Advanced,
+        Proficiency::Expert,
+    ];
+
+    /// The English name, which is also how the profile saves it; pass it to
+    /// `tr` for display.
+    pub fn label(self) -> &'static str {
+        match self {
+            Proficiency::Beginner => tr_noop("Beginner"),
+            Proficiency::Intermediate => tr_noop("Intermediate"),
+            Proficiency::Advanced => tr_noop("Advanced"),
+            Proficiency::Expert => tr_noop("Expert"),
+        }
+    }
+
+    /// Filled dots out of four, e.g. "●●●○".
+    pub fn dots(self) -> String {
+        let filled = Self::ALL
+            .iter()
+            .position(|level| *level == self)
+            .unwrap_or(0)
+            + 1;
+        format!(
+            "{}{}",
+            "●".repeat(filled),
+            "○".repeat(Self::ALL.len() - filled)
+        )
+    }
+
+    fn from_label(label: &str) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|level| level.label() == label)
+            .unwrap_or(Proficiency::Intermediate)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Skill {
+    pub name: String,
+    pub level: Proficiency,
+}
+
+#[derive(Clone, Debug)]
+pub struct ProfileLink {
+    /// Shown instead of the URL; may be empty.
+    pub label: String,
+    pub url: String,
+}
+
+impl ProfileLink {
+    pub fn title(&self) -> String {
+        if !self.label.is_empty() {
+            return self.label.clone();
+        }
+        glib::Uri::parse(&self.url, glib::UriFlags::NONE)
+            .ok()
+            .and_then(|uri| uri.host())
+            .map_or_else(|| self.url.clone(), |host| host.to_string())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+    pub display_name: String,
+    pub headline: String,
+    pub bio: String,
+    pub avatar: Option<PathBuf>,
+    pub cover: Option<PathBuf>,
+    pub skills: Vec<Skill>,
+    pub links: Vec<ProfileLink>,
+    /// IANA identifier such as "Europe/Berlin"; empty when not shared.
+    pub timezone: String,
+    pub languages: Vec<String>,
+}
+
+impl Profile {
+    pub fn load() -> Self {
+        let Some(key_file) = storage::load_key_file(PROFILE_FILE) else {
+            return Self::with_samples();
+        };
+
+        let group = PROFILE_GROUP;
+        let path = |key: &str| {
+            key_file
+                .string(group, key)
+                .ok()
+                .map(|path| PathBuf::from(path.as_str()))
+                .filter(|path| path.exists())
+        };
+        let mut skills: Vec<(usize, Skill)> = storage::groups_with_prefix(&key_file, SKILL_PREFIX)
+            .into_iter()
+            .map(|(index, group)| {
+                (
+                    index.parse().unwrap_or_default(),
+                    Skill {
+                        name: storage::string_or(&key_file, &group, "name", ""),
+                        level: Proficiency::from_label(&storage::string_or(
+                            &key_file, &group, "level", "",
+                        )),
+                    },
+                )
+            })
+            .collect();
+        skills.sort_by_key(|(index, _)| *index);
+        let mut links: Vec<(usize, ProfileLink)> =
+            storage::groups_with_prefix(&key_file, LINK_PREFIX)
+                .into_iter()
+                .map(|(index, group)| {
+                    (
+                        index.parse().unwrap_or_default(),
+                        ProfileLink {
+                            label: storage::string_or(&key_file, &group, "label", ""),
+                            url: storage::string_or(&key_file, &group, "url", ""),
+                        },
+                    )
+                })
+                .collect();
+        links.sort_by_key(|(index, _)| *index);
+
+        Self {
+            display_name: storage::string_or(&key_file, group, "display_name", PLACEHOLDER_NAME),
+            headline: storage::string_or(&key_file, group, "headline", ""),
+            bio: storage::string_or(&key_file, group, "bio", ""),
+            avatar: path("avatar"),
+            cover: path("cover"),
+            skills: skills.into_iter().map(|(_, skill)| skill).collect(),
+            links: links.into_iter().map(|(_, link)| link).collect(),
+            timezone: storage::string_or(&key_file, group, "timezone", ""),
+            languages: storage::list(&key_file, group, "languages"),
+        }
+    }
+
+    fn with_samples() -> Self {
+        let skill = |name: &str, level| Skill {
+            name: name.to_string(),
+            level,
+        };
+        Self {
+            display_name: PLACEHOLDER_NAME.to_string(),
+            headline: "Software Developer | Mentor | Open Source Enthusiast".to_string(),
+            bio: String::new(),
+            avatar: None,
+            cover: None,
+            skills: vec![
+                skill("Rust", Proficiency::Advanced),
+                skill("GTK", Proficiency::Intermediate),
+            ],
+            links: Vec::new(),
+            timezone: glib::TimeZone::local().identifier().to_string(),
+            languages: vec!["English".to_string()],
+        }
+    }
+
+    pub fn save(&self) {
+        let key_file = glib::KeyFile::new();
+
+        let group = PROFILE_GROUP;
+        key_file.set_string(group, "display_name", &self.display_name);
+        key_file.set_string(group, "headline", &self.headline);
+        key_file.set_string(group, "bio", &self.bio);
+        if let Some(avatar) = &self.avatar {
+            key_file.set_string(group, "avatar", &avatar.to_string_lossy());
+        }
+        if let Some(cover) = &self.cover {
+            key_file.set_string(group, "cover", &cover.to_string_lossy());
+        }
+        key_file.set_string(group, "timezone", &self.timezone);
+        storage::set_list(&key_file, group, "languages", &self.languages);
+
+        for (index, skill) in self.skills.iter().enumerate() {
+            let group = format!("{SKILL_PREFIX}{index}");
+            key_file.set_string(&group, "name", &skill.name);
+            key_file.set_string(&group, "level", skill.level.label());
+        }
+        for (index, link) in self.links.iter().enumerate() {
+            let group = format!("{LINK_PREFIX}{index}");
+            key_file.set_string(&group, "label", &link.label);
+            key_file.set_string(&group, "url", &link.url);
+        }
+
+        storage::save_key_file(PROFILE_FILE, &key_file);
+    }
+
+    /// Checks edited values before they replace the saved profile.
+    pub fn validate(&self) -> Result<(), String> {
+        let name_length = self.display_name.chars().count();
+        if name_length == 0 {
+            return Err(tr("Enter a display name"));
+        }
+        if name_length > MAX_NAME_LENGTH {
+            return Err(trn(
+                "Display names can be at most {n} character",
+                "Display names can be at most {n} characters",
+                MAX_NAME_LENGTH as u64,
+            ));
+        }
+        if self.headline.chars().count() > MAX_HEADLINE_LENGTH {
+            return Err(trn(
+                "Headlines can be at most {n} character",
+                "Headlines can be at most {n} characters",
+                MAX_HEADLINE_LENGTH as u64,
+            ));
+        }
+        if self.bio.chars().count() > MAX_BIO_LENGTH {
+            return Err(trn(
+                "Bios can be at most {n} character",
+                "Bios can be at most {n} characters",
+                MAX_BIO_LENGTH as u64,
+            ));
+        }
+
+        if self.skills.len() > MAX_SKILLS {
+            return Err(trn(
+                "List at most {n} skill",
+                "List at most {n} skills",
+                MAX_SKILLS as u64,
+            ));
+        }
+        for (index, skill) in self.skills.iter().enumerate() {
+            if skill.name.is_empty() {
+                return Err(tr("Skills need a name"));
+            }
+            if self.skills[..index]
+                .iter()
+                .any(|other| other.name.eq_ignore_ascii_case(&skill.name))
+            {
+                return Err(tr_with(
+                    "“{skill}” is listed twice",
+                    &[("skill", &skill.name)],
+                ));
+            }
+        }
+
+        if self.links.len() > MAX_LINKS {
+            return Err(trn(
+                "Add at most {n} link",
+                "Add at most {n} links",
+                MAX_LINKS as u64,
+            ));
+        }
+        for link in &self.links {
+            util::validate_url(&link.url)
+                .map_err(|err| format!("{}: {}", link.title(), tr(err)))?;
+        }
+
+        if !self.timezone.is_empty() && time_zone(&self.timezone).is_none() {
+            return Err(tr_with(
+                "“{zone}” is not a known time zone",
+                &[("zone", &self.timezone)],
+            ));
+        }
+        if self.languages.len() > MAX_LANGUAGES {
+            return Err(trn(
+                "List at most {n} language",
+                "List at most {n} languages",
+                MAX_LANGUAGES as u64,
+            ));
+        }
+        Ok(())
+    }
+
+    /// "Europe/Berlin · 14:05 local time", or `None` without a time zone.
+    pub fn local_time_text(&self) -> Option<String> {
+        let zone = time_zone(&self.timezone)?;
+        let time = glib::DateTime::now(&zone).ok()?.format("%H:%M").ok()?;
+        Some(tr_with(
+            "{zone} · {time} local time",
+            &[("zone", &self.timezone), ("time", &time)],
+        ))
+    }
+
+    /// Saves `edited` and deletes images the old profile no longer uses.
+    pub fn replace(&mut self, edited: Profile) {
+        for old in [&self.avatar, &self.cover].into_iter().flatten() {
+            if edited.avatar.as_ref() != Some(old) && edited.cover.as_ref() != Some(old) {
+                let _ = std::fs::remove_file(old);
+            }
+        }
+        *self = edited;
+        self.save();
+    }
+}
+
+/// Looks up an IANA time zone; GLib falls back to UTC for unknown names.
+fn time_zone(identifier: &str) -> Option<glib::TimeZone> {
+    let zone = glib::TimeZone::new(Some(identifier));
+    (zone.identifier() == identifier).then_some(zone)
+}
+
+/// Crops `pixbuf` to the given rectangle, scales it to `width`×`height` and
+/// stores it as a PNG in the profile image directory.
+pub fn save_cropped_image(
+    pixbuf: &gdk_pixbuf::Pixbuf,
+    crop: (i32, i32, i32, i32),
+    width: i32,
+    height: i32,
+    name: &str,
+) -> Result<PathBuf, String> {
+    let (x, y, crop_width, crop_height) = crop;
+    let x = x.clamp(0, pixbuf.width() - 1);
+    let y = y.clamp(0, pixbuf.height() - 1);
+    let cropped = pixbuf.new_subpixbuf(
+        x,
+        y,
+        crop_width.clamp(1, pixbuf.width() - x),
+        crop_height.clamp(1, pixbuf.height() - y),
+    );
+    let scaled = cropped
+        .scale_simple(width, height, gdk_pixbuf::InterpType::Bilinear)
+        .ok_or_else(|| tr("Could not scale the image"))?;
+
+    let dir = storage::account_path(IMAGE_DIR);
+    std::fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+    let path = dir.join(format!("{name}-{}.png", glib::uuid_string_random()));
+    scaled
+        .savev(&path, "png", &[])
+        .map_err(|err| err.to_string())?;
+    Ok(path)
+}
+
+/// Loads an image for cropping, with its EXIF orientation applied.
+pub fn load_image(path: &Path) -> Result<gdk_pixbuf::Pixbuf, String> {
+    let pixbuf = gdk_pixbuf::Pixbuf::from_file(path).map_err(|err| err.to_string())?;
+    Ok(pixbuf.apply_embedded_orientation().unwrap_or(pixbuf))
+}
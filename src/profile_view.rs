@@ -0,0 +1,1410 @@
+// Profile header, profile editor, image cropping and avatars
+use crate::activity::{self, Activity, Visibility};
+use crate::community::{Relationship, User, ME};
+use crate::i18n::{self, tr, tr_noop, tr_with};
+use crate::link_sheet;
+use crate::navigation;
+use crate::profile::{self, Proficiency, Profile, ProfileLink, Skill};
+use crate::sizing;
+use crate::state::AppState;
+use crate::util;
+use gtk4::prelude::*;
+use gtk4::{cairo, gdk_pixbuf, gio, glib};
+use gtk4::{
+    Box as GtkBox, Button, DrawingArea, DropDown, Entry, Frame, Label, Orientation, Picture,
+    ScrolledWindow, TextView,
+};
+use std::cell::{Cell, RefCell};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+const COVER_PREVIEW_HEIGHT: i32 = 140;
+const CROP_AREA_WIDTH: i32 = 440;
+const CROP_AREA_HEIGHT: i32 = 320;
+
+// ============================================================================
+// Avatars
+// ============================================================================
+
+/// A user's avatar: their picture when they have one, else their emoji.
+pub fn create_avatar(user: Option<&User>, css_class: &str, size: i32) -> gtk4::Widget {
+    match user.and_then(|user| user.avatar_image.as_ref()) {
+        Some(path) if path.exists() => create_avatar_image(path, css_class, size),
+        _ => {
+            let label = Label::new(Some(user.map_or("👤", |user| user.avatar.as_str())));
+            label.add_css_class(css_class);
+            label.upcast()
+        }
+    }
+}
+
+fn create_avatar_image(path: &std::path::Path, css_class: &str, size: i32) -> gtk4::Widget {
+    let picture = Picture::for_filename(path);
+    sizing::set_size_request(&picture, size, size);
+    picture.set_can_shrink(true);
+    picture.set_content_fit(gtk4::ContentFit::Cover);
+    picture.set_halign(gtk4::Align::Center);
+    picture.set_valign(gtk4::Align::Center);
+    picture.set_overflow(gtk4::Overflow::Hidden);
+    picture.add_css_class("avatar-image");
+    picture.add_css_class(css_class);
+    picture.upcast()
+}
+
+// ============================================================================
+// Profile header
+// ============================================================================
+
+/// The profile header card. It rebuilds its details whenever the profile
+/// changes; callers may append more widgets (stats, buttons) below them.
+pub fn create_profile_header(state: &Rc<AppState>) -> GtkBox {
+    let header_box = GtkBox::new(Orientation::Vertical, 16);
+    header_box.add_css_class("profile-header");
+    header_box.set_margin_start(20);
+    header_box.set_margin_end(20);
+    header_box.set_margin_top(20);
+    header_box.set_margin_bottom(20);
+
+    let details = GtkBox::new(Orientation::Vertical, 8);
+    header_box.append(&details);
+
+    let populate = {
+        let state = state.clone();
+        let details = details.clone();
+        move || {
+            while let Some(child) = details.first_child() {
+                details.remove(&child);
+            }
+            let profile = state.profile.borrow();
+
+            if let Some(cover) = &profile.cover {
+                let picture = Picture::for_filename(cover);
+                picture.set_content_fit(gtk4::ContentFit::Cover);
+                picture.set_height_request(COVER_PREVIEW_HEIGHT);
+                picture.set_overflow(gtk4::Overflow::Hidden);
+                picture.add_css_class("profile-cover");
+                details.append(&picture);
+            }
+
+            let avatar = create_avatar(state.community.borrow().user(ME), "profile-avatar", 120);
+            details.append(&avatar);
+
+            let name = Label::new(Some(&profile.display_name));
+            name.add_css_class("profile-name");
+            details.append(&name);
+
+            if !profile.headline.is_empty() {
+                let headline = Label::new(Some(&profile.headline));
+                headline.add_css_class("profile-bio");
+                headline.set_wrap(true);
+                details.append(&headline);
+            }
+            if !profile.bio.is_empty() {
+                let bio = Label::new(Some(&profile.bio));
+                bio.add_css_class("profile-about");
+                bio.set_wrap(true);
+                bio.set_justify(gtk4::Justification::Center);
+                details.append(&bio);
+            }
+
+            let mut meta = Vec::new();
+            if let Some(time) = profile.local_time_text() {
+                meta.push(format!("🕐 {time}"));
+            }
+            if !profile.languages.is_empty() {
+                meta.push(format!("🗣️ {}", profile.languages.join(", ")));
+            }
+            if !meta.is_empty() {
+                let meta_label = Label::new(Some(&meta.join("   ")));
+                meta_label.add_css_class("profile-meta");
+                details.append(&meta_label);
+            }
+
+            if !profile.skills.is_empty() {
+                let skills_box = gtk4::FlowBox::new();
+                skills_box.set_selection_mode(gtk4::SelectionMode::None);
+                skills_box.set_halign(gtk4::Align::Center);
+                skills_box.set_max_children_per_line(6);
+                for skill in &profile.skills {
+                    let chip = Label::new(Some(&format!("{}  {}", skill.name, skill.level.dots())));
+                    chip.set_tooltip_text(Some(&tr(skill.level.label())));
+                    chip.add_css_class("profile-skill");
+                    skills_box.insert(&chip, -1);
+                }
+                details.append(&skills_box);
+            }
+
+            if !profile.links.is_empty() {
+                let links_box = GtkBox::new(Orientation::Horizontal, 8);
+                links_box.set_halign(gtk4::Align::Center);
+                for link in &profile.links {
+                    let button = gtk4::LinkButton::with_label(&link.url, &link.title());
+                    button.add_css_class("profile-link");
+                    links_box.append(&button);
+                }
+                details.append(&links_box);
+            }
+        }
+    };
+    populate();
+    state.profile_changed.connect(populate);
+
+    header_box
+}
+
+// ============================================================================
+// Stats and people lists
+// ============================================================================
+
+/// Post, follower and following counts; the latter two open people lists.
+pub fn create_profile_stats(state: &Rc<AppState>) -> GtkBox {
+    let stats_box = GtkBox::new(Orientation::Horizontal, 40);
+    stats_box.set_halign(gtk4::Align::Center);
+    stats_box.set_margin_top(16);
+
+    let populate: Rc<dyn Fn()> = {
+        let state = state.clone();
+        let stats_box = stats_box.clone();
+        Rc::new(move || {
+            while let Some(child) = stats_box.first_child() {
+                stats_box.remove(&child);
+            }
+            let posts = state
+                .feed
+                .borrow()
+                .posts()
+                .iter()
+                .filter(|post| post.author == ME)
+                .count();
+            let (followers, following) = {
+                let community = state.community.borrow();
+                (community.followers(ME).len(), community.following(ME).len())
+            };
+
+            stats_box.append(&create_stat(posts, &tr("Posts")));
+            for (count, label, following) in [
+                (followers, tr_noop("Followers"), false),
+                (following, tr_noop("Following"), true),
+            ] {
+                let button = Button::new();
+                button.set_child(Some(&create_stat(count, &tr(label))));
+                button.add_css_class("stat-button");
+                let state = state.clone();
+                button.connect_clicked(move |button| {
+                    let kind = if following {
+                        PeopleList::Following
+                    } else {
+                        PeopleList::Followers
+                    };
+                    open_people_list(button, &state, kind);
+                });
+                stats_box.append(&button);
+            }
+        })
+    };
+    populate();
+    let p = populate.clone();
+    state.feed_changed.connect(move || p());
+    state.community_changed.connect(move || populate());
+
+    stats_box
+}
+
+pub fn create_stat(count: usize, label: &str) -> GtkBox {
+    let stat_box = GtkBox::new(Orientation::Vertical, 4);
+    let value_label = Label::new(Some(&util::format_count(count)));
+    value_label.add_css_class("stat-value");
+    let label_label = Label::new(Some(label));
+    label_label.add_css_class("stat-label");
+    stat_box.append(&value_label);
+    stat_box.append(&label_label);
+    stat_box
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PeopleList {
+    Followers,
+    Following,
+    /// Blocked and muted users.
+    Hidden,
+}
+
+impl PeopleList {
+    fn title(self) -> String {
+        match self {
+            PeopleList::Followers => tr("Followers"),
+            PeopleList::Following => tr("Following"),
+            PeopleList::Hidden => tr("Blocked & Muted"),
+        }
+    }
+}
+
+/// Lists people related to the signed-in user, each with a relationship
+/// menu. The list refreshes while open as relationships change.
+pub fn open_people_list(parent: &impl IsA<gtk4::Widget>, state: &Rc<AppState>, kind: PeopleList) {
+    let window = gtk4::Window::builder()
+        .title(kind.title())
+        .modal(true)
+        .default_width(400)
+        .default_height(460)
+        .build();
+    window.add_css_class("share-sheet");
+    if let Some(parent) = parent.root().and_downcast::<gtk4::Window>() {
+        window.set_transient_for(Some(&parent));
+    }
+
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_start(24);
+    content.set_margin_end(24);
+    content.set_margin_top(24);
+    content.set_margin_bottom(24);
+
+    let title_label = Label::new(Some(&kind.title()));
+    title_label.set_halign(gtk4::Align::Start);
+    title_label.add_css_class("dialog-title");
+    content.append(&title_label);
+
+    let list = gtk4::ListBox::new();
+    list.set_selection_mode(gtk4::SelectionMode::None);
+    list.add_css_class("share-picker-list");
+
+    let populate = {
+        let state = state.clone();
+        let list = list.clone();
+        move || {
+            while let Some(child) = list.first_child() {
+                list.remove(&child);
+            }
+            let community = state.community.borrow();
+            let sections = match kind {
+                PeopleList::Followers => vec![(String::new(), community.followers(ME))],
+                PeopleList::Following => vec![(String::new(), community.following(ME))],
+                PeopleList::Hidden => vec![
+                    (tr("Blocked"), community.blocked_users()),
+                    (tr("Muted"), community.muted_users()),
+                ],
+            };
+            for (heading, people) in &sections {
+                if people.is_empty() {
+                    continue;
+                }
+                if !heading.is_empty() {
+                    let header = Label::new(Some(heading));
+                    header.set_halign(gtk4::Align::Start);
+                    header.add_css_class("share-picker-heading");
+                    list.append(&header);
+                }
+                for user in people {
+                    let row = GtkBox::new(Orientation::Horizontal, 12);
+                    row.set_margin_start(8);
+                    row.set_margin_end(8);
+                    row.set_margin_top(6);
+                    row.set_margin_bottom(6);
+                    let avatar = create_avatar(Some(user), "chat-item-avatar", 32);
+                    navigation::link_to_profile(&avatar, &user.id);
+                    row.append(&avatar);
+                    let name = Label::new(Some(&format!("{}  @{}", user.name, user.handle)));
+                    name.set_halign(gtk4::Align::Start);
+                    name.set_hexpand(true);
+                    name.add_css_class("chat-item-name");
+                    navigation::link_to_profile(&name, &user.id);
+                    row.append(&name);
+                    row.append(&create_relationship_menu(&state, &user.id));
+                    list.append(&row);
+                }
+            }
+            if sections.iter().all(|(_, people)| people.is_empty()) {
+                let empty = Label::new(Some(&match kind {
+                    PeopleList::Followers => tr("No followers yet"),
+                    PeopleList::Following => tr("You aren't following anyone yet"),
+                    PeopleList::Hidden => tr("You haven't blocked or muted anyone"),
+                }));
+                empty.add_css_class("share-empty");
+                list.append(&empty);
+            }
+        }
+    };
+    populate();
+    let listener = state.community_changed.connect(populate);
+
+    let scroll = ScrolledWindow::new();
+    scroll.set_vexpand(true);
+    scroll.set_child(Some(&list));
+    content.append(&scroll);
+
+    let close_btn = Button::with_label(&tr("Close"));
+    close_btn.add_css_class("secondary-button");
+    close_btn.set_halign(gtk4::Align::End);
+    let window_clone = window.clone();
+    close_btn.connect_clicked(move |_| window_clone.close());
+    content.append(&close_btn);
+
+    let state = state.clone();
+    window.connect_close_request(move |_| {
+        state.community_changed.disconnect(listener);
+        glib::Propagation::Proceed
+    });
+
+    window.set_child(Some(&content));
+    window.present();
+}
+
+// ============================================================================
+// Relationships
+// ============================================================================
+
+/// A "⋯" menu to follow, mute or block `user_id`.
+pub fn create_relationship_menu(state: &Rc<AppState>, user_id: &str) -> gtk4::MenuButton {
+    let menu_btn = gtk4::MenuButton::new();
+    menu_btn.set_label("⋯");
+    menu_btn.set_tooltip_text(Some(&tr("Follow, mute or block")));
+    menu_btn.add_css_class("relationship-menu");
+    menu_btn.set_valign(gtk4::Align::Center);
+
+    let popover = gtk4::Popover::new();
+    let items = GtkBox::new(Orientation::Vertical, 4);
+    items.set_margin_start(4);
+    items.set_margin_end(4);
+    items.set_margin_top(4);
+    items.set_margin_bottom(4);
+
+    let (relationship, private, muted, name) = {
+        let community = state.community.borrow();
+        let user = community.user(user_id);
+        (
+            community.relationship(user_id),
+            user.is_some_and(|user| user.private),
+            community.is_muted(user_id),
+            community.user_name(user_id),
+        )
+    };
+
+    let mut actions: Vec<(&str, RelationshipAction)> = Vec::new();
+    match relationship {
+        Relationship::Myself => {}
+        Relationship::Following => {
+            actions.push((tr_noop("Unfollow"), RelationshipAction::Unfollow))
+        }
+        Relationship::Requested => actions.push((
+            tr_noop("Cancel Follow Request"),
+            RelationshipAction::Unfollow,
+        )),
+        Relationship::NotFollowing if private => {
+            actions.push((tr_noop("🔒 Request to Follow"), RelationshipAction::Follow))
+        }
+        Relationship::NotFollowing => {
+            actions.push((tr_noop("➕ Follow"), RelationshipAction::Follow))
+        }
+        Relationship::Blocked => actions.push((tr_noop("Unblock"), RelationshipAction::Unblock)),
+    }
+    if !matches!(relationship, Relationship::Myself | Relationship::Blocked) {
+        if muted {
+            actions.push((tr_noop("🔊 Unmute"), RelationshipAction::Unmute));
+        } else {
+            actions.push((tr_noop("🔇 Mute"), RelationshipAction::Mute));
+        }
+        actions.push((tr_noop("🚫 Block"), RelationshipAction::Block));
+    }
+
+    for (label, action) in actions {
+        let button = Button::with_label(&tr(label));
+        button.add_css_class("flat");
+        button.set_halign(gtk4::Align::Fill);
+        if action == RelationshipAction::Block {
+            button.add_css_class("destructive-action");
+        }
+        let state = state.clone();
+        let user_id = user_id.to_string();
+        let name = name.clone();
+        let popover_clone = popover.clone();
+        button.connect_clicked(move |button| {
+            popover_clone.popdown();
+            if action == RelationshipAction::Block {
+                confirm_block(button, &state, &user_id, &name);
+            } else {
+                apply_relationship_action(&state, &user_id, action);
+            }
+        });
+        items.append(&button);
+    }
+
+    popover.set_child(Some(&items));
+    menu_btn.set_popover(Some(&popover));
+    menu_btn.set_visible(relationship != Relationship::Myself);
+    menu_btn
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RelationshipAction {
+    Follow,
+    Unfollow,
+    Mute,
+    Unmute,
+    Block,
+    Unblock,
+}
+
+pub fn apply_relationship_action(state: &Rc<AppState>, user_id: &str, action: RelationshipAction) {
+    {
+        let mut community = state.community.borrow_mut();
+        match action {
+            RelationshipAction::Follow => {
+                community.follow(user_id);
+            }
+            RelationshipAction::Unfollow => community.unfollow(user_id),
+            RelationshipAction::Mute => community.mute(user_id),
+            RelationshipAction::Unmute => community.unmute(user_id),
+            RelationshipAction::Block => community.block(user_id),
+            RelationshipAction::Unblock => community.unblock(user_id),
+        }
+    }
+    state.community_changed.emit();
+    state.feed_changed.emit();
+}
+
+fn confirm_block(parent: &impl IsA<gtk4::Widget>, state: &Rc<AppState>, user_id: &str, name: &str) {
+    let dialog = gtk4::AlertDialog::builder()
+        .message(tr_with("Block {name}?", &[("name", name)]))
+        .detail(tr(
+            "You won't see each other's posts, comments or messages, \
+             and any follows between you are removed.",
+        ))
+        .buttons([tr("Cancel"), tr("Block")])
+        .cancel_button(0)
+        .default_button(0)
+        .modal(true)
+        .build();
+    let window = parent.root().and_downcast::<gtk4::Window>();
+    let state = state.clone();
+    let user_id = user_id.to_string();
+    dialog.choose(window.as_ref(), gio::Cancellable::NONE, move |result| {
+        if result == Ok(1) {
+            apply_relationship_action(&state, &user_id, RelationshipAction::Block);
+        }
+    });
+}
+
+/// Pending follow requests with accept and decline buttons; hidden when
+/// there are none.
+pub fn create_follow_requests(state: &Rc<AppState>) -> GtkBox {
+    let requests_box = GtkBox::new(Orientation::Vertical, 12);
+
+    let populate = {
+        let state = state.clone();
+        let requests_box = requests_box.clone();
+        move || {
+            while let Some(child) = requests_box.first_child() {
+                requests_box.remove(&child);
+            }
+            let community = state.community.borrow();
+            let requests = community.incoming_requests();
+            requests_box.set_visible(!requests.is_empty());
+            if requests.is_empty() {
+                return;
+            }
+
+            let title = Label::new(Some(&tr_with(
+                "Follow Requests ({count})",
+                &[("count", &i18n::number(requests.len()))],
+            )));
+            title.set_halign(gtk4::Align::Start);
+            title.add_css_class("section-subtitle");
+            requests_box.append(&title);
+
+            let now = util::now();
+            for request in requests {
+                let frame = Frame::new(None);
+                frame.add_css_class("notification-item");
+                frame.add_css_class("notification-unread");
+                let row = GtkBox::new(Orientation::Horizontal, 12);
+                row.set_margin_start(16);
+                row.set_margin_end(16);
+                row.set_margin_top(12);
+                row.set_margin_bottom(12);
+
+                let user = community.user(&request.from);
+                row.append(&create_avatar(user, "chat-item-avatar", 36));
+                let text = Label::new(Some(&tr_with(
+                    "{name} wants to follow you · {time}",
+                    &[
+                        ("name", &community.user_name(&request.from)),
+                        (
+                            "time",
+                            &util::format_relative_time(request.requested_at, now),
+                        ),
+                    ],
+                )));
+                text.set_halign(gtk4::Align::Start);
+                text.set_hexpand(true);
+                text.set_wrap(true);
+                row.append(&text);
+
+                let accept_btn = Button::with_label(&tr("Accept"));
+                accept_btn.add_css_class("create-post-button");
+                let decline_btn = Button::with_label(&tr("Decline"));
+                decline_btn.add_css_class("secondary-button");
+                for (button, accept) in [(&accept_btn, true), (&decline_btn, false)] {
+                    let state = state.clone();
+                    let from = request.from.clone();
+                    button.connect_clicked(move |_| {
+                        {
+                            let mut community = state.community.borrow_mut();
+                            if accept {
+                                community.accept_request(&from);
+                            } else {
+                                community.decline_request(&from);
+                            }
+                        }
+                        state.community_changed.emit();
+                    });
+                }
+                row.append(&accept_btn);
+                row.append(&decline_btn);
+
+                frame.set_child(Some(&row));
+                requests_box.append(&frame);
+            }
+        }
+    };
+    populate();
+    state.community_changed.connect(populate);
+
+    requests_box
+}
+
+// ============================================================================
+// Activity timeline
+// ============================================================================
+
+/// A user's activity, a page at a time. The signed-in user's own items each
+/// get a visibility setting.
+pub fn create_activity_timeline(state: &Rc<AppState>, user_id: &str) -> GtkBox {
+    let timeline_box = GtkBox::new(Orientation::Vertical, 12);
+
+    let activity_label = Label::new(Some(&tr("Recent Activity")));
+    activity_label.add_css_class("section-subtitle");
+    activity_label.set_halign(gtk4::Align::Start);
+    activity_label.set_margin_top(20);
+    timeline_box.append(&activity_label);
+
+    let items_box = GtkBox::new(Orientation::Vertical, 12);
+    timeline_box.append(&items_box);
+
+    let pager = GtkBox::new(Orientation::Horizontal, 12);
+    pager.set_halign(gtk4::Align::Center);
+    let newer_btn = Button::with_label(&tr("‹ Newer"));
+    newer_btn.add_css_class("secondary-button");
+    let page_label = Label::new(None);
+    page_label.add_css_class("activity-time");
+    let older_btn = Button::with_label(&tr("Older ›"));
+    older_btn.add_css_class("secondary-button");
+    pager.append(&newer_btn);
+    pager.append(&page_label);
+    pager.append(&older_btn);
+    timeline_box.append(&pager);
+
+    let page = Rc::new(Cell::new(0usize));
+    let populate: Rc<dyn Fn()> = {
+        let state = state.clone();
+        let user_id = user_id.to_string();
+        let page = page.clone();
+        let newer_btn = newer_btn.clone();
+        let older_btn = older_btn.clone();
+        Rc::new(move || {
+            while let Some(child) = items_box.first_child() {
+                items_box.remove(&child);
+            }
+            let items = activity::timeline(&state, &user_id);
+            let pages = items.len().div_ceil(activity::PAGE_SIZE).max(1);
+            page.set(page.get().min(pages - 1));
+
+            let now = util::now();
+            for item in items
+                .iter()
+                .skip(page.get() * activity::PAGE_SIZE)
+                .take(activity::PAGE_SIZE)
+            {
+                items_box.append(&create_activity_item(&state, item, now, user_id == ME));
+            }
+            if items.is_empty() {
+                let empty = Label::new(Some(&if user_id == ME {
+                    tr("Nothing here yet — your posts, comments, group joins and shares will show up here.")
+                } else {
+                    tr("No posts or comments yet.")
+                }));
+                empty.set_wrap(true);
+                empty.add_css_class("share-empty");
+                items_box.append(&empty);
+            }
+
+            page_label.set_text(&tr_with(
+                "Page {page} of {pages}",
+                &[
+                    ("page", &i18n::number(page.get() + 1)),
+                    ("pages", &i18n::number(pages)),
+                ],
+            ));
+            newer_btn.set_sensitive(page.get() > 0);
+            older_btn.set_sensitive(page.get() + 1 < pages);
+            pager.set_visible(pages > 1);
+        })
+    };
+
+    let (p, pg) = (populate.clone(), page.clone());
+    newer_btn.connect_clicked(move |_| {
+        pg.set(pg.get().saturating_sub(1));
+        p();
+    });
+    let (p, pg) = (populate.clone(), page);
+    older_btn.connect_clicked(move |_| {
+        pg.set(pg.get() + 1);
+        p();
+    });
+    navigation::refresh_while_mapped(&timeline_box, state, populate);
+
+    timeline_box
+}
+
+fn create_activity_item(state: &Rc<AppState>, item: &Activity, now: i64, editable: bool) -> Frame {
+    let frame = Frame::new(None);
+    frame.add_css_class("activity-item");
+
+    let item_box = GtkBox::new(Orientation::Horizontal, 16);
+    item_box.set_margin_start(16);
+    item_box.set_margin_end(16);
+    item_box.set_margin_top(12);
+    item_box.set_margin_bottom(12);
+
+    let icon_label = Label::new(Some(item.kind.icon()));
+    icon_label.add_css_class("activity-icon");
+
+    let activity_label = Label::new(None);
+    sizing::set_markup(&activity_label, &util::linkify_markup(&item.text));
+    link_sheet::connect_links(&activity_label, state);
+    activity_label.set_halign(gtk4::Align::Start);
+    activity_label.set_hexpand(true);
+    activity_label.set_wrap(true);
+    activity_label.set_xalign(0.0);
+    activity_label.add_css_class("activity-text");
+
+    let time_label = Label::new(Some(&util::format_relative_time(item.timestamp, now)));
+    time_label.add_css_class("activity-time");
+
+    item_box.append(&icon_label);
+    item_box.append(&activity_label);
+    item_box.append(&time_label);
+    frame.set_child(Some(&item_box));
+    if !editable {
+        return frame;
+    }
+
+    let labels: Vec<String> = Visibility::ALL.iter().map(|v| tr(v.label())).collect();
+    let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+    let visibility = DropDown::from_strings(&labels);
+    visibility.set_tooltip_text(Some(&tr("Who can see this on your profile")));
+    visibility.set_valign(gtk4::Align::Center);
+    let current = state.activity_privacy.borrow().visibility(&item.key);
+    let index = Visibility::ALL
+        .iter()
+        .position(|v| *v == current)
+        .unwrap_or(0);
+    visibility.set_selected(index as u32);
+    let state = state.clone();
+    let key = item.key.clone();
+    visibility.connect_selected_notify(move |dropdown| {
+        if let Some(chosen) = Visibility::ALL.get(dropdown.selected() as usize) {
+            state
+                .activity_privacy
+                .borrow_mut()
+                .set_visibility(&key, *chosen);
+        }
+    });
+
+    item_box.append(&visibility);
+    frame
+}
+
+// ============================================================================
+// Profile editor
+// ============================================================================
+
+struct SkillRow {
+    row: GtkBox,
+    name: Entry,
+    level: DropDown,
+}
+
+struct LinkRow {
+    row: GtkBox,
+    label: Entry,
+    url: Entry,
+}
+
+struct ProfileEditor {
+    state: Rc<AppState>,
+    window: gtk4::Window,
+    avatar: RefCell<Option<PathBuf>>,
+    cover: RefCell<Option<PathBuf>>,
+    /// Images cropped in this session, removed again if left unused.
+    created: RefCell<Vec<PathBuf>>,
+    saved: Cell<bool>,
+    avatar_slot: GtkBox,
+    cover_slot: GtkBox,
+    skills_box: GtkBox,
+    skills: RefCell<Vec<SkillRow>>,
+    links_box: GtkBox,
+    links: RefCell<Vec<LinkRow>>,
+}
+
+pub fn open_profile_editor(parent: &impl IsA<gtk4::Widget>, state: &Rc<AppState>) {
+    let profile = state.profile.borrow().clone();
+
+    let window = gtk4::Window::builder()
+        .title(tr("Edit Profile"))
+        .modal(true)
+        .default_width(560)
+        .default_height(720)
+        .build();
+    window.add_css_class("share-sheet");
+    if let Some(parent) = parent.root().and_downcast::<gtk4::Window>() {
+        window.set_transient_for(Some(&parent));
+    }
+
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_start(24);
+    content.set_margin_end(24);
+    content.set_margin_top(24);
+    content.set_margin_bottom(24);
+
+    let title = Label::new(Some(&tr("✏️ Edit Profile")));
+    title.set_halign(gtk4::Align::Start);
+    title.add_css_class("dialog-title");
+    content.append(&title);
+
+    let editor = Rc::new(ProfileEditor {
+        state: state.clone(),
+        window: window.clone(),
+        avatar: RefCell::new(profile.avatar.clone()),
+        cover: RefCell::new(profile.cover.clone()),
+        created: RefCell::new(Vec::new()),
+        saved: Cell::new(false),
+        avatar_slot: GtkBox::new(Orientation::Horizontal, 0),
+        cover_slot: GtkBox::new(Orientation::Horizontal, 0),
+        skills_box: GtkBox::new(Orientation::Vertical, 8),
+        skills: RefCell::new(Vec::new()),
+        links_box: GtkBox::new(Orientation::Vertical, 8),
+        links: RefCell::new(Vec::new()),
+    });
+
+    // Images
+    content.append(&form_label(&tr("Photo")));
+    let avatar_row = GtkBox::new(Orientation::Horizontal, 12);
+    avatar_row.append(&editor.avatar_slot);
+    let change_avatar_btn = Button::with_label(&tr("Change Photo…"));
+    change_avatar_btn.add_css_class("action-button");
+    change_avatar_btn.set_valign(gtk4::Align::Center);
+    let remove_avatar_btn = Button::with_label(&tr("Remove"));
+    remove_avatar_btn.add_css_class("secondary-button");
+    remove_avatar_btn.set_valign(gtk4::Align::Center);
+    avatar_row.append(&change_avatar_btn);
+    avatar_row.append(&remove_avatar_btn);
+    content.append(&avatar_row);
+
+    content.append(&form_label(&tr("Cover image")));
+    editor.cover_slot.set_hexpand(true);
+    content.append(&editor.cover_slot);
+    let cover_buttons = GtkBox::new(Orientation::Horizontal, 12);
+    let change_cover_btn = Button::with_label(&tr("Change Cover…"));
+    change_cover_btn.add_css_class("action-button");
+    let remove_cover_btn = Button::with_label(&tr("Remove"));
+    remove_cover_btn.add_css_class("secondary-button");
+    cover_buttons.append(&change_cover_btn);
+    cover_buttons.append(&remove_cover_btn);
+    content.append(&cover_buttons);
+    editor.update_images();
+
+    // Text fields
+    content.append(&form_label(&tr("Display name")));
+    let name_entry = Entry::new();
+    name_entry.set_text(&profile.display_name);
+    name_entry.set_max_length(profile::MAX_NAME_LENGTH as i32);
+    content.append(&name_entry);
+
+    content.append(&form_label(&tr("Headline")));
+    let headline_entry = Entry::new();
+    headline_entry.set_text(&profile.headline);
+    headline_entry.set_placeholder_text(Some(&tr("e.g. Backend engineer and Rust mentor")));
+    headline_entry.set_max_length(profile::MAX_HEADLINE_LENGTH as i32);
+    content.append(&headline_entry);
+
+    let bio_label = form_label(&tr("Bio"));
+    content.append(&bio_label);
+    let bio_view = TextView::new();
+    bio_view.set_wrap_mode(gtk4::WrapMode::WordChar);
+    sizing::set_size_request(&bio_view, -1, 100);
+    bio_view.buffer().set_text(&profile.bio);
+    let bio_frame = Frame::new(None);
+    bio_frame.add_css_class("post-text-area");
+    bio_frame.set_child(Some(&bio_view));
+    content.append(&bio_frame);
+    let update_bio_label = move |buffer: &gtk4::TextBuffer| {
+        bio_label.set_text(&tr_with(
+            "Bio ({count}/{max})",
+            &[
+                ("count", &i18n::number(buffer.char_count())),
+                ("max", &i18n::number(profile::MAX_BIO_LENGTH)),
+            ],
+        ));
+    };
+    update_bio_label(&bio_view.buffer());
+    bio_view.buffer().connect_changed(update_bio_label);
+
+    // Skills
+    content.append(&form_label(&tr("Skills")));
+    content.append(&editor.skills_box);
+    for skill in &profile.skills {
+        editor.add_skill_row(Some(skill));
+    }
+    let add_skill_btn = Button::with_label(&tr("➕ Add Skill"));
+    add_skill_btn.add_css_class("secondary-button");
+    add_skill_btn.set_halign(gtk4::Align::Start);
+    content.append(&add_skill_btn);
+
+    // Links
+    content.append(&form_label(&tr("Links")));
+    content.append(&editor.links_box);
+    for link in &profile.links {
+        editor.add_link_row(Some(link));
+    }
+    let add_link_btn = Button::with_label(&tr("➕ Add Link"));
+    add_link_btn.add_css_class("secondary-button");
+    add_link_btn.set_halign(gtk4::Align::Start);
+    content.append(&add_link_btn);
+
+    content.append(&form_label(&tr("Time zone")));
+    let timezone_entry = Entry::new();
+    timezone_entry.set_text(&profile.timezone);
+    timezone_entry.set_placeholder_text(Some(&tr("e.g. Europe/Berlin or America/New_York")));
+    content.append(&timezone_entry);
+
+    content.append(&form_label(&tr("Languages")));
+    let languages_entry = Entry::new();
+    languages_entry.set_text(&profile.languages.join(", "));
+    languages_entry.set_placeholder_text(Some(&tr("Comma-separated, e.g. English, Spanish")));
+    content.append(&languages_entry);
+
+    let private_check =
+        gtk4::CheckButton::with_label(&tr("🔒 Private profile — approve new followers"));
+    private_check.set_active(
+        state
+            .community
+            .borrow()
+            .user(ME)
+            .is_some_and(|me| me.private),
+    );
+    private_check.set_margin_top(8);
+    content.append(&private_check);
+
+    let error_label = Label::new(None);
+    error_label.set_halign(gtk4::Align::Start);
+    error_label.set_wrap(true);
+    error_label.add_css_class("form-error");
+    error_label.set_visible(false);
+    content.append(&error_label);
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 12);
+    buttons.set_halign(gtk4::Align::End);
+    let cancel_btn = Button::with_label(&tr("Cancel"));
+    cancel_btn.add_css_class("secondary-button");
+    let save_btn = Button::with_label(&tr("Save"));
+    save_btn.add_css_class("create-post-button");
+    buttons.append(&cancel_btn);
+    buttons.append(&save_btn);
+    content.append(&buttons);
+
+    let scroll = ScrolledWindow::new();
+    scroll.set_hscrollbar_policy(gtk4::PolicyType::Never);
+    scroll.set_child(Some(&content));
+    window.set_child(Some(&scroll));
+
+    let e = editor.clone();
+    change_avatar_btn.connect_clicked(move |_| e.choose_image(true));
+    let e = editor.clone();
+    remove_avatar_btn.connect_clicked(move |_| {
+        e.avatar.replace(None);
+        e.update_images();
+    });
+    let e = editor.clone();
+    change_cover_btn.connect_clicked(move |_| e.choose_image(false));
+    let e = editor.clone();
+    remove_cover_btn.connect_clicked(move |_| {
+        e.cover.replace(None);
+        e.update_images();
+    });
+    let e = editor.clone();
+    add_skill_btn.connect_clicked(move |_| e.add_skill_row(None));
+    let e = editor.clone();
+    add_link_btn.connect_clicked(move |_| e.add_link_row(None));
+
+    let window_clone = window.clone();
+    cancel_btn.connect_clicked(move |_| window_clone.close());
+
+    let e = editor.clone();
+    save_btn.connect_clicked(move |_| {
+        let buffer = bio_view.buffer();
+        let edited = Profile {
+            display_name: name_entry.text().trim().to_string(),
+            headline: headline_entry.text().trim().to_string(),
+            bio: buffer
+                .text(&buffer.start_iter(), &buffer.end_iter(), false)
+                .trim()
+                .to_string(),
+            avatar: e.avatar.borrow().clone(),
+            cover: e.cover.borrow().clone(),
+            skills: e.collect_skills(),
+            links: e.collect_links(),
+            timezone: timezone_entry.text().trim().to_string(),
+            languages: languages_entry
+                .text()
+                .split(',')
+                .map(|language| language.trim().to_string())
+                .filter(|language| !language.is_empty())
+                .collect(),
+        };
+        if let Err(err) = edited.validate() {
+            error_label.set_text(&err);
+            error_label.set_visible(true);
+            return;
+        }
+
+        e.state.community.borrow_mut().update_me(
+            &edited.display_name,
+            edited.avatar.clone(),
+            private_check.is_active(),
+        );
+        e.state.profile.borrow_mut().replace(edited);
+        e.saved.set(true);
+        e.state.profile_changed.emit();
+        e.state.community_changed.emit();
+        e.state.feed_changed.emit();
+        e.window.close();
+    });
+
+    let e = editor;
+    window.connect_close_request(move |_| {
+        // Drop crops that didn't make it into the saved profile
+        let profile = e.state.profile.borrow();
+        for path in e.created.borrow().iter() {
+            if !e.saved.get()
+                || (profile.avatar.as_ref() != Some(path) && profile.cover.as_ref() != Some(path))
+            {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+        glib::Propagation::Proceed
+    });
+
+    window.present();
+}
+
+impl ProfileEditor {
+    fn update_images(&self) {
+        while let Some(child) = self.avatar_slot.first_child() {
+            self.avatar_slot.remove(&child);
+        }
+        let avatar = match self.avatar.borrow().as_ref() {
+            Some(path) => create_avatar_image(path, "editor-avatar", 72),
+            None => create_avatar(
+                self.state.community.borrow().user(ME),
+                "create-post-avatar",
+                72,
+            ),
+        };
+        self.avatar_slot.append(&avatar);
+
+        while let Some(child) = self.cover_slot.first_child() {
+            self.cover_slot.remove(&child);
+        }
+        let cover: gtk4::Widget = match self.cover.borrow().as_ref() {
+            Some(path) => {
+                let picture = Picture::for_filename(path);
+                picture.set_content_fit(gtk4::ContentFit::Cover);
+                picture.upcast()
+            }
+            None => Label::new(Some(&tr("No cover image"))).upcast(),
+        };
+        cover.set_hexpand(true);
+        cover.set_height_request(COVER_PREVIEW_HEIGHT);
+        cover.set_overflow(gtk4::Overflow::Hidden);
+        cover.add_css_class("profile-cover");
+        self.cover_slot.append(&cover);
+    }
+
+    fn choose_image(self: &Rc<Self>, avatar: bool) {
+        let filter = gtk4::FileFilter::new();
+        filter.set_name(Some(&tr("Images")));
+        filter.add_pixbuf_formats();
+        let filters = gio::ListStore::new::<gtk4::FileFilter>();
+        filters.append(&filter);
+        let dialog = gtk4::FileDialog::builder()
+            .title(if avatar {
+                tr("Choose a Photo")
+            } else {
+                tr("Choose a Cover Image")
+            })
+            .filters(&filters)
+            .modal(true)
+            .build();
+
+        let editor = self.clone();
+        dialog.open(Some(&self.window), gio::Cancellable::NONE, move |result| {
+            let Some(path) = result.ok().and_then(|file| file.path()) else {
+                return;
+            };
+            let pixbuf = match profile::load_image(&path) {
+                Ok(pixbuf) => pixbuf,
+                Err(err) => {
+                    show_error(
+                        &editor.window,
+                        &tr_with("Could not open the image: {error}", &[("error", &err)]),
+                    );
+                    return;
+                }
+            };
+            let (aspect, width, height, name) = if avatar {
+                (1.0, profile::AVATAR_SIZE, profile::AVATAR_SIZE, "avatar")
+            } else {
+                (
+                    f64::from(profile::COVER_WIDTH) / f64::from(profile::COVER_HEIGHT),
+                    profile::COVER_WIDTH,
+                    profile::COVER_HEIGHT,
+                    "cover",
+                )
+            };
+            let e = editor.clone();
+            let source = pixbuf.clone();
+            open_crop_dialog(&editor.window, pixbuf, aspect, move |crop| {
+                match profile::save_cropped_image(&source, crop, width, height, name) {
+                    Ok(path) => {
+                        e.created.borrow_mut().push(path.clone());
+                        if avatar {
+                            e.avatar.replace(Some(path));
+                        } else {
+                            e.cover.replace(Some(path));
+                        }
+                        e.update_images();
+                    }
+                    Err(err) => show_error(
+                        &e.window,
+                        &tr_with("Could not save the image: {error}", &[("error", &err)]),
+                    ),
+                }
+            });
+        });
+    }
+
+    fn add_skill_row(self: &Rc<Self>, skill: Option<&Skill>) {
+        let row = GtkBox::new(Orientation::Horizontal, 8);
+        let name = Entry::new();
+        name.set_placeholder_text(Some(&tr("Skill")));
+        name.set_hexpand(true);
+        let levels: Vec<String> = Proficiency::ALL
+            .iter()
+            .map(|level| tr(level.label()))
+            .collect();
+        let levels: Vec<&str> = levels.iter().map(String::as_str).collect();
+        let level = DropDown::from_strings(&levels);
+        level.set_selected(1);
+        if let Some(skill) = skill {
+            name.set_text(&skill.name);
+            let index = Proficiency::ALL
+                .iter()
+                .position(|level| *level == skill.level)
+                .unwrap_or(1);
+            level.set_selected(index as u32);
+        }
+        let remove_btn = Button::with_label("✕");
+        remove_btn.add_css_class("secondary-button");
+        row.append(&name);
+        row.append(&level);
+        row.append(&remove_btn);
+        self.skills_box.append(&row);
+
+        let editor = Rc::downgrade(self);
+        let row_clone = row.clone();
+        remove_btn.connect_clicked(move |_| {
+            if let Some(editor) = editor.upgrade() {
+                editor.skills_box.remove(&row_clone);
+                editor
+                    .skills
+                    .borrow_mut()
+                    .retain(|skill| skill.row != row_clone);
+            }
+        });
+        self.skills.borrow_mut().push(SkillRow { row, name, level });
+    }
+
+    fn add_link_row(self: &Rc<Self>, link: Option<&ProfileLink>) {
+        let row = GtkBox::new(Orientation::Horizontal, 8);
+        let label = Entry::new();
+        label.set_placeholder_text(Some(&tr("Label (e.g. GitHub)")));
+        let url = Entry::new();
+        url.set_placeholder_text(Some("https://"));
+        url.set_hexpand(true);
+        if let Some(link) = link {
+            label.set_text(&link.label);
+            url.set_text(&link.url);
+        }
+        let remove_btn = Button::with_label("✕");
+        remove_btn.add_css_class("secondary-button");
+        row.append(&label);
+        row.append(&url);
+        row.append(&remove_btn);
+        self.links_box.append(&row);
+
+        let editor = Rc::downgrade(self);
+        let row_clone = row.clone();
+        remove_btn.connect_clicked(move |_| {
+            if let Some(editor) = editor.upgrade() {
+                editor.links_box.remove(&row_clone);
+                editor
+                    .links
+                    .borrow_mut()
+                    .retain(|link| link.row != row_clone);
+            }
+        });
+        self.links.borrow_mut().push(LinkRow { row, label, url });
+    }
+
+    /// Skills from the rows, ignoring rows left blank.
+    fn collect_skills(&self) -> Vec<Skill> {
+        self.skills
+            .borrow()
+            .iter()
+            .map(|row| Skill {
+                name: row.name.text().trim().to_string(),
+                level: Proficiency::ALL
+                    .get(row.level.selected() as usize)
+                    .copied()
+                    .unwrap_or(Proficiency::Intermediate),
+            })
+            .filter(|skill| !skill.name.is_empty())
+            .collect()
+    }
+
+    fn collect_links(&self) -> Vec<ProfileLink> {
+        self.links
+            .borrow()
+            .iter()
+            .map(|row| ProfileLink {
+                label: row.label.text().trim().to_string(),
+                url: row.url.text().trim().to_string(),
+            })
+            .filter(|link| !link.label.is_empty() || !link.url.is_empty())
+            .collect()
+    }
+}
+
+// ============================================================================
+// Cropping
+// ============================================================================
+
+/// Crop rectangle in image pixels, kept at a fixed aspect ratio.
+#[derive(Clone, Copy)]
+struct Crop {
+    center_x: f64,
+    center_y: f64,
+    /// 1.0 is the largest rectangle that fits; larger values zoom in.
+    zoom: f64,
+}
+
+impl Crop {
+    fn size(&self, image_width: f64, image_height: f64, aspect: f64) -> (f64, f64) {
+        let width = image_width.min(image_height * aspect) / self.zoom;
+        (width, width / aspect)
+    }
+
+    /// Keeps the rectangle inside the image.
+    fn clamp(mut self, image_width: f64, image_height: f64, aspect: f64) -> Self {
+        let (width, height) = self.size(image_width, image_height, aspect);
+        self.center_x = self.center_x.clamp(width / 2.0, image_width - width / 2.0);
+        self.center_y = self
+            .center_y
+            .clamp(height / 2.0, image_height - height / 2.0);
+        self
+    }
+}
+
+/// Lets the user drag and zoom a crop rectangle over `pixbuf`; `on_crop`
+/// gets `(x, y, width, height)` in image pixels.
+fn open_crop_dialog(
+    parent: &gtk4::Window,
+    pixbuf: gdk_pixbuf::Pixbuf,
+    aspect: f64,
+    on_crop: impl Fn((i32, i32, i32, i32)) + 'static,
+) {
+    let window = gtk4::Window::builder()
+        .title(tr("Crop Image"))
+        .modal(true)
+        .transient_for(parent)
+        .build();
+    window.add_css_class("share-sheet");
+
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_start(24);
+    content.set_margin_end(24);
+    content.set_margin_top(24);
+    content.set_margin_bottom(24);
+
+    let title = Label::new(Some(&tr("Crop Image")));
+    title.set_halign(gtk4::Align::Start);
+    title.add_css_class("dialog-title");
+    content.append(&title);
+    let hint = Label::new(Some(&tr("Drag to move the frame, use the slider to zoom.")));
+    hint.set_halign(gtk4::Align::Start);
+    hint.add_css_class("upload-hint");
+    content.append(&hint);
+
+    let image_width = f64::from(pixbuf.width());
+    let image_height = f64::from(pixbuf.height());
+    let crop = Rc::new(Cell::new(Crop {
+        center_x: image_width / 2.0,
+        center_y: image_height / 2.0,
+        zoom: 1.0,
+    }));
+    // Image-to-widget scale and offset, as last drawn
+    let fit = Rc::new(Cell::new((1.0, 0.0, 0.0)));
+
+    let area = DrawingArea::new();
+    area.set_content_width(CROP_AREA_WIDTH);
+    area.set_content_height(CROP_AREA_HEIGHT);
+    area.set_cursor_from_name(Some("move"));
+    let crop_clone = crop.clone();
+    let fit_clone = fit.clone();
+    area.set_draw_func(move |_, cr, width, height| {
+        let scale = (f64::from(width) / image_width).min(f64::from(height) / image_height);
+        let offset_x = (f64::from(width) - image_width * scale) / 2.0;
+        let offset_y = (f64::from(height) - image_height * scale) / 2.0;
+        fit_clone.set((scale, offset_x, offset_y));
+
+        cr.save().ok();
+        cr.translate(offset_x, offset_y);
+        cr.scale(scale, scale);
+        cr.set_source_pixbuf(&pixbuf, 0.0, 0.0);
+        cr.paint().ok();
+        cr.restore().ok();
+
+        let current = crop_clone.get();
+        let (crop_width, crop_height) = current.size(image_width, image_height, aspect);
+        let x = offset_x + (current.center_x - crop_width / 2.0) * scale;
+        let y = offset_y + (current.center_y - crop_height / 2.0) * scale;
+        let (w, h) = (crop_width * scale, crop_height * scale);
+
+        // Dim everything outside the crop
+        cr.set_fill_rule(cairo::FillRule::EvenOdd);
+        cr.rectangle(0.0, 0.0, f64::from(width), f64::from(height));
+        cr.rectangle(x, y, w, h);
+        cr.set_source_rgba(0.0, 0.0, 0.0, 0.55);
+        cr.fill().ok();
+
+        cr.set_source_rgba(1.0, 1.0, 1.0, 0.9);
+        cr.set_line_width(2.0);
+        cr.rectangle(x, y, w, h);
+        cr.stroke().ok();
+        if aspect == 1.0 {
+            // Avatars are shown round
+            cr.arc(
+                x + w / 2.0,
+                y + h / 2.0,
+                w / 2.0,
+                0.0,
+                std::f64::consts::TAU,
+            );
+            cr.set_dash(&[4.0, 4.0], 0.0);
+            cr.stroke().ok();
+        }
+    });
+    content.append(&area);
+
+    let drag = gtk4::GestureDrag::new();
+    let start = Rc::new(Cell::new((0.0, 0.0)));
+    let crop_clone = crop.clone();
+    let start_clone = start.clone();
+    drag.connect_drag_begin(move |_, _, _| {
+        let current = crop_clone.get();
+        start_clone.set((current.center_x, current.center_y));
+    });
+    let crop_clone = crop.clone();
+    let area_clone = area.clone();
+    drag.connect_drag_update(move |_, dx, dy| {
+        let (scale, _, _) = fit.get();
+        let (start_x, start_y) = start.get();
+        let moved = Crop {
+            center_x: start_x + dx / scale,
+            center_y: start_y + dy / scale,
+            ..crop_clone.get()
+        };
+        crop_clone.set(moved.clamp(image_width, image_height, aspect));
+        area_clone.queue_draw();
+    });
+    area.add_controller(drag);
+
+    let zoom = gtk4::Scale::with_range(Orientation::Horizontal, 1.0, 4.0, 0.05);
+    zoom.set_draw_value(false);
+    let crop_clone = crop.clone();
+    let area_clone = area.clone();
+    zoom.connect_value_changed(move |zoom| {
+        let zoomed = Crop {
+            zoom: zoom.value(),
+            ..crop_clone.get()
+        };
+        crop_clone.set(zoomed.clamp(image_width, image_height, aspect));
+        area_clone.queue_draw();
+    });
+    content.append(&form_label(&tr("Zoom")));
+    content.append(&zoom);
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 12);
+    buttons.set_halign(gtk4::Align::End);
+    let cancel_btn = Button::with_label(&tr("Cancel"));
+    cancel_btn.add_css_class("secondary-button");
+    let apply_btn = Button::with_label(&tr("Apply"));
+    apply_btn.add_css_class("create-post-button");
+    buttons.append(&cancel_btn);
+    buttons.append(&apply_btn);
+    content.append(&buttons);
+    window.set_child(Some(&content));
+
+    let window_clone = window.clone();
+    cancel_btn.connect_clicked(move |_| window_clone.close());
+
+    let window_clone = window.clone();
+    apply_btn.connect_clicked(move |_| {
+        let current = crop.get();
+        let (width, height) = current.size(image_width, image_height, aspect);
+        on_crop((
+            (current.center_x - width / 2.0).round() as i32,
+            (current.center_y - height / 2.0).round() as i32,
+            width.round() as i32,
+            height.round() as i32,
+        ));
+        window_clone.close();
+    });
+
+    window.present();
+}
+
+fn show_error(parent: &gtk4::Window, message: &str) {
+    gtk4::AlertDialog::builder()
+        .message(message)
+        .modal(true)
+        .build()
+        .show(Some(parent));
+}
+
+fn form_label(text: &str) -> Label {
+    let label = Label::new(Some(text));
+    label.set_halign(gtk4::Align::Start);
+    label.add_css_class("form-label");
+    label
+}
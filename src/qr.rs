@@ -0,0 +1,621 @@
+// QR codes (ISO/IEC 18004), for showing two-factor secrets to phones
+//
+// Only what that needs: byte mode, error correction level M and versions
+// 1–10, which hold up to 213 bytes. The mask is chosen by the standard's
+// penalty rules.
+
+/// Per version 1–10: total codewords, error correction codewords per
+/// block and number of blocks, at level M.
+const VERSIONS: [(usize, usize, usize); 10] = [
+    (26, 10, 1),
+    (44, 16, 1),
+    (70, 26, 1),
+    (100, 18, 2),
+    (134, 24, 2),
+    (172, 16, 4),
+    (196, 18, 4),
+    (242, 22, 4),
+    (292, 22, 5),
+    (346, 26, 5),
+];
+
+/// Centers of the alignment patterns, per version.
+const ALIGNMENT: [&[usize]; 10] = [
+    &[],
+    &[6, 18],
+    &[6, 22],
+    &[6, 26],
+    &[6, 30],
+    &[6, 34],
+    &[6, 22, 38],
+    &[6, 24, 42],
+    &[6, 26, 46],
+    &[6, 28, 50],
+];
+
+/// Level M in the format information.
+const EC_LEVEL_BITS: u32 = 0b00;
+
+pub struct QrCode {
+    size: usize,
+    dark: Vec<bool>,
+}
+
+impl QrCode {
+    /// Encodes `data`, or None if it is too long.
+    pub fn encode(data: &[u8]) -> Option<QrCode> {
+        let (version, &(total, ec_len, blocks)) = VERSIONS
+            .iter()
+            .enumerate()
+            .map(|(index, spec)| (index + 1, spec))
+            .find(|(version, (total, ec_len, blocks))| {
+                let count_bits = if *version < 10 { 8 } else { 16 };
+                4 + count_bits + data.len() * 8 <= (total - ec_len * blocks) * 8
+            })?;
+        let codewords = data_codewords(data, version, total - ec_len * blocks);
+        let codewords = add_error_correction(&codewords, total, ec_len, blocks);
+
+        let mut matrix = Matrix::new(version);
+        matrix.draw_function_patterns();
+        matrix.draw_codewords(&codewords);
+        let mask = (0..8)
+            .min_by_key(|&mask| {
+                matrix.apply_mask(mask);
+                matrix.draw_format(mask);
+                let penalty = matrix.penalty();
+                matrix.apply_mask(mask);
+                penalty
+            })
+            .unwrap_or(0);
+        matrix.apply_mask(mask);
+        matrix.draw_format(mask);
+
+        Some(QrCode {
+            size: matrix.size,
+            dark: matrix.dark,
+        })
+    }
+
+    /// Modules per side, without the quiet zone.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.dark[y * self.size + x]
+    }
+}
+
+/// The data bits in byte mode, padded to `capacity` codewords.
+fn data_codewords(data: &[u8], version: usize, capacity: usize) -> Vec<u8> {
+    let mut bits = BitBuffer::default();
+    bits.push(0b0100, 4);
+    bits.push(data.len() as u32, if version < 10 { 8 } else { 16 });
+    for &byte in data {
+        bits.push(u32::from(byte), 8);
+    }
+    let terminator = (capacity * 8 - bits.len).min(4);
+    bits.push(0, terminator);
+    bits.push(0, (8 - bits.len % 8) % 8);
+    let mut codewords = bits.bytes;
+    for pad in [0xec, 0x11].into_iter().cycle() {
+        if codewords.len() >= capacity {
+            break;
+        }
+        codewords.push(pad);
+    }
+    codewords
+}
+
+#[derive(Default)]
+struct BitBuffer {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl BitBuffer {
+    fn push(&mut self, value: u32, count: usize) {
+        for i in (0..count).rev() {
+            if self.len.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if (value >> i) & 1 == 1 {
+                let last = self.bytes.len() - 1;
+                self.bytes[last] |= 0x80 >> (self.len % 8);
+            }
+            self.len += 1;
+        }
+    }
+}
+
+/// Splits the data into blocks, adds Reed-Solomon codewords to each and
+/// interleaves them. The later blocks are a codeword longer when the data
+/// doesn't divide evenly.
+fn add_error_correction(data: &[u8], total: usize, ec_len: usize, blocks: usize) -> Vec<u8> {
+    let short_blocks = blocks - total % blocks;
+    let short_len = total / blocks - ec_len;
+    let divisor = rs_divisor(ec_len);
+
+    let mut data_blocks = Vec::new();
+    let mut ec_blocks = Vec::new();
+    let mut start = 0;
+    for block in 0..blocks {
+        let len = short_len + usize::from(block >= short_blocks);
+        let chunk = &data[start..start + len];
+        start += len;
+        ec_blocks.push(rs_remainder(chunk, &divisor));
+        data_blocks.push(chunk);
+    }
+
+    let mut result = Vec::with_capacity(total);
+    for i in 0..=short_len {
+        for block in &data_blocks {
+            if let Some(&codeword) = block.get(i) {
+                result.push(codeword);
+            }
+        }
+    }
+    for i in 0..ec_len {
+        for block in &ec_blocks {
+            result.push(block[i]);
+        }
+    }
+    result
+}
+
+/// Multiplication in GF(2⁸) modulo x⁸ + x⁴ + x³ + x² + 1.
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut product: u8 = 0;
+    for i in (0..8).rev() {
+        product = (product << 1) ^ ((product >> 7) * 0x1d);
+        product ^= ((y >> i) & 1) * x;
+    }
+    product
+}
+
+/// The generator polynomial of `degree`, leading term omitted.
+fn rs_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0; degree];
+    result[degree - 1] = 1;
+    let mut root: u8 = 1;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_multiply(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+    result
+}
+
+fn rs_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0; divisor.len()];
+    for &byte in data {
+        let factor = byte ^ result.remove(0);
+        result.push(0);
+        for (remainder, &coefficient) in result.iter_mut().zip(divisor) {
+            *remainder ^= gf_multiply(coefficient, factor);
+        }
+    }
+    result
+}
+
+struct Matrix {
+    version: usize,
+    size: usize,
+    dark: Vec<bool>,
+    /// Finder, timing, alignment and format modules, which data and masks
+    /// leave alone.
+    function: Vec<bool>,
+}
+
+impl Matrix {
+    fn new(version: usize) -> Self {
+        let size = version * 4 + 17;
+        Self {
+            version,
+            size,
+            dark: vec![false; size * size],
+            function: vec![false; size * size],
+        }
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.dark[y * self.size + x] = dark;
+        self.function[y * self.size + x] = true;
+    }
+
+    fn draw_function_patterns(&mut self) {
+        let size = self.size;
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+        self.draw_finder(3, 3);
+        self.draw_finder(size - 4, 3);
+        self.draw_finder(3, size - 4);
+
+        let positions = ALIGNMENT[self.version - 1];
+        let last = positions.len().saturating_sub(1);
+        for (i, &x) in positions.iter().enumerate() {
+            for (j, &y) in positions.iter().enumerate() {
+                // Those corners already have finder patterns
+                if (i == 0 && (j == 0 || j == last)) || (i == last && j == 0) {
+                    continue;
+                }
+                for dy in 0..5usize {
+                    for dx in 0..5usize {
+                        let distance = dx.abs_diff(2).max(dy.abs_diff(2));
+                        self.set_function(x + dx - 2, y + dy - 2, distance != 1);
+                    }
+                }
+            }
+        }
+
+        // Reserve the format areas; the real bits go in once the mask is known
+        self.draw_format(0);
+        self.draw_version();
+    }
+
+    /// A finder pattern centered on (`x`, `y`), with its light separator.
+    fn draw_finder(&mut self, x: usize, y: usize) {
+        for dy in -4i32..=4 {
+            for dx in -4i32..=4 {
+                let (Some(px), Some(py)) = (
+                    x.checked_add_signed(dx as isize),
+                    y.checked_add_signed(dy as isize),
+                ) else {
+                    continue;
+                };
+                if px < self.size && py < self.size {
+                    let distance = dx.abs().max(dy.abs());
+                    self.set_function(px, py, distance != 2 && distance != 4);
+                }
+            }
+        }
+    }
+
+    fn draw_format(&mut self, mask: u32) {
+        let data = (EC_LEVEL_BITS << 3) | mask;
+        let mut remainder = data;
+        for _ in 0..10 {
+            remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+        }
+        let bits = ((data << 10) | remainder) ^ 0x5412;
+        let bit = |i: u32| (bits >> i) & 1 == 1;
+        let size = self.size;
+
+        for i in 0..6 {
+            self.set_function(8, i, bit(i as u32));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i as u32));
+        }
+
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i as u32));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i as u32));
+        }
+        // Always dark
+        self.set_function(8, size - 8, true);
+    }
+
+    fn draw_version(&mut self) {
+        if self.version < 7 {
+            return;
+        }
+        let mut remainder = self.version as u32;
+        for _ in 0..12 {
+            remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1f25);
+        }
+        let bits = ((self.version as u32) << 12) | remainder;
+        for i in 0..18 {
+            let dark = (bits >> i) & 1 == 1;
+            let a = self.size - 11 + i % 3;
+            let b = i / 3;
+            self.set_function(a, b, dark);
+            self.set_function(b, a, dark);
+        }
+    }
+
+    /// Places the codewords in the zigzag order of the standard: two
+    /// columns at a time from the right, alternately up and down, skipping
+    /// the vertical timing pattern.
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let mut index = 0;
+        let mut right = size - 1;
+        loop {
+            if right == 6 {
+                right = 5;
+            }
+            for vertical in 0..size {
+                for j in 0..2 {
+                    let x = right - j;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward {
+                        size - 1 - vertical
+                    } else {
+                        vertical
+                    };
+                    if !self.function[y * size + x] && index < codewords.len() * 8 {
+                        self.dark[y * size + x] =
+                            (codewords[index / 8] >> (7 - index % 8)) & 1 == 1;
+                        index += 1;
+                    }
+                }
+            }
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+    }
+
+    /// Flips the data modules `mask` selects; applying it twice undoes it.
+    fn apply_mask(&mut self, mask: u32) {
+        let size = self.size;
+        for y in 0..size {
+            for x in 0..size {
+                let flip = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                if flip && !self.function[y * size + x] {
+                    self.dark[y * size + x] ^= true;
+                }
+            }
+        }
+    }
+
+    /// How hard the symbol is to scan, by the standard's four rules: long
+    /// runs, 2×2 blocks, finder-like patterns and dark/light imbalance.
+    fn penalty(&self) -> usize {
+        let size = self.size;
+        let at = |x: usize, y: usize| self.dark[y * size + x];
+        let mut penalty = 0;
+
+        for transpose in [false, true] {
+            let get = |line: usize, i: usize| if transpose { at(line, i) } else { at(i, line) };
+            for line in 0..size {
+                let mut run = 1;
+                for i in 1..size {
+                    if get(line, i) == get(line, i - 1) {
+                        run += 1;
+                    } else {
+                        run = 1;
+                    }
+                    if run == 5 {
+                        penalty += 3;
+                    } else if run > 5 {
+                        penalty += 1;
+                    }
+                }
+
+                const FINDER_LIKE: [bool; 11] = [
+                    true, false, true, true, true, false, true, false, false, false, false,
+                ];
+                for start in 0..size.saturating_sub(10) {
+                    let matches = |reversed: bool| {
+                        (0..11).all(|i| {
+                            let expected = FINDER_LIKE[if reversed { 10 - i } else { i }];
+                            get(line, start + i) == expected
+                        })
+                    };
+                    if matches(false) {
+                        penalty += 40;
+                    }
+                    if matches(true) {
+                        penalty += 40;
+                    }
+                }
+            }
+        }
+
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let color = at(x, y);
+                if at(x + 1, y) == color && at(x, y + 1) == color && at(x + 1, y + 1) == color {
+                    penalty += 3;
+                }
+            }
+        }
+
+        let total = size * size;
+        let dark = self.dark.iter().filter(|&&dark| dark).count();
+        let deviation = (dark * 20).abs_diff(total * 10);
+        penalty += deviation.div_ceil(total).saturating_sub(1) * 10;
+        penalty
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Format information at level M for masks 0–7, as tabled in the
+    /// standard.
+    const FORMAT_M: [u32; 8] = [
+        0b101010000010010,
+        0b101000100100101,
+        0b101111001111100,
+        0b101101101001011,
+        0b100010111111001,
+        0b100000011001110,
+        0b100111110010111,
+        0b100101010100000,
+    ];
+
+    /// The format bits next to the top left finder.
+    fn read_format(dark: impl Fn(usize, usize) -> bool) -> u32 {
+        let mut positions: Vec<(usize, usize)> = (0..6).map(|i| (8, i)).collect();
+        positions.extend([(8, 7), (8, 8), (7, 8)]);
+        positions.extend((9..15).map(|i| (14 - i, 8)));
+        positions
+            .iter()
+            .enumerate()
+            .map(|(i, &(x, y))| u32::from(dark(x, y)) << i)
+            .sum()
+    }
+
+    /// Reads the data codewords back out of `code`: undoes the mask named
+    /// in its format bits and follows the zigzag from the bottom right.
+    fn read_codewords(code: &QrCode, version: usize) -> (u32, Vec<u8>) {
+        let size = code.size();
+        let format = read_format(|x, y| code.is_dark(x, y));
+        let mask = FORMAT_M
+            .iter()
+            .position(|&bits| bits == format)
+            .expect("format bits for level M") as u32;
+
+        let mut function = Matrix::new(version);
+        function.draw_function_patterns();
+        let mut unmasked = Matrix::new(version);
+        unmasked.function = function.function.clone();
+        for y in 0..size {
+            for x in 0..size {
+                unmasked.dark[y * size + x] = code.is_dark(x, y);
+            }
+        }
+        unmasked.apply_mask(mask);
+
+        let mut bits = Vec::new();
+        let mut column = size as isize - 1;
+        let mut upward = true;
+        while column > 0 {
+            if column == 6 {
+                column -= 1;
+            }
+            let rows: Vec<usize> = if upward {
+                (0..size).rev().collect()
+            } else {
+                (0..size).collect()
+            };
+            for y in rows {
+                for x in [column as usize, column as usize - 1] {
+                    if !function.function[y * size + x] {
+                        bits.push(unmasked.dark[y * size + x]);
+                    }
+                }
+            }
+            upward = !upward;
+            column -= 2;
+        }
+        let bytes = bits
+            .chunks_exact(8)
+            .map(|byte| {
+                byte.iter()
+                    .fold(0, |value, &bit| (value << 1) | u8::from(bit))
+            })
+            .collect();
+        (mask, bytes)
+    }
+
+    #[test]
+    fn error_correction_matches_the_standard_example() {
+        // "HELLO WORLD" at 1-M, from the worked example of the standard
+        let data = [
+            32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17,
+        ];
+        assert_eq!(
+            rs_remainder(&data, &rs_divisor(10)),
+            [196, 35, 39, 119, 235, 215, 231, 226, 93, 23]
+        );
+    }
+
+    #[test]
+    fn format_bits_match_the_standard_table() {
+        for (mask, &expected) in FORMAT_M.iter().enumerate() {
+            let mut matrix = Matrix::new(1);
+            matrix.draw_format(mask as u32);
+            let size = matrix.size;
+            assert_eq!(read_format(|x, y| matrix.dark[y * size + x]), expected);
+        }
+    }
+
+    #[test]
+    fn version_bits_match_the_standard_table() {
+        let mut matrix = Matrix::new(7);
+        matrix.draw_version();
+        let size = matrix.size;
+        let bits: u32 = (0..18)
+            .map(|i| u32::from(matrix.dark[(i / 3) * size + size - 11 + i % 3]) << i)
+            .sum();
+        assert_eq!(bits, 0b000111110010010100);
+    }
+
+    #[test]
+    fn encodes_a_short_text_at_version_1_with_mask_4() {
+        // As other encoders draw it, # dark
+        let expected = [
+            "#######.##..#.#######",
+            "#.....#....#..#.....#",
+            "#.###.#..#.#..#.###.#",
+            "#.###.#.#..#..#.###.#",
+            "#.###.#.###.#.#.###.#",
+            "#.....#.#..#..#.....#",
+            "#######.#.#.#.#######",
+            "........#..##........",
+            "#...#.######.#####..#",
+            "...#....#.###....####",
+            "..######..##.##.#..#.",
+            "#####...##...#.......",
+            "#####.#.#.#.#.##..##.",
+            "........#.#.####.#.##",
+            "#######.###.#.#.##.#.",
+            "#.....#..#.###.##..##",
+            "#.###.#.##.#.##...##.",
+            "#.###.#..#..#...##.##",
+            "#.###.#..###...###...",
+            "#.....#....#.#.......",
+            "#######.#########.#.#",
+        ];
+        let code = QrCode::encode(b"HELLO WORLD").unwrap();
+        assert_eq!(code.size(), 21);
+        for (y, row) in expected.iter().enumerate() {
+            let drawn: String = (0..21)
+                .map(|x| if code.is_dark(x, y) { '#' } else { '.' })
+                .collect();
+            assert_eq!(drawn, *row, "row {y}");
+        }
+        let (mask, codewords) = read_codewords(&code, 1);
+        assert_eq!(mask, 4);
+        assert_eq!(
+            codewords,
+            add_error_correction(&data_codewords(b"HELLO WORLD", 1, 16), 26, 10, 1)
+        );
+    }
+
+    #[test]
+    fn encodes_a_provisioning_link_at_version_7_with_mask_6() {
+        let link = b"otpauth://totp/LinkWithMentor:ada%40example.com?\
+            secret=JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP&issuer=LinkWithMentor";
+        let code = QrCode::encode(link).unwrap();
+        assert_eq!(code.size(), 45);
+        let (mask, codewords) = read_codewords(&code, 7);
+        assert_eq!(mask, 6);
+        assert_eq!(
+            codewords,
+            add_error_correction(&data_codewords(link, 7, 124), 196, 18, 4)
+        );
+    }
+
+    #[test]
+    fn refuses_data_past_version_10() {
+        assert!(QrCode::encode(&[b'a'; 213]).is_some());
+        assert!(QrCode::encode(&[b'a'; 214]).is_none());
+    }
+}
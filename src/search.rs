@@ -0,0 +1,785 @@
+// Full-text search over people, groups, posts, comments, messages, media,
+// jobs and events
+use crate::community::Community;
+use crate::feed::Feed;
+use crate::i18n::{tr, tr_noop, tr_with, trn};
+use crate::listings::{ListingKind, Listings};
+use crate::media::MediaLibrary;
+use crate::search_query::{self, Attachment, Query};
+use crate::util;
+use gtk4::glib;
+use std::collections::{HashMap, HashSet};
+
+/// Characters of context kept around the first match in a snippet.
+const SNIPPET_LEAD: usize = 40;
+const SNIPPET_LENGTH: usize = 160;
+/// Matches in a title count this many times more than in the body.
+const TITLE_WEIGHT: f64 = 2.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResultKind {
+    Person,
+    Group,
+    Post,
+    Comment,
+    Message,
+    Media,
+    Job,
+    Event,
+}
+
+impl ResultKind {
+    pub fn icon(self) -> &'static str {
+        match self {
+            ResultKind::Person => "👤",
+            ResultKind::Group => "👥",
+            ResultKind::Post => "📝",
+            ResultKind::Comment => "💬",
+            ResultKind::Message => "✉️",
+            ResultKind::Media => "📸",
+            ResultKind::Job => "💼",
+            ResultKind::Event => "🎮",
+        }
+    }
+}
+
+/// The tabs above the results.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchTab {
+    All,
+    People,
+    Groups,
+    Posts,
+    Media,
+}
+
+impl SearchTab {
+    pub const ALL: [SearchTab; 5] = [
+        SearchTab::All,
+        SearchTab::People,
+        SearchTab::Groups,
+        SearchTab::Posts,
+        SearchTab::Media,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchTab::All => tr_noop("All"),
+            SearchTab::People => tr_noop("People"),
+            SearchTab::Groups => tr_noop("Groups"),
+            SearchTab::Posts => tr_noop("Posts"),
+            SearchTab::Media => tr_noop("Media"),
+        }
+    }
+
+    /// Comments and messages are listed with posts; jobs and events only
+    /// under All.
+    pub fn includes(self, kind: ResultKind) -> bool {
+        match self {
+            SearchTab::All => true,
+            SearchTab::People => kind == ResultKind::Person,
+            SearchTab::Groups => kind == ResultKind::Group,
+            SearchTab::Posts => matches!(
+                kind,
+                ResultKind::Post | ResultKind::Comment | ResultKind::Message
+            ),
+            SearchTab::Media => kind == ResultKind::Media,
+        }
+    }
+}
+
+/// Where a result leads when opened.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ResultTarget {
+    Profile(String),
+    Group(String),
+    Post(String),
+    Conversation(String),
+    Media(String),
+    Job(String),
+    Event(String),
+}
+
+struct Document {
+    kind: ResultKind,
+    target: ResultTarget,
+    title: String,
+    body: String,
+    /// Shown under the title, e.g. "Comment on “Just shipped…”".
+    context: String,
+    /// 0 for people, which have no date.
+    timestamp: i64,
+    /// Lower-cased id and handle of the author, for `from:`.
+    author_keys: Vec<String>,
+    /// Ids and name slugs of the group, chat or album it's in, for `in:`.
+    places: Vec<String>,
+    has_location: bool,
+    has_poll: bool,
+}
+
+impl Document {
+    fn has(&self, attachment: Attachment) -> bool {
+        match attachment {
+            Attachment::Any => self.kind == ResultKind::Media || self.has_location || self.has_poll,
+            Attachment::Location => self.has_location,
+            Attachment::Poll => self.has_poll,
+        }
+    }
+
+    /// The `from:`, `in:`, `type:`, `has:` and date filters.
+    fn passes_filters(&self, query: &Query) -> bool {
+        let any_of = |wanted: &[String], keys: &[String]| {
+            wanted.is_empty() || wanted.iter().any(|value| keys.contains(value))
+        };
+        any_of(&query.from, &self.author_keys)
+            && any_of(&query.places, &self.places)
+            && (query.kinds.is_empty() || query.kinds.contains(&self.kind))
+            && query.has.iter().all(|attachment| self.has(*attachment))
+            && query
+                .before
+                .is_none_or(|before| self.timestamp > 0 && self.timestamp < before)
+            && query
+                .after
+                .is_none_or(|after| self.timestamp > 0 && self.timestamp >= after)
+    }
+
+    /// Quoted phrases have to appear as written, in the title or the body,
+    /// and excluded words and phrases must not appear at all.
+    fn passes_phrases(&self, query: &Query) -> bool {
+        if query.phrases.is_empty() && query.excluded.is_empty() {
+            return true;
+        }
+        let fields = [tokenize(&self.title), tokenize(&self.body)];
+        let contains = |phrase: &Vec<String>| {
+            fields.iter().any(|words| {
+                words
+                    .windows(phrase.len())
+                    .any(|window| window == phrase.as_slice())
+            })
+        };
+        query.phrases.iter().all(contains) && !query.excluded.iter().any(contains)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Title,
+    Body,
+}
+
+struct Posting {
+    document: usize,
+    field: Field,
+    count: u32,
+}
+
+pub struct SearchResult {
+    pub kind: ResultKind,
+    pub target: ResultTarget,
+    /// Pango markup with the matched words in bold.
+    pub title_markup: String,
+    pub context: String,
+    pub snippet_markup: Option<String>,
+    /// When it was posted, sent or uploaded; 0 for people.
+    pub timestamp: i64,
+    /// User id of whoever wrote or shared it.
+    pub author: Option<String>,
+}
+
+/// An inverted index from lower-cased words to the documents using them.
+pub struct SearchIndex {
+    documents: Vec<Document>,
+    postings: HashMap<String, Vec<Posting>>,
+}
+
+impl SearchIndex {
+    /// Indexes everything the signed-in user can see; blocked people and
+    /// their posts and chats are left out.
+    pub fn build(
+        community: &Community,
+        feed: &Feed,
+        media: &MediaLibrary,
+        listings: &Listings,
+    ) -> Self {
+        let mut documents = Vec::new();
+        let author_keys = |id: &str| {
+            let mut keys = vec![id.to_lowercase()];
+            if let Some(user) = community.user(id) {
+                keys.push(user.handle.to_lowercase());
+            }
+            keys
+        };
+
+        for user in community.users() {
+            if community.is_blocked(&user.id) {
+                continue;
+            }
+            documents.push(Document {
+                kind: ResultKind::Person,
+                target: ResultTarget::Profile(user.id.clone()),
+                title: user.name.clone(),
+                body: format!("@{} {}", user.handle, user.headline),
+                context: tr_with("Person · @{handle}", &[("handle", &user.handle)]),
+                timestamp: 0,
+                author_keys: Vec::new(),
+                places: Vec::new(),
+                has_location: false,
+                has_poll: false,
+            });
+        }
+
+        for group in community.groups() {
+            documents.push(Document {
+                kind: ResultKind::Group,
+                target: ResultTarget::Group(group.id.clone()),
+                title: group.name.clone(),
+                body: group.description.clone(),
+                context: trn(
+                    "Group · {n} member",
+                    "Group · {n} members",
+                    group.member_count.into(),
+                ),
+                timestamp: group.joined_at.unwrap_or_default(),
+                author_keys: Vec::new(),
+                places: vec![group.id.clone(), search_query::slug(&group.name)],
+                has_location: false,
+                has_poll: false,
+            });
+        }
+
+        for post in feed.posts() {
+            if community.hidden_from_feed(&post.author) {
+                continue;
+            }
+            let mut body = post.text.clone();
+            if let Some(poll) = &post.poll {
+                body.push('\n');
+                body.push_str(&poll.question);
+            }
+            if let Some(location) = &post.location {
+                body.push('\n');
+                body.push_str(&location.title());
+            }
+            documents.push(Document {
+                kind: ResultKind::Post,
+                target: ResultTarget::Post(post.id.clone()),
+                title: community.user_name(&post.author),
+                body,
+                context: tr("Post"),
+                timestamp: post.created_at,
+                author_keys: author_keys(&post.author),
+                places: Vec::new(),
+                has_location: post.location.is_some(),
+                has_poll: post.poll.is_some(),
+            });
+
+            for comment in &post.comments {
+                if community.hidden_from_feed(&comment.author) {
+                    continue;
+                }
+                documents.push(Document {
+                    kind: ResultKind::Comment,
+                    target: ResultTarget::Post(post.id.clone()),
+                    title: community.user_name(&comment.author),
+                    body: comment.text.clone(),
+                    context: tr_with("Comment on “{title}”", &[("title", &post.title())]),
+                    timestamp: comment.created_at,
+                    author_keys: author_keys(&comment.author),
+                    places: Vec::new(),
+                    has_location: false,
+                    has_poll: false,
+                });
+            }
+        }
+
+        for conversation in community.conversations() {
+            if community.is_blocked_conversation(conversation) {
+                continue;
+            }
+            let conversation_title = community.conversation_title(conversation);
+            let mut places = vec![conversation.id.clone()];
+            places.extend(conversation.title.as_deref().map(search_query::slug));
+            for message in &conversation.messages {
+                if community.is_blocked(&message.sender) {
+                    continue;
+                }
+                documents.push(Document {
+                    kind: ResultKind::Message,
+                    target: ResultTarget::Conversation(conversation.id.clone()),
+                    title: community.user_name(&message.sender),
+                    body: message.text.clone(),
+                    context: tr_with(
+                        "Message in {conversation}",
+                        &[("conversation", &conversation_title)],
+                    ),
+                    timestamp: message.sent_at,
+                    author_keys: author_keys(&message.sender),
+                    places: places.clone(),
+                    has_location: message.location.is_some(),
+                    has_poll: false,
+                });
+            }
+        }
+
+        for item in media.items() {
+            if community.is_blocked(&item.shared_by) {
+                continue;
+            }
+            let mut body = item.tags.join(" ");
+            for extra in [&item.album, &item.file_name].into_iter().flatten() {
+                body.push(' ');
+                body.push_str(extra);
+            }
+            documents.push(Document {
+                kind: ResultKind::Media,
+                target: ResultTarget::Media(item.id.clone()),
+                title: item.title.clone(),
+                body,
+                context: tr_with(
+                    "Media · shared by {name}",
+                    &[("name", &item.shared_by_name())],
+                ),
+                timestamp: item.uploaded_at,
+                author_keys: author_keys(&item.shared_by),
+                places: item
+                    .album
+                    .as_deref()
+                    .map(search_query::slug)
+                    .into_iter()
+                    .collect(),
+                has_location: false,
+                has_poll: false,
+            });
+        }
+
+        for listing in listings.all() {
+            let (kind, target, context) = match listing.kind {
+                ListingKind::Job => (
+                    ResultKind::Job,
+                    ResultTarget::Job(listing.id.clone()),
+                    tr_with(
+                        "Job · {host} · {place}",
+                        &[("host", &listing.host), ("place", &listing.place)],
+                    ),
+                ),
+                ListingKind::Event => (
+                    ResultKind::Event,
+                    ResultTarget::Event(listing.id.clone()),
+                    tr_with(
+                        "Event · {date} · {place}",
+                        &[
+                            (
+                                "date",
+                                &util::format_date_time(listing.starts_at.unwrap_or_default()),
+                            ),
+                            ("place", &listing.place),
+                        ],
+                    ),
+                ),
+            };
+            documents.push(Document {
+                kind,
+                target,
+                title: listing.title.clone(),
+                body: format!(
+                    "{}\n{} {}",
+                    listing.description, listing.host, listing.place
+                ),
+                context,
+                timestamp: listing.posted_at,
+                author_keys: Vec::new(),
+                places: vec![search_query::slug(&listing.host)],
+                has_location: false,
+                has_poll: false,
+            });
+        }
+
+        Self::from_documents(documents)
+    }
+
+    fn from_documents(documents: Vec<Document>) -> Self {
+        let mut postings: HashMap<String, Vec<Posting>> = HashMap::new();
+        for (index, document) in documents.iter().enumerate() {
+            for (field, text) in [
+                (Field::Title, &document.title),
+                (Field::Body, &document.body),
+            ] {
+                let mut counts: HashMap<String, u32> = HashMap::new();
+                for word in tokenize(text) {
+                    *counts.entry(word).or_default() += 1;
+                }
+                for (word, count) in counts {
+                    postings.entry(word).or_default().push(Posting {
+                        document: index,
+                        field,
+                        count,
+                    });
+                }
+            }
+        }
+
+        Self {
+            documents,
+            postings,
+        }
+    }
+
+    /// Documents matching every word and filter of `query`, best first.
+    /// Loose words also match as prefixes and with a typo or two, ranked
+    /// below exact hits; words in quoted phrases have to match exactly.
+    /// With only filters or exclusions, the newest results come first.
+    pub fn search(&self, query: &Query, tab: SearchTab) -> Vec<SearchResult> {
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matched_words: HashSet<&str> = HashSet::new();
+        let mut scores: Option<HashMap<usize, f64>> = None;
+        let loose = query.words.iter().map(|word| (word, true));
+        let exact = query.phrases.iter().flatten().map(|word| (word, false));
+        for (term, typos_allowed) in loose.chain(exact) {
+            let term_scores = self.term_scores(term, typos_allowed, &mut matched_words);
+            // Every word has to match somewhere in the document
+            scores = Some(match scores {
+                None => term_scores,
+                Some(scores) => scores
+                    .into_iter()
+                    .filter_map(|(document, score)| {
+                        term_scores
+                            .get(&document)
+                            .map(|term_score| (document, score + term_score))
+                    })
+                    .collect(),
+            });
+        }
+        let scores: Vec<(usize, f64)> = match scores {
+            Some(scores) => scores.into_iter().collect(),
+            None => (0..self.documents.len())
+                .map(|index| (index, 0.0))
+                .collect(),
+        };
+
+        let mut ranked: Vec<(usize, f64)> = scores
+            .into_iter()
+            .filter(|(index, _)| {
+                let document = &self.documents[*index];
+                tab.includes(document.kind)
+                    && document.passes_filters(query)
+                    && document.passes_phrases(query)
+            })
+            .collect();
+        ranked.sort_by(|(a, a_score), (b, b_score)| {
+            b_score.total_cmp(a_score).then(
+                self.documents[*b]
+                    .timestamp
+                    .cmp(&self.documents[*a].timestamp),
+            )
+        });
+
+        ranked
+            .into_iter()
+            .map(|(index, _)| {
+                let document = &self.documents[index];
+                SearchResult {
+                    kind: document.kind,
+                    target: document.target.clone(),
+                    title_markup: highlight(&document.title, &matched_words),
+                    context: document.context.clone(),
+                    snippet_markup: snippet(&document.body, &matched_words),
+                    timestamp: document.timestamp,
+                    author: document.author_keys.first().cloned(),
+                }
+            })
+            .collect()
+    }
+
+    /// The best score of `term` in each document that contains it.
+    fn term_scores<'a>(
+        &'a self,
+        term: &str,
+        typos_allowed: bool,
+        matched_words: &mut HashSet<&'a str>,
+    ) -> HashMap<usize, f64> {
+        let total = self.documents.len() as f64;
+        let mut term_scores: HashMap<usize, f64> = HashMap::new();
+        for (word, postings) in &self.postings {
+            let quality = if typos_allowed {
+                match_quality(term, word)
+            } else {
+                (term == word).then_some(1.0)
+            };
+            let Some(quality) = quality else {
+                continue;
+            };
+            matched_words.insert(word);
+            let idf = (1.0 + total / postings.len() as f64).ln();
+            for posting in postings {
+                let weight = match posting.field {
+                    Field::Title => TITLE_WEIGHT,
+                    Field::Body => 1.0,
+                };
+                let score = quality * idf * weight * (1.0 + f64::from(posting.count).ln());
+                let best = term_scores.entry(posting.document).or_default();
+                *best = best.max(score);
+            }
+        }
+        term_scores
+    }
+}
+
+/// Lower-cased words, splitting on anything that isn't a letter or digit.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// How well an indexed `word` matches a query `term`: 1 for the same word,
+/// less for a prefix or a word a typo or two away, `None` otherwise.
+fn match_quality(term: &str, word: &str) -> Option<f64> {
+    if term == word {
+        return Some(1.0);
+    }
+    let length = term.chars().count();
+    if length >= 2 && word.starts_with(term) {
+        return Some(0.7);
+    }
+    let allowed = match length {
+        0..=3 => return None,
+        4..=7 => 1,
+        _ => 2,
+    };
+    match edit_distance(term, word, allowed)? {
+        1 => Some(0.5),
+        _ => Some(0.3),
+    }
+}
+
+/// Levenshtein distance between `a` and `b`, or `None` once it exceeds
+/// `limit`.
+fn edit_distance(a: &str, b: &str, limit: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > limit {
+        return None;
+    }
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        if current.iter().min().is_some_and(|best| *best > limit) {
+            return None;
+        }
+        previous = current;
+    }
+    previous
+        .last()
+        .copied()
+        .filter(|distance| *distance <= limit)
+}
+
+/// Escapes `text` as Pango markup with the words in `matched` in bold.
+fn highlight(text: &str, matched: &HashSet<&str>) -> String {
+    let mut markup = String::new();
+    let mut word_start: Option<usize> = None;
+    let flush = |markup: &mut String, word: &str| {
+        let escaped = glib::markup_escape_text(word);
+        if matched.contains(word.to_lowercase().as_str()) {
+            markup.push_str(&format!("<b>{escaped}</b>"));
+        } else {
+            markup.push_str(&escaped);
+        }
+    };
+    for (offset, c) in text.char_indices() {
+        if c.is_alphanumeric() {
+            word_start.get_or_insert(offset);
+            continue;
+        }
+        if let Some(start) = word_start.take() {
+            flush(&mut markup, &text[start..offset]);
+        }
+        markup.push_str(&glib::markup_escape_text(c.encode_utf8(&mut [0; 4])));
+    }
+    if let Some(start) = word_start {
+        flush(&mut markup, &text[start..]);
+    }
+    markup
+}
+
+/// A highlighted excerpt of `body` around its first matched word.
+fn snippet(body: &str, matched: &HashSet<&str>) -> Option<String> {
+    let body = body.trim();
+    if body.is_empty() {
+        return None;
+    }
+    let chars: Vec<(usize, char)> = body.char_indices().collect();
+    let first_match = tokenize_with_offsets(body)
+        .into_iter()
+        .find(|(_, word)| matched.contains(word.as_str()))
+        .map(|(offset, _)| offset)
+        .unwrap_or(0);
+    let match_char = chars
+        .iter()
+        .position(|(offset, _)| *offset == first_match)
+        .unwrap_or(0);
+    let start_char = match_char.saturating_sub(SNIPPET_LEAD);
+    let end_char = (start_char + SNIPPET_LENGTH).min(chars.len());
+    let start = chars[start_char].0;
+    let end = chars
+        .get(end_char)
+        .map_or(body.len(), |(offset, _)| *offset);
+
+    let excerpt = body[start..end].replace('\n', " ");
+    let mut markup = String::new();
+    if start > 0 {
+        markup.push('…');
+    }
+    markup.push_str(&highlight(&excerpt, matched));
+    if end < body.len() {
+        markup.push('…');
+    }
+    Some(markup)
+}
+
+fn tokenize_with_offsets(text: &str) -> Vec<(usize, String)> {
+    let mut words = Vec::new();
+    let mut word_start: Option<usize> = None;
+    for (offset, c) in text.char_indices() {
+        if c.is_alphanumeric() {
+            word_start.get_or_insert(offset);
+        } else if let Some(start) = word_start.take() {
+            words.push((start, text[start..offset].to_lowercase()));
+        }
+    }
+    if let Some(start) = word_start {
+        words.push((start, text[start..].to_lowercase()));
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(kind: ResultKind, id: &str, title: &str, body: &str, timestamp: i64) -> Document {
+        Document {
+            kind,
+            target: ResultTarget::Post(id.to_string()),
+            title: title.to_string(),
+            body: body.to_string(),
+            context: String::new(),
+            timestamp,
+            author_keys: Vec::new(),
+            places: Vec::new(),
+            has_location: false,
+            has_poll: false,
+        }
+    }
+
+    fn ids(index: &SearchIndex, input: &str) -> Vec<String> {
+        let (query, errors) = search_query::parse(input);
+        assert!(errors.is_empty(), "{errors:?}");
+        index
+            .search(&query, SearchTab::All)
+            .into_iter()
+            .map(|result| match result.target {
+                ResultTarget::Post(id) => id,
+                target => panic!("unexpected target {target:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn edit_distance_stops_at_the_limit() {
+        assert_eq!(edit_distance("rust", "rust", 0), Some(0));
+        assert_eq!(edit_distance("rust", "rest", 1), Some(1));
+        assert_eq!(edit_distance("kitten", "sitting", 3), Some(3));
+        assert_eq!(edit_distance("kitten", "sitting", 2), None);
+        assert_eq!(edit_distance("go", "golang", 3), None);
+        assert_eq!(edit_distance("", "abc", 3), Some(3));
+    }
+
+    #[test]
+    fn match_quality_allows_more_typos_in_longer_words() {
+        assert_eq!(match_quality("rust", "rust"), Some(1.0));
+        assert_eq!(match_quality("ru", "rustacean"), Some(0.7));
+        // A single letter is too short to be a prefix
+        assert_eq!(match_quality("r", "rust"), None);
+        // Up to three letters, no typos
+        assert_eq!(match_quality("gtk", "gtx"), None);
+        // Four to seven letters, one typo
+        assert_eq!(match_quality("rsut", "rust"), None);
+        assert_eq!(match_quality("rust", "bust"), Some(0.5));
+        assert_eq!(match_quality("mentor", "mantra"), None);
+        // Eight or more, two typos
+        assert_eq!(match_quality("developer", "develoqer"), Some(0.5));
+        assert_eq!(match_quality("developer", "divelopar"), Some(0.3));
+        assert_eq!(match_quality("developer", "davalopar"), None);
+    }
+
+    #[test]
+    fn title_matches_rank_above_body_matches() {
+        let index = SearchIndex::from_documents(vec![
+            document(
+                ResultKind::Post,
+                "body",
+                "Weekend plans",
+                "Learning rust",
+                2,
+            ),
+            document(ResultKind::Post, "title", "Rust tips", "Some notes", 1),
+            document(ResultKind::Post, "none", "Cooking", "Pasta recipes", 3),
+        ]);
+        assert_eq!(ids(&index, "rust"), ["title", "body"]);
+    }
+
+    #[test]
+    fn exact_matches_rank_above_typos() {
+        let index = SearchIndex::from_documents(vec![
+            document(ResultKind::Post, "typo", "", "A mentar session", 2),
+            document(ResultKind::Post, "exact", "", "A mentor session", 1),
+        ]);
+        assert_eq!(ids(&index, "mentor"), ["exact", "typo"]);
+    }
+
+    #[test]
+    fn every_word_has_to_match() {
+        let index = SearchIndex::from_documents(vec![
+            document(ResultKind::Post, "both", "", "Rust meetup tonight", 1),
+            document(ResultKind::Post, "one", "", "Rust release notes", 2),
+        ]);
+        assert_eq!(ids(&index, "rust meetup"), ["both"]);
+    }
+
+    #[test]
+    fn filter_only_queries_list_the_newest_first() {
+        let index = SearchIndex::from_documents(vec![
+            document(ResultKind::Post, "old", "", "First", 10),
+            document(ResultKind::Comment, "comment", "", "Second", 30),
+            document(ResultKind::Post, "new", "", "Third", 20),
+        ]);
+        assert_eq!(ids(&index, "type:post"), ["new", "old"]);
+        assert!(ids(&index, "").is_empty());
+    }
+
+    #[test]
+    fn highlight_escapes_markup() {
+        let matched = HashSet::from(["rust"]);
+        assert_eq!(
+            highlight("<b>Rust</b> & C", &matched),
+            "&lt;b&gt;<b>Rust</b>&lt;/b&gt; &amp; C"
+        );
+    }
+
+    #[test]
+    fn snippets_start_near_the_first_match() {
+        let body = format!("{} rust", "word ".repeat(60));
+        let snippet = snippet(&body, &HashSet::from(["rust"])).unwrap();
+        assert!(snippet.starts_with('…'), "{snippet}");
+        assert!(snippet.ends_with("<b>rust</b>"), "{snippet}");
+    }
+}
@@ -0,0 +1,224 @@
+// Recent and saved searches, and the saved searches' new-result counts
+use crate::i18n::tr;
+use crate::state::AppState;
+use crate::storage;
+use crate::util;
+use gtk4::glib;
+use gtk4::prelude::*;
+use std::rc::Rc;
+
+const SEARCHES_FILE: &str = "searches.ini";
+const RECENT_PREFIX: &str = "recent:";
+const SAVED_PREFIX: &str = "saved:";
+
+/// How many recent searches are kept.
+const RECENT_LIMIT: usize = 10;
+
+#[derive(Clone, Debug)]
+pub struct RecentSearch {
+    pub query: String,
+    pub searched_at: i64,
+}
+
+#[derive(Clone, Debug)]
+pub struct SavedSearch {
+    pub id: String,
+    pub query: String,
+    pub saved_at: i64,
+    /// Send a desktop notification when new results turn up.
+    pub alerts: bool,
+    /// Results newer than this have already been counted.
+    pub checked_at: i64,
+    /// New results since the search was last run.
+    pub new_results: u32,
+}
+
+pub struct SearchHistory {
+    /// Newest first.
+    recent: Vec<RecentSearch>,
+    saved: Vec<SavedSearch>,
+    /// A search to show next, e.g. from a saved search notification.
+    requested: Option<String>,
+}
+
+impl SearchHistory {
+    pub fn load() -> Self {
+        let Some(key_file) = storage::load_key_file(SEARCHES_FILE) else {
+            return Self {
+                recent: Vec::new(),
+                saved: Vec::new(),
+                requested: None,
+            };
+        };
+
+        let mut recent: Vec<RecentSearch> = storage::groups_with_prefix(&key_file, RECENT_PREFIX)
+            .into_iter()
+            .map(|(_, group)| RecentSearch {
+                query: storage::string_or(&key_file, &group, "query", ""),
+                searched_at: key_file.int64(&group, "searched_at").unwrap_or_default(),
+            })
+            .filter(|search| !search.query.is_empty())
+            .collect();
+        recent.sort_by_key(|search| std::cmp::Reverse(search.searched_at));
+
+        let mut saved: Vec<SavedSearch> = storage::groups_with_prefix(&key_file, SAVED_PREFIX)
+            .into_iter()
+            .map(|(id, group)| SavedSearch {
+                query: storage::string_or(&key_file, &group, "query", ""),
+                saved_at: key_file.int64(&group, "saved_at").unwrap_or_default(),
+                alerts: key_file.boolean(&group, "alerts").unwrap_or(true),
+                checked_at: key_file.int64(&group, "checked_at").unwrap_or_default(),
+                new_results: key_file
+                    .integer(&group, "new_results")
+                    .unwrap_or_default()
+                    .max(0) as u32,
+                id,
+            })
+            .filter(|search| !search.query.is_empty())
+            .collect();
+        saved.sort_by_key(|search| search.saved_at);
+
+        Self {
+            recent,
+            saved,
+            requested: None,
+        }
+    }
+
+    pub fn save(&self) {
+        let key_file = glib::KeyFile::new();
+        for (index, search) in self.recent.iter().enumerate() {
+            let group = format!("{RECENT_PREFIX}{index}");
+            key_file.set_string(&group, "query", &search.query);
+            key_file.set_int64(&group, "searched_at", search.searched_at);
+        }
+        for search in &self.saved {
+            let group = format!("{SAVED_PREFIX}{}", search.id);
+            key_file.set_string(&group, "query", &search.query);
+            key_file.set_int64(&group, "saved_at", search.saved_at);
+            key_file.set_boolean(&group, "alerts", search.alerts);
+            key_file.set_int64(&group, "checked_at", search.checked_at);
+            key_file.set_integer(&group, "new_results", search.new_results as i32);
+        }
+        storage::save_key_file(SEARCHES_FILE, &key_file);
+    }
+
+    pub fn recent(&self) -> &[RecentSearch] {
+        &self.recent
+    }
+
+    pub fn saved(&self) -> &[SavedSearch] {
+        &self.saved
+    }
+
+    /// Moves `query` to the top of the recent searches. Callers check the
+    /// "save-search-history" setting first.
+    pub fn record(&mut self, query: &str) {
+        let query = query.trim();
+        if query.is_empty() {
+            return;
+        }
+        self.recent.retain(|search| search.query != query);
+        self.recent.insert(
+            0,
+            RecentSearch {
+                query: query.to_string(),
+                searched_at: util::now(),
+            },
+        );
+        self.recent.truncate(RECENT_LIMIT);
+        self.save();
+    }
+
+    pub fn clear_recent(&mut self) {
+        self.recent.clear();
+        self.save();
+    }
+
+    pub fn saved_search(&self, query: &str) -> Option<&SavedSearch> {
+        self.saved
+            .iter()
+            .find(|search| search.query == query.trim())
+    }
+
+    /// Saves `query`; only results newer than now count as new.
+    pub fn save_search(&mut self, query: &str) -> Result<(), String> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err(tr("Type something to search for first"));
+        }
+        if self.saved_search(query).is_some() {
+            return Err(tr("This search is already saved"));
+        }
+        let now = util::now();
+        self.saved.push(SavedSearch {
+            id: glib::uuid_string_random().to_string(),
+            query: query.to_string(),
+            saved_at: now,
+            alerts: true,
+            checked_at: now,
+            new_results: 0,
+        });
+        self.save();
+        Ok(())
+    }
+
+    pub fn remove_saved(&mut self, id: &str) {
+        self.saved.retain(|search| search.id != id);
+        self.save();
+    }
+
+    pub fn set_alerts(&mut self, id: &str, alerts: bool) {
+        if let Some(search) = self.saved.iter_mut().find(|search| search.id == id) {
+            search.alerts = alerts;
+            self.save();
+        }
+    }
+
+    /// Adds `found` new results to a saved search checked up to `now`.
+    pub fn add_new_results(&mut self, id: &str, found: u32, now: i64) {
+        if let Some(search) = self.saved.iter_mut().find(|search| search.id == id) {
+            search.new_results += found;
+            search.checked_at = now;
+            self.save();
+        }
+    }
+
+    /// Clears the new-result count once the user has run the search.
+    pub fn mark_seen(&mut self, query: &str) {
+        let query = query.trim();
+        if let Some(search) = self
+            .saved
+            .iter_mut()
+            .find(|search| search.query == query && search.new_results > 0)
+        {
+            search.new_results = 0;
+            self.save();
+        }
+    }
+
+    pub fn request(&mut self, query: &str) {
+        self.requested = Some(query.to_string());
+    }
+
+    pub fn take_request(&mut self) -> Option<String> {
+        self.requested.take()
+    }
+}
+
+/// Forgets the recent searches whenever the "save-search-history" setting
+/// is switched off.
+pub fn watch(state: &Rc<AppState>) {
+    let Some(settings) = state.preferences.settings() else {
+        return;
+    };
+    let state_clone = state.clone();
+    settings.connect_changed(Some("save-search-history"), move |settings, key| {
+        if !settings.boolean(key) {
+            state_clone.searches.borrow_mut().clear_recent();
+            state_clone.searches_changed.emit();
+        }
+    });
+    // GSettings only reports changes to keys that have been read
+    settings.boolean("save-search-history");
+}
@@ -0,0 +1,396 @@
+// Search query syntax: from:, in:, type:, has:, before:/after:, quoted
+// phrases and -exclusions, plus autocomplete for the search entry
+use crate::community::Community;
+use crate::i18n::{tr, tr_with};
+use crate::search::{self, ResultKind};
+use crate::util::DAY;
+use gtk4::glib;
+
+const OPERATORS: [&str; 6] = ["from:", "in:", "type:", "has:", "before:", "after:"];
+
+/// Names accepted by `type:`.
+const TYPES: [(&str, ResultKind); 10] = [
+    ("post", ResultKind::Post),
+    ("comment", ResultKind::Comment),
+    ("message", ResultKind::Message),
+    ("media", ResultKind::Media),
+    ("user", ResultKind::Person),
+    ("person", ResultKind::Person),
+    ("people", ResultKind::Person),
+    ("group", ResultKind::Group),
+    ("job", ResultKind::Job),
+    ("event", ResultKind::Event),
+];
+
+/// What `has:` can ask for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Attachment {
+    /// Any of the others, or a media file.
+    Any,
+    Location,
+    Poll,
+}
+
+impl Attachment {
+    const ALL: [(&'static str, Attachment); 3] = [
+        ("attachment", Attachment::Any),
+        ("location", Attachment::Location),
+        ("poll", Attachment::Poll),
+    ];
+}
+
+/// A parsed search. Everything listed has to match; an empty query
+/// matches nothing.
+#[derive(Default, Debug)]
+pub struct Query {
+    /// Lower-cased words, matched loosely.
+    pub words: Vec<String>,
+    /// Quoted phrases, as lower-cased words that must appear in order.
+    pub phrases: Vec<Vec<String>>,
+    /// Words and phrases that rule a result out.
+    pub excluded: Vec<Vec<String>>,
+    /// User ids or handles, without the @.
+    pub from: Vec<String>,
+    /// Group or conversation ids, or their names as slugs.
+    pub places: Vec<String>,
+    pub kinds: Vec<ResultKind>,
+    pub has: Vec<Attachment>,
+    /// Unix timestamps; `before` is exclusive, `after` inclusive.
+    pub before: Option<i64>,
+    pub after: Option<i64>,
+}
+
+impl Query {
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+            && self.phrases.is_empty()
+            && self.excluded.is_empty()
+            && self.from.is_empty()
+            && self.places.is_empty()
+            && self.kinds.is_empty()
+            && self.has.is_empty()
+            && self.before.is_none()
+            && self.after.is_none()
+    }
+}
+
+/// Parses `input`, returning what could be understood along with a message
+/// for each part that couldn't.
+pub fn parse(input: &str) -> (Query, Vec<String>) {
+    let mut query = Query::default();
+    let mut errors = Vec::new();
+    let mut rest = input.trim_start();
+
+    while !rest.is_empty() {
+        let excluded = rest.starts_with('-') && rest.len() > 1;
+        if excluded {
+            rest = &rest[1..];
+        }
+
+        let current = rest;
+        let (token, quoted, remainder) = next_token(current, &mut errors);
+        rest = remainder.trim_start();
+
+        if quoted {
+            let words = search::tokenize(&token);
+            if words.is_empty() {
+                continue;
+            }
+            if excluded {
+                query.excluded.push(words);
+            } else {
+                query.phrases.push(words);
+            }
+            continue;
+        }
+
+        if let Some((operator, value)) = split_operator(&token) {
+            // A quoted value, e.g. in:"Rust Study Group"
+            let value = if value.starts_with('"') {
+                let (value, _, remainder) = next_token(&current[operator.len()..], &mut errors);
+                rest = remainder.trim_start();
+                value
+            } else {
+                value.to_string()
+            };
+            if excluded {
+                errors.push(tr_with(
+                    "“{operator}” filters can't be excluded with -",
+                    &[("operator", operator)],
+                ));
+                continue;
+            }
+            apply_operator(&mut query, operator, &value, &mut errors);
+            continue;
+        }
+
+        let words = search::tokenize(&token);
+        if excluded {
+            if !words.is_empty() {
+                query.excluded.push(words);
+            }
+        } else {
+            query.words.extend(words);
+        }
+    }
+
+    (query, errors)
+}
+
+/// The next whitespace-separated or quoted token, whether it was quoted,
+/// and the text after it.
+fn next_token<'a>(text: &'a str, errors: &mut Vec<String>) -> (String, bool, &'a str) {
+    if let Some(inner) = text.strip_prefix('"') {
+        return match inner.find('"') {
+            Some(end) => (inner[..end].to_string(), true, &inner[end + 1..]),
+            None => {
+                errors.push(tr("Missing closing quote"));
+                (inner.to_string(), true, "")
+            }
+        };
+    }
+    let end = text.find(char::is_whitespace).unwrap_or(text.len());
+    (text[..end].to_string(), false, &text[end..])
+}
+
+/// Splits `name:value` when `name` is one of the filters. Anything else,
+/// such as "re:" or a link, is searched for as written.
+fn split_operator(token: &str) -> Option<(&str, &str)> {
+    let colon = token.find(':')?;
+    let (operator, value) = token.split_at(colon + 1);
+    OPERATORS
+        .iter()
+        .any(|known| known.eq_ignore_ascii_case(operator))
+        .then_some((operator, value))
+}
+
+fn apply_operator(query: &mut Query, operator: &str, value: &str, errors: &mut Vec<String>) {
+    let value = value.trim();
+    let lower = operator.to_lowercase();
+    if value.is_empty() {
+        errors.push(tr_with(
+            "“{operator}” needs a value",
+            &[("operator", &lower)],
+        ));
+        return;
+    }
+
+    match lower.as_str() {
+        "from:" => query
+            .from
+            .push(value.trim_start_matches('@').to_lowercase()),
+        "in:" => query.places.push(slug(value.trim_start_matches('#'))),
+        "type:" => {
+            for name in value.split('|').filter(|name| !name.is_empty()) {
+                match TYPES
+                    .iter()
+                    .find(|(type_name, _)| type_name.eq_ignore_ascii_case(name))
+                {
+                    Some((_, kind)) if !query.kinds.contains(kind) => query.kinds.push(*kind),
+                    Some(_) => {}
+                    None => errors.push(tr_with(
+                        "Unknown type “{name}” — use post, comment, message, media, user, group, job or event",
+                        &[("name", name)],
+                    )),
+                }
+            }
+        }
+        "has:" => match Attachment::ALL
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(value))
+        {
+            Some((_, attachment)) => query.has.push(*attachment),
+            None => errors.push(tr_with(
+                "Unknown “has:” value “{value}” — use attachment, location or poll",
+                &[("value", value)],
+            )),
+        },
+        "before:" | "after:" => match parse_date(value) {
+            // before: means earlier than that day; after: means later
+            Some(day_start) if lower == "before:" => query.before = Some(day_start),
+            Some(day_start) => query.after = Some(day_start + DAY),
+            None => errors.push(match today() {
+                Ok(today) => tr_with(
+                    "“{value}” isn't a date — write dates like {example}",
+                    &[("value", value), ("example", &today)],
+                ),
+                Err(_) => tr_with(
+                    "“{value}” isn't a date — write dates as YYYY-MM-DD",
+                    &[("value", value)],
+                ),
+            }),
+        },
+        _ => unreachable!(),
+    }
+}
+
+/// The local midnight starting `YYYY-MM-DD`.
+fn parse_date(value: &str) -> Option<i64> {
+    let mut parts = value.splitn(3, '-').map(|part| part.parse::<i32>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    glib::DateTime::from_local(year, month, day, 0, 0, 0.0)
+        .ok()
+        .map(|date| date.to_unix())
+}
+
+/// Today's date as `YYYY-MM-DD`.
+fn today() -> Result<String, glib::BoolError> {
+    Ok(glib::DateTime::now_local()?.format("%Y-%m-%d")?.into())
+}
+
+/// "Rust Study Group" → "rust-study-group", for comparing `in:` values.
+pub fn slug(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// A completion for the word being typed.
+pub struct Suggestion {
+    pub label: String,
+    /// The whole entry text with the suggestion applied.
+    pub text: String,
+}
+
+/// Completions for the last word of `input`: filter names, then people,
+/// places, types and dates for the filter being typed.
+pub fn suggestions(input: &str, community: &Community) -> Vec<Suggestion> {
+    const LIMIT: usize = 6;
+
+    if input.is_empty()
+        || input.ends_with(char::is_whitespace)
+        || input.matches('"').count() % 2 == 1
+    {
+        return Vec::new();
+    }
+    let start = input
+        .rfind(char::is_whitespace)
+        .map_or(0, |offset| offset + 1);
+    let (before, word) = input.split_at(start);
+    let word = word.strip_prefix('-').unwrap_or(word);
+    let complete = |replacement: &str| Suggestion {
+        label: replacement.to_string(),
+        text: format!("{before}{replacement} "),
+    };
+
+    let Some(colon) = word.find(':') else {
+        if word.chars().count() < 2 {
+            return Vec::new();
+        }
+        let lower = word.to_lowercase();
+        return OPERATORS
+            .iter()
+            .filter(|operator| operator.starts_with(&lower) && **operator != lower)
+            .map(|operator| Suggestion {
+                label: operator.to_string(),
+                text: format!("{before}{operator}"),
+            })
+            .collect();
+    };
+
+    let operator = word[..=colon].to_lowercase();
+    let value = word[colon + 1..].to_lowercase();
+    let candidates: Vec<String> = match operator.as_str() {
+        "from:" => {
+            let partial = value.trim_start_matches('@');
+            community
+                .users()
+                .iter()
+                .filter(|user| !community.is_blocked(&user.id))
+                .filter(|user| {
+                    user.handle.to_lowercase().starts_with(partial)
+                        || user.name.to_lowercase().starts_with(partial)
+                })
+                .map(|user| format!("from:@{}", user.handle))
+                .collect()
+        }
+        "in:" => {
+            let partial = value.trim_start_matches('#');
+            let groups = community.groups().iter().map(|group| group.id.clone());
+            let chats = community
+                .conversations()
+                .iter()
+                .filter_map(|conversation| conversation.title.as_deref().map(slug));
+            groups
+                .chain(chats)
+                .filter(|place| place.starts_with(partial))
+                .map(|place| format!("in:#{place}"))
+                .collect()
+        }
+        "type:" => {
+            let (chosen, partial) = value.rsplit_once('|').unwrap_or(("", &value));
+            let prefix = if chosen.is_empty() {
+                "type:".to_string()
+            } else {
+                format!("type:{chosen}|")
+            };
+            [
+                "post", "comment", "message", "media", "user", "group", "job", "event",
+            ]
+            .into_iter()
+            .filter(|name| name.starts_with(partial))
+            .map(|name| format!("{prefix}{name}"))
+            .collect()
+        }
+        "has:" => Attachment::ALL
+            .iter()
+            .map(|(name, _)| *name)
+            .filter(|name| name.starts_with(&value))
+            .map(|name| format!("has:{name}"))
+            .collect(),
+        "before:" | "after:" => today()
+            .map(|today| vec![format!("{operator}{today}")])
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    candidates
+        .iter()
+        .filter(|candidate| candidate.to_lowercase() != word.to_lowercase())
+        .take(LIMIT)
+        .map(|candidate| complete(candidate))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_operators_are_words() {
+        let (query, errors) = parse("re:meeting https://example.com");
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(
+            query.words,
+            ["re", "meeting", "https", "example", "com"].map(String::from)
+        );
+    }
+
+    #[test]
+    fn known_operators_ignore_case() {
+        let (query, errors) = parse("From:@Alice type:post");
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(query.from, ["alice"]);
+        assert_eq!(query.kinds, [ResultKind::Post]);
+        assert!(query.words.is_empty());
+    }
+
+    #[test]
+    fn exclusions_alone_are_a_query() {
+        let (query, errors) = parse("-spam");
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(query.excluded, [vec!["spam".to_string()]]);
+        assert!(!query.is_empty());
+    }
+
+    #[test]
+    fn dates_are_local_days() {
+        let (query, errors) = parse("after:2025-03-01 before:2025-03-10");
+        assert!(errors.is_empty(), "{errors:?}");
+        let day = |day| parse_date(&format!("2025-03-{day:02}")).unwrap();
+        assert_eq!(query.after, Some(day(1) + DAY));
+        assert_eq!(query.before, Some(day(10)));
+        assert!(today().is_ok());
+    }
+}
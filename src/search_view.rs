@@ -0,0 +1,478 @@
+// Search bar with query autocomplete, result tabs, highlighted results and
+// recent and saved searches
+use crate::i18n::{tr, tr_with, trn};
+use crate::navigation;
+use crate::search::{ResultTarget, SearchIndex, SearchResult, SearchTab};
+use crate::search_query;
+use crate::sizing;
+use crate::state::AppState;
+use crate::util;
+use gtk4::glib;
+use gtk4::prelude::*;
+use gtk4::{Box as GtkBox, Button, Entry, Frame, Label, Orientation};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+/// The entry, the All / People / Groups / Posts / Media tabs and the
+/// results, which update as you type. Query syntax errors show under the
+/// entry along with completions for the word being typed. With nothing
+/// typed, saved and recent searches are listed instead.
+pub fn create_search_panel(state: &Rc<AppState>) -> GtkBox {
+    let panel = GtkBox::new(Orientation::Vertical, 20);
+
+    let search_box = GtkBox::new(Orientation::Horizontal, 12);
+    let search_entry = Entry::new();
+    search_entry.set_placeholder_text(Some(&tr("Search for anything...")));
+    search_entry.set_hexpand(true);
+    search_entry.add_css_class("search-entry-large");
+
+    let search_btn = Button::with_label(&tr("🔍 Search"));
+    search_btn.add_css_class("search-button");
+
+    let save_btn = Button::with_label(&tr("☆ Save"));
+    save_btn.add_css_class("secondary-button");
+    save_btn.set_tooltip_text(Some(&tr(
+        "Save this search and get notified of new results",
+    )));
+
+    search_box.append(&search_entry);
+    search_box.append(&search_btn);
+    search_box.append(&save_btn);
+    panel.append(&search_box);
+
+    let suggestions_box = GtkBox::new(Orientation::Horizontal, 8);
+    suggestions_box.set_visible(false);
+    panel.append(&suggestions_box);
+
+    let error_label = Label::new(None);
+    error_label.add_css_class("form-error");
+    error_label.set_halign(gtk4::Align::Start);
+    error_label.set_wrap(true);
+    error_label.set_xalign(0.0);
+    error_label.set_visible(false);
+    panel.append(&error_label);
+
+    let hint_label = Label::new(Some(&tr(
+        "Try from:@alice, in:#rust-developers, type:post|media, has:attachment, \
+         before:/after: dates, \"exact phrases\" and -exclusions",
+    )));
+    hint_label.add_css_class("search-hint");
+    hint_label.set_halign(gtk4::Align::Start);
+    hint_label.set_wrap(true);
+    hint_label.set_xalign(0.0);
+    panel.append(&hint_label);
+
+    let tabs_box = GtkBox::new(Orientation::Horizontal, 12);
+    panel.append(&tabs_box);
+
+    let results_label = Label::new(Some(&tr("Recent Searches")));
+    results_label.add_css_class("section-subtitle");
+    results_label.set_halign(gtk4::Align::Start);
+    results_label.set_margin_top(20);
+    panel.append(&results_label);
+
+    let results_box = GtkBox::new(Orientation::Vertical, 20);
+    panel.append(&results_box);
+
+    // Rebuilt on the next search after anything searchable changes
+    let index: Rc<RefCell<Option<SearchIndex>>> = Rc::new(RefCell::new(None));
+    let tab = Rc::new(Cell::new(SearchTab::All));
+
+    // Running a search remembers it and clears its saved search's count
+    let submit: Rc<dyn Fn()> = {
+        let state = state.clone();
+        let search_entry = search_entry.clone();
+        Rc::new(move || {
+            let text = search_entry.text();
+            let (query, errors) = search_query::parse(&text);
+            if query.is_empty() || !errors.is_empty() {
+                return;
+            }
+            let mut searches = state.searches.borrow_mut();
+            if state.preferences.save_search_history() {
+                searches.record(&text);
+            }
+            searches.mark_seen(&text);
+            drop(searches);
+            state.searches_changed.emit();
+        })
+    };
+    let open_search: Rc<dyn Fn(&str)> = {
+        let search_entry = search_entry.clone();
+        let submit = submit.clone();
+        Rc::new(move |query| {
+            search_entry.set_text(query);
+            search_entry.set_position(-1);
+            submit();
+        })
+    };
+
+    let populate: Rc<dyn Fn()> = {
+        let state = state.clone();
+        let index = index.clone();
+        let tab = tab.clone();
+        let search_entry = search_entry.clone();
+        let save_btn = save_btn.clone();
+        let open_search = open_search.clone();
+        Rc::new(move || {
+            while let Some(child) = results_box.first_child() {
+                results_box.remove(&child);
+            }
+            let text = search_entry.text();
+            let (query, errors) = search_query::parse(&text);
+            error_label.set_text(&errors.join("\n"));
+            error_label.set_visible(!errors.is_empty());
+            if errors.is_empty() {
+                search_entry.remove_css_class("error");
+            } else {
+                search_entry.add_css_class("error");
+            }
+            let saved = state.searches.borrow().saved_search(&text).is_some();
+            save_btn.set_label(&if saved {
+                tr("★ Saved")
+            } else {
+                tr("☆ Save")
+            });
+            save_btn.set_sensitive(!saved && !query.is_empty() && errors.is_empty());
+
+            if text.trim().is_empty() {
+                results_label.set_visible(false);
+                append_search_history(&state, &results_box, &open_search);
+                return;
+            }
+            results_label.set_visible(true);
+            if query.is_empty() {
+                results_label.set_text(&tr("Nothing to search for yet"));
+                return;
+            }
+
+            let results = index
+                .borrow_mut()
+                .get_or_insert_with(|| {
+                    SearchIndex::build(
+                        &state.community.borrow(),
+                        &state.feed.borrow(),
+                        &state.media.borrow(),
+                        &state.listings.borrow(),
+                    )
+                })
+                .search(&query, tab.get());
+            results_label.set_text(&if results.is_empty() {
+                tr_with("No results for “{query}”", &[("query", text.trim())])
+            } else {
+                trn(
+                    "{n} result for “{query}”",
+                    "{n} results for “{query}”",
+                    results.len() as u64,
+                )
+                .replace("{query}", text.trim())
+            });
+            for result in &results {
+                results_box.append(&create_search_result(result));
+            }
+        })
+    };
+
+    let mut buttons: Vec<Button> = Vec::new();
+    for option in SearchTab::ALL {
+        let tab_btn = Button::with_label(&tr(option.label()));
+        tab_btn.add_css_class("tab-button");
+        if option == tab.get() {
+            tab_btn.add_css_class("tab-active");
+        }
+        tabs_box.append(&tab_btn);
+        buttons.push(tab_btn);
+    }
+    for (button, option) in buttons.iter().zip(SearchTab::ALL) {
+        let buttons = buttons.clone();
+        let tab = tab.clone();
+        let populate = populate.clone();
+        button.connect_clicked(move |clicked| {
+            for button in &buttons {
+                button.remove_css_class("tab-active");
+            }
+            clicked.add_css_class("tab-active");
+            tab.set(option);
+            populate();
+        });
+    }
+
+    let p = populate.clone();
+    let state_clone = state.clone();
+    search_entry.connect_changed(move |entry| {
+        update_suggestions(&state_clone, entry, &suggestions_box);
+        p();
+    });
+    let s = submit.clone();
+    search_entry.connect_activate(move |_| s());
+    let s = submit.clone();
+    search_btn.connect_clicked(move |_| s());
+
+    let state_clone = state.clone();
+    let entry = search_entry.clone();
+    save_btn.connect_clicked(move |_| {
+        let result = state_clone.searches.borrow_mut().save_search(&entry.text());
+        if result.is_ok() {
+            state_clone.searches_changed.emit();
+        }
+    });
+
+    // A search asked for elsewhere, e.g. by a saved search notification
+    let show_requested = {
+        let state = state.clone();
+        let open_search = open_search.clone();
+        move || -> bool {
+            let requested = state.searches.borrow_mut().take_request();
+            requested.map(|query| open_search(&query)).is_some()
+        }
+    };
+
+    let invalidate = {
+        let index = index.clone();
+        let populate = populate.clone();
+        move || {
+            index.replace(None);
+            populate();
+        }
+    };
+    let invalidate: Rc<dyn Fn()> = Rc::new(invalidate);
+    for listeners in [
+        &state.community_changed,
+        &state.feed_changed,
+        &state.media_changed,
+        &state.listings_changed,
+    ] {
+        let invalidate = invalidate.clone();
+        listeners.connect(move || invalidate());
+    }
+
+    let p = populate.clone();
+    let show = show_requested.clone();
+    state.searches_changed.connect(move || {
+        if !show() {
+            p();
+        }
+    });
+
+    populate();
+    show_requested();
+    panel
+}
+
+/// Chips completing the word at the end of `entry`; clicking one puts it
+/// in the entry.
+fn update_suggestions(state: &AppState, entry: &Entry, suggestions_box: &GtkBox) {
+    while let Some(child) = suggestions_box.first_child() {
+        suggestions_box.remove(&child);
+    }
+    let suggestions = search_query::suggestions(&entry.text(), &state.community.borrow());
+    suggestions_box.set_visible(!suggestions.is_empty());
+    for suggestion in suggestions {
+        let chip = Button::with_label(&suggestion.label);
+        chip.add_css_class("suggestion-chip");
+        let entry = entry.clone();
+        chip.connect_clicked(move |_| {
+            entry.set_text(&suggestion.text);
+            entry.grab_focus();
+            entry.set_position(-1);
+        });
+        suggestions_box.append(&chip);
+    }
+}
+
+/// Saved searches, with their new-result counts, then recent ones.
+fn append_search_history(state: &Rc<AppState>, results_box: &GtkBox, open: &Rc<dyn Fn(&str)>) {
+    let searches = state.searches.borrow();
+    let now = util::now();
+
+    if !searches.saved().is_empty() {
+        results_box.append(&create_history_heading(&tr("⭐ Saved Searches"), None));
+        for saved in searches.saved() {
+            let detail = match saved.new_results {
+                0 => tr_with(
+                    "Saved {time}",
+                    &[("time", &util::format_relative_time(saved.saved_at, now))],
+                ),
+                count => trn("{n} new result", "{n} new results", count.into()),
+            };
+            let row = create_history_row("⭐", &saved.query, &detail, open);
+            if saved.new_results > 0 {
+                row.add_css_class("unread");
+            }
+
+            let alerts_btn = Button::with_label(if saved.alerts { "🔔" } else { "🔕" });
+            alerts_btn.set_tooltip_text(Some(&if saved.alerts {
+                tr("Stop notifying me of new results")
+            } else {
+                tr("Notify me of new results")
+            }));
+            alerts_btn.add_css_class("notification-button");
+            alerts_btn.set_valign(gtk4::Align::Center);
+            let state_clone = state.clone();
+            let (id, alerts) = (saved.id.clone(), saved.alerts);
+            alerts_btn.connect_clicked(move |_| {
+                state_clone.searches.borrow_mut().set_alerts(&id, !alerts);
+                state_clone.searches_changed.emit();
+            });
+
+            let remove_btn = Button::with_label("✕");
+            remove_btn.set_tooltip_text(Some(&tr("Remove saved search")));
+            remove_btn.add_css_class("notification-button");
+            remove_btn.set_valign(gtk4::Align::Center);
+            let state_clone = state.clone();
+            let id = saved.id.clone();
+            remove_btn.connect_clicked(move |_| {
+                state_clone.searches.borrow_mut().remove_saved(&id);
+                state_clone.searches_changed.emit();
+            });
+
+            if let Some(row_box) = row.child().and_downcast::<GtkBox>() {
+                row_box.append(&alerts_btn);
+                row_box.append(&remove_btn);
+            }
+            results_box.append(&row);
+        }
+    }
+
+    let clear_btn = Button::with_label(&tr("Clear"));
+    clear_btn.add_css_class("secondary-button");
+    clear_btn.set_sensitive(!searches.recent().is_empty());
+    let state_clone = state.clone();
+    clear_btn.connect_clicked(move |_| {
+        state_clone.searches.borrow_mut().clear_recent();
+        state_clone.searches_changed.emit();
+    });
+    results_box.append(&create_history_heading(
+        &tr("Recent Searches"),
+        Some(&clear_btn),
+    ));
+
+    if searches.recent().is_empty() {
+        let empty = Label::new(Some(&if state.preferences.save_search_history() {
+            tr("Your searches will show up here")
+        } else {
+            tr("Search history is off — turn it on under Settings › Privacy & Security")
+        }));
+        empty.add_css_class("share-empty");
+        results_box.append(&empty);
+    }
+    for recent in searches.recent() {
+        let detail = tr_with(
+            "Searched {time}",
+            &[("time", &util::format_relative_time(recent.searched_at, now))],
+        );
+        results_box.append(&create_history_row("🕘", &recent.query, &detail, open));
+    }
+}
+
+fn create_history_heading(title: &str, button: Option<&Button>) -> GtkBox {
+    let heading_box = GtkBox::new(Orientation::Horizontal, 12);
+    heading_box.set_margin_top(20);
+    let label = Label::new(Some(title));
+    label.add_css_class("section-subtitle");
+    label.set_halign(gtk4::Align::Start);
+    label.set_hexpand(true);
+    heading_box.append(&label);
+    if let Some(button) = button {
+        heading_box.append(button);
+    }
+    heading_box
+}
+
+/// A past or saved search; clicking it runs the search again.
+fn create_history_row(icon: &str, query: &str, detail: &str, open: &Rc<dyn Fn(&str)>) -> Frame {
+    let frame = create_result_card(icon, &glib::markup_escape_text(query), detail, None, None);
+    let click = gtk4::GestureClick::new();
+    let open = open.clone();
+    let query = query.to_string();
+    click.connect_released(move |_, _, _, _| open(&query));
+    frame.add_controller(click);
+    frame.set_cursor_from_name(Some("pointer"));
+    frame
+}
+
+fn create_search_result(result: &SearchResult) -> Frame {
+    create_result_card(
+        result.kind.icon(),
+        &result.title_markup,
+        &result.context,
+        result.snippet_markup.as_deref(),
+        Some(result.target.clone()),
+    )
+}
+
+fn create_result_card(
+    icon: &str,
+    title_markup: &str,
+    category: &str,
+    snippet_markup: Option<&str>,
+    target: Option<ResultTarget>,
+) -> Frame {
+    let frame = Frame::new(None);
+    frame.add_css_class("search-result");
+
+    let result_box = GtkBox::new(Orientation::Horizontal, 16);
+    result_box.set_margin_start(16);
+    result_box.set_margin_end(16);
+    result_box.set_margin_top(12);
+    result_box.set_margin_bottom(12);
+
+    let icon_label = Label::new(Some(icon));
+    icon_label.add_css_class("search-result-icon");
+
+    let content_box = GtkBox::new(Orientation::Vertical, 4);
+    content_box.set_hexpand(true);
+
+    let title_label = Label::new(None);
+    sizing::set_markup(&title_label, title_markup);
+    title_label.set_halign(gtk4::Align::Start);
+    title_label.add_css_class("search-result-title");
+
+    let category_label = Label::new(Some(category));
+    category_label.set_halign(gtk4::Align::Start);
+    category_label.add_css_class("search-result-category");
+
+    content_box.append(&title_label);
+    content_box.append(&category_label);
+
+    if let Some(snippet_markup) = snippet_markup {
+        let snippet_label = Label::new(None);
+        sizing::set_markup(&snippet_label, snippet_markup);
+        snippet_label.set_halign(gtk4::Align::Start);
+        snippet_label.set_xalign(0.0);
+        snippet_label.set_wrap(true);
+        snippet_label.add_css_class("search-result-snippet");
+        content_box.append(&snippet_label);
+    }
+
+    result_box.append(&icon_label);
+    result_box.append(&content_box);
+
+    if let Some(target) = target {
+        if let ResultTarget::Profile(user_id) = &target {
+            navigation::link_to_profile(&title_label, user_id);
+        }
+        let view_btn = Button::with_label(&tr("View"));
+        view_btn.add_css_class("view-button");
+        view_btn.set_valign(gtk4::Align::Center);
+        view_btn.connect_clicked(move |button| open_target(button, &target));
+        result_box.append(&view_btn);
+    }
+
+    frame.set_child(Some(&result_box));
+    frame
+}
+
+/// Shows the profile, page or conversation a result belongs to.
+fn open_target(widget: &impl IsA<gtk4::Widget>, target: &ResultTarget) {
+    let (action, parameter) = match target {
+        ResultTarget::Profile(id) => ("show-profile", id.to_variant()),
+        ResultTarget::Group(_) => ("show-page", "groups".to_variant()),
+        ResultTarget::Post(_) => ("show-page", "home".to_variant()),
+        ResultTarget::Conversation(id) => ("open-conversation", id.to_variant()),
+        ResultTarget::Media(_) => ("show-page", "media".to_variant()),
+        ResultTarget::Job(_) => ("show-page", "jobs".to_variant()),
+        ResultTarget::Event(_) => ("show-page", "events".to_variant()),
+    };
+    navigation::activate(widget, action, Some(&parameter));
+}
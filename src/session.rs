@@ -0,0 +1,155 @@
+// The account signed in on this device
+//
+// Signing in gets a session token from the account server (the stand-in in
+// auth.rs). It is kept in the keyring, so the next start goes straight to
+// the app, and rechecked against the server's records every minute, so a
+// session revoked from another device ends here too.
+use crate::auth::SignedIn;
+use crate::i18n::{tr, tr_noop};
+use crate::keyring;
+use crate::profile;
+use crate::state::AppState;
+use crate::util;
+use gtk4::glib;
+use std::cell::Cell;
+use std::rc::Rc;
+
+const CHECK_INTERVAL_SECONDS: u32 = 60;
+/// Names this device in the sessions list; the host name by default.
+const DEVICE_ENV: &str = "LINKWITHMENTOR_DEVICE_NAME";
+const ENDED_ELSEWHERE: &str =
+    tr_noop("You were signed out: this session was revoked or has expired.");
+
+#[derive(Clone, Debug)]
+pub struct Session {
+    pub token: String,
+    pub id: String,
+    pub email: String,
+    pub name: String,
+}
+
+thread_local! {
+    /// Why the last session ended, for the sign-in page to explain,
+    /// untranslated until shown since the language may not be set yet.
+    static NOTICE: Cell<Option<&'static str>> = const { Cell::new(None) };
+}
+
+pub fn device_name() -> String {
+    std::env::var(DEVICE_ENV)
+        .ok()
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| glib::host_name().to_string())
+}
+
+/// The signed-in account's email, or empty while signed out.
+pub fn email(state: &AppState) -> String {
+    state
+        .session
+        .borrow()
+        .as_ref()
+        .map(|session| session.email.clone())
+        .unwrap_or_default()
+}
+
+/// Resumes the session stored for this device if the server still accepts
+/// it, and starts checking it. Call once at startup.
+pub fn restore(state: &Rc<AppState>) {
+    let device = device_name();
+    if let Some(token) = keyring::load(&device) {
+        let signed_in = state.auth.borrow_mut().authenticate(&token, util::now());
+        match signed_in {
+            Some(signed_in) => start(state, token, signed_in),
+            None => {
+                keyring::clear(&device);
+                set_notice(ENDED_ELSEWHERE);
+            }
+        }
+    }
+
+    let state = state.clone();
+    glib::timeout_add_seconds_local(CHECK_INTERVAL_SECONDS, move || {
+        check(&state);
+        glib::ControlFlow::Continue
+    });
+}
+
+/// Signs in with a token the server just issued.
+pub fn sign_in(state: &Rc<AppState>, token: String) {
+    let signed_in = state.auth.borrow_mut().authenticate(&token, util::now());
+    let Some(signed_in) = signed_in else {
+        return;
+    };
+    keyring::store(&device_name(), &token);
+    start(state, token, signed_in);
+    state.auth_changed.emit();
+}
+
+fn start(state: &Rc<AppState>, token: String, signed_in: SignedIn) {
+    state.switch_account(Some(&signed_in.email));
+
+    // New profiles take the account's name
+    {
+        let mut profile = state.profile.borrow_mut();
+        if profile.display_name == profile::PLACEHOLDER_NAME && !signed_in.name.is_empty() {
+            profile.display_name = signed_in.name.clone();
+            profile.save();
+            let mut community = state.community.borrow_mut();
+            let private = community.is_private();
+            community.update_me(&profile.display_name, profile.avatar.clone(), private);
+        }
+    }
+
+    *state.session.borrow_mut() = Some(Session {
+        token,
+        id: signed_in.session_id,
+        email: signed_in.email,
+        name: signed_in.name,
+    });
+}
+
+/// Signs out of the server and forgets the token.
+pub fn sign_out(state: &Rc<AppState>) {
+    let Some(session) = state.session.borrow_mut().take() else {
+        return;
+    };
+    state.auth.borrow_mut().sign_out(&session.token);
+    keyring::clear(&device_name());
+    state.switch_account(None);
+    state.auth_changed.emit();
+}
+
+/// Ends the session here if the server no longer accepts it, e.g. because
+/// it was revoked from another device or the password was reset.
+pub fn check(state: &Rc<AppState>) {
+    let Some(token) = state
+        .session
+        .borrow()
+        .as_ref()
+        .map(|session| session.token.clone())
+    else {
+        return;
+    };
+    let valid = state
+        .auth
+        .borrow_mut()
+        .authenticate(&token, util::now())
+        .is_some();
+    if !valid {
+        state.session.borrow_mut().take();
+        keyring::clear(&device_name());
+        state.switch_account(None);
+        set_notice(ENDED_ELSEWHERE);
+        state.auth_changed.emit();
+    }
+}
+
+/// Explains on the sign-in page why it is shown. `notice` is a message
+/// marked with `tr_noop`, translated when the page shows it.
+pub fn set_notice(notice: &'static str) {
+    NOTICE.set(Some(notice));
+}
+
+/// The notice, translated.
+pub fn take_notice() -> Option<String> {
+    NOTICE.take().map(tr)
+}
@@ -0,0 +1,519 @@
+// Share history and delivery to people, groups and conversations
+use crate::community::{Conversation, ME};
+use crate::i18n::{self, tr, tr_noop, tr_with, trn};
+use crate::location::Location;
+use crate::media::MediaKind;
+use crate::state::AppState;
+use crate::storage;
+use crate::util::{self, DAY, HOUR};
+use gtk4::glib;
+use std::rc::Rc;
+
+const SHARES_FILE: &str = "shares.ini";
+const SHARE_PREFIX: &str = "share:";
+
+/// The six options offered by the Share view.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShareKind {
+    Document,
+    Image,
+    Video,
+    Link,
+    Location,
+    Poll,
+}
+
+impl ShareKind {
+    pub const ALL: [ShareKind; 6] = [
+        ShareKind::Document,
+        ShareKind::Image,
+        ShareKind::Video,
+        ShareKind::Link,
+        ShareKind::Location,
+        ShareKind::Poll,
+    ];
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            ShareKind::Document => "📄",
+            ShareKind::Image => "🖼️",
+            ShareKind::Video => "📹",
+            ShareKind::Link => "🔗",
+            ShareKind::Location => "📍",
+            ShareKind::Poll => "📊",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ShareKind::Document => tr_noop("Documents"),
+            ShareKind::Image => tr_noop("Images"),
+            ShareKind::Video => tr_noop("Videos"),
+            ShareKind::Link => tr_noop("Links"),
+            ShareKind::Location => tr_noop("Location"),
+            ShareKind::Poll => tr_noop("Polls"),
+        }
+    }
+
+    /// The library items this option picks from, if any.
+    pub fn media_kinds(self) -> &'static [MediaKind] {
+        match self {
+            ShareKind::Document => &[MediaKind::Document, MediaKind::Audio],
+            ShareKind::Image => &[MediaKind::Image],
+            ShareKind::Video => &[MediaKind::Video],
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShareContent {
+    Media {
+        id: String,
+        title: String,
+        kind: MediaKind,
+    },
+    Link {
+        url: String,
+        title: String,
+    },
+    Location {
+        latitude: f64,
+        longitude: f64,
+        label: String,
+    },
+}
+
+impl ShareContent {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            ShareContent::Media { kind, .. } => kind.icon(),
+            ShareContent::Link { .. } => ShareKind::Link.icon(),
+            ShareContent::Location { .. } => ShareKind::Location.icon(),
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            ShareContent::Media { title, .. } => title.clone(),
+            ShareContent::Link { url, title } if title.is_empty() => url.clone(),
+            ShareContent::Link { title, .. } => title.clone(),
+            ShareContent::Location {
+                latitude,
+                longitude,
+                label,
+            } if label.is_empty() => format!(
+                "{}, {}",
+                i18n::decimal(*latitude, 5),
+                i18n::decimal(*longitude, 5)
+            ),
+            ShareContent::Location { label, .. } => label.clone(),
+        }
+    }
+
+    pub fn location(&self) -> Option<Location> {
+        match self {
+            ShareContent::Location {
+                latitude,
+                longitude,
+                label,
+            } => Some(Location {
+                latitude: *latitude,
+                longitude: *longitude,
+                label: label.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// The text posted into conversations when this content is shared.
+    pub fn message_text(&self, note: &str) -> String {
+        let body = match self {
+            ShareContent::Media { title, .. } => {
+                format!(
+                    "{} {}",
+                    self.icon(),
+                    tr_with("Shared {title}", &[("title", title)])
+                )
+            }
+            ShareContent::Link { url, .. } => format!("{} {} — {url}", self.icon(), self.title()),
+            // The map preview under the message shows the coordinates
+            ShareContent::Location { .. } => format!("{} {}", self.icon(), self.title()),
+        };
+        if note.trim().is_empty() {
+            body
+        } else {
+            format!("{body}\n{}", note.trim())
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Recipient {
+    User(String),
+    Group(String),
+    Conversation(String),
+}
+
+impl Recipient {
+    /// Who receives what is shared into `conversation`: the other person for
+    /// direct chats, the conversation itself for group chats.
+    pub fn for_conversation(conversation: &Conversation) -> Self {
+        match conversation.participants.as_slice() {
+            [user_id] if conversation.title.is_none() => Recipient::User(user_id.clone()),
+            _ => Recipient::Conversation(conversation.id.clone()),
+        }
+    }
+
+    fn encode(&self) -> String {
+        match self {
+            Recipient::User(id) => format!("user:{id}"),
+            Recipient::Group(id) => format!("group:{id}"),
+            Recipient::Conversation(id) => format!("conversation:{id}"),
+        }
+    }
+
+    fn decode(value: &str) -> Option<Self> {
+        let (kind, id) = value.split_once(':')?;
+        let id = id.to_string();
+        match kind {
+            "user" => Some(Recipient::User(id)),
+            "group" => Some(Recipient::Group(id)),
+            "conversation" => Some(Recipient::Conversation(id)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShareStatus {
+    Sending,
+    Delivered,
+    /// Some recipients could not be reached; holds the reasons.
+    Partial(String),
+    Failed(String),
+}
+
+impl ShareStatus {
+    pub fn label(&self) -> String {
+        match self {
+            ShareStatus::Sending => tr("⏳ Sending…"),
+            ShareStatus::Delivered => tr("✅ Delivered"),
+            ShareStatus::Partial(reason) => {
+                tr_with("⚠️ Partly delivered: {reason}", &[("reason", reason)])
+            }
+            ShareStatus::Failed(reason) => tr_with("❌ Failed: {reason}", &[("reason", reason)]),
+        }
+    }
+
+    pub fn css_class(&self) -> &'static str {
+        match self {
+            ShareStatus::Sending => "share-status-sending",
+            ShareStatus::Delivered => "share-status-delivered",
+            ShareStatus::Partial(_) | ShareStatus::Failed(_) => "share-status-failed",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ShareRecord {
+    pub id: String,
+    pub content: ShareContent,
+    pub recipients: Vec<Recipient>,
+    pub message: String,
+    pub created_at: i64,
+    pub status: ShareStatus,
+}
+
+pub struct ShareHistory {
+    records: Vec<ShareRecord>,
+}
+
+impl ShareHistory {
+    pub fn load() -> Self {
+        let Some(key_file) = storage::load_key_file(SHARES_FILE) else {
+            return Self::with_samples();
+        };
+
+        let mut records: Vec<ShareRecord> = storage::groups_with_prefix(&key_file, SHARE_PREFIX)
+            .into_iter()
+            .filter_map(|(id, group)| {
+                let get = |key: &str| storage::string_or(&key_file, &group, key, "");
+                let content = match get("type").as_str() {
+                    "media" => ShareContent::Media {
+                        id: get("media_id"),
+                        title: get("title"),
+                        kind: MediaKind::from_id(&get("media_kind")),
+                    },
+                    "link" => ShareContent::Link {
+                        url: get("url"),
+                        title: get("title"),
+                    },
+                    "location" => ShareContent::Location {
+                        latitude: key_file.double(&group, "latitude").ok()?,
+                        longitude: key_file.double(&group, "longitude").ok()?,
+                        label: get("title"),
+                    },
+                    _ => return None,
+                };
+                let status = match get("status").as_str() {
+                    "delivered" => ShareStatus::Delivered,
+                    "partial" => ShareStatus::Partial(get("status_detail")),
+                    "failed" => ShareStatus::Failed(get("status_detail")),
+                    // A share still sending when the app quit never completed
+                    _ => ShareStatus::Failed(tr("Interrupted")),
+                };
+                Some(ShareRecord {
+                    content,
+                    recipients: storage::list(&key_file, &group, "recipients")
+                        .iter()
+                        .filter_map(|value| Recipient::decode(value))
+                        .collect(),
+                    message: get("message"),
+                    created_at: key_file.int64(&group, "created_at").unwrap_or_default(),
+                    status,
+                    id,
+                })
+            })
+            .collect();
+        records.sort_by_key(|record| std::cmp::Reverse(record.created_at));
+
+        Self { records }
+    }
+
+    fn with_samples() -> Self {
+        let now = util::now();
+        let media = |id: &str, title: &str, kind| ShareContent::Media {
+            id: id.to_string(),
+            title: title.to_string(),
+            kind,
+        };
+        let record = |id: &str, content, recipient, age| ShareRecord {
+            id: id.to_string(),
+            content,
+            recipients: vec![recipient],
+            message: String::new(),
+            created_at: now - age,
+            status: ShareStatus::Delivered,
+        };
+
+        Self {
+            records: vec![
+                record(
+                    "sample-1",
+                    media("", "Project Proposal.pdf", MediaKind::Document),
+                    Recipient::User("alice".to_string()),
+                    HOUR,
+                ),
+                record(
+                    "sample-2",
+                    media("", "Screenshot.png", MediaKind::Image),
+                    Recipient::Group("rust-developers".to_string()),
+                    3 * HOUR,
+                ),
+                record(
+                    "sample-3",
+                    media("", "Tutorial.mp4", MediaKind::Video),
+                    Recipient::User("bob".to_string()),
+                    DAY,
+                ),
+            ],
+        }
+    }
+
+    pub fn save(&self) {
+        let key_file = glib::KeyFile::new();
+
+        for record in &self.records {
+            let group = format!("{SHARE_PREFIX}{}", record.id);
+            match &record.content {
+                ShareContent::Media { id, title, kind } => {
+                    key_file.set_string(&group, "type", "media");
+                    key_file.set_string(&group, "media_id", id);
+                    key_file.set_string(&group, "media_kind", kind.id());
+                    key_file.set_string(&group, "title", title);
+                }
+                ShareContent::Link { url, title } => {
+                    key_file.set_string(&group, "type", "link");
+                    key_file.set_string(&group, "url", url);
+                    key_file.set_string(&group, "title", title);
+                }
+                ShareContent::Location {
+                    latitude,
+                    longitude,
+                    label,
+                } => {
+                    key_file.set_string(&group, "type", "location");
+                    key_file.set_double(&group, "latitude", *latitude);
+                    key_file.set_double(&group, "longitude", *longitude);
+                    key_file.set_string(&group, "title", label);
+                }
+            }
+
+            let recipients: Vec<String> = record.recipients.iter().map(Recipient::encode).collect();
+            storage::set_list(&key_file, &group, "recipients", &recipients);
+            key_file.set_string(&group, "message", &record.message);
+            key_file.set_int64(&group, "created_at", record.created_at);
+
+            let (status, detail) = match &record.status {
+                ShareStatus::Sending => ("sending", ""),
+                ShareStatus::Delivered => ("delivered", ""),
+                ShareStatus::Partial(reason) => ("partial", reason.as_str()),
+                ShareStatus::Failed(reason) => ("failed", reason.as_str()),
+            };
+            key_file.set_string(&group, "status", status);
+            key_file.set_string(&group, "status_detail", detail);
+        }
+
+        storage::save_key_file(SHARES_FILE, &key_file);
+    }
+
+    /// Records newest first.
+    pub fn records(&self) -> &[ShareRecord] {
+        &self.records
+    }
+
+    fn add(&mut self, record: ShareRecord) {
+        self.records.insert(0, record);
+        self.save();
+    }
+
+    fn set_status(&mut self, id: &str, status: ShareStatus) {
+        if let Some(record) = self.records.iter_mut().find(|record| record.id == id) {
+            record.status = status;
+            self.save();
+        }
+    }
+}
+
+/// Describes the recipients the way the history list shows them.
+pub fn describe_recipients(state: &AppState, recipients: &[Recipient]) -> String {
+    let community = state.community.borrow();
+    let names: Vec<String> = recipients
+        .iter()
+        .map(|recipient| match recipient {
+            Recipient::User(id) => community.user_name(id),
+            Recipient::Group(id) => community
+                .group(id)
+                .map(|group| group.name.clone())
+                .unwrap_or_else(|| id.clone()),
+            Recipient::Conversation(id) => community
+                .conversation(id)
+                .map(|conversation| community.conversation_title(conversation))
+                .unwrap_or_else(|| id.clone()),
+        })
+        .collect();
+
+    match (recipients, names.as_slice()) {
+        (_, []) => tr("Not shared yet"),
+        ([Recipient::Group(_) | Recipient::Conversation(_)], [one]) => {
+            tr_with("Shared in {name}", &[("name", one)])
+        }
+        (_, [one]) => tr_with("Shared with {name}", &[("name", one)]),
+        (_, [first, second]) => tr_with(
+            "Shared with {first} and {second}",
+            &[("first", first), ("second", second)],
+        ),
+        (_, [first, rest @ ..]) => trn(
+            "Shared with {first} and {n} other",
+            "Shared with {first} and {n} others",
+            rest.len() as u64,
+        )
+        .replace("{first}", first),
+    }
+}
+
+/// Records one share per content item and delivers it to every recipient.
+pub fn send_share(
+    state: &Rc<AppState>,
+    contents: Vec<ShareContent>,
+    recipients: Vec<Recipient>,
+    message: String,
+) {
+    let now = util::now();
+    let mut ids = Vec::new();
+    for content in contents {
+        let id = glib::uuid_string_random().to_string();
+        state.shares.borrow_mut().add(ShareRecord {
+            id: id.clone(),
+            content,
+            recipients: recipients.clone(),
+            message: message.clone(),
+            created_at: now,
+            status: ShareStatus::Sending,
+        });
+        ids.push(id);
+    }
+    state.shares_changed.emit();
+
+    // Deliver once the UI has shown the pending entries
+    let state = state.clone();
+    glib::idle_add_local_once(move || {
+        for id in ids {
+            deliver(&state, &id);
+        }
+        state.shares_changed.emit();
+        state.community_changed.emit();
+    });
+}
+
+fn deliver(state: &AppState, share_id: &str) {
+    let Some(record) = state
+        .shares
+        .borrow()
+        .records()
+        .iter()
+        .find(|record| record.id == share_id)
+        .cloned()
+    else {
+        return;
+    };
+
+    let text = record.content.message_text(&record.message);
+    let location = record.content.location();
+    let mut failures = Vec::new();
+    {
+        let mut community = state.community.borrow_mut();
+        for recipient in &record.recipients {
+            let delivered = match recipient {
+                Recipient::User(user_id) if community.is_blocked(user_id) => {
+                    failures.push(tr_with(
+                        "{name} is blocked",
+                        &[("name", &community.user_name(user_id))],
+                    ));
+                    continue;
+                }
+                Recipient::User(user_id) => {
+                    let conversation = community.direct_conversation(user_id);
+                    community.send_message(&conversation, ME, &text, location.clone())
+                }
+                Recipient::Conversation(id) => {
+                    community.send_message(id, ME, &text, location.clone())
+                }
+                Recipient::Group(id) => match community.group(id) {
+                    Some(group) if group.joined => true,
+                    Some(group) => {
+                        failures.push(tr_with(
+                            "not a member of {group}",
+                            &[("group", &group.name)],
+                        ));
+                        continue;
+                    }
+                    None => false,
+                },
+            };
+            if !delivered {
+                failures.push(tr("recipient not found"));
+            }
+        }
+    }
+
+    let status = if failures.is_empty() {
+        ShareStatus::Delivered
+    } else if failures.len() == record.recipients.len() {
+        ShareStatus::Failed(failures.join(", "))
+    } else {
+        ShareStatus::Partial(failures.join(", "))
+    };
+    state.shares.borrow_mut().set_status(share_id, status);
+}
@@ -0,0 +1,634 @@
+// Share sheet: pick content, choose recipients, add a message
+use crate::community::ME;
+use crate::i18n::{tr, tr_noop, tr_with, trn};
+use crate::location_view;
+use crate::media::{self, MediaItem};
+use crate::poll_view;
+use crate::profile_view;
+use crate::share::{self, Recipient, ShareContent, ShareKind};
+use crate::state::AppState;
+use crate::util;
+use gtk4::prelude::*;
+use gtk4::{gio, glib};
+use gtk4::{
+    Box as GtkBox, Button, CheckButton, Entry, Label, ListBox, Orientation, ScrolledWindow,
+    SearchEntry, Stack,
+};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+struct ShareSheet {
+    state: Rc<AppState>,
+    window: gtk4::Window,
+    stack: Stack,
+    contents: RefCell<Vec<ShareContent>>,
+    recipients: RefCell<Vec<Recipient>>,
+    message: Entry,
+    next_btn: Button,
+    back_btn: Button,
+    send_btn: Button,
+    summary: Label,
+    recipient_list: ListBox,
+    /// Library listener to disconnect when the sheet closes.
+    media_listener: RefCell<Option<usize>>,
+}
+
+/// Opens the sheet on the content picker for `kind`.
+pub fn open_share_sheet(parent: &impl IsA<gtk4::Widget>, state: &Rc<AppState>, kind: ShareKind) {
+    open_content_picker(parent, state, kind, Vec::new());
+}
+
+/// Opens the sheet on the content picker for `kind` with `recipient`
+/// already chosen, as the chat's attach menu does.
+pub fn open_share_sheet_to(
+    parent: &impl IsA<gtk4::Widget>,
+    state: &Rc<AppState>,
+    kind: ShareKind,
+    recipient: Recipient,
+) {
+    open_content_picker(parent, state, kind, vec![recipient]);
+}
+
+fn open_content_picker(
+    parent: &impl IsA<gtk4::Widget>,
+    state: &Rc<AppState>,
+    kind: ShareKind,
+    recipients: Vec<Recipient>,
+) {
+    // Polls are published to the feed rather than sent
+    if kind == ShareKind::Poll {
+        poll_view::open_poll_composer(parent, state);
+        return;
+    }
+
+    let sheet = ShareSheet::new(
+        parent,
+        state,
+        &format!(
+            "{} {}",
+            kind.icon(),
+            tr_with("Share {what}", &[("what", &tr(kind.label()))])
+        ),
+    );
+    sheet.recipients.replace(recipients);
+    let picker = sheet.create_content_page(kind);
+    sheet.stack.add_named(&picker, Some("content"));
+    sheet
+        .stack
+        .add_named(&sheet.create_recipient_page(), Some("recipients"));
+    sheet.show_page("content");
+    sheet.window.present();
+}
+
+/// Opens the sheet with `contents` already chosen, straight on recipients.
+pub fn open_share_sheet_for(
+    parent: &impl IsA<gtk4::Widget>,
+    state: &Rc<AppState>,
+    contents: Vec<ShareContent>,
+) {
+    if contents.is_empty() {
+        return;
+    }
+    let title = match contents.as_slice() {
+        [one] => format!(
+            "{} {}",
+            one.icon(),
+            tr_with("Share {what}", &[("what", &one.title())])
+        ),
+        many => trn("🔗 Share {n} item", "🔗 Share {n} items", many.len() as u64),
+    };
+    let sheet = ShareSheet::new(parent, state, &title);
+    sheet.contents.replace(contents);
+    sheet
+        .stack
+        .add_named(&sheet.create_recipient_page(), Some("recipients"));
+    sheet.show_page("recipients");
+    sheet.back_btn.set_visible(false);
+    sheet.window.present();
+}
+
+impl ShareSheet {
+    fn new(parent: &impl IsA<gtk4::Widget>, state: &Rc<AppState>, title: &str) -> Rc<Self> {
+        let window = gtk4::Window::builder()
+            .title(title)
+            .modal(true)
+            .default_width(560)
+            .default_height(680)
+            .build();
+        window.add_css_class("share-sheet");
+        if let Some(parent) = parent.root().and_downcast::<gtk4::Window>() {
+            window.set_transient_for(Some(&parent));
+        }
+
+        let root = GtkBox::new(Orientation::Vertical, 16);
+        root.set_margin_start(24);
+        root.set_margin_end(24);
+        root.set_margin_top(24);
+        root.set_margin_bottom(24);
+
+        let title_label = Label::new(Some(title));
+        title_label.set_halign(gtk4::Align::Start);
+        title_label.add_css_class("dialog-title");
+        root.append(&title_label);
+
+        let stack = Stack::builder()
+            .transition_type(gtk4::StackTransitionType::SlideLeftRight)
+            .vexpand(true)
+            .build();
+        root.append(&stack);
+
+        let summary = Label::new(None);
+        summary.set_halign(gtk4::Align::Start);
+        summary.add_css_class("share-summary");
+        root.append(&summary);
+
+        let footer = GtkBox::new(Orientation::Horizontal, 12);
+        let cancel_btn = Button::with_label(&tr("Cancel"));
+        cancel_btn.add_css_class("secondary-button");
+        let spacer = GtkBox::new(Orientation::Horizontal, 0);
+        spacer.set_hexpand(true);
+        let back_btn = Button::with_label(&tr("← Back"));
+        back_btn.add_css_class("secondary-button");
+        let next_btn = Button::with_label(&tr("Next →"));
+        next_btn.add_css_class("create-post-button");
+        next_btn.set_sensitive(false);
+        let send_btn = Button::with_label(&tr("🚀 Send"));
+        send_btn.add_css_class("create-post-button");
+        send_btn.set_sensitive(false);
+
+        footer.append(&cancel_btn);
+        footer.append(&spacer);
+        footer.append(&back_btn);
+        footer.append(&next_btn);
+        footer.append(&send_btn);
+        root.append(&footer);
+        window.set_child(Some(&root));
+
+        let message = Entry::new();
+        message.set_placeholder_text(Some(&tr("Add a message (optional)")));
+
+        let sheet = Rc::new(Self {
+            state: state.clone(),
+            window: window.clone(),
+            stack,
+            contents: RefCell::new(Vec::new()),
+            recipients: RefCell::new(Vec::new()),
+            message,
+            next_btn: next_btn.clone(),
+            back_btn: back_btn.clone(),
+            send_btn: send_btn.clone(),
+            summary,
+            recipient_list: ListBox::new(),
+            media_listener: RefCell::new(None),
+        });
+
+        let w = window.clone();
+        cancel_btn.connect_clicked(move |_| w.close());
+        let s = sheet.clone();
+        back_btn.connect_clicked(move |_| s.show_page("content"));
+        let s = sheet.clone();
+        next_btn.connect_clicked(move |_| s.show_page("recipients"));
+        let s = sheet.clone();
+        send_btn.connect_clicked(move |_| s.send());
+
+        let s = sheet.clone();
+        window.connect_close_request(move |_| {
+            if let Some(id) = s.media_listener.take() {
+                s.state.media_changed.disconnect(id);
+            }
+            glib::Propagation::Proceed
+        });
+
+        sheet
+    }
+
+    fn show_page(&self, name: &str) {
+        self.stack.set_visible_child_name(name);
+        let on_content = name == "content";
+        self.back_btn.set_visible(!on_content);
+        self.next_btn.set_visible(on_content);
+        self.send_btn.set_visible(!on_content);
+        self.update_summary();
+    }
+
+    fn set_contents(&self, contents: Vec<ShareContent>) {
+        self.next_btn.set_sensitive(!contents.is_empty());
+        self.contents.replace(contents);
+        self.update_summary();
+    }
+
+    fn update_summary(&self) {
+        let items = self.contents.borrow().len();
+        let recipients = self.recipients.borrow().len();
+        self.send_btn.set_sensitive(items > 0 && recipients > 0);
+        self.summary.set_text(&match (items, recipients) {
+            (0, _) => tr("Choose what to share"),
+            (n, 0) => trn(
+                "{n} item selected · choose recipients",
+                "{n} items selected · choose recipients",
+                n as u64,
+            ),
+            (n, m) => tr_with(
+                "Sharing {items} with {recipients}",
+                &[
+                    ("items", &trn("{n} item", "{n} items", n as u64)),
+                    (
+                        "recipients",
+                        &trn("{n} recipient", "{n} recipients", m as u64),
+                    ),
+                ],
+            ),
+        });
+    }
+
+    fn send(&self) {
+        let contents = self.contents.take();
+        let recipients = self.recipients.take();
+        share::send_share(
+            &self.state,
+            contents,
+            recipients,
+            self.message.text().to_string(),
+        );
+        self.window.close();
+    }
+
+    // ------------------------------------------------------------------
+    // Content pickers
+    // ------------------------------------------------------------------
+
+    fn create_content_page(self: &Rc<Self>, kind: ShareKind) -> gtk4::Widget {
+        match kind {
+            ShareKind::Document | ShareKind::Image | ShareKind::Video => {
+                self.create_media_picker(kind)
+            }
+            ShareKind::Link => self.create_link_picker(),
+            ShareKind::Location => self.create_location_picker(),
+            ShareKind::Poll => unreachable!("polls open the poll composer"),
+        }
+    }
+
+    fn create_media_picker(self: &Rc<Self>, kind: ShareKind) -> gtk4::Widget {
+        let page = GtkBox::new(Orientation::Vertical, 12);
+
+        let browse_btn = Button::with_label(&tr("📂 Upload from computer…"));
+        browse_btn.add_css_class("secondary-button");
+        browse_btn.set_halign(gtk4::Align::Start);
+        page.append(&browse_btn);
+
+        let upload_panel = media::create_upload_panel();
+        page.append(&upload_panel);
+
+        let scroll = ScrolledWindow::new();
+        scroll.set_vexpand(true);
+        let list = ListBox::new();
+        list.set_selection_mode(gtk4::SelectionMode::None);
+        list.add_css_class("share-picker-list");
+        scroll.set_child(Some(&list));
+        page.append(&scroll);
+
+        let selected: Rc<RefCell<HashSet<String>>> = Rc::default();
+        let populate = {
+            let sheet = Rc::downgrade(self);
+            let list = list.clone();
+            let selected = selected.clone();
+            move || {
+                let Some(sheet) = sheet.upgrade() else {
+                    return;
+                };
+                let items: Vec<MediaItem> = sheet
+                    .state
+                    .media
+                    .borrow()
+                    .items()
+                    .iter()
+                    .filter(|item| kind.media_kinds().contains(&item.kind))
+                    .cloned()
+                    .collect();
+                sheet.populate_media_list(&list, &items, &selected);
+            }
+        };
+        populate();
+        let id = self.state.media_changed.connect(populate);
+        self.media_listener.replace(Some(id));
+
+        let state = self.state.clone();
+        browse_btn.connect_clicked(move |button| {
+            let dialog = gtk4::FileDialog::builder()
+                .title(tr("Upload and Share"))
+                .modal(true)
+                .build();
+            let window = button.root().and_downcast::<gtk4::Window>();
+            let state = state.clone();
+            let panel = upload_panel.clone();
+            dialog.open_multiple(window.as_ref(), gio::Cancellable::NONE, move |result| {
+                if let Ok(list) = result {
+                    let files = list.iter::<gio::File>().filter_map(Result::ok).collect();
+                    media::start_uploads(&state, &panel, files);
+                }
+            });
+        });
+
+        page.upcast()
+    }
+
+    fn populate_media_list(
+        self: &Rc<Self>,
+        list: &ListBox,
+        items: &[MediaItem],
+        selected: &Rc<RefCell<HashSet<String>>>,
+    ) {
+        while let Some(child) = list.first_child() {
+            list.remove(&child);
+        }
+        if items.is_empty() {
+            let empty = Label::new(Some(&tr("Nothing here yet — upload a file to share it.")));
+            empty.add_css_class("share-empty");
+            list.append(&empty);
+            return;
+        }
+
+        let now = util::now();
+        for item in items {
+            let row = GtkBox::new(Orientation::Horizontal, 12);
+            row.set_margin_start(8);
+            row.set_margin_end(8);
+            row.set_margin_top(6);
+            row.set_margin_bottom(6);
+
+            let check = CheckButton::new();
+            check.set_active(selected.borrow().contains(&item.id));
+            let icon = Label::new(Some(item.kind.icon()));
+            icon.add_css_class("share-item-icon");
+            let title = Label::new(Some(&item.title));
+            title.set_halign(gtk4::Align::Start);
+            title.set_hexpand(true);
+            title.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+            title.add_css_class("share-item-file");
+            let time = Label::new(Some(&util::format_relative_time(item.uploaded_at, now)));
+            time.add_css_class("share-item-time");
+
+            row.append(&check);
+            row.append(&icon);
+            row.append(&title);
+            row.append(&time);
+            list.append(&row);
+
+            let sheet = self.clone();
+            let selected = selected.clone();
+            let item = item.clone();
+            check.connect_toggled(move |check| {
+                if check.is_active() {
+                    selected.borrow_mut().insert(item.id.clone());
+                } else {
+                    selected.borrow_mut().remove(&item.id);
+                }
+                let contents: Vec<ShareContent> = sheet
+                    .state
+                    .media
+                    .borrow()
+                    .items()
+                    .iter()
+                    .filter(|item| selected.borrow().contains(&item.id))
+                    .map(|item| ShareContent::Media {
+                        id: item.id.clone(),
+                        title: item.title.clone(),
+                        kind: item.kind,
+                    })
+                    .collect();
+                sheet.set_contents(contents);
+            });
+        }
+    }
+
+    fn create_link_picker(self: &Rc<Self>) -> gtk4::Widget {
+        let page = GtkBox::new(Orientation::Vertical, 12);
+
+        let url_entry = Entry::new();
+        url_entry.set_placeholder_text(Some("https://example.com/article"));
+        let title_entry = Entry::new();
+        title_entry.set_placeholder_text(Some(&tr("Title (optional)")));
+        let error_label = Label::new(None);
+        error_label.set_halign(gtk4::Align::Start);
+        error_label.add_css_class("form-error");
+
+        page.append(&form_label(&tr("Link")));
+        page.append(&url_entry);
+        page.append(&form_label(&tr("Title")));
+        page.append(&title_entry);
+        page.append(&error_label);
+
+        let update = {
+            let sheet = self.clone();
+            let url_entry = url_entry.clone();
+            let title_entry = title_entry.clone();
+            move || {
+                let url = url_entry.text().trim().to_string();
+                match util::validate_url(&url) {
+                    Ok(()) => {
+                        error_label.set_text("");
+                        sheet.set_contents(vec![ShareContent::Link {
+                            url,
+                            title: title_entry.text().trim().to_string(),
+                        }]);
+                    }
+                    Err(message) => {
+                        error_label.set_text(&if url.is_empty() {
+                            String::new()
+                        } else {
+                            tr(message)
+                        });
+                        sheet.set_contents(Vec::new());
+                    }
+                }
+            }
+        };
+        let update = Rc::new(update);
+        let u = update.clone();
+        url_entry.connect_changed(move |_| u());
+        title_entry.connect_changed(move |_| update());
+
+        page.upcast()
+    }
+
+    fn create_location_picker(self: &Rc<Self>) -> gtk4::Widget {
+        let sheet = self.clone();
+        location_view::create_location_picker(&self.state, move |location| {
+            sheet.set_contents(vec![ShareContent::Location {
+                latitude: location.latitude,
+                longitude: location.longitude,
+                label: location.label.clone(),
+            }]);
+        })
+        .upcast()
+    }
+
+    // ------------------------------------------------------------------
+    // Recipients
+    // ------------------------------------------------------------------
+
+    fn create_recipient_page(self: &Rc<Self>) -> gtk4::Widget {
+        let page = GtkBox::new(Orientation::Vertical, 12);
+
+        let search = SearchEntry::new();
+        search.set_placeholder_text(Some(&tr("Search people, groups and chats")));
+        page.append(&search);
+
+        let scroll = ScrolledWindow::new();
+        scroll.set_vexpand(true);
+        self.recipient_list
+            .set_selection_mode(gtk4::SelectionMode::None);
+        self.recipient_list.add_css_class("share-picker-list");
+        scroll.set_child(Some(&self.recipient_list));
+        page.append(&scroll);
+
+        page.append(&form_label(&tr("Message")));
+        page.append(&self.message);
+
+        self.populate_recipients("");
+        let sheet = self.clone();
+        search.connect_search_changed(move |search| sheet.populate_recipients(&search.text()));
+
+        page.upcast()
+    }
+
+    fn populate_recipients(self: &Rc<Self>, query: &str) {
+        let list = &self.recipient_list;
+        while let Some(child) = list.first_child() {
+            list.remove(&child);
+        }
+
+        let query = query.trim().to_lowercase();
+        let matches = |text: &str| query.is_empty() || text.to_lowercase().contains(&query);
+
+        let community = self.state.community.borrow();
+        let people: Vec<(Recipient, gtk4::Widget, String)> = community
+            .users()
+            .iter()
+            .filter(|user| {
+                user.id != ME
+                    && !community.is_blocked(&user.id)
+                    && (matches(&user.name) || matches(&user.handle))
+            })
+            .map(|user| {
+                (
+                    Recipient::User(user.id.clone()),
+                    profile_view::create_avatar(Some(user), "chat-item-avatar", 32),
+                    format!("{}  @{}", user.name, user.handle),
+                )
+            })
+            .collect();
+        let groups: Vec<(Recipient, gtk4::Widget, String)> = community
+            .groups()
+            .iter()
+            .filter(|group| matches(&group.name))
+            .map(|group| {
+                let title = if group.joined {
+                    group.name.clone()
+                } else {
+                    tr_with("{group}  (not joined)", &[("group", &group.name)])
+                };
+                (
+                    Recipient::Group(group.id.clone()),
+                    icon_label(&group.emoji),
+                    title,
+                )
+            })
+            .collect();
+        let chats: Vec<(Recipient, gtk4::Widget, String)> = community
+            .conversations()
+            .iter()
+            .filter(|conversation| conversation.title.is_some())
+            .map(|conversation| {
+                (
+                    Recipient::Conversation(conversation.id.clone()),
+                    icon_label("💬"),
+                    community.conversation_title(conversation),
+                )
+            })
+            .filter(|(_, _, title)| matches(title))
+            .collect();
+        drop(community);
+
+        for (heading, entries) in [
+            (tr_noop("People"), people),
+            (tr_noop("Groups"), groups),
+            (tr_noop("Chats"), chats),
+        ] {
+            if entries.is_empty() {
+                continue;
+            }
+            let header = Label::new(Some(&tr(heading)));
+            header.set_halign(gtk4::Align::Start);
+            header.add_css_class("share-picker-heading");
+            list.append(&header);
+            if let Some(row) = list.last_child().and_downcast::<gtk4::ListBoxRow>() {
+                row.set_activatable(false);
+            }
+
+            for (recipient, icon, title) in entries {
+                self.append_recipient_row(recipient, &icon, &title);
+            }
+        }
+
+        if list.first_child().is_none() {
+            let empty = Label::new(Some(&tr("No matches")));
+            empty.add_css_class("share-empty");
+            list.append(&empty);
+        }
+    }
+
+    fn append_recipient_row(
+        self: &Rc<Self>,
+        recipient: Recipient,
+        icon: &gtk4::Widget,
+        title: &str,
+    ) {
+        let row = GtkBox::new(Orientation::Horizontal, 12);
+        row.set_margin_start(8);
+        row.set_margin_end(8);
+        row.set_margin_top(6);
+        row.set_margin_bottom(6);
+
+        let check = CheckButton::new();
+        check.set_active(self.recipients.borrow().contains(&recipient));
+        let title_label = Label::new(Some(title));
+        title_label.set_halign(gtk4::Align::Start);
+        title_label.set_hexpand(true);
+        title_label.add_css_class("chat-item-name");
+
+        row.append(&check);
+        row.append(icon);
+        row.append(&title_label);
+        self.recipient_list.append(&row);
+
+        let sheet = self.clone();
+        check.connect_toggled(move |check| {
+            {
+                let mut recipients = sheet.recipients.borrow_mut();
+                if check.is_active() {
+                    if !recipients.contains(&recipient) {
+                        recipients.push(recipient.clone());
+                    }
+                } else {
+                    recipients.retain(|r| *r != recipient);
+                }
+            }
+            sheet.update_summary();
+        });
+    }
+}
+
+fn icon_label(icon: &str) -> gtk4::Widget {
+    let label = Label::new(Some(icon));
+    label.add_css_class("chat-item-avatar");
+    label.upcast()
+}
+
+fn form_label(text: &str) -> Label {
+    let label = Label::new(Some(text));
+    label.set_halign(gtk4::Align::Start);
+    label.add_css_class("form-label");
+    label
+}
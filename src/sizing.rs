@@ -0,0 +1,372 @@
+// Text size and layout density
+//
+// style.css is written for 100% text and comfortable spacing. A generated
+// provider above it and the theme repeats each of its font sizes scaled by
+// the "text-scale" setting, and in compact density each padding, margin
+// and minimum size, tightened. Toplevels get a percentage font size, so
+// entries and other text without a CSS size follow along while the font
+// itself stays the desktop's. Sizes set in code go through
+// `set_size_request`, and markup through `set_markup`, whose absolute
+// Pango sizes the CSS can't reach, to stay in step. Everything updates as soon as a
+// setting or the stylesheet changes.
+use crate::assets;
+use gtk4::prelude::*;
+use gtk4::{gdk, gio, glib, pango, CssProvider};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+/// Above the theme variants, so scaled sizes win over them.
+const PRIORITY: u32 = gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION + 2;
+
+/// Spacing kept in compact density.
+const COMPACT_SPACING: f64 = 0.6;
+
+/// The nodes without a parent, whose font size is relative to the
+/// desktop font.
+const ROOT_SELECTOR: &str = "window, tooltip";
+
+const SIZE_PROPERTIES: [&str; 1] = ["font-size"];
+const SPACING_PROPERTIES: [&str; 12] = [
+    "padding",
+    "padding-top",
+    "padding-bottom",
+    "padding-left",
+    "padding-right",
+    "margin",
+    "margin-top",
+    "margin-bottom",
+    "margin-left",
+    "margin-right",
+    "min-height",
+    "min-width",
+];
+
+/// A widget sized in code, with its size at 100%.
+struct SizedWidget {
+    widget: glib::WeakRef<gtk4::Widget>,
+    width: i32,
+    height: i32,
+}
+
+/// A label with absolute font sizes in its markup, at 100%.
+struct SizedLabel {
+    label: glib::WeakRef<gtk4::Label>,
+    sizes: pango::AttrList,
+}
+
+thread_local! {
+    static TEXT_SCALE: Cell<f64> = const { Cell::new(1.0) };
+    static SIZED: RefCell<Vec<SizedWidget>> = const { RefCell::new(Vec::new()) };
+    static SIZED_LABELS: RefCell<Vec<SizedLabel>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Sets `widget`'s size request, scaled by the text size and kept scaled
+/// when it changes. -1 leaves a dimension unset, as in GTK.
+pub fn set_size_request(widget: &impl IsA<gtk4::Widget>, width: i32, height: i32) {
+    let widget = widget.upcast_ref::<gtk4::Widget>();
+    let scale = TEXT_SCALE.get();
+    widget.set_size_request(scaled(width, scale), scaled(height, scale));
+    SIZED.with(|sized| {
+        sized.borrow_mut().push(SizedWidget {
+            widget: widget.downgrade(),
+            width,
+            height,
+        });
+    });
+}
+
+/// Sets `label`'s markup. Relative sizes such as <big> follow the CSS;
+/// absolute ones such as size="14pt" are scaled by the text size here,
+/// and kept scaled when it changes.
+pub fn set_markup(label: &gtk4::Label, markup: &str) {
+    label.set_markup(markup);
+    SIZED_LABELS.with(|labels| {
+        labels
+            .borrow_mut()
+            .retain(|sized| sized.label.upgrade().is_some_and(|known| known != *label));
+    });
+    let Some(sizes) = absolute_sizes(markup) else {
+        label.set_attributes(None);
+        return;
+    };
+    label.set_attributes(Some(&scaled_sizes(&sizes, TEXT_SCALE.get())));
+    SIZED_LABELS.with(|labels| {
+        labels.borrow_mut().push(SizedLabel {
+            label: label.downgrade(),
+            sizes,
+        });
+    });
+}
+
+/// The absolute size attributes in `markup`, if it has any.
+fn absolute_sizes(markup: &str) -> Option<pango::AttrList> {
+    // Links are GTK's addition to the markup; they hold no text of their
+    // own, so leaving them out keeps the offsets right
+    let (attributes, _, _) = pango::parse_markup(&strip_links(markup), '\0').ok()?;
+    attributes.filter(|attribute| {
+        matches!(
+            attribute.type_(),
+            pango::AttrType::Size | pango::AttrType::AbsoluteSize
+        )
+    })
+}
+
+fn strip_links(markup: &str) -> String {
+    let mut text = String::new();
+    let mut rest = markup;
+    while let Some(start) = [rest.find("<a "), rest.find("</a>")]
+        .into_iter()
+        .flatten()
+        .min()
+    {
+        text.push_str(&rest[..start]);
+        rest = rest[start..]
+            .find('>')
+            .map_or("", |end| &rest[start + end + 1..]);
+    }
+    text.push_str(rest);
+    text
+}
+
+/// `sizes` times `scale`.
+fn scaled_sizes(sizes: &pango::AttrList, scale: f64) -> pango::AttrList {
+    let scaled = pango::AttrList::new();
+    for attribute in sizes.attributes() {
+        let Some(size) = attribute.downcast_ref::<pango::AttrSize>() else {
+            continue;
+        };
+        let value = (f64::from(size.size()) * scale).round() as i32;
+        let mut resized = pango::Attribute::from(if size.absolute() {
+            pango::AttrSize::new_size_absolute(value)
+        } else {
+            pango::AttrSize::new(value)
+        });
+        resized.set_start_index(attribute.start_index());
+        resized.set_end_index(attribute.end_index());
+        scaled.insert(resized);
+    }
+    scaled
+}
+
+fn scaled(size: i32, scale: f64) -> i32 {
+    if size > 0 {
+        (f64::from(size) * scale).round() as i32
+    } else {
+        size
+    }
+}
+
+struct Sizing {
+    settings: gio::Settings,
+    provider: CssProvider,
+}
+
+impl Sizing {
+    fn apply(&self) {
+        let scale = self.settings.double("text-scale");
+        let compact = self.settings.string("density") == "compact";
+        TEXT_SCALE.set(scale);
+
+        let stylesheet = assets::load_bytes("style.css")
+            .map(|data| String::from_utf8_lossy(&data).into_owned())
+            .unwrap_or_default();
+        self.provider
+            .load_from_data(&scaled_css(&stylesheet, scale, compact));
+
+        SIZED.with(|sized| {
+            sized.borrow_mut().retain(|sized| {
+                let Some(widget) = sized.widget.upgrade() else {
+                    return false;
+                };
+                widget.set_size_request(scaled(sized.width, scale), scaled(sized.height, scale));
+                true
+            });
+        });
+        SIZED_LABELS.with(|labels| {
+            labels.borrow_mut().retain(|sized| {
+                let Some(label) = sized.label.upgrade() else {
+                    return false;
+                };
+                label.set_attributes(Some(&scaled_sizes(&sized.sizes, scale)));
+                true
+            });
+        });
+    }
+}
+
+/// Applies the text size and density in `settings` and follows their
+/// changes. Call once the display is up, after the theme.
+pub fn install(settings: &gio::Settings) {
+    let Some(display) = gdk::Display::default() else {
+        return;
+    };
+    let provider = CssProvider::new();
+    gtk4::style_context_add_provider_for_display(&display, &provider, PRIORITY);
+
+    let sizing = Rc::new(Sizing {
+        settings: settings.clone(),
+        provider,
+    });
+    sizing.apply();
+
+    for key in ["text-scale", "density"] {
+        let sizing = sizing.clone();
+        settings.connect_changed(Some(key), move |_, _| sizing.apply());
+    }
+    assets::watch("style.css", move || sizing.apply());
+}
+
+/// Rules repeating the sizes in `stylesheet`: font sizes times `scale`,
+/// and spacing tightened when `compact`. Rules keep their order, so later
+/// ones still win as they do in the stylesheet. They follow a rule scaling
+/// the desktop font's size, leaving its family and style alone.
+fn scaled_css(stylesheet: &str, scale: f64, compact: bool) -> String {
+    let mut css = format!(
+        "{ROOT_SELECTOR} {{\n    font-size: {:.1}%;\n}}\n",
+        scale * 100.0
+    );
+    for (selector, declarations) in css_rules(&strip_comments(stylesheet)) {
+        let mut body = String::new();
+        for declaration in declarations.split(';') {
+            let Some((property, value)) = declaration.split_once(':') else {
+                continue;
+            };
+            let property = property.trim();
+            let value = if SIZE_PROPERTIES.contains(&property) {
+                scale_lengths(value, scale, 1)
+            } else if compact && SPACING_PROPERTIES.contains(&property) {
+                scale_lengths(value, COMPACT_SPACING, 0)
+            } else {
+                continue;
+            };
+            body.push_str(&format!("    {property}: {value};\n"));
+        }
+        if !body.is_empty() {
+            css.push_str(&format!("{selector} {{\n{body}}}\n"));
+        }
+    }
+    css
+}
+
+fn strip_comments(stylesheet: &str) -> String {
+    let mut text = String::new();
+    let mut rest = stylesheet;
+    while let Some(start) = rest.find("/*") {
+        text.push_str(&rest[..start]);
+        rest = rest[start + 2..]
+            .find("*/")
+            .map_or("", |end| &rest[start + 2 + end + 2..]);
+    }
+    text.push_str(rest);
+    text
+}
+
+/// The (selector, declarations) of each rule. Statements outside rules,
+/// such as @define-color, are skipped.
+fn css_rules(stylesheet: &str) -> Vec<(&str, &str)> {
+    stylesheet
+        .split('}')
+        .filter_map(|chunk| {
+            let (head, declarations) = chunk.split_once('{')?;
+            let selector = head.rsplit(';').next()?.trim();
+            (!selector.is_empty() && !selector.starts_with('@')).then_some((selector, declarations))
+        })
+        .collect()
+}
+
+/// `value` with each "Npx" length multiplied by `factor`, to `decimals`
+/// places.
+fn scale_lengths(value: &str, factor: f64, decimals: usize) -> String {
+    value
+        .split_whitespace()
+        .map(
+            |token| match token.strip_suffix("px").map(str::parse::<f64>) {
+                Some(Ok(length)) => format!("{:.decimals$}px", length * factor),
+                _ => token.to_string(),
+            },
+        )
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_pixel_lengths_are_scaled() {
+        assert_eq!(scale_lengths(" 14px", 1.25, 1), "17.5px");
+        assert_eq!(scale_lengths("8px 12px", 0.5, 0), "4px 6px");
+        // Relative units already follow the root font size
+        for value in ["1.2em", "0.9rem", "120%", "larger", "0", "calc(2px+1em)"] {
+            assert_eq!(scale_lengths(value, 2.0, 1), value);
+        }
+        assert_eq!(scale_lengths("8px auto 1em", 0.5, 0), "4px auto 1em");
+    }
+
+    #[test]
+    fn scaled_css_repeats_font_sizes() {
+        let css = scaled_css(
+            "@define-color accent #8b5cf6;\n\
+             /* .ignored { font-size: 99px; } */\n\
+             .title { font-size: 20px; color: @accent; padding: 10px; }\n\
+             .small { font-size: 0.8em; }",
+            1.5,
+            false,
+        );
+        assert_eq!(
+            css,
+            "window, tooltip {\n    font-size: 150.0%;\n}\n\
+             .title {\n    font-size: 30.0px;\n}\n\
+             .small {\n    font-size: 0.8em;\n}\n"
+        );
+    }
+
+    #[test]
+    fn compact_tightens_spacing() {
+        let css = scaled_css(
+            ".row { padding: 10px 20px; min-height: 40px; border-width: 2px; }",
+            1.0,
+            true,
+        );
+        assert_eq!(
+            css,
+            "window, tooltip {\n    font-size: 100.0%;\n}\n\
+             .row {\n    padding: 6px 12px;\n    min-height: 24px;\n}\n"
+        );
+        // Comfortable density leaves spacing alone
+        assert!(!scaled_css(".row { padding: 10px; }", 1.0, false).contains(".row"));
+    }
+
+    #[test]
+    fn links_are_stripped_for_pango() {
+        assert_eq!(
+            strip_links("see <a href=\"https://a.example/?x=1&amp;y=2\">a.example</a> <b>now</b>"),
+            "see a.example <b>now</b>"
+        );
+        assert_eq!(strip_links("no links"), "no links");
+    }
+
+    #[test]
+    fn absolute_markup_sizes_are_scaled() {
+        assert!(absolute_sizes("<b>bold</b> and <big>big</big>").is_none());
+
+        let sizes = absolute_sizes(
+            "<a href=\"x\">link</a> <span size=\"10pt\">ten</span> \
+             <span size=\"12288\">twelve</span>",
+        )
+        .unwrap();
+        let scaled: Vec<_> = scaled_sizes(&sizes, 1.5)
+            .attributes()
+            .into_iter()
+            .map(|attribute| {
+                let size = attribute.downcast_ref::<pango::AttrSize>().unwrap();
+                (attribute.start_index(), size.size(), size.absolute())
+            })
+            .collect();
+        assert_eq!(
+            scaled,
+            [(5, 15 * pango::SCALE, false), (9, 18 * pango::SCALE, false)]
+        );
+    }
+}
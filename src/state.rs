@@ -0,0 +1,196 @@
+// Shared application state handed to every view
+use crate::activity::ActivityPrivacy;
+use crate::auth::AuthServer;
+use crate::community::Community;
+use crate::feed::Feed;
+use crate::hashtags::FollowedTags;
+use crate::links::LinkStore;
+use crate::listings::Listings;
+use crate::location::MapService;
+use crate::media::MediaLibrary;
+use crate::notifications::NotificationCenter;
+use crate::preferences::Preferences;
+use crate::profile::Profile;
+use crate::search_history::SearchHistory;
+use crate::session::Session;
+use crate::share::ShareHistory;
+use crate::storage;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+type Callback = Rc<dyn Fn()>;
+
+/// The listeners connected while building one set of pages, disconnected
+/// together when the pages are replaced. See `AppState::scoped`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ListenerScope(usize);
+
+thread_local! {
+    static CURRENT_SCOPE: Cell<Option<ListenerScope>> = const { Cell::new(None) };
+    static NEXT_SCOPE: Cell<usize> = const { Cell::new(0) };
+}
+
+struct Listener {
+    id: usize,
+    scope: Option<ListenerScope>,
+    callback: Callback,
+}
+
+/// A list of callbacks run whenever part of the state changes.
+#[derive(Default)]
+pub struct Listeners {
+    callbacks: RefCell<Vec<Listener>>,
+    next_id: Cell<usize>,
+}
+
+impl Listeners {
+    /// Registers `callback`; the returned id can be passed to `disconnect`.
+    pub fn connect(&self, callback: impl Fn() + 'static) -> usize {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        self.callbacks.borrow_mut().push(Listener {
+            id,
+            scope: CURRENT_SCOPE.get(),
+            callback: Rc::new(callback),
+        });
+        id
+    }
+
+    /// Removes a listener, e.g. when the dialog that registered it closes.
+    pub fn disconnect(&self, id: usize) {
+        self.callbacks
+            .borrow_mut()
+            .retain(|listener| listener.id != id);
+    }
+
+    fn disconnect_scope(&self, scope: ListenerScope) {
+        self.callbacks
+            .borrow_mut()
+            .retain(|listener| listener.scope != Some(scope));
+    }
+
+    pub fn emit(&self) {
+        // Clone first so callbacks may connect new listeners or borrow state.
+        let callbacks: Vec<_> = self
+            .callbacks
+            .borrow()
+            .iter()
+            .map(|listener| listener.callback.clone())
+            .collect();
+        for callback in callbacks {
+            callback();
+        }
+    }
+}
+
+pub struct AppState {
+    pub media: RefCell<MediaLibrary>,
+    pub media_changed: Listeners,
+    pub community: RefCell<Community>,
+    pub community_changed: Listeners,
+    pub shares: RefCell<ShareHistory>,
+    pub shares_changed: Listeners,
+    pub feed: RefCell<Feed>,
+    pub feed_changed: Listeners,
+    pub listings: RefCell<Listings>,
+    pub listings_changed: Listeners,
+    pub links: RefCell<LinkStore>,
+    pub links_changed: Listeners,
+    pub maps: RefCell<MapService>,
+    pub profile: RefCell<Profile>,
+    pub profile_changed: Listeners,
+    pub activity_privacy: RefCell<ActivityPrivacy>,
+    pub notifications: RefCell<NotificationCenter>,
+    pub notifications_changed: Listeners,
+    pub preferences: Preferences,
+    pub searches: RefCell<SearchHistory>,
+    pub searches_changed: Listeners,
+    pub followed_tags: RefCell<FollowedTags>,
+    pub tags_changed: Listeners,
+    pub auth: RefCell<AuthServer>,
+    /// The signed-in account, None while the sign-in page is shown.
+    pub session: RefCell<Option<Session>>,
+    pub auth_changed: Listeners,
+}
+
+impl AppState {
+    pub fn load() -> Rc<Self> {
+        Rc::new(Self {
+            media: RefCell::new(MediaLibrary::load()),
+            media_changed: Listeners::default(),
+            community: RefCell::new(Community::load()),
+            community_changed: Listeners::default(),
+            shares: RefCell::new(ShareHistory::load()),
+            shares_changed: Listeners::default(),
+            feed: RefCell::new(Feed::load()),
+            feed_changed: Listeners::default(),
+            listings: RefCell::new(Listings::load()),
+            listings_changed: Listeners::default(),
+            links: RefCell::new(LinkStore::load()),
+            links_changed: Listeners::default(),
+            maps: RefCell::new(MapService::load()),
+            profile: RefCell::new(Profile::load()),
+            profile_changed: Listeners::default(),
+            activity_privacy: RefCell::new(ActivityPrivacy::load()),
+            notifications: RefCell::new(NotificationCenter::load()),
+            notifications_changed: Listeners::default(),
+            preferences: Preferences::load(),
+            searches: RefCell::new(SearchHistory::load()),
+            searches_changed: Listeners::default(),
+            followed_tags: RefCell::new(FollowedTags::load()),
+            tags_changed: Listeners::default(),
+            auth: RefCell::new(AuthServer::load()),
+            session: RefCell::new(None),
+            auth_changed: Listeners::default(),
+        })
+    }
+
+    /// Loads the stores of the account signed in as `email`, or empties them
+    /// on signing out, so no account sees another's data. Emitting
+    /// `auth_changed` afterwards is up to the caller.
+    pub fn switch_account(&self, email: Option<&str>) {
+        storage::set_account(email);
+        *self.media.borrow_mut() = MediaLibrary::load();
+        *self.community.borrow_mut() = Community::load();
+        *self.shares.borrow_mut() = ShareHistory::load();
+        *self.feed.borrow_mut() = Feed::load();
+        *self.listings.borrow_mut() = Listings::load();
+        *self.links.borrow_mut() = LinkStore::load();
+        *self.profile.borrow_mut() = Profile::load();
+        *self.activity_privacy.borrow_mut() = ActivityPrivacy::load();
+        *self.notifications.borrow_mut() = NotificationCenter::load();
+        *self.searches.borrow_mut() = SearchHistory::load();
+        *self.followed_tags.borrow_mut() = FollowedTags::load();
+    }
+
+    /// Runs `build`, tagging every listener it connects with a new scope, so
+    /// that what it built can be replaced later without its listeners
+    /// keeping it alive and updating it.
+    pub fn scoped<T>(&self, build: impl FnOnce() -> T) -> (T, ListenerScope) {
+        let scope = ListenerScope(NEXT_SCOPE.get());
+        NEXT_SCOPE.set(scope.0 + 1);
+        let outer = CURRENT_SCOPE.replace(Some(scope));
+        let built = build();
+        CURRENT_SCOPE.set(outer);
+        (built, scope)
+    }
+
+    /// Disconnects every listener connected inside `scope`.
+    pub fn disconnect_scope(&self, scope: ListenerScope) {
+        for listeners in [
+            &self.media_changed,
+            &self.community_changed,
+            &self.shares_changed,
+            &self.feed_changed,
+            &self.listings_changed,
+            &self.links_changed,
+            &self.profile_changed,
+            &self.notifications_changed,
+            &self.searches_changed,
+            &self.tags_changed,
+            &self.auth_changed,
+        ] {
+            listeners.disconnect_scope(scope);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+// Local persistence helpers
+//
+// Every store in the app is saved as a GLib key file under the user data
+// directory (e.g. ~/.local/share/linkwithmentor on Linux). What belongs to
+// an account lives in its own directory under `accounts/`; what belongs to
+// the device (the account server, the session, the map source) at the top.
+use gtk4::glib;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
+const APP_DIR: &str = "linkwithmentor";
+const ACCOUNTS_DIR: &str = "accounts";
+/// Stores saved at the top by versions without accounts, moved to the
+/// first account signed in. Profile pictures stay, as the profile refers to
+/// them by path.
+const LEGACY_ACCOUNT_FILES: &[&str] = &[
+    "activity.ini",
+    "community.ini",
+    "feed.ini",
+    "hashtags.ini",
+    "links.ini",
+    "media.ini",
+    "media",
+    "notifications.ini",
+    "profile.ini",
+    "searches.ini",
+    "shares.ini",
+];
+
+thread_local! {
+    /// The signed-in account's directory.
+    static ACCOUNT_DIR: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+#[cfg(not(test))]
+pub fn data_dir() -> PathBuf {
+    glib::user_data_dir().join(APP_DIR)
+}
+
+/// Tests each run on a thread of their own, and get a directory of their
+/// own, so they neither touch real data nor each other's.
+#[cfg(test)]
+pub fn data_dir() -> PathBuf {
+    thread_local! {
+        static TEST_DIR: PathBuf = std::env::temp_dir()
+            .join(format!("{APP_DIR}-test-{}", glib::uuid_string_random()));
+    }
+    TEST_DIR.with(PathBuf::clone)
+}
+
+/// Where the signed-in account's stores live, or `None` while signed out.
+pub fn account_dir() -> Option<PathBuf> {
+    ACCOUNT_DIR.with(|dir| dir.borrow().clone())
+}
+
+/// Switches the stores to `email`'s directory, or to none when signing out.
+pub fn set_account(email: Option<&str>) {
+    let dir = email.map(|email| {
+        let digest = Sha256::digest(email.trim().to_lowercase().as_bytes());
+        let name: String = digest[..16]
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        data_dir().join(ACCOUNTS_DIR).join(name)
+    });
+    if let Some(dir) = &dir {
+        adopt_legacy_files(dir);
+    }
+    ACCOUNT_DIR.with(|current| *current.borrow_mut() = dir);
+}
+
+/// Moves the stores of a version without accounts into `dir`, if it is the
+/// first account on this device.
+fn adopt_legacy_files(dir: &Path) {
+    let root = data_dir();
+    if root.join(ACCOUNTS_DIR).exists() {
+        return;
+    }
+    if let Err(err) = std::fs::create_dir_all(dir) {
+        eprintln!("Could not create {}: {err}", dir.display());
+        return;
+    }
+    for name in LEGACY_ACCOUNT_FILES {
+        let old = root.join(name);
+        if old.exists() {
+            if let Err(err) = std::fs::rename(&old, dir.join(name)) {
+                eprintln!("Could not move {name} to the account: {err}");
+            }
+        }
+    }
+}
+
+/// Where the signed-in account keeps the files named `name`; while signed
+/// out there are none to find there.
+pub fn account_path(name: &str) -> PathBuf {
+    account_dir().unwrap_or_else(data_dir).join(name)
+}
+
+/// Loads one of the signed-in account's stores; nothing while signed out.
+pub fn load_key_file(name: &str) -> Option<glib::KeyFile> {
+    load_key_file_from(&account_dir()?, name)
+}
+
+/// Saves one of the signed-in account's stores; while signed out there is
+/// nowhere to save it.
+pub fn save_key_file(name: &str, key_file: &glib::KeyFile) {
+    if let Some(dir) = account_dir() {
+        save_key_file_to(&dir, name, key_file);
+    }
+}
+
+/// Loads a store shared by every account on this device.
+pub fn load_device_key_file(name: &str) -> Option<glib::KeyFile> {
+    load_key_file_from(&data_dir(), name)
+}
+
+pub fn save_device_key_file(name: &str, key_file: &glib::KeyFile) {
+    save_key_file_to(&data_dir(), name, key_file);
+}
+
+fn load_key_file_from(dir: &Path, name: &str) -> Option<glib::KeyFile> {
+    let key_file = glib::KeyFile::new();
+    key_file
+        .load_from_file(dir.join(name), glib::KeyFileFlags::NONE)
+        .ok()?;
+    Some(key_file)
+}
+
+fn save_key_file_to(dir: &Path, name: &str, key_file: &glib::KeyFile) {
+    if let Err(err) = std::fs::create_dir_all(dir) {
+        eprintln!("Could not create {}: {err}", dir.display());
+        return;
+    }
+    if let Err(err) = key_file.save_to_file(dir.join(name)) {
+        eprintln!("Could not save {name}: {err}");
+    }
+}
+
+pub fn string_or(key_file: &glib::KeyFile, group: &str, key: &str, default: &str) -> String {
+    key_file
+        .string(group, key)
+        .map(|value| value.to_string())
+        .unwrap_or_else(|_| default.to_string())
+}
+
+/// Returns `(id, group)` for every group named `<prefix><id>`.
+pub fn groups_with_prefix(key_file: &glib::KeyFile, prefix: &str) -> Vec<(String, String)> {
+    key_file
+        .groups()
+        .iter()
+        .filter_map(|group| {
+            let group = group.as_str();
+            group
+                .strip_prefix(prefix)
+                .map(|id| (id.to_string(), group.to_string()))
+        })
+        .collect()
+}
+
+/// Writes a list in the native key-file list format (`a;b;c;`).
+pub fn set_list(key_file: &glib::KeyFile, group: &str, key: &str, values: &[String]) {
+    let encoded: String = values
+        .iter()
+        .map(|value| {
+            let escaped = value
+                .replace('\\', "\\\\")
+                .replace(';', "\\;")
+                .replace('\n', "\\n");
+            format!("{escaped};")
+        })
+        .collect();
+    key_file.set_value(group, key, &encoded);
+}
+
+pub fn list(key_file: &glib::KeyFile, group: &str, key: &str) -> Vec<String> {
+    key_file
+        .string_list(group, key)
+        .map(|values| values.iter().map(|value| value.to_string()).collect())
+        .unwrap_or_default()
+}
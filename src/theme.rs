@@ -0,0 +1,319 @@
+// Runtime theming: light, dark and high-contrast variants, accent colors
+// and following the desktop's color scheme
+//
+// style.css only uses the @app_* colors. The active variant sheet in
+// themes/ defines the palette, and a generated provider defines @app_accent
+// and @app_accent_end. Both are swapped on the display whenever the "theme"
+// or "accent-color" setting or the desktop's preference changes, so nothing
+// needs a restart.
+use crate::assets;
+use crate::i18n::tr_noop;
+use gtk4::prelude::*;
+use gtk4::{gdk, gio, glib, CssProvider};
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Above style.css, so variant rules win over the base ones.
+const PRIORITY: u32 = gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION + 1;
+
+/// The XDG settings portal, which reports the desktop's color scheme and
+/// contrast on Linux.
+const PORTAL_NAME: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const PORTAL_INTERFACE: &str = "org.freedesktop.portal.Settings";
+const APPEARANCE_NAMESPACE: &str = "org.freedesktop.appearance";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Variant {
+    Light,
+    Dark,
+    HighContrast,
+}
+
+impl Variant {
+    const ALL: [Variant; 3] = [Variant::Light, Variant::Dark, Variant::HighContrast];
+
+    fn stylesheet(self) -> &'static str {
+        match self {
+            Variant::Light => "light.css",
+            Variant::Dark => "dark.css",
+            Variant::HighContrast => "high-contrast.css",
+        }
+    }
+}
+
+/// A preset accent, drawn as a gradient from `start` to `end`.
+pub struct Accent {
+    /// Stored in the "accent-color" setting.
+    pub key: &'static str,
+    pub label: &'static str,
+    start: &'static str,
+    end: &'static str,
+}
+
+pub const ACCENTS: [Accent; 6] = [
+    Accent {
+        key: "purple",
+        label: tr_noop("Purple"),
+        start: "#667eea",
+        end: "#764ba2",
+    },
+    Accent {
+        key: "blue",
+        label: tr_noop("Blue"),
+        start: "#2f80ed",
+        end: "#1c4fa0",
+    },
+    Accent {
+        key: "teal",
+        label: tr_noop("Teal"),
+        start: "#11998e",
+        end: "#0b6e66",
+    },
+    Accent {
+        key: "green",
+        label: tr_noop("Green"),
+        start: "#43a047",
+        end: "#2e7d32",
+    },
+    Accent {
+        key: "orange",
+        label: tr_noop("Orange"),
+        start: "#f2994a",
+        end: "#d9534f",
+    },
+    Accent {
+        key: "pink",
+        label: tr_noop("Pink"),
+        start: "#ec4899",
+        end: "#be185d",
+    },
+];
+
+/// The color an "accent-color" value stands for: a preset's first color,
+/// or a custom "#rrggbb". Unknown values fall back to the first preset.
+pub fn accent_rgba(value: &str) -> gdk::RGBA {
+    let start = ACCENTS
+        .iter()
+        .find(|accent| accent.key == value)
+        .map_or(value, |accent| accent.start);
+    gdk::RGBA::parse(start)
+        .or_else(|_| gdk::RGBA::parse(ACCENTS[0].start))
+        .expect("preset accents are valid colors")
+}
+
+/// "#rrggbb", ignoring alpha.
+pub fn hex(rgba: &gdk::RGBA) -> String {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        channel(rgba.red()),
+        channel(rgba.green()),
+        channel(rgba.blue())
+    )
+}
+
+/// CSS defining the accent colors, plus a class per preset for the
+/// swatches in Settings.
+fn accent_css(value: &str) -> String {
+    let (start, end) = match ACCENTS.iter().find(|accent| accent.key == value) {
+        Some(accent) => (accent.start.to_string(), accent.end.to_string()),
+        // Custom colors get a darker shade of themselves as the gradient end
+        None => {
+            let start = hex(&accent_rgba(value));
+            let end = format!("shade({start}, 0.75)");
+            (start, end)
+        }
+    };
+    let mut css =
+        format!("@define-color app_accent {start};\n@define-color app_accent_end {end};\n");
+    for accent in &ACCENTS {
+        css.push_str(&format!(
+            ".accent-swatch-{} {{ background: linear-gradient(135deg, {} 0%, {} 100%); }}\n",
+            accent.key, accent.start, accent.end
+        ));
+    }
+    css
+}
+
+/// What the desktop asks for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct SystemScheme {
+    dark: bool,
+    high_contrast: bool,
+}
+
+impl SystemScheme {
+    fn variant(self) -> Variant {
+        if self.high_contrast {
+            Variant::HighContrast
+        } else if self.dark {
+            Variant::Dark
+        } else {
+            Variant::Light
+        }
+    }
+}
+
+struct Theme {
+    settings: gio::Settings,
+    display: gdk::Display,
+    gtk_settings: gtk4::Settings,
+    variants: Vec<(Variant, CssProvider)>,
+    active: Cell<Option<Variant>>,
+    accent: CssProvider,
+    system: Cell<SystemScheme>,
+    /// Kept alive for its SettingChanged signal.
+    portal: Option<gio::DBusProxy>,
+}
+
+impl Theme {
+    /// Picks the variant for the current settings and swaps it in, then
+    /// regenerates the accent colors.
+    fn apply(&self) {
+        let variant = match self.settings.string("theme").as_str() {
+            "light" => Variant::Light,
+            "dark" => Variant::Dark,
+            "high-contrast" => Variant::HighContrast,
+            _ => self.system.get().variant(),
+        };
+        if self.active.get() != Some(variant) {
+            for (other, provider) in &self.variants {
+                if *other == variant {
+                    gtk4::style_context_add_provider_for_display(&self.display, provider, PRIORITY);
+                } else if self.active.get() == Some(*other) {
+                    gtk4::style_context_remove_provider_for_display(&self.display, provider);
+                }
+            }
+            self.active.set(Some(variant));
+            // Built-in widgets such as popovers and text views follow along
+            self.gtk_settings
+                .set_gtk_application_prefer_dark_theme(variant == Variant::Dark);
+        }
+        self.accent
+            .load_from_data(&accent_css(&self.settings.string("accent-color")));
+    }
+
+    fn set_system(&self, system: SystemScheme) {
+        if self.system.replace(system) != system {
+            self.apply();
+        }
+    }
+}
+
+/// Loads the theme for `settings` and keeps it in step with them and with
+/// the desktop. Call once the display is up, after the base stylesheet.
+pub fn install(settings: &gio::Settings) {
+    let Some(display) = gdk::Display::default() else {
+        return;
+    };
+    let Some(gtk_settings) = gtk4::Settings::default() else {
+        return;
+    };
+
+    // What GTK picked up before we touch it is the desktop's own choice
+    let theme_name = gtk_settings
+        .gtk_theme_name()
+        .map(|name| name.to_lowercase())
+        .unwrap_or_default();
+    let mut system = SystemScheme {
+        dark: gtk_settings.is_gtk_application_prefer_dark_theme() || theme_name.contains("dark"),
+        high_contrast: theme_name.contains("highcontrast"),
+    };
+    let portal = gio::DBusProxy::for_bus_sync(
+        gio::BusType::Session,
+        gio::DBusProxyFlags::NONE,
+        None,
+        PORTAL_NAME,
+        PORTAL_PATH,
+        PORTAL_INTERFACE,
+        None::<&gio::Cancellable>,
+    )
+    .ok();
+    if let Some(portal) = &portal {
+        if let Some(scheme) = read_portal(portal, "color-scheme") {
+            system.dark = scheme == 1;
+        }
+        if let Some(contrast) = read_portal(portal, "contrast") {
+            system.high_contrast = contrast == 1;
+        }
+    }
+
+    let variants = Variant::ALL
+        .into_iter()
+        .map(|variant| {
+            let provider = CssProvider::new();
+            assets::load_css(&provider, &format!("themes/{}", variant.stylesheet()));
+            (variant, provider)
+        })
+        .collect();
+    let accent = CssProvider::new();
+    gtk4::style_context_add_provider_for_display(&display, &accent, PRIORITY);
+
+    let theme = Rc::new(Theme {
+        settings: settings.clone(),
+        display,
+        gtk_settings,
+        variants,
+        active: Cell::new(None),
+        accent,
+        system: Cell::new(system),
+        portal,
+    });
+    theme.apply();
+
+    for key in ["theme", "accent-color"] {
+        let theme = theme.clone();
+        settings.connect_changed(Some(key), move |_, _| theme.apply());
+    }
+
+    if let Some(portal) = &theme.portal {
+        let theme = theme.clone();
+        portal.connect_local("g-signal", false, move |values| {
+            let signal: String = values.get(2)?.get().ok()?;
+            let parameters: glib::Variant = values.get(3)?.get().ok()?;
+            if signal != "SettingChanged" {
+                return None;
+            }
+            let (namespace, key, value) = parameters.get::<(String, String, glib::Variant)>()?;
+            if namespace != APPEARANCE_NAMESPACE {
+                return None;
+            }
+            let value = unwrap_variant(value).get::<u32>()?;
+            let mut system = theme.system.get();
+            match key.as_str() {
+                "color-scheme" => system.dark = value == 1,
+                "contrast" => system.high_contrast = value == 1,
+                _ => return None,
+            }
+            theme.set_system(system);
+            None
+        });
+    }
+}
+
+/// Reads an org.freedesktop.appearance value: color-scheme is 1 for dark
+/// and 2 for light, contrast is 1 for high.
+fn read_portal(portal: &gio::DBusProxy, key: &str) -> Option<u32> {
+    let parameters = (APPEARANCE_NAMESPACE, key).to_variant();
+    let call = |method: &str| {
+        portal.call_sync(
+            method,
+            Some(&parameters),
+            gio::DBusCallFlags::NONE,
+            1000,
+            None::<&gio::Cancellable>,
+        )
+    };
+    // ReadOne is newer; older portals only have Read
+    let reply = call("ReadOne").or_else(|_| call("Read")).ok()?;
+    unwrap_variant(reply.child_value(0)).get::<u32>()
+}
+
+/// Read wraps its value in one more variant than ReadOne does.
+fn unwrap_variant(mut value: glib::Variant) -> glib::Variant {
+    while let Some(inner) = value.as_variant() {
+        value = inner;
+    }
+    value
+}
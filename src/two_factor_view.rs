@@ -0,0 +1,372 @@
+// Two-factor authentication: the settings row, enrollment with a QR code,
+// recovery codes and turning it off
+use crate::auth::{self, AuthError};
+use crate::i18n::{tr, trn};
+use crate::qr::QrCode;
+use crate::session;
+use crate::sizing;
+use crate::state::AppState;
+use crate::totp;
+use crate::util;
+use gtk4::prelude::*;
+use gtk4::{Box as GtkBox, Button, DrawingArea, Entry, Label, Orientation, PasswordEntry};
+use std::rc::Rc;
+
+/// Modules of light margin around a QR code, as scanners need.
+const QR_QUIET_ZONE: usize = 4;
+const QR_SIZE: i32 = 200;
+
+/// The status and Set Up/Manage button for the settings row, kept current.
+pub fn create_status(state: &Rc<AppState>) -> GtkBox {
+    let status_box = GtkBox::new(Orientation::Horizontal, 12);
+    status_box.set_valign(gtk4::Align::Center);
+    let status_label = Label::new(None);
+    status_label.add_css_class("settings-value");
+    let button = Button::new();
+    button.add_css_class("edit-button");
+    status_box.append(&status_label);
+    status_box.append(&button);
+
+    let update = {
+        let state = state.clone();
+        let button = button.clone();
+        move || {
+            let email = session::email(&state);
+            let auth = state.auth.borrow();
+            if auth.two_factor_enabled(&email) {
+                let left = auth.recovery_codes_left(&email) as u64;
+                status_label.set_text(&format!(
+                    "{} · {}",
+                    tr("Enabled"),
+                    trn("{n} recovery code left", "{n} recovery codes left", left)
+                ));
+                button.set_label(&tr("Manage"));
+            } else {
+                status_label.set_text(&tr("Disabled"));
+                button.set_label(&tr("Set Up"));
+            }
+        }
+    };
+    update();
+    state.auth_changed.connect(update);
+
+    let state = state.clone();
+    button.connect_clicked(move |button| {
+        let enabled = state
+            .auth
+            .borrow()
+            .two_factor_enabled(&session::email(&state));
+        if enabled {
+            show_manage(button, &state);
+        } else {
+            show_enrollment(button, &state);
+        }
+    });
+    status_box
+}
+
+/// A dialog window in the style of the share sheet, with its content box.
+pub fn create_dialog(parent: &impl IsA<gtk4::Widget>, title: &str) -> (gtk4::Window, GtkBox) {
+    let window = gtk4::Window::builder()
+        .title(title)
+        .modal(true)
+        .default_width(420)
+        .build();
+    window.add_css_class("share-sheet");
+    window.set_transient_for(parent.root().and_downcast::<gtk4::Window>().as_ref());
+
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_start(24);
+    content.set_margin_end(24);
+    content.set_margin_top(24);
+    content.set_margin_bottom(24);
+    window.set_child(Some(&content));
+    (window, content)
+}
+
+pub fn create_code_entry(placeholder: &str) -> Entry {
+    let entry = Entry::new();
+    entry.set_placeholder_text(Some(placeholder));
+    entry.add_css_class("two-factor-code");
+    entry
+}
+
+pub fn create_text(text: &str, css_class: &str) -> Label {
+    let label = Label::new(Some(text));
+    label.set_halign(gtk4::Align::Start);
+    label.set_wrap(true);
+    label.set_xalign(0.0);
+    label.add_css_class(css_class);
+    label
+}
+
+/// `text` as a QR code, drawn black on white whatever the theme.
+fn create_qr_image(text: &str) -> DrawingArea {
+    let area = DrawingArea::new();
+    area.add_css_class("qr-code");
+    area.set_halign(gtk4::Align::Center);
+    sizing::set_size_request(&area, QR_SIZE, QR_SIZE);
+
+    let code = QrCode::encode(text.as_bytes());
+    area.set_draw_func(move |_, cr, width, height| {
+        let Some(code) = &code else {
+            return;
+        };
+        let modules = code.size() + QR_QUIET_ZONE * 2;
+        let side = f64::from(width.min(height));
+        let module = (side / modules as f64).floor().max(1.0);
+        let x0 = ((f64::from(width) - module * modules as f64) / 2.0).floor();
+        let y0 = ((f64::from(height) - module * modules as f64) / 2.0).floor();
+
+        cr.set_source_rgb(1.0, 1.0, 1.0);
+        cr.rectangle(x0, y0, module * modules as f64, module * modules as f64);
+        let _ = cr.fill();
+        cr.set_source_rgb(0.0, 0.0, 0.0);
+        for y in 0..code.size() {
+            for x in 0..code.size() {
+                if code.is_dark(x, y) {
+                    cr.rectangle(
+                        x0 + module * (x + QR_QUIET_ZONE) as f64,
+                        y0 + module * (y + QR_QUIET_ZONE) as f64,
+                        module,
+                        module,
+                    );
+                }
+            }
+        }
+        let _ = cr.fill();
+    });
+    area
+}
+
+/// Runs `submit` from the button and from Enter in the entry.
+pub fn connect_submit(button: &Button, entry: &Entry, submit: impl Fn() + 'static) {
+    let submit = Rc::new(submit);
+    let submit_clone = submit.clone();
+    button.connect_clicked(move |_| submit_clone());
+    entry.connect_activate(move |_| submit());
+}
+
+/// Shows the secret as a QR code and a typed key, then asks for a code from
+/// it before turning two-factor authentication on.
+fn show_enrollment(parent: &impl IsA<gtk4::Widget>, state: &Rc<AppState>) {
+    let email = session::email(state);
+    let secret = state.auth.borrow_mut().begin_enrollment(&email);
+    let uri = totp::provisioning_uri(&secret, auth::ISSUER, &email);
+    let (window, content) = create_dialog(parent, &tr("Two-Factor Authentication"));
+
+    let title = create_text(&tr("🔐 Set up two-factor authentication"), "dialog-title");
+    let intro = create_text(
+        &tr("Scan this code with an authenticator app, such as Google Authenticator, Aegis or FreeOTP."),
+        "settings-description",
+    );
+    let key_caption = create_text(&tr("Or enter this key by hand:"), "form-label");
+    let key_label = Label::new(Some(&totp::format_secret(&secret)));
+    key_label.set_selectable(true);
+    key_label.add_css_class("two-factor-key");
+
+    let code_caption = create_text(
+        &tr("Enter the 6-digit code the app shows to finish:"),
+        "form-label",
+    );
+    let code_entry = create_code_entry("123456");
+    code_entry.set_max_length(totp::DIGITS as i32);
+    code_entry.set_input_purpose(gtk4::InputPurpose::Digits);
+    let error_label = create_text("", "form-error");
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 12);
+    buttons.set_halign(gtk4::Align::End);
+    let cancel_btn = Button::with_label(&tr("Cancel"));
+    cancel_btn.add_css_class("secondary-button");
+    let confirm_btn = Button::with_label(&tr("Turn On"));
+    confirm_btn.add_css_class("create-post-button");
+    buttons.append(&cancel_btn);
+    buttons.append(&confirm_btn);
+
+    content.append(&title);
+    content.append(&intro);
+    content.append(&create_qr_image(&uri));
+    content.append(&key_caption);
+    content.append(&key_label);
+    content.append(&code_caption);
+    content.append(&code_entry);
+    content.append(&error_label);
+    content.append(&buttons);
+
+    let window_clone = window.clone();
+    cancel_btn.connect_clicked(move |_| window_clone.close());
+
+    let state = state.clone();
+    let window_clone = window.clone();
+    let entry = code_entry.clone();
+    connect_submit(&confirm_btn, &code_entry, move || {
+        let result = state
+            .auth
+            .borrow_mut()
+            .confirm_enrollment(&email, &entry.text(), util::now());
+        match result {
+            Ok(codes) => {
+                state.auth_changed.emit();
+                show_recovery_codes(&window_clone, &codes);
+            }
+            Err(error) => {
+                error_label.set_text(&error.to_string());
+                entry.set_text("");
+            }
+        }
+    });
+
+    window.present();
+}
+
+/// Replaces `window`'s content with freshly made recovery codes, which are
+/// not shown again.
+fn show_recovery_codes(window: &gtk4::Window, codes: &[String]) {
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_start(24);
+    content.set_margin_end(24);
+    content.set_margin_top(24);
+    content.set_margin_bottom(24);
+
+    let title = create_text(&tr("🗝️ Save your recovery codes"), "dialog-title");
+    let intro = create_text(
+        &tr("Each code signs you in once if you lose your phone. Keep them somewhere safe: they won't be shown again."),
+        "settings-description",
+    );
+
+    let codes_text = codes
+        .chunks(2)
+        .map(|pair| pair.join("    "))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let codes_label = Label::new(Some(&codes_text));
+    codes_label.set_selectable(true);
+    codes_label.add_css_class("recovery-codes");
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 12);
+    buttons.set_halign(gtk4::Align::End);
+    let copy_btn = Button::with_label(&tr("📋 Copy"));
+    copy_btn.add_css_class("secondary-button");
+    let done_btn = Button::with_label(&tr("Done"));
+    done_btn.add_css_class("create-post-button");
+    buttons.append(&copy_btn);
+    buttons.append(&done_btn);
+
+    content.append(&title);
+    content.append(&intro);
+    content.append(&codes_label);
+    content.append(&buttons);
+    window.set_child(Some(&content));
+
+    let all_codes = codes.join("\n");
+    copy_btn.connect_clicked(move |button| {
+        button.clipboard().set_text(&all_codes);
+        button.set_label(&tr("✅ Copied"));
+    });
+    let window = window.clone();
+    done_btn.connect_clicked(move |_| window.close());
+}
+
+/// Asks for the password and a current code before making new recovery
+/// codes or turning two-factor authentication off.
+fn show_manage(parent: &impl IsA<gtk4::Widget>, state: &Rc<AppState>) {
+    let (window, content) = create_dialog(parent, &tr("Two-Factor Authentication"));
+    let email = session::email(state);
+
+    let title = create_text(&tr("🔐 Two-factor authentication is on"), "dialog-title");
+    let left = state.auth.borrow().recovery_codes_left(&email) as u64;
+    let intro = create_text(
+        &trn(
+            "You have {n} recovery code left.",
+            "You have {n} recovery codes left.",
+            left,
+        ),
+        "settings-description",
+    );
+    let caption = create_text(
+        &tr("To make changes, enter your password and a code from your authenticator app or a recovery code:"),
+        "form-label",
+    );
+    let password_entry = PasswordEntry::new();
+    password_entry.set_placeholder_text(Some(&tr("Password")));
+    password_entry.set_show_peek_icon(true);
+    let code_entry = create_code_entry(&tr("Code"));
+    let error_label = create_text("", "form-error");
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 12);
+    buttons.set_halign(gtk4::Align::End);
+    let cancel_btn = Button::with_label(&tr("Cancel"));
+    cancel_btn.add_css_class("secondary-button");
+    let regenerate_btn = Button::with_label(&tr("New Recovery Codes"));
+    regenerate_btn.add_css_class("secondary-button");
+    let disable_btn = Button::with_label(&tr("Turn Off"));
+    disable_btn.add_css_class("danger-button");
+    buttons.append(&cancel_btn);
+    buttons.append(&regenerate_btn);
+    buttons.append(&disable_btn);
+
+    content.append(&title);
+    content.append(&intro);
+    content.append(&caption);
+    content.append(&password_entry);
+    content.append(&code_entry);
+    content.append(&error_label);
+    content.append(&buttons);
+
+    let window_clone = window.clone();
+    cancel_btn.connect_clicked(move |_| window_clone.close());
+
+    let show_error = {
+        let error_label = error_label.clone();
+        let code_entry = code_entry.clone();
+        move |error: AuthError| {
+            error_label.set_text(&error.to_string());
+            code_entry.set_text("");
+        }
+    };
+
+    {
+        let state = state.clone();
+        let window = window.clone();
+        let email = email.clone();
+        let password_entry = password_entry.clone();
+        let code_entry = code_entry.clone();
+        let show_error = show_error.clone();
+        regenerate_btn.connect_clicked(move |_| {
+            let result = state.auth.borrow_mut().regenerate_recovery_codes(
+                &email,
+                &password_entry.text(),
+                &code_entry.text(),
+                util::now(),
+            );
+            match result {
+                Ok(codes) => {
+                    state.auth_changed.emit();
+                    show_recovery_codes(&window, &codes);
+                }
+                Err(error) => show_error(error),
+            }
+        });
+    }
+
+    let state = state.clone();
+    let window_clone = window.clone();
+    let entry = code_entry.clone();
+    connect_submit(&disable_btn, &code_entry, move || {
+        let result = state.auth.borrow_mut().disable_two_factor(
+            &email,
+            &password_entry.text(),
+            &entry.text(),
+            util::now(),
+        );
+        match result {
+            Ok(()) => {
+                state.auth_changed.emit();
+                window_clone.close();
+            }
+            Err(error) => show_error(error),
+        }
+    });
+
+    window.present();
+}
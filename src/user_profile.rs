@@ -0,0 +1,391 @@
+// Profile pages for other people, pushed onto the navigation stack when a
+// name or avatar is clicked
+use crate::community::{Relationship, User, ME};
+use crate::i18n::{tr, tr_noop, tr_with};
+use crate::navigation;
+use crate::profile_view::{self, RelationshipAction};
+use crate::state::AppState;
+use crate::util;
+use gtk4::glib;
+use gtk4::prelude::*;
+use gtk4::{Box as GtkBox, Button, DropDown, Entry, Frame, Label, Orientation, ScrolledWindow};
+use std::rc::Rc;
+
+const SESSION_LENGTHS: [&str; 4] = [
+    tr_noop("30 minutes"),
+    tr_noop("45 minutes"),
+    tr_noop("1 hour"),
+    tr_noop("90 minutes"),
+];
+const MAX_TOPIC_LENGTH: usize = 120;
+
+/// `user_id`'s profile: header, stats and actions, then their activity
+/// unless they are private and not followed, or blocked.
+pub fn create_user_profile_page(state: &Rc<AppState>, user_id: &str) -> ScrolledWindow {
+    let scroll = ScrolledWindow::new();
+    let page_box = GtkBox::new(Orientation::Vertical, 24);
+    page_box.set_margin_start(40);
+    page_box.set_margin_end(40);
+    page_box.set_margin_top(30);
+    page_box.set_margin_bottom(30);
+
+    let back_btn = Button::with_label(&tr("‹ Back"));
+    back_btn.add_css_class("back-button");
+    back_btn.set_halign(gtk4::Align::Start);
+    back_btn.connect_clicked(|button| navigation::activate(button, "navigate-back", None));
+    page_box.append(&back_btn);
+
+    let body = GtkBox::new(Orientation::Vertical, 24);
+    page_box.append(&body);
+
+    let populate: Rc<dyn Fn()> = {
+        let state = state.clone();
+        let user_id = user_id.to_string();
+        Rc::new(move || {
+            while let Some(child) = body.first_child() {
+                body.remove(&child);
+            }
+            let Some(user) = state.community.borrow().user(&user_id).cloned() else {
+                let missing = Label::new(Some(&tr("This account doesn't exist anymore.")));
+                missing.add_css_class("share-empty");
+                body.append(&missing);
+                return;
+            };
+            let relationship = state.community.borrow().relationship(&user_id);
+
+            body.append(&create_user_header(&state, &user, relationship));
+
+            let notice = match relationship {
+                Relationship::Blocked => Some((
+                    tr_with("🚫 You blocked {name}", &[("name", &user.name)]),
+                    tr("Unblock them to see their profile and activity."),
+                )),
+                Relationship::Following | Relationship::Myself => None,
+                _ if user.private => Some((
+                    tr("🔒 This account is private"),
+                    tr_with(
+                        "Follow {name} to see their posts and activity.",
+                        &[("name", &user.name)],
+                    ),
+                )),
+                _ => None,
+            };
+            match notice {
+                Some((title, detail)) => body.append(&create_notice(&title, &detail)),
+                None => body.append(&profile_view::create_activity_timeline(&state, &user_id)),
+            }
+        })
+    };
+    navigation::refresh_while_mapped(&page_box, state, populate);
+
+    scroll.set_child(Some(&page_box));
+    scroll
+}
+
+fn create_user_header(state: &Rc<AppState>, user: &User, relationship: Relationship) -> GtkBox {
+    let header_box = GtkBox::new(Orientation::Vertical, 8);
+    header_box.add_css_class("profile-header");
+    header_box.set_margin_start(20);
+    header_box.set_margin_end(20);
+    header_box.set_margin_top(20);
+    header_box.set_margin_bottom(20);
+
+    header_box.append(&profile_view::create_avatar(
+        Some(user),
+        "profile-avatar",
+        120,
+    ));
+
+    let name = Label::new(Some(&user.name));
+    name.add_css_class("profile-name");
+    header_box.append(&name);
+
+    let mut meta = format!("@{}", user.handle);
+    if user.private {
+        meta.push_str(" · 🔒 Private");
+    }
+    if state.community.borrow().is_muted(&user.id) {
+        meta.push_str(" · 🔇 Muted");
+    }
+    if relationship != Relationship::Blocked && user.following.iter().any(|id| id == ME) {
+        meta.push_str(" · Follows you");
+    }
+    let meta_label = Label::new(Some(&meta));
+    meta_label.add_css_class("profile-meta");
+    header_box.append(&meta_label);
+
+    if !user.headline.is_empty() {
+        let headline = Label::new(Some(&user.headline));
+        headline.add_css_class("profile-bio");
+        headline.set_wrap(true);
+        header_box.append(&headline);
+    }
+
+    let stats_box = GtkBox::new(Orientation::Horizontal, 40);
+    stats_box.set_halign(gtk4::Align::Center);
+    stats_box.set_margin_top(16);
+    let posts = state
+        .feed
+        .borrow()
+        .posts()
+        .iter()
+        .filter(|post| post.author == user.id)
+        .count();
+    let (followers, following) = {
+        let community = state.community.borrow();
+        (
+            community.followers(&user.id).len(),
+            community.following(&user.id).len(),
+        )
+    };
+    stats_box.append(&profile_view::create_stat(posts, &tr("Posts")));
+    stats_box.append(&profile_view::create_stat(followers, &tr("Followers")));
+    stats_box.append(&profile_view::create_stat(following, &tr("Following")));
+    header_box.append(&stats_box);
+
+    header_box.append(&create_user_actions(state, user, relationship));
+    header_box
+}
+
+/// Follow, message and book-session buttons plus the relationship menu.
+/// Private accounts only take messages and bookings from their followers.
+fn create_user_actions(state: &Rc<AppState>, user: &User, relationship: Relationship) -> GtkBox {
+    let actions = GtkBox::new(Orientation::Horizontal, 12);
+    actions.set_halign(gtk4::Align::Center);
+    actions.set_margin_top(16);
+
+    let (label, action) = match relationship {
+        Relationship::Myself => return actions,
+        Relationship::Blocked => (tr("Unblock"), RelationshipAction::Unblock),
+        Relationship::Following => (tr("✓ Following"), RelationshipAction::Unfollow),
+        Relationship::Requested => (tr("⏳ Requested"), RelationshipAction::Unfollow),
+        Relationship::NotFollowing if user.private => {
+            (tr("🔒 Request to Follow"), RelationshipAction::Follow)
+        }
+        Relationship::NotFollowing => (tr("➕ Follow"), RelationshipAction::Follow),
+    };
+    let follow_btn = Button::with_label(&label);
+    follow_btn.add_css_class(if action == RelationshipAction::Follow {
+        "edit-profile-button"
+    } else {
+        "secondary-button"
+    });
+    match relationship {
+        Relationship::Following => follow_btn.set_tooltip_text(Some(&tr("Unfollow"))),
+        Relationship::Requested => follow_btn.set_tooltip_text(Some(&tr("Cancel follow request"))),
+        _ => {}
+    }
+    let state_clone = state.clone();
+    let user_id = user.id.clone();
+    follow_btn.connect_clicked(move |_| {
+        profile_view::apply_relationship_action(&state_clone, &user_id, action);
+    });
+    actions.append(&follow_btn);
+
+    let reachable = relationship == Relationship::Following
+        || (relationship != Relationship::Blocked && !user.private);
+    if reachable {
+        let message_btn = Button::with_label(&tr("💬 Message"));
+        message_btn.add_css_class("secondary-button");
+        let state_clone = state.clone();
+        let user_id = user.id.clone();
+        message_btn.connect_clicked(move |button| {
+            let conversation = state_clone
+                .community
+                .borrow_mut()
+                .direct_conversation(&user_id);
+            navigation::activate(
+                button,
+                "open-conversation",
+                Some(&conversation.to_variant()),
+            );
+        });
+        actions.append(&message_btn);
+
+        let book_btn = Button::with_label(&tr("📅 Book Session"));
+        book_btn.add_css_class("secondary-button");
+        let state_clone = state.clone();
+        let user_id = user.id.clone();
+        book_btn.connect_clicked(move |button| {
+            open_session_dialog(button, &state_clone, &user_id);
+        });
+        actions.append(&book_btn);
+    }
+
+    actions.append(&profile_view::create_relationship_menu(state, &user.id));
+    actions
+}
+
+fn create_notice(title: &str, detail: &str) -> Frame {
+    let frame = Frame::new(None);
+    frame.add_css_class("profile-notice");
+
+    let notice_box = GtkBox::new(Orientation::Vertical, 8);
+    notice_box.set_margin_start(24);
+    notice_box.set_margin_end(24);
+    notice_box.set_margin_top(24);
+    notice_box.set_margin_bottom(24);
+
+    let title_label = Label::new(Some(title));
+    title_label.add_css_class("section-subtitle");
+    let detail_label = Label::new(Some(detail));
+    detail_label.set_wrap(true);
+    detail_label.add_css_class("share-empty");
+    notice_box.append(&title_label);
+    notice_box.append(&detail_label);
+
+    frame.set_child(Some(&notice_box));
+    frame
+}
+
+// ============================================================================
+// Session booking
+// ============================================================================
+
+/// Asks for a date, time, length and topic, then sends the session request
+/// to `user_id` as a direct message.
+fn open_session_dialog(parent: &impl IsA<gtk4::Widget>, state: &Rc<AppState>, user_id: &str) {
+    let name = state.community.borrow().user_name(user_id);
+    let window = gtk4::Window::builder()
+        .title(tr_with("Book a Session with {name}", &[("name", &name)]))
+        .modal(true)
+        .default_width(420)
+        .build();
+    window.add_css_class("share-sheet");
+    if let Some(parent) = parent.root().and_downcast::<gtk4::Window>() {
+        window.set_transient_for(Some(&parent));
+    }
+
+    let form = GtkBox::new(Orientation::Vertical, 12);
+    form.set_margin_start(24);
+    form.set_margin_end(24);
+    form.set_margin_top(24);
+    form.set_margin_bottom(24);
+
+    let title_label = Label::new(Some(&tr_with(
+        "📅 Book a Session with {name}",
+        &[("name", &name)],
+    )));
+    title_label.set_halign(gtk4::Align::Start);
+    title_label.add_css_class("dialog-title");
+    form.append(&title_label);
+
+    form.append(&form_label(&tr("Date")));
+    let calendar = gtk4::Calendar::new();
+    calendar.add_css_class("session-calendar");
+    form.append(&calendar);
+
+    let time_row = GtkBox::new(Orientation::Horizontal, 8);
+    let hour = gtk4::SpinButton::with_range(0.0, 23.0, 1.0);
+    hour.set_value(10.0);
+    let minute = gtk4::SpinButton::with_range(0.0, 45.0, 15.0);
+    for spin in [&hour, &minute] {
+        spin.set_numeric(true);
+        spin.connect_output(|spin| {
+            spin.set_text(&format!("{:02}", spin.value() as i32));
+            glib::Propagation::Stop
+        });
+    }
+    let length_names = SESSION_LENGTHS.map(tr);
+    let length_names: Vec<&str> = length_names.iter().map(String::as_str).collect();
+    let length = DropDown::from_strings(&length_names);
+    length.set_selected(2);
+    length.set_hexpand(true);
+    time_row.append(&hour);
+    time_row.append(&Label::new(Some(":")));
+    time_row.append(&minute);
+    time_row.append(&length);
+    form.append(&form_label(&tr("Start time and length")));
+    form.append(&time_row);
+
+    form.append(&form_label(&tr("Topic")));
+    let topic = Entry::new();
+    topic.set_placeholder_text(Some(&tr("e.g. Code review of my first Rust crate")));
+    topic.set_max_length(MAX_TOPIC_LENGTH as i32);
+    form.append(&topic);
+
+    let error_label = Label::new(None);
+    error_label.set_halign(gtk4::Align::Start);
+    error_label.add_css_class("form-error");
+    form.append(&error_label);
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 12);
+    buttons.set_halign(gtk4::Align::End);
+    let cancel_btn = Button::with_label(&tr("Cancel"));
+    cancel_btn.add_css_class("secondary-button");
+    let book_btn = Button::with_label(&tr("Send Request"));
+    book_btn.add_css_class("create-post-button");
+    buttons.append(&cancel_btn);
+    buttons.append(&book_btn);
+    form.append(&buttons);
+
+    let window_clone = window.clone();
+    cancel_btn.connect_clicked(move |_| window_clone.close());
+
+    let state = state.clone();
+    let user_id = user_id.to_string();
+    let window_clone = window.clone();
+    book_btn.connect_clicked(move |_| {
+        let topic_text = topic.text().trim().to_string();
+        if topic_text.is_empty() {
+            error_label.set_text(&tr("Enter a topic for the session"));
+            return;
+        }
+        let date = calendar.date();
+        let Ok(start) = glib::DateTime::from_local(
+            date.year(),
+            date.month(),
+            date.day_of_month(),
+            hour.value_as_int(),
+            minute.value_as_int(),
+            0.0,
+        ) else {
+            error_label.set_text(&tr("Pick a valid date and time"));
+            return;
+        };
+        if start.to_unix() <= util::now() {
+            error_label.set_text(&tr("Pick a time in the future"));
+            return;
+        }
+        let length_label = SESSION_LENGTHS
+            .get(length.selected() as usize)
+            .unwrap_or(&SESSION_LENGTHS[0]);
+        let text = tr_with(
+            "📅 Session request: {topic}\n{when} · {length}",
+            &[
+                ("topic", &topic_text),
+                ("when", &util::format_date_time(start.to_unix())),
+                ("length", &tr(length_label)),
+            ],
+        );
+
+        let conversation = {
+            let mut community = state.community.borrow_mut();
+            let conversation = community.direct_conversation(&user_id);
+            community
+                .send_message(&conversation, ME, &text, None)
+                .then_some(conversation)
+        };
+        let Some(conversation) = conversation else {
+            error_label.set_text(&tr("The request couldn't be sent"));
+            return;
+        };
+        state.community_changed.emit();
+        navigation::activate(
+            &window_clone,
+            "open-conversation",
+            Some(&conversation.to_variant()),
+        );
+        window_clone.close();
+    });
+
+    window.set_child(Some(&form));
+    window.present();
+}
+
+fn form_label(text: &str) -> Label {
+    let label = Label::new(Some(text));
+    label.set_halign(gtk4::Align::Start);
+    label.add_css_class("form-label");
+    label
+}
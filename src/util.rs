@@ -0,0 +1,201 @@
+// Small formatting helpers shared by the views
+use crate::hashtags;
+use crate::i18n::{self, tr, tr_noop, tr_with, trn};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const MINUTE: i64 = 60;
+pub const HOUR: i64 = 60 * MINUTE;
+pub const DAY: i64 = 24 * HOUR;
+pub const WEEK: i64 = 7 * DAY;
+
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// Formats a timestamp the way the feed shows it: "2 hours ago", "1 week ago",
+/// in the current language.
+pub fn format_relative_time(timestamp: i64, now: i64) -> String {
+    let elapsed = (now - timestamp).max(0);
+    let count = |unit: i64| (elapsed / unit) as u64;
+    match elapsed {
+        e if e < MINUTE => tr("Just now"),
+        e if e < HOUR => trn("{n} min ago", "{n} min ago", count(MINUTE)),
+        e if e < DAY => trn("{n} hour ago", "{n} hours ago", count(HOUR)),
+        e if e < WEEK => trn("{n} day ago", "{n} days ago", count(DAY)),
+        e if e < 30 * DAY => trn("{n} week ago", "{n} weeks ago", count(WEEK)),
+        e if e < 365 * DAY => trn("{n} month ago", "{n} months ago", count(30 * DAY)),
+        _ => trn("{n} year ago", "{n} years ago", count(365 * DAY)),
+    }
+}
+
+/// Formats the time left until `timestamp`: "in 45 min", "in 3 days".
+pub fn format_time_until(timestamp: i64, now: i64) -> String {
+    let remaining = (timestamp - now).max(0);
+    let count = |unit: i64| (remaining / unit) as u64;
+    match remaining {
+        r if r < MINUTE => tr("in less than a minute"),
+        r if r < HOUR => trn("in {n} min", "in {n} min", count(MINUTE)),
+        r if r < DAY => trn("in {n} hour", "in {n} hours", count(HOUR)),
+        r if r < WEEK => trn("in {n} day", "in {n} days", count(DAY)),
+        _ => trn("in {n} week", "in {n} weeks", count(WEEK)),
+    }
+}
+
+/// Compact counts for stats, e.g. "856", "1.2k" or "3M".
+pub fn format_count(count: usize) -> String {
+    let (value, msgid) = match count {
+        0..=999 => return i18n::number(count),
+        1_000..=999_999 => (count as f64 / 1_000.0, tr_noop("{count}k")),
+        _ => (count as f64 / 1_000_000.0, tr_noop("{count}M")),
+    };
+    let value = if (value * 10.0).round() % 10.0 == 0.0 {
+        i18n::decimal(value, 0)
+    } else {
+        i18n::decimal(value, 1)
+    };
+    tr_with(msgid, &[("count", &value)])
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = [tr_noop("KB"), tr_noop("MB"), tr_noop("GB"), tr_noop("TB")];
+    if bytes < 1024 {
+        return tr_with("{size} B", &[("size", &i18n::number(bytes))]);
+    }
+    let mut value = bytes as f64;
+    let mut unit = "B";
+    for next in UNITS {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next;
+    }
+    format!("{} {}", i18n::decimal(value, 1), tr(unit))
+}
+
+/// Formats a timestamp as an absolute local date, e.g. "March 4, 2025 14:30".
+pub fn format_date_time(timestamp: i64) -> String {
+    let Ok(date) = gtk4::glib::DateTime::from_unix_local(timestamp) else {
+        return String::new();
+    };
+    tr_with(
+        "{month} {day}, {year} {time}",
+        &[
+            ("month", &i18n::month_name(date.month())),
+            ("day", &i18n::number(date.day_of_month())),
+            ("year", &i18n::number(date.year())),
+            ("time", &format_clock(&date)),
+        ],
+    )
+}
+
+/// "14:30" in the current locale's digits.
+fn format_clock(date: &gtk4::glib::DateTime) -> String {
+    i18n::number(format!("{:02}:{:02}", date.hour(), date.minute()))
+}
+
+/// Heading used to group items by date: "Today", "Yesterday", "This Week",
+/// "Earlier This Month" or the month name, e.g. "March 2025".
+pub fn date_group_label(timestamp: i64, now: i64) -> String {
+    use gtk4::glib::DateTime;
+
+    let (Ok(date), Ok(today)) = (
+        DateTime::from_unix_local(timestamp),
+        DateTime::from_unix_local(now),
+    ) else {
+        return String::new();
+    };
+
+    let days_apart = {
+        let start_of = |d: &DateTime| {
+            let (y, m, day) = d.ymd();
+            DateTime::from_local(y, m, day, 0, 0, 0.0)
+                .map(|d| d.to_unix())
+                .unwrap_or_default()
+        };
+        // Round so days shortened or stretched by DST still count as one.
+        ((start_of(&today) - start_of(&date)) as f64 / DAY as f64).round() as i64
+    };
+
+    match days_apart {
+        d if d <= 0 => tr("Today"),
+        1 => tr("Yesterday"),
+        d if d < 7 => tr("This Week"),
+        _ if date.year() == today.year() && date.month() == today.month() => {
+            tr("Earlier This Month")
+        }
+        _ => tr_with(
+            "{month} {year}",
+            &[
+                ("month", &i18n::month_name(date.month())),
+                ("year", &i18n::number(date.year())),
+            ],
+        ),
+    }
+}
+
+/// Compact age used in lists: "2m", "1h", "3d", "2w".
+pub fn format_short_time(timestamp: i64, now: i64) -> String {
+    let elapsed = (now - timestamp).max(0);
+    let count = |unit: i64| i18n::number(elapsed / unit);
+    match elapsed {
+        e if e < MINUTE => tr("now"),
+        e if e < HOUR => tr_with("{count}m", &[("count", &count(MINUTE))]),
+        e if e < DAY => tr_with("{count}h", &[("count", &count(HOUR))]),
+        e if e < WEEK => tr_with("{count}d", &[("count", &count(DAY))]),
+        _ => tr_with("{count}w", &[("count", &count(WEEK))]),
+    }
+}
+
+/// Escapes `text` as Pango markup and turns web and share links and
+/// #hashtags into clickable `<a>` tags.
+pub fn linkify_markup(text: &str) -> String {
+    const SCHEMES: [&str; 3] = ["https://", "http://", "linkwithmentor://"];
+
+    let mut markup = String::new();
+    let mut rest = text;
+    while let Some(start) = SCHEMES.iter().filter_map(|scheme| rest.find(scheme)).min() {
+        let end = rest[start..]
+            .find(char::is_whitespace)
+            .map_or(rest.len(), |offset| start + offset);
+        let url = &rest[start..end];
+        markup.push_str(&hashtag_markup(&rest[..start]));
+        let url = gtk4::glib::markup_escape_text(url);
+        markup.push_str(&format!("<a href=\"{url}\">{url}</a>"));
+        rest = &rest[end..];
+    }
+    markup.push_str(&hashtag_markup(rest));
+    markup
+}
+
+/// Escapes `text` as Pango markup with its #hashtags linked.
+fn hashtag_markup(text: &str) -> String {
+    let mut markup = String::new();
+    let mut last = 0;
+    for (start, end) in hashtags::find(text) {
+        markup.push_str(&gtk4::glib::markup_escape_text(&text[last..start]));
+        markup.push_str(&format!(
+            "<a href=\"{}\">{}</a>",
+            gtk4::glib::markup_escape_text(&hashtags::url(&text[start..end])),
+            gtk4::glib::markup_escape_text(&text[start..end])
+        ));
+        last = end;
+    }
+    markup.push_str(&gtk4::glib::markup_escape_text(&text[last..]));
+    markup
+}
+
+/// Accepts absolute http:// and https:// links with a host. Errors are
+/// untranslated; pass them through `tr`.
+pub fn validate_url(url: &str) -> Result<(), &'static str> {
+    let uri = gtk4::glib::Uri::parse(url, gtk4::glib::UriFlags::NONE)
+        .map_err(|_| tr_noop("Not a valid link"))?;
+    match uri.scheme().as_str() {
+        "http" | "https" if uri.host().is_some_and(|host| !host.is_empty()) => Ok(()),
+        "http" | "https" => Err(tr_noop("The link needs a host name")),
+        _ => Err(tr_noop("Only http:// and https:// links are supported")),
+    }
+}
@@ -0,0 +1,520 @@
+// Full-screen lightbox for media items
+use crate::i18n::{self, tr, tr_noop, tr_with};
+use crate::media::{MediaItem, MediaKind};
+use crate::sizing;
+use crate::util;
+use gtk4::prelude::*;
+use gtk4::{gdk, gio, glib};
+use gtk4::{
+    Box as GtkBox, Button, Label, MediaFile, Orientation, Overlay, Picture, ScrolledWindow,
+    ToggleButton, Video,
+};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+const MIN_ZOOM: f64 = 1.0;
+const MAX_ZOOM: f64 = 8.0;
+const ZOOM_STEP: f64 = 1.25;
+const SLIDESHOW_SECONDS: u32 = 4;
+
+/// Shared state of one open viewer window.
+struct Viewer {
+    items: Vec<MediaItem>,
+    index: Cell<usize>,
+    zoom: Cell<f64>,
+    window: gtk4::Window,
+    stage: ScrolledWindow,
+    counter: Label,
+    zoom_label: Label,
+    info_rows: GtkBox,
+    picture: RefCell<Option<Picture>>,
+    stream: RefCell<Option<MediaFile>>,
+    slideshow_btn: ToggleButton,
+    slideshow: RefCell<Option<glib::SourceId>>,
+    pan_origin: Cell<(f64, f64)>,
+    pinch_origin: Cell<f64>,
+}
+
+/// Opens `items` in a full-screen viewer starting at `index`.
+pub fn open_viewer(parent: &impl IsA<gtk4::Widget>, items: Vec<MediaItem>, index: usize) {
+    if items.is_empty() {
+        return;
+    }
+
+    let window = gtk4::Window::builder()
+        .title(tr("Media Viewer"))
+        .default_width(1200)
+        .default_height(800)
+        .build();
+    window.add_css_class("media-viewer");
+    if let Some(parent) = parent.root().and_downcast::<gtk4::Window>() {
+        window.set_transient_for(Some(&parent));
+    }
+
+    let root = GtkBox::new(Orientation::Horizontal, 0);
+
+    // Stage with the current item and the overlaid controls
+    let overlay = Overlay::new();
+    overlay.set_hexpand(true);
+    overlay.set_vexpand(true);
+
+    let stage = ScrolledWindow::new();
+    stage.add_css_class("viewer-stage");
+    overlay.set_child(Some(&stage));
+
+    let top_bar = GtkBox::new(Orientation::Horizontal, 12);
+    top_bar.add_css_class("viewer-toolbar");
+    top_bar.set_valign(gtk4::Align::Start);
+    top_bar.set_margin_start(20);
+    top_bar.set_margin_end(20);
+    top_bar.set_margin_top(16);
+
+    let counter = Label::new(None);
+    counter.add_css_class("viewer-counter");
+    let spacer = GtkBox::new(Orientation::Horizontal, 0);
+    spacer.set_hexpand(true);
+
+    let zoom_out_btn = Button::with_label("➖");
+    zoom_out_btn.set_tooltip_text(Some(&tr("Zoom out (-)")));
+    let zoom_label = Label::new(Some(&tr("Fit")));
+    zoom_label.add_css_class("viewer-zoom");
+    let zoom_in_btn = Button::with_label("➕");
+    zoom_in_btn.set_tooltip_text(Some(&tr("Zoom in (+)")));
+    let slideshow_btn = ToggleButton::with_label(&tr("▶️ Slideshow"));
+    slideshow_btn.set_tooltip_text(Some(&tr("Start or stop the slideshow (Space)")));
+    let info_btn = ToggleButton::with_label(&tr("ℹ️ Info"));
+    info_btn.set_active(true);
+    info_btn.set_tooltip_text(Some(&tr("Show details (I)")));
+    let close_btn = Button::with_label("✕");
+    close_btn.set_tooltip_text(Some(&tr("Close (Esc)")));
+
+    for button in [
+        zoom_out_btn.upcast_ref::<gtk4::Widget>(),
+        zoom_in_btn.upcast_ref(),
+        slideshow_btn.upcast_ref(),
+        info_btn.upcast_ref(),
+        close_btn.upcast_ref(),
+    ] {
+        button.add_css_class("viewer-button");
+    }
+
+    top_bar.append(&counter);
+    top_bar.append(&spacer);
+    top_bar.append(&zoom_out_btn);
+    top_bar.append(&zoom_label);
+    top_bar.append(&zoom_in_btn);
+    top_bar.append(&slideshow_btn);
+    top_bar.append(&info_btn);
+    top_bar.append(&close_btn);
+    overlay.add_overlay(&top_bar);
+
+    let prev_btn = Button::with_label("‹");
+    prev_btn.add_css_class("viewer-nav-button");
+    prev_btn.set_halign(gtk4::Align::Start);
+    prev_btn.set_valign(gtk4::Align::Center);
+    prev_btn.set_margin_start(20);
+    overlay.add_overlay(&prev_btn);
+
+    let next_btn = Button::with_label("›");
+    next_btn.add_css_class("viewer-nav-button");
+    next_btn.set_halign(gtk4::Align::End);
+    next_btn.set_valign(gtk4::Align::Center);
+    next_btn.set_margin_end(20);
+    overlay.add_overlay(&next_btn);
+
+    // Metadata side panel
+    let info_panel = GtkBox::new(Orientation::Vertical, 16);
+    info_panel.add_css_class("viewer-sidebar");
+    sizing::set_size_request(&info_panel, 300, -1);
+
+    let info_title = Label::new(Some(&tr("Details")));
+    info_title.set_halign(gtk4::Align::Start);
+    info_title.add_css_class("viewer-sidebar-title");
+    info_title.set_margin_start(20);
+    info_title.set_margin_top(24);
+    let info_rows = GtkBox::new(Orientation::Vertical, 12);
+    info_rows.set_margin_start(20);
+    info_rows.set_margin_end(20);
+    info_panel.append(&info_title);
+    info_panel.append(&info_rows);
+
+    root.append(&overlay);
+    root.append(&info_panel);
+    window.set_child(Some(&root));
+
+    let viewer = Rc::new(Viewer {
+        index: Cell::new(index.min(items.len() - 1)),
+        items,
+        zoom: Cell::new(MIN_ZOOM),
+        window: window.clone(),
+        stage: stage.clone(),
+        counter,
+        zoom_label,
+        info_rows,
+        picture: RefCell::new(None),
+        stream: RefCell::new(None),
+        slideshow_btn: slideshow_btn.clone(),
+        slideshow: RefCell::new(None),
+        pan_origin: Cell::new((0.0, 0.0)),
+        pinch_origin: Cell::new(MIN_ZOOM),
+    });
+
+    let v = viewer.clone();
+    prev_btn.connect_clicked(move |_| v.step(-1));
+    let v = viewer.clone();
+    next_btn.connect_clicked(move |_| v.step(1));
+    let v = viewer.clone();
+    zoom_in_btn.connect_clicked(move |_| v.set_zoom(v.zoom.get() * ZOOM_STEP, None));
+    let v = viewer.clone();
+    zoom_out_btn.connect_clicked(move |_| v.set_zoom(v.zoom.get() / ZOOM_STEP, None));
+    let v = viewer.clone();
+    slideshow_btn.connect_toggled(move |button| v.set_slideshow(button.is_active()));
+    info_btn.connect_toggled(move |button| info_panel.set_visible(button.is_active()));
+    let w = window.clone();
+    close_btn.connect_clicked(move |_| w.close());
+
+    let v = viewer.clone();
+    window.connect_close_request(move |_| {
+        v.set_slideshow(false);
+        v.stop_playback();
+        glib::Propagation::Proceed
+    });
+
+    viewer.connect_gestures();
+    viewer.connect_keys(&info_btn);
+    viewer.show_current();
+
+    window.fullscreen();
+    window.present();
+}
+
+impl Viewer {
+    fn current(&self) -> &MediaItem {
+        &self.items[self.index.get()]
+    }
+
+    fn step(&self, delta: isize) {
+        let len = self.items.len() as isize;
+        let next = (self.index.get() as isize + delta).rem_euclid(len);
+        self.index.set(next as usize);
+        self.show_current();
+    }
+
+    fn jump_to(&self, index: usize) {
+        self.index.set(index.min(self.items.len() - 1));
+        self.show_current();
+    }
+
+    fn stop_playback(&self) {
+        if let Some(stream) = self.stream.take() {
+            stream.set_playing(false);
+        }
+    }
+
+    fn show_current(&self) {
+        self.stop_playback();
+        self.picture.replace(None);
+        self.zoom.set(MIN_ZOOM);
+        self.zoom_label.set_text(&tr("Fit"));
+
+        let item = self.current().clone();
+        self.counter.set_text(&format!(
+            "{} / {}  ·  {}",
+            self.index.get() + 1,
+            self.items.len(),
+            item.title
+        ));
+
+        let content: gtk4::Widget = match (item.kind, item.path()) {
+            (MediaKind::Image, Some(path)) => {
+                let picture = Picture::for_filename(&path);
+                picture.set_can_shrink(true);
+                picture.set_content_fit(gtk4::ContentFit::Contain);
+                picture.add_css_class("viewer-picture");
+                self.picture.replace(Some(picture.clone()));
+                picture.upcast()
+            }
+            (MediaKind::Video | MediaKind::Audio, Some(path)) => {
+                let stream = MediaFile::for_filename(&path);
+                let video = Video::new();
+                video.set_media_stream(Some(&stream));
+                video.add_css_class("viewer-video");
+                stream.play();
+                self.stream.replace(Some(stream));
+                video.upcast()
+            }
+            (_, path) => create_placeholder(&item, path),
+        };
+
+        self.stage.set_child(Some(&content));
+        self.update_info(&item);
+    }
+
+    fn update_info(&self, item: &MediaItem) {
+        while let Some(child) = self.info_rows.first_child() {
+            self.info_rows.remove(&child);
+        }
+
+        let type_text = if item.mime.is_empty() {
+            item.kind.id().to_string()
+        } else {
+            gio::content_type_from_mime_type(&item.mime)
+                .map(|content_type| gio::content_type_get_description(&content_type).to_string())
+                .unwrap_or_else(|| item.mime.clone())
+        };
+        let dimensions = match (item.width, item.height) {
+            (Some(width), Some(height)) => {
+                format!("{} × {}", i18n::number(width), i18n::number(height))
+            }
+            _ => "—".to_string(),
+        };
+        let size = if item.size > 0 {
+            util::format_size(item.size)
+        } else {
+            "—".to_string()
+        };
+
+        let rows = [
+            (tr_noop("Name"), item.title.clone()),
+            (tr_noop("Type"), type_text),
+            (tr_noop("Size"), size),
+            (tr_noop("Dimensions"), dimensions),
+            (
+                tr_noop("Uploaded"),
+                util::format_date_time(item.uploaded_at),
+            ),
+            (tr_noop("Shared by"), item.shared_by_name()),
+        ];
+
+        for (label, value) in rows {
+            let row = GtkBox::new(Orientation::Vertical, 2);
+            let label_label = Label::new(Some(&tr(label)));
+            label_label.set_halign(gtk4::Align::Start);
+            label_label.add_css_class("viewer-info-label");
+            let value_label = Label::new(Some(&value));
+            value_label.set_halign(gtk4::Align::Start);
+            value_label.set_wrap(true);
+            value_label.set_selectable(true);
+            value_label.add_css_class("viewer-info-value");
+            row.append(&label_label);
+            row.append(&value_label);
+            self.info_rows.append(&row);
+        }
+    }
+
+    /// Sets the zoom level, keeping `anchor` (stage coordinates) in place.
+    fn set_zoom(&self, zoom: f64, anchor: Option<(f64, f64)>) {
+        let Some(picture) = self.picture.borrow().clone() else {
+            return;
+        };
+
+        let old_zoom = self.zoom.get();
+        let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        if (zoom - old_zoom).abs() < f64::EPSILON {
+            return;
+        }
+        self.zoom.set(zoom);
+
+        let (width, height) = (self.stage.width() as f64, self.stage.height() as f64);
+        if zoom <= MIN_ZOOM {
+            picture.set_size_request(-1, -1);
+            self.zoom_label.set_text(&tr("Fit"));
+            return;
+        }
+        picture.set_size_request((width * zoom) as i32, (height * zoom) as i32);
+        self.zoom_label.set_text(&tr_with(
+            "{percent}%",
+            &[("percent", &i18n::decimal(zoom * 100.0, 0))],
+        ));
+
+        // Re-centre after the next layout so the anchor point stays put.
+        let (anchor_x, anchor_y) = anchor.unwrap_or((width / 2.0, height / 2.0));
+        let ratio = zoom / old_zoom;
+        let hadjustment = self.stage.hadjustment();
+        let vadjustment = self.stage.vadjustment();
+        let (old_x, old_y) = (hadjustment.value(), vadjustment.value());
+        glib::idle_add_local_once(move || {
+            hadjustment.set_value((old_x + anchor_x) * ratio - anchor_x);
+            vadjustment.set_value((old_y + anchor_y) * ratio - anchor_y);
+        });
+    }
+
+    fn set_slideshow(self: &Rc<Self>, running: bool) {
+        if let Some(source) = self.slideshow.take() {
+            source.remove();
+        }
+        if self.slideshow_btn.is_active() != running {
+            self.slideshow_btn.set_active(running);
+        }
+        self.slideshow_btn.set_label(&if running {
+            tr("⏸️ Slideshow")
+        } else {
+            tr("▶️ Slideshow")
+        });
+
+        if running {
+            let viewer = Rc::downgrade(self);
+            let source = glib::timeout_add_seconds_local(SLIDESHOW_SECONDS, move || {
+                let Some(viewer) = viewer.upgrade() else {
+                    return glib::ControlFlow::Break;
+                };
+                viewer.step(1);
+                glib::ControlFlow::Continue
+            });
+            self.slideshow.replace(Some(source));
+        }
+    }
+
+    fn connect_gestures(self: &Rc<Self>) {
+        // Ctrl + scroll wheel, or touchpad scroll with Ctrl, zooms
+        let scroll = gtk4::EventControllerScroll::new(gtk4::EventControllerScrollFlags::VERTICAL);
+        let v = self.clone();
+        scroll.connect_scroll(move |controller, _, dy| {
+            if !controller
+                .current_event_state()
+                .contains(gdk::ModifierType::CONTROL_MASK)
+            {
+                return glib::Propagation::Proceed;
+            }
+            let factor = if dy < 0.0 { ZOOM_STEP } else { 1.0 / ZOOM_STEP };
+            v.set_zoom(v.zoom.get() * factor, v.pointer_position(controller));
+            glib::Propagation::Stop
+        });
+        self.stage.add_controller(scroll);
+
+        // Touchpad pinch
+        let pinch = gtk4::GestureZoom::new();
+        let v = self.clone();
+        pinch.connect_begin(move |_, _| v.pinch_origin.set(v.zoom.get()));
+        let v = self.clone();
+        pinch.connect_scale_changed(move |gesture, scale| {
+            let anchor = gesture.bounding_box_center();
+            v.set_zoom(v.pinch_origin.get() * scale, anchor);
+        });
+        self.stage.add_controller(pinch);
+
+        // Drag to pan while zoomed in
+        let drag = gtk4::GestureDrag::new();
+        let v = self.clone();
+        drag.connect_drag_begin(move |_, _, _| {
+            v.pan_origin
+                .set((v.stage.hadjustment().value(), v.stage.vadjustment().value()));
+        });
+        let v = self.clone();
+        drag.connect_drag_update(move |_, offset_x, offset_y| {
+            if v.zoom.get() <= MIN_ZOOM {
+                return;
+            }
+            let (origin_x, origin_y) = v.pan_origin.get();
+            v.stage.hadjustment().set_value(origin_x - offset_x);
+            v.stage.vadjustment().set_value(origin_y - offset_y);
+        });
+        self.stage.add_controller(drag);
+
+        // Double click toggles between fit and 2×
+        let click = gtk4::GestureClick::new();
+        let v = self.clone();
+        click.connect_pressed(move |_, n_press, x, y| {
+            if n_press == 2 {
+                let zoom = if v.zoom.get() > MIN_ZOOM {
+                    MIN_ZOOM
+                } else {
+                    2.0
+                };
+                v.set_zoom(zoom, Some((x, y)));
+            }
+        });
+        self.stage.add_controller(click);
+    }
+
+    fn pointer_position(&self, controller: &gtk4::EventControllerScroll) -> Option<(f64, f64)> {
+        let event = controller.current_event()?;
+        let (x, y) = event.position()?;
+        let native = self.stage.native()?;
+        let (offset_x, offset_y) = native.surface_transform();
+        let point = native.compute_point(
+            &self.stage,
+            &gtk4::graphene::Point::new((x - offset_x) as f32, (y - offset_y) as f32),
+        )?;
+        Some((point.x() as f64, point.y() as f64))
+    }
+
+    fn connect_keys(self: &Rc<Self>, info_btn: &ToggleButton) {
+        let keys = gtk4::EventControllerKey::new();
+        let v = self.clone();
+        let info_btn = info_btn.clone();
+        keys.connect_key_pressed(move |_, key, _, _| {
+            match key {
+                gdk::Key::Left | gdk::Key::Page_Up | gdk::Key::BackSpace => v.step(-1),
+                gdk::Key::Right | gdk::Key::Page_Down => v.step(1),
+                gdk::Key::Home => v.jump_to(0),
+                gdk::Key::End => v.jump_to(v.items.len() - 1),
+                gdk::Key::plus | gdk::Key::equal | gdk::Key::KP_Add => {
+                    v.set_zoom(v.zoom.get() * ZOOM_STEP, None)
+                }
+                gdk::Key::minus | gdk::Key::KP_Subtract => {
+                    v.set_zoom(v.zoom.get() / ZOOM_STEP, None)
+                }
+                gdk::Key::_0 | gdk::Key::KP_0 => v.set_zoom(MIN_ZOOM, None),
+                gdk::Key::space => v.set_slideshow(!v.slideshow_btn.is_active()),
+                gdk::Key::i => info_btn.set_active(!info_btn.is_active()),
+                gdk::Key::f | gdk::Key::F11 => {
+                    if v.window.is_fullscreen() {
+                        v.window.unfullscreen();
+                    } else {
+                        v.window.fullscreen();
+                    }
+                }
+                gdk::Key::Escape => {
+                    if v.slideshow_btn.is_active() {
+                        v.set_slideshow(false);
+                    } else {
+                        v.window.close();
+                    }
+                }
+                _ => return glib::Propagation::Proceed,
+            }
+            glib::Propagation::Stop
+        });
+        self.window.add_controller(keys);
+    }
+}
+
+/// Shown for documents and for items without a local file.
+fn create_placeholder(item: &MediaItem, path: Option<std::path::PathBuf>) -> gtk4::Widget {
+    let placeholder = GtkBox::new(Orientation::Vertical, 16);
+    placeholder.set_valign(gtk4::Align::Center);
+    placeholder.set_halign(gtk4::Align::Center);
+
+    let icon = Label::new(Some(item.kind.icon()));
+    icon.add_css_class("viewer-placeholder-icon");
+    let title = Label::new(Some(&item.title));
+    title.add_css_class("viewer-placeholder-title");
+    placeholder.append(&icon);
+    placeholder.append(&title);
+
+    match path {
+        Some(path) => {
+            let open_btn = Button::with_label(&tr("📂 Open with default app"));
+            open_btn.add_css_class("create-post-button");
+            open_btn.set_halign(gtk4::Align::Center);
+            open_btn.connect_clicked(move |button| {
+                let launcher = gtk4::FileLauncher::new(Some(&gio::File::for_path(&path)));
+                let window = button.root().and_downcast::<gtk4::Window>();
+                launcher.launch(window.as_ref(), gio::Cancellable::NONE, |result| {
+                    if let Err(err) = result {
+                        eprintln!("Could not open file: {err}");
+                    }
+                });
+            });
+            placeholder.append(&open_btn);
+        }
+        None => {
+            let note = Label::new(Some(&tr("No preview available")));
+            note.add_css_class("viewer-info-label");
+            placeholder.append(&note);
+        }
+    }
+
+    placeholder.upcast()
+}